@@ -0,0 +1,37 @@
+#![feature(test)]
+
+#[cfg(test)]
+mod tests {
+    extern crate test;
+    use rand::{thread_rng, Rng};
+    use refimage::OptimumExposureBuilder;
+    use std::time::Duration;
+    use test::{black_box, Bencher};
+
+    /// Pixels in a 16 MP frame.
+    const LEN: usize = 4096 * 4096;
+
+    #[bench]
+    /// [`refimage::OptimumExposure::calculate_copy`] on a 16 MP `u16` frame.
+    fn bench_calculate_copy_16mp(b: &mut Bencher) {
+        let mut data = vec![0u16; LEN];
+        thread_rng().fill(&mut data[..]);
+        let opt_exp = OptimumExposureBuilder::default().build().unwrap();
+        b.iter(|| {
+            let res = opt_exp.calculate_copy(&data, Duration::from_millis(100), 1);
+            black_box(res)
+        });
+    }
+
+    #[bench]
+    /// Copying and fully sorting a 16 MP `u16` frame, for comparison.
+    fn bench_sort_copy_16mp(b: &mut Bencher) {
+        let mut data = vec![0u16; LEN];
+        thread_rng().fill(&mut data[..]);
+        b.iter(|| {
+            let mut img = data.clone();
+            img.sort();
+            black_box(img)
+        });
+    }
+}
@@ -0,0 +1,40 @@
+#![feature(test)]
+
+#[cfg(test)]
+mod tests {
+    extern crate test;
+    use bincode::Options;
+    use rand::{thread_rng, Rng};
+    use refimage::{ColorSpace, DynamicImageOwned, ImageOwned};
+    use test::{black_box, Bencher};
+
+    /// Serialize a random 4096x4096 `u16` frame with `bincode`.
+    fn frame() -> Vec<u8> {
+        let mut data = vec![0u16; 4096 * 4096];
+        thread_rng().fill(&mut data[..]);
+        let img = ImageOwned::from_owned(data, 4096, 4096, ColorSpace::Gray).unwrap();
+        bincode::serialize(&DynamicImageOwned::from(img)).unwrap()
+    }
+
+    #[bench]
+    /// Deserialize a 4096x4096 `u16` frame, verifying the checksum.
+    fn bench_deserialize_4k(b: &mut Bencher) {
+        let blob = frame();
+        b.iter(|| {
+            let res: DynamicImageOwned = bincode::deserialize(&blob).unwrap();
+            black_box(res);
+        });
+    }
+
+    #[bench]
+    /// Deserialize a 4096x4096 `u16` frame, skipping the checksum.
+    fn bench_deserialize_unchecked_4k(b: &mut Bencher) {
+        let blob = frame();
+        b.iter(|| {
+            let mut de =
+                bincode::Deserializer::from_slice(&blob, bincode::options().with_fixint_encoding());
+            let res = DynamicImageOwned::deserialize_unchecked(&mut de).unwrap();
+            black_box(res);
+        });
+    }
+}
@@ -0,0 +1,172 @@
+//! Conversion between images with and without an alpha channel.
+//!
+//! Images with an alpha channel are represented by the [`ColorSpace::GrayAlpha`] and
+//! [`ColorSpace::Rgba`] color spaces.
+
+use crate::{
+    coretraits::clamp_from_f64, ColorSpace, DynamicImageOwned, DynamicImageRef, ImageOwned,
+    ImageProps, ImageRef, PixelStor,
+};
+
+/// Get the color space of an image after adding an alpha channel.
+fn with_alpha(cspace: &ColorSpace) -> Result<ColorSpace, &'static str> {
+    match cspace {
+        ColorSpace::Gray => Ok(ColorSpace::GrayAlpha),
+        ColorSpace::Rgb => Ok(ColorSpace::Rgba),
+        ColorSpace::Bayer(_) => Err("Bayer mosaic images can not have an alpha channel."),
+        ColorSpace::GrayAlpha | ColorSpace::Rgba => Err("Image already has an alpha channel."),
+        ColorSpace::Custom(_, _) | ColorSpace::Cmyk => {
+            Err("Alpha channel can only be added to gray and RGB images.")
+        }
+    }
+}
+
+/// Get the color space of an image after removing the alpha channel.
+pub(crate) fn without_alpha(cspace: &ColorSpace) -> Result<ColorSpace, &'static str> {
+    match cspace {
+        ColorSpace::GrayAlpha => Ok(ColorSpace::Gray),
+        ColorSpace::Rgba => Ok(ColorSpace::Rgb),
+        _ => Err("Image does not have an alpha channel."),
+    }
+}
+
+macro_rules! impl_alpha {
+    ($($t:tt)*) => {
+        $($t)* {
+            /// Add an alpha channel to the image.
+            ///
+            /// Gray images are converted to gray-alpha images ([`ColorSpace::GrayAlpha`]),
+            /// and RGB images are converted to RGBA images ([`ColorSpace::Rgba`]).
+            ///
+            /// # Arguments
+            /// - `value`: The value of the alpha channel, e.g.
+            ///   [`PixelStor::DEFAULT_MAX_VALUE`] for an opaque image.
+            ///
+            /// # Errors
+            /// - If the image is a Bayer mosaic image.
+            /// - If the image already has an alpha channel.
+            /// - If the image has any other color space.
+            pub fn add_alpha(&self, value: T) -> Result<ImageOwned<T>, &'static str> {
+                let cspace = with_alpha(&self.cspace)?;
+                let channels = self.channels() as usize;
+                let mut data = Vec::with_capacity(self.len() / channels * (channels + 1));
+                for px in self.as_slice().chunks_exact(channels) {
+                    data.extend_from_slice(px);
+                    data.push(value);
+                }
+                ImageOwned::new(data, self.width(), self.height(), cspace)
+            }
+
+            /// Remove the alpha channel of the image.
+            ///
+            /// Gray-alpha images ([`ColorSpace::GrayAlpha`]) are converted to gray images,
+            /// and RGBA images ([`ColorSpace::Rgba`]) are converted to RGB images. The color
+            /// channels are not premultiplied.
+            ///
+            /// # Errors
+            /// - If the image does not have an alpha channel.
+            pub fn remove_alpha(&self) -> Result<ImageOwned<T>, &'static str> {
+                let cspace = without_alpha(&self.cspace)?;
+                let channels = self.channels() as usize;
+                let data = self
+                    .as_slice()
+                    .chunks_exact(channels)
+                    .flat_map(|px| px[..channels - 1].iter().copied())
+                    .collect();
+                ImageOwned::new(data, self.width(), self.height(), cspace)
+            }
+        }
+    };
+}
+
+impl_alpha!(impl<T: PixelStor> ImageOwned<T>);
+impl_alpha!(impl<T: PixelStor> ImageRef<'_, T>);
+
+macro_rules! impl_alpha_dynamic {
+    ($t:ty) => {
+        impl $t {
+            /// Add an alpha channel to the image.
+            ///
+            /// `value` is converted to the pixel type of the image, clamping it to the
+            /// valid range. See [`ImageOwned::add_alpha`] for details.
+            ///
+            /// # Errors
+            /// - If the image is a Bayer mosaic image.
+            /// - If the image already has an alpha channel.
+            /// - If the image has any other color space.
+            pub fn add_alpha(&self, value: f64) -> Result<DynamicImageOwned, &'static str> {
+                Ok(match self {
+                    Self::U8(img) => img.add_alpha(clamp_from_f64(value))?.into(),
+                    Self::U16(img) => img.add_alpha(clamp_from_f64(value))?.into(),
+                    Self::I16(img) => img.add_alpha(clamp_from_f64(value))?.into(),
+                    Self::U32(img) => img.add_alpha(clamp_from_f64(value))?.into(),
+                    Self::F32(img) => img.add_alpha(clamp_from_f64(value))?.into(),
+                })
+            }
+
+            /// Remove the alpha channel of the image.
+            ///
+            /// See [`ImageOwned::remove_alpha`] for details.
+            ///
+            /// # Errors
+            /// - If the image does not have an alpha channel.
+            pub fn remove_alpha(&self) -> Result<DynamicImageOwned, &'static str> {
+                Ok(match self {
+                    Self::U8(img) => img.remove_alpha()?.into(),
+                    Self::U16(img) => img.remove_alpha()?.into(),
+                    Self::I16(img) => img.remove_alpha()?.into(),
+                    Self::U32(img) => img.remove_alpha()?.into(),
+                    Self::F32(img) => img.remove_alpha()?.into(),
+                })
+            }
+        }
+    };
+}
+
+impl_alpha_dynamic!(DynamicImageRef<'_>);
+impl_alpha_dynamic!(DynamicImageOwned);
+
+mod test {
+    #[test]
+    fn test_alpha() {
+        use crate::{
+            BayerPattern, ColorSpace, DynamicImageOwned, ImageOwned, ImageProps, ImageRef,
+        };
+        let mut data = vec![1u8, 2, 3, 4, 5, 6];
+        let img = ImageRef::new(&mut data, 2, 1, ColorSpace::Rgb).unwrap();
+        let rgba = img.add_alpha(255).unwrap();
+        assert_eq!(rgba.color_space(), ColorSpace::Rgba);
+        assert_eq!(rgba.as_slice(), &[1, 2, 3, 255, 4, 5, 6, 255]);
+        assert_eq!(
+            rgba.add_alpha(0),
+            Err("Image already has an alpha channel.")
+        );
+        // The last band of a custom color space is not an alpha channel
+        let mut rgbn = vec![1u8, 2, 3, 4];
+        let img4 = ImageRef::new(&mut rgbn, 1, 1, ColorSpace::Custom(4, "RGBN".into())).unwrap();
+        assert!(img4.remove_alpha().is_err());
+        assert!(img4.add_alpha(0).is_err());
+        let rgb = rgba.remove_alpha().unwrap();
+        assert_eq!(rgb.color_space(), ColorSpace::Rgb);
+        assert_eq!(rgb.as_slice(), img.as_slice());
+        assert_eq!(
+            rgb.remove_alpha(),
+            Err("Image does not have an alpha channel.")
+        );
+
+        let img = ImageOwned::from_owned(vec![0.5f32, 0.25], 1, 2, ColorSpace::Gray).unwrap();
+        let img = DynamicImageOwned::from(img).add_alpha(2.0).unwrap();
+        assert_eq!(img.channels(), 2);
+        assert_eq!(img.as_slice_f32().unwrap(), &[0.5, 1.0, 0.25, 1.0]);
+        let img = img.remove_alpha().unwrap();
+        assert_eq!(img.color_space(), ColorSpace::Gray);
+        assert_eq!(img.as_slice_f32().unwrap(), &[0.5, 0.25]);
+
+        let img = ImageOwned::from_owned(vec![0u16; 4], 2, 2, BayerPattern::Rggb.into()).unwrap();
+        assert!(img.add_alpha(0).is_err());
+        let img = ImageOwned::from_owned(vec![0u16; 12], 2, 2, ColorSpace::Custom(3, "X".into()))
+            .unwrap();
+        assert!(img.add_alpha(0).is_err());
+        assert!(img.remove_alpha().is_err());
+    }
+}
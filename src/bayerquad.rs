@@ -0,0 +1,90 @@
+//! Color-aware traversal of Bayer mosaic images.
+
+use crate::{BayerPattern, ColorSpace, ImageProps, ImageRef, PixelStor};
+
+/// A 2x2 cell of a Bayer mosaic image, with the samples labeled by color.
+///
+/// Obtained from [`ImageRef::bayer_quads`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BayerQuad<T: PixelStor> {
+    /// The column of the cell, in units of 2x2 cells.
+    pub x: usize,
+    /// The row of the cell, in units of 2x2 cells.
+    pub y: usize,
+    /// The red sample.
+    pub r: T,
+    /// The green sample on the same row as the red sample.
+    pub g1: T,
+    /// The green sample on the same row as the blue sample.
+    pub g2: T,
+    /// The blue sample.
+    pub b: T,
+}
+
+/// Indices of the red, first green, second green and blue samples in a 2x2 cell,
+/// with the cell ordered top-left, top-right, bottom-left, bottom-right.
+fn quad_order(pattern: BayerPattern) -> [usize; 4] {
+    match pattern {
+        BayerPattern::Rggb => [0, 1, 2, 3],
+        BayerPattern::Bggr => [3, 2, 1, 0],
+        BayerPattern::Grbg => [1, 0, 3, 2],
+        BayerPattern::Gbrg => [2, 3, 0, 1],
+    }
+}
+
+impl<T: PixelStor> ImageRef<'_, T> {
+    /// Iterate over the image as 2x2 cells, with the samples labeled by color
+    /// according to the Bayer pattern of the image.
+    ///
+    /// The cells are visited in row-major order over a `width / 2` by `height / 2`
+    /// grid. If the width or height is odd, the last column or row is skipped.
+    ///
+    /// # Errors
+    /// - If the image is not a Bayer mosaic image.
+    pub fn bayer_quads(&self) -> Result<impl Iterator<Item = BayerQuad<T>> + '_, &'static str> {
+        let ColorSpace::Bayer(pattern) = self.cspace else {
+            return Err("Image is not a Bayer mosaic image.");
+        };
+        let [r, g1, g2, b] = quad_order(pattern);
+        let width = self.width();
+        let (cols, rows) = (width / 2, self.height() / 2);
+        let data = self.as_slice();
+        Ok((0..rows).flat_map(move |y| {
+            (0..cols).map(move |x| {
+                let top = 2 * y * width + 2 * x;
+                let cell = [
+                    data[top],
+                    data[top + 1],
+                    data[top + width],
+                    data[top + width + 1],
+                ];
+                BayerQuad {
+                    x,
+                    y,
+                    r: cell[r],
+                    g1: cell[g1],
+                    g2: cell[g2],
+                    b: cell[b],
+                }
+            })
+        }))
+    }
+}
+
+mod test {
+    #[test]
+    fn test_bayer_quads() {
+        use crate::{BayerPattern, ColorSpace, ImageRef};
+        let mut data: Vec<u8> = (0..20).collect();
+        let img = ImageRef::new(&mut data, 5, 4, ColorSpace::Bayer(BayerPattern::Grbg))
+            .expect("Failed to create ImageRef");
+        let quads: Vec<_> = img.bayer_quads().unwrap().collect();
+        assert_eq!(quads.len(), 4);
+        let q = quads[3];
+        assert_eq!((q.x, q.y), (1, 1));
+        assert_eq!((q.r, q.g1, q.g2, q.b), (13, 12, 18, 17));
+        let img =
+            ImageRef::new(&mut data, 5, 4, ColorSpace::Gray).expect("Failed to create ImageRef");
+        assert!(img.bayer_quads().is_err());
+    }
+}
@@ -0,0 +1,204 @@
+//! Software binning of images.
+
+use std::num::NonZeroUsize;
+
+use num_traits::{NumCast, Zero};
+
+use crate::{
+    ColorSpace, DynamicImageOwned, DynamicImageRef, Enlargeable, ImageOwned, ImageProps, ImageRef,
+    PixelStor,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// How the pixels in a binning block are combined.
+pub enum BinMode {
+    /// Sum the pixels, saturating at the maximum value of the pixel type.
+    Sum,
+    /// Average the pixels.
+    Average,
+}
+
+/// Bin interleaved image data by `bx` by `by` pixels.
+///
+/// Bayer mosaic images are binned by combining pixels of the same color, such that
+/// the output has the same Bayer pattern as the input.
+///
+/// # Errors
+/// - If the image is a Bayer mosaic image, and the binning factors are not even.
+/// - If the binned image would be empty.
+fn run_bin<T: PixelStor + Enlargeable>(
+    data: &[T],
+    width: usize,
+    height: usize,
+    cspace: &ColorSpace,
+    bx: NonZeroUsize,
+    by: NonZeroUsize,
+    mode: BinMode,
+) -> Result<ImageOwned<T>, &'static str> {
+    let (bx, by) = (bx.get(), by.get());
+    let channels = cspace.num_channels();
+    let bayer = cspace.is_bayer();
+    let (owid, ohei) = if bayer {
+        if !bx.is_multiple_of(2) || !by.is_multiple_of(2) {
+            return Err("Bayer images can only be binned by multiples of 2.");
+        }
+        // Keep whole 2x2 cells of the mosaic
+        (2 * (width / (2 * bx)), 2 * (height / (2 * by)))
+    } else {
+        (width / bx, height / by)
+    };
+    if owid == 0 || ohei == 0 {
+        return Err("Binning factor is larger than the image.");
+    }
+    let count: T::Larger = NumCast::from(bx * by).ok_or("Binning factor is too large.")?;
+    // Pixels of the same color are 2 pixels apart in a Bayer mosaic
+    let step = if bayer { 2 } else { 1 };
+    let origin = |pos: usize, factor: usize| {
+        if bayer {
+            2 * factor * (pos / 2) + pos % 2
+        } else {
+            factor * pos
+        }
+    };
+    let mut out = Vec::with_capacity(owid * ohei * channels);
+    for oy in 0..ohei {
+        let y0 = origin(oy, by);
+        for ox in 0..owid {
+            let x0 = origin(ox, bx);
+            for ch in 0..channels {
+                let mut sum = T::Larger::zero();
+                for j in 0..by {
+                    let row = (y0 + j * step) * width;
+                    for i in 0..bx {
+                        sum += data[(row + x0 + i * step) * channels + ch].make_larger();
+                    }
+                }
+                out.push(match mode {
+                    BinMode::Sum => T::clamp_larger(sum),
+                    BinMode::Average => T::clamp_larger(sum / count),
+                });
+            }
+        }
+    }
+    ImageOwned::new(out, owid, ohei, cspace.clone())
+}
+
+macro_rules! impl_bin {
+    ($($t:tt)*) => {
+        $($t)* {
+            /// Bin the image by `bx` by `by` pixels in software.
+            ///
+            /// The binned image is `width / bx` by `height / by` pixels, and remainder
+            /// rows and columns are dropped. Each channel is binned independently, and the
+            /// pixels are accumulated in the larger type of the pixel type (see
+            /// [`Enlargeable`]) to avoid intermediate overflow.
+            ///
+            /// Bayer mosaic images are binned by combining the pixels of the same color,
+            /// and keep their Bayer pattern. Only whole 2x2 cells of the mosaic are kept.
+            ///
+            /// # Arguments
+            /// - `bx`: The horizontal binning factor.
+            /// - `by`: The vertical binning factor.
+            /// - `mode`: How the pixels are combined ([`BinMode`]).
+            ///
+            /// # Errors
+            /// - If the image is a Bayer mosaic image, and `bx` or `by` is not a multiple of 2.
+            /// - If the binned image would be empty.
+            pub fn bin(
+                &self,
+                bx: NonZeroUsize,
+                by: NonZeroUsize,
+                mode: BinMode,
+            ) -> Result<ImageOwned<T>, &'static str> {
+                run_bin(
+                    self.as_slice(),
+                    self.width(),
+                    self.height(),
+                    &self.cspace,
+                    bx,
+                    by,
+                    mode,
+                )
+            }
+        }
+    };
+}
+
+impl_bin!(impl<T: PixelStor + Enlargeable> ImageOwned<T>);
+impl_bin!(impl<T: PixelStor + Enlargeable> ImageRef<'_, T>);
+
+macro_rules! impl_bin_dynamic {
+    ($t:ty) => {
+        impl $t {
+            /// Bin the image by `bx` by `by` pixels in software.
+            ///
+            /// See [`ImageOwned::bin`] for details.
+            ///
+            /// # Errors
+            /// - If the image is a Bayer mosaic image, and `bx` or `by` is not a multiple of 2.
+            /// - If the binned image would be empty.
+            pub fn bin(
+                &self,
+                bx: NonZeroUsize,
+                by: NonZeroUsize,
+                mode: BinMode,
+            ) -> Result<DynamicImageOwned, &'static str> {
+                match self {
+                    Self::U8(img) => Ok(img.bin(bx, by, mode)?.into()),
+                    Self::U16(img) => Ok(img.bin(bx, by, mode)?.into()),
+                    Self::I16(img) => Ok(img.bin(bx, by, mode)?.into()),
+                    Self::U32(img) => Ok(img.bin(bx, by, mode)?.into()),
+                    Self::F32(img) => Ok(img.bin(bx, by, mode)?.into()),
+                }
+            }
+        }
+    };
+}
+
+impl_bin_dynamic!(DynamicImageRef<'_>);
+impl_bin_dynamic!(DynamicImageOwned);
+
+mod test {
+    #[test]
+    fn test_bin() {
+        use super::BinMode;
+        use crate::{BayerPattern, ColorSpace, ImageOwned, ImageProps, ImageRef};
+        use std::num::NonZeroUsize;
+        let two = NonZeroUsize::new(2).unwrap();
+        let three = NonZeroUsize::new(3).unwrap();
+        let data: Vec<u8> = vec![
+            200, 100, 1, 2, 3, //
+            50, 10, 3, 4, 5, //
+            9, 9, 9, 9, 9, //
+        ];
+        let img = ImageOwned::from_owned(data, 5, 3, ColorSpace::Gray).unwrap();
+        let sum = img.bin(two, two, BinMode::Sum).unwrap();
+        assert_eq!((sum.width(), sum.height()), (2, 1));
+        assert_eq!(sum.as_slice(), &[255, 10]);
+        let avg = img.bin(two, two, BinMode::Average).unwrap();
+        assert_eq!(avg.as_slice(), &[90, 2]);
+        assert!(img
+            .bin(three, NonZeroUsize::new(4).unwrap(), BinMode::Sum)
+            .is_err());
+
+        let mut data: Vec<u16> = (0..24).collect();
+        let img = ImageRef::new(&mut data, 4, 2, ColorSpace::Rgb).unwrap();
+        let sum = img.bin(two, two, BinMode::Sum).unwrap();
+        assert_eq!(sum.color_space(), ColorSpace::Rgb);
+        assert_eq!(sum.as_slice(), &[30, 34, 38, 54, 58, 62]);
+
+        // Pixels of the same color are combined, and the pattern is kept
+        let data: Vec<u16> = (0..48).collect();
+        let img = ImageOwned::from_owned(data, 8, 6, BayerPattern::Grbg.into()).unwrap();
+        assert!(img.bin(three, two, BinMode::Sum).is_err());
+        let avg = img.bin(two, two, BinMode::Average).unwrap();
+        assert_eq!(avg.color_space(), BayerPattern::Grbg.into());
+        assert_eq!((avg.width(), avg.height()), (4, 2));
+        assert_eq!(avg.as_slice(), &[9, 10, 13, 14, 17, 18, 21, 22]);
+        let img = crate::DynamicImageOwned::from(img);
+        assert_eq!(
+            img.bin(two, two, BinMode::Average).unwrap().as_slice_u16(),
+            Some(avg.as_slice())
+        );
+    }
+}
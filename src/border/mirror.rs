@@ -10,9 +10,11 @@
 
 use std::cell::RefCell;
 
-use crate::demosaic::BayerResult;
+use num_traits::Zero;
 
-use super::bayer::BayerRead;
+use crate::demosaic::{BayerError, BayerRead, BayerResult};
+
+use super::{BorderMode, ReadRow};
 
 /// Tuple structs (x1, x2, x3) designating the different sub-regions
 /// of the output lines.
@@ -24,31 +26,6 @@ use super::bayer::BayerRead;
 /// ```
 pub struct BorderMirror(usize, usize, usize, usize, RefCell<usize>);
 
-macro_rules! fill_row {
-    ($dst:ident, $x1:expr, $x2:expr, $x3:expr) => {{
-        let mut i;
-        let mut j;
-
-        // Left border.
-        i = $x1;
-        j = $x1 + 1;
-        while i > 0 {
-            $dst[i - 1] = $dst[j];
-            i -= 1;
-            j += 1;
-        }
-
-        // Right border.
-        i = $x2;
-        j = $x2 - 2;
-        while i < $x3 {
-            $dst[i] = $dst[j];
-            i += 1;
-            j -= 1;
-        }
-    }};
-}
-
 impl BorderMirror {
     pub fn new(width: usize, padding: usize) -> Self {
         let x1 = padding;
@@ -70,13 +47,13 @@ impl BorderMirror {
     }
 }
 
-impl<T: Copy> BayerRead<T> for BorderMirror {
+impl<T: Copy + Zero> BayerRead<T> for BorderMirror {
     fn read_row(&self, r: &[T], dst: &mut [T]) -> BayerResult<()> {
-        let (x1, x2, x3, width, start) = self.unpack();
+        let (x1, _, x3, width, start) = self.unpack();
         let end = start.checked_add(width).expect("overflow");
-        dst[x1..x2].copy_from_slice(&r[start..end]);
+        ReadRow::read_row(&BorderMode::Mirror, &r[start..end], x1, &mut dst[..x3])
+            .map_err(|_| BayerError::WrongResolution)?;
         self.update(end);
-        fill_row!(dst, x1, x2, x3);
         Ok(())
     }
 }
@@ -84,7 +61,7 @@ impl<T: Copy> BayerRead<T> for BorderMirror {
 #[cfg(test)]
 mod tests {
     use super::BorderMirror;
-    use crate::demosaic::border_mirror::BayerRead;
+    use crate::demosaic::BayerRead;
 
     #[test]
     fn test_mirror_even() {
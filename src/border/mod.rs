@@ -0,0 +1,149 @@
+//! Border handling shared by the demosaicing algorithms and the convolution filters.
+//!
+//! A [`BorderMode`] describes how the samples outside a row or column of an image are
+//! filled in, e.g. by [`ImageOwned::convolve_separable`](crate::ImageOwned::convolve_separable).
+//! Rows can be padded for custom kernels with [`ReadRow::read_row`].
+//!
+//! The demosaicing algorithms that mirror the border pad the rows of the mosaic through
+//! the same [`ReadRow`] implementation, since mirroring about the edge sample keeps the
+//! color filter phase of the padded samples. Replicating the edge sample does not, hence
+//! the demosaicing algorithms that replicate the border repeat the edge pair of samples,
+//! i.e. one period of the mosaic, instead of using [`BorderMode::Replicate`].
+//!
+//! # Usage
+//! ```
+//! use refimage::border::{BorderMode, ReadRow};
+//!
+//! let row = [1u8, 2, 3, 4];
+//! let mut padded = [0u8; 4 + 2 * 2];
+//! BorderMode::Mirror.read_row(&row, 2, &mut padded).unwrap();
+//! assert_eq!(padded, [3, 2, 1, 2, 3, 4, 3, 2]);
+//! ```
+use num_traits::Zero;
+
+mod mirror;
+mod none;
+mod replicate;
+
+pub(crate) use mirror::BorderMirror;
+pub(crate) use none::BorderNone;
+pub(crate) use replicate::BorderReplicate;
+
+/// How samples outside the image are treated by filters such as
+/// [`ImageOwned::convolve_separable`](crate::ImageOwned::convolve_separable).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BorderMode {
+    /// Samples outside the image are zero.
+    Zero,
+    /// Samples outside the image take the value of the nearest edge sample.
+    #[default]
+    Replicate,
+    /// Samples outside the image are mirrored about the edge sample, without
+    /// repeating it, i.e. `... x2 x1 | x0 x1 x2 ...`.
+    Mirror,
+}
+
+impl BorderMode {
+    /// Map the index `idx` of a sample in a line of `len` samples to the index of the
+    /// sample to read, or [`None`] if the sample is zero.
+    pub(crate) fn index(self, idx: isize, len: usize) -> Option<usize> {
+        let len = len as isize;
+        if (0..len).contains(&idx) {
+            return Some(idx as usize);
+        }
+        match self {
+            BorderMode::Zero => None,
+            BorderMode::Replicate => Some(idx.clamp(0, len - 1) as usize),
+            BorderMode::Mirror if len == 1 => Some(0),
+            BorderMode::Mirror => {
+                let period = 2 * (len - 1);
+                let idx = idx.rem_euclid(period);
+                Some(if idx < len { idx } else { period - idx } as usize)
+            }
+        }
+    }
+
+    /// Pad a row of samples with `padding` samples on either side, using `zero` for
+    /// [`BorderMode::Zero`]. The caller checks the lengths of the rows.
+    pub(crate) fn pad_row<T: Copy>(self, src: &[T], padding: usize, dst: &mut [T], zero: T) {
+        let len = src.len();
+        dst[padding..padding + len].copy_from_slice(src);
+        let border = (0..padding).chain(padding + len..dst.len());
+        for i in border {
+            dst[i] = self
+                .index(i as isize - padding as isize, len)
+                .map_or(zero, |j| src[j]);
+        }
+    }
+}
+
+/// Padding of rows of samples, for filters that read past the edges of an image.
+pub trait ReadRow<T> {
+    /// Pad a row of samples with `padding` samples on either side.
+    ///
+    /// # Arguments
+    /// - `src`: The samples of the row.
+    /// - `padding`: The number of samples to add on either side of the row.
+    /// - `dst`: The padded row, of length `src.len() + 2 * padding`.
+    ///
+    /// # Errors
+    /// - If `src` is empty.
+    /// - If the length of `dst` is not `src.len() + 2 * padding`.
+    fn read_row(&self, src: &[T], padding: usize, dst: &mut [T]) -> Result<(), &'static str>;
+}
+
+impl<T: Copy + Zero> ReadRow<T> for BorderMode {
+    fn read_row(&self, src: &[T], padding: usize, dst: &mut [T]) -> Result<(), &'static str> {
+        if src.is_empty() {
+            return Err("Row is empty.");
+        }
+        if Some(dst.len())
+            != padding
+                .checked_mul(2)
+                .and_then(|p| p.checked_add(src.len()))
+        {
+            return Err("Destination length does not match the padded row.");
+        }
+        self.pad_row(src, padding, dst, T::zero());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BorderMode, ReadRow};
+
+    #[test]
+    fn test_border_mode() {
+        let idx = |mode: BorderMode| -> Vec<Option<usize>> {
+            (-3..7).map(|i| mode.index(i, 4)).collect()
+        };
+        // Negative indices mark zero samples
+        let zero = [-1, -1, -1, 0, 1, 2, 3, -1, -1, -1].map(|i: isize| usize::try_from(i).ok());
+        assert_eq!(idx(BorderMode::Zero), zero);
+        assert_eq!(
+            idx(BorderMode::Replicate),
+            [0, 0, 0, 0, 1, 2, 3, 3, 3, 3].map(Some)
+        );
+        assert_eq!(
+            idx(BorderMode::Mirror),
+            [3, 2, 1, 0, 1, 2, 3, 2, 1, 0].map(Some)
+        );
+        assert_eq!(BorderMode::Mirror.index(-2, 1), Some(0));
+    }
+
+    #[test]
+    fn test_read_row() {
+        let src = [1u16, 2, 3];
+        let mut dst = [0u16; 3 + 2 * 4];
+        BorderMode::Zero.read_row(&src, 4, &mut dst).unwrap();
+        assert_eq!(dst, [0, 0, 0, 0, 1, 2, 3, 0, 0, 0, 0]);
+        BorderMode::Replicate.read_row(&src, 4, &mut dst).unwrap();
+        assert_eq!(dst, [1, 1, 1, 1, 1, 2, 3, 3, 3, 3, 3]);
+        // Mirrored periodically if the padding exceeds the row
+        BorderMode::Mirror.read_row(&src, 4, &mut dst).unwrap();
+        assert_eq!(dst, [1, 2, 3, 2, 1, 2, 3, 2, 1, 2, 3]);
+        assert!(BorderMode::Zero.read_row(&src, 3, &mut dst).is_err());
+        assert!(ReadRow::<u8>::read_row(&BorderMode::Zero, &[], 0, &mut []).is_err());
+    }
+}
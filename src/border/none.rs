@@ -1,10 +1,11 @@
 //! Bayer reader without any additional border logic.
+//!
+//! Rows are copied without padding, for demosaicing algorithms that do not read past
+//! the edges of the image, hence no [`super::BorderMode`] applies.
 
 use std::cell::RefCell;
 
-use crate::demosaic::BayerResult;
-
-use super::bayer::BayerRead;
+use crate::demosaic::{BayerRead, BayerResult};
 
 pub struct BorderNone(RefCell<usize>);
 
@@ -7,12 +7,14 @@
 //! ```text
 //!   r0' g0' r0' g0' | r0 g0 r1 g1 r2 g2 ... rl gl rm gm rn gn | rn' gn' rn' gn'
 //! ```
+//!
+//! Unlike [`BorderMode::Replicate`](super::BorderMode::Replicate), which repeats the edge
+//! sample, the edge pair of samples is repeated, so that the padded samples keep the
+//! color filter phase of the mosaic. Hence this reader does not use [`super::ReadRow`].
 
 use std::cell::RefCell;
 
-use crate::demosaic::BayerResult;
-
-use super::bayer::BayerRead;
+use crate::demosaic::{BayerRead, BayerResult};
 
 /// Tuple structs (x1, x2, x3) designating the different sub-regions
 /// of the output lines.
@@ -92,7 +94,7 @@ impl<T: Copy> BayerRead<T> for BorderReplicate {
 #[cfg(test)]
 mod tests {
     use super::BorderReplicate;
-    use crate::demosaic::border_replicate::BayerRead;
+    use crate::demosaic::BayerRead;
 
     #[test]
     fn test_replicate_even() {
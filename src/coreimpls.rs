@@ -1,8 +1,22 @@
 #[cfg(feature = "rayon")]
-use rayon::{iter::ParallelIterator, slice::ParallelSliceMut};
+use rayon::{
+    iter::{
+        IndexedParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator,
+        ParallelIterator,
+    },
+    slice::{ParallelSlice, ParallelSliceMut},
+};
 
-use crate::{demosaic::ColorFilterArray, BayerPattern, ColorSpace, PixelStor, PixelType};
+use crate::{
+    border::BorderMode, demosaic::ColorFilterArray, BayerPattern, BayerShift, ColorSpace,
+    Enlargeable, ImageProps, PixelStor, PixelType, RefImageError, Rotation,
+};
+use num_traits::{Bounded, Float, Zero};
 
+use crate::coretraits::{clamp_from_f64, large_to_f64};
+use crate::optimumexposure::percentile_index;
+
+/// Get the [`PixelType`] from its `i8` representation, i.e. the discriminant of the enum.
 impl TryFrom<i8> for PixelType {
     type Error = &'static str;
 
@@ -10,12 +24,50 @@ impl TryFrom<i8> for PixelType {
         match value {
             8 => Ok(Self::U8),
             16 => Ok(Self::U16),
+            32 => Ok(Self::U32),
+            64 => Ok(Self::U64),
+            -8 => Ok(Self::I8),
+            -16 => Ok(Self::I16),
+            -128 => Ok(Self::I32),
+            -78 => Ok(Self::I64),
+            #[cfg(feature = "f16")]
+            -17 => Ok(Self::F16),
             -32 => Ok(Self::F32),
+            -64 => Ok(Self::F64),
             _ => Err("Invalid value for PixelType"),
         }
     }
 }
 
+impl PixelType {
+    /// Check if the pixel type is a floating point type.
+    pub fn is_float(&self) -> bool {
+        match self {
+            Self::F32 | Self::F64 => true,
+            #[cfg(feature = "f16")]
+            Self::F16 => true,
+            _ => false,
+        }
+    }
+
+    /// Check if the pixel type is signed, i.e. a signed integer or floating point type.
+    pub fn is_signed(&self) -> bool {
+        !matches!(self, Self::U8 | Self::U16 | Self::U32 | Self::U64)
+    }
+
+    /// Get the size of one pixel value of this type in bytes.
+    pub fn size_bytes(&self) -> usize {
+        match self {
+            Self::U8 | Self::I8 => 1,
+            Self::U16 | Self::I16 => 2,
+            #[cfg(feature = "f16")]
+            Self::F16 => 2,
+            Self::U32 | Self::I32 | Self::F32 => 4,
+            Self::U64 | Self::I64 | Self::F64 => 8,
+        }
+    }
+}
+
 impl TryInto<ColorFilterArray> for ColorSpace {
     type Error = &'static str;
 
@@ -30,6 +82,10 @@ impl TryInto<ColorFilterArray> for ColorSpace {
             ColorSpace::Gray => Err("Gray color space not supported in Bayer images."),
             ColorSpace::Rgb => Err("RGB color space not supported in Bayer images."),
             ColorSpace::Custom(_, _) => Err("Custom color space not supported in Bayer images."),
+            ColorSpace::Cmyk => Err("CMYK color space not supported in Bayer images."),
+            ColorSpace::GrayAlpha | ColorSpace::Rgba => {
+                Err("Alpha color spaces not supported in Bayer images.")
+            }
         }
     }
 }
@@ -41,6 +97,90 @@ impl Into<ColorSpace> for BayerPattern {
     }
 }
 
+impl BayerPattern {
+    /// Get the position `(x, y)` of the red pixel in the first 2x2 cell of the mosaic.
+    pub(crate) fn red_pixel(&self) -> (usize, usize) {
+        match self {
+            BayerPattern::Rggb => (0, 0),
+            BayerPattern::Grbg => (1, 0),
+            BayerPattern::Gbrg => (0, 1),
+            BayerPattern::Bggr => (1, 1),
+        }
+    }
+
+    /// Get the Bayer pattern with the red pixel of the first 2x2 cell at `(x % 2, y % 2)`.
+    pub(crate) fn from_red_pixel(x: usize, y: usize) -> Self {
+        match (x % 2, y % 2) {
+            (0, 0) => BayerPattern::Rggb,
+            (1, 0) => BayerPattern::Grbg,
+            (0, 1) => BayerPattern::Gbrg,
+            _ => BayerPattern::Bggr,
+        }
+    }
+}
+
+/// Rec. 601 luminance coefficients for RGB images.
+pub(crate) const LUMA_COEFFS: [f64; 3] = [0.299, 0.587, 0.114];
+
+/// Check that there is one luminance coefficient per channel, and that the
+/// coefficients sum to 1.
+pub(crate) fn check_luma_coeffs(channels: usize, wts: &[f64]) -> Result<(), &'static str> {
+    if channels != wts.len() {
+        return Err("Number of channels and weights do not match.");
+    }
+    if (wts.iter().sum::<f64>() - 1.0).abs() > 1e-3 {
+        return Err("Luma coefficients must sum to 1.");
+    }
+    Ok(())
+}
+
+/// Convert interleaved CMYK data to luminance in-place, storing the result in the first
+/// quarter of the data.
+///
+/// Each pixel is converted to RGB as `(1 - C) * (1 - K)` etc. on values normalized to
+/// the valid range of `T`, and the luminance is the weighted sum of the RGB values.
+pub(crate) fn run_luma_cmyk<T: PixelStor>(
+    len: usize,
+    data: &mut [T],
+    wts: &[f64],
+) -> Result<(), &'static str> {
+    if wts.len() != 3 {
+        return Err("Number of channels and weights do not match.");
+    }
+    let min = PixelStor::to_f64(T::DEFAULT_MIN_VALUE);
+    let max = PixelStor::to_f64(T::DEFAULT_MAX_VALUE);
+    let luma = |px: &[T]| {
+        let ink = |v: T| (PixelStor::to_f64(v) - min) / (max - min);
+        let k = 1.0 - ink(px[3]);
+        let v = px[..3]
+            .iter()
+            .zip(wts.iter())
+            .fold(0f64, |acc, (&c, &w)| acc + (1.0 - ink(c)) * k * w);
+        let v = min + v * (max - min);
+        clamp_from_f64(if T::PIXEL_TYPE.is_float() {
+            v
+        } else {
+            v.round()
+        })
+    };
+    #[cfg(not(feature = "rayon"))]
+    {
+        for i in 0..len / 4 {
+            data[i] = luma(&data[i * 4..(i + 1) * 4]);
+        }
+    }
+    #[cfg(feature = "rayon")]
+    {
+        data[..len]
+            .par_chunks_exact_mut(4)
+            .for_each(|px| px[0] = luma(px));
+        for i in 0..len / 4 {
+            data[i] = data[i * 4];
+        }
+    }
+    Ok(())
+}
+
 /// Run the luminance conversion on a slice of pixel data.
 pub(crate) fn run_luma<T: PixelStor>(
     channels: usize,
@@ -100,9 +240,943 @@ pub(crate) fn run_luma<T: PixelStor>(
     Ok(())
 }
 
+/// Check that two images have the same dimensions and color space.
+///
+/// # Errors
+/// Describes the first property that does not match.
+pub(crate) fn check_same_layout<A: ImageProps, B: ImageProps>(
+    a: &A,
+    b: &B,
+) -> Result<(), &'static str> {
+    if a.width() != b.width() {
+        Err("Image widths do not match.")
+    } else if a.height() != b.height() {
+        Err("Image heights do not match.")
+    } else if a.channels() != b.channels() {
+        Err("Image channel counts do not match.")
+    } else if a.color_space() != b.color_space() {
+        Err("Image color spaces do not match.")
+    } else {
+        Ok(())
+    }
+}
+
+/// Subtract `b` from `a` element-wise, saturating at [`PixelStor::DEFAULT_MIN_VALUE`].
+pub(crate) fn run_checked_sub<T: PixelStor + Enlargeable>(a: &[T], b: &[T]) -> Vec<T> {
+    let signed = T::Larger::min_value() < T::Larger::zero();
+    a.iter()
+        .zip(b.iter())
+        .map(|(&a, &b)| {
+            let (a, b) = (a.make_larger(), b.make_larger());
+            // Unsigned larger types can not hold negative differences
+            if a < b && !signed {
+                return T::DEFAULT_MIN_VALUE;
+            }
+            let v = T::clamp_larger(a - b);
+            if v < T::DEFAULT_MIN_VALUE {
+                T::DEFAULT_MIN_VALUE
+            } else {
+                v
+            }
+        })
+        .collect()
+}
+
+/// Divide `data` by `flat` element-wise and multiply by `scale`, saturating into the
+/// valid range of the type. Division by zero yields [`PixelStor::DEFAULT_MAX_VALUE`].
+pub(crate) fn run_flat_divide<T: PixelStor + Enlargeable>(
+    data: &[T],
+    flat: &[T],
+    scale: f64,
+) -> Vec<T> {
+    let min = T::DEFAULT_MIN_VALUE.to_f64();
+    let max = T::DEFAULT_MAX_VALUE.to_f64();
+    let round = !T::PIXEL_TYPE.is_float();
+    data.iter()
+        .zip(flat.iter())
+        .map(|(&v, &f)| {
+            let f = large_to_f64(f.make_larger());
+            if f == 0.0 {
+                return T::DEFAULT_MAX_VALUE;
+            }
+            let v = large_to_f64(v.make_larger()) / f * scale;
+            let v = if round { v.round() } else { v };
+            // NaN (e.g. from an infinite flat) maps to the minimum
+            T::from_f64(v.clamp(min, max).max(min))
+        })
+        .collect()
+}
+
+/// Reverse the order of the pixels in each row of interleaved image data.
+pub(crate) fn flip_rows<T: Copy>(data: &[T], width: usize, channels: usize) -> Vec<T> {
+    let mut out = Vec::with_capacity(data.len());
+    for row in data.chunks_exact(width * channels) {
+        for px in row.chunks_exact(channels).rev() {
+            out.extend_from_slice(px);
+        }
+    }
+    out
+}
+
+/// Reverse the order of the rows of interleaved image data.
+pub(crate) fn flip_cols<T: Copy>(data: &[T], width: usize, channels: usize) -> Vec<T> {
+    let mut out = Vec::with_capacity(data.len());
+    for row in data.chunks_exact(width * channels).rev() {
+        out.extend_from_slice(row);
+    }
+    out
+}
+
+/// Get the color space of an image flipped along a dimension of size `extent`.
+///
+/// The Bayer pattern changes only if the flipped dimension is even.
+pub(crate) fn flip_cspace(cspace: &ColorSpace, extent: usize, horizontal: bool) -> ColorSpace {
+    match cspace {
+        ColorSpace::Bayer(pat) if extent.is_multiple_of(2) => ColorSpace::Bayer(if horizontal {
+            pat.flip_horizontal()
+        } else {
+            pat.flip_vertical()
+        }),
+        cspace => cspace.clone(),
+    }
+}
+
+/// Map the coordinates of a pixel in an image of size `(width, height)` to its
+/// coordinates after rotation.
+fn rotate_coords(
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    rotation: Rotation,
+) -> (usize, usize) {
+    match rotation {
+        Rotation::Rotate90 => (height - 1 - y, x),
+        Rotation::Rotate180 => (width - 1 - x, height - 1 - y),
+        Rotation::Rotate270 => (y, width - 1 - x),
+    }
+}
+
+/// Rotate interleaved image data, moving whole pixels.
+///
+/// The output is `height` pixels wide for rotations by 90 and 270 degrees.
+pub(crate) fn run_rotate<T: Copy>(
+    data: &[T],
+    width: usize,
+    height: usize,
+    channels: usize,
+    rotation: Rotation,
+) -> Vec<T> {
+    let owid = match rotation {
+        Rotation::Rotate180 => width,
+        Rotation::Rotate90 | Rotation::Rotate270 => height,
+    };
+    let mut out = data[..width * height * channels].to_vec();
+    for (idx, px) in data.chunks_exact(channels).take(width * height).enumerate() {
+        let (x, y) = rotate_coords(idx % width, idx / width, width, height, rotation);
+        let dst = (y * owid + x) * channels;
+        out[dst..dst + channels].copy_from_slice(px);
+    }
+    out
+}
+
+/// Get the color space of an image of size `(width, height)` after rotation.
+///
+/// The Bayer pattern is remapped by tracking where the red pixel of the first
+/// 2x2 cell of the mosaic ends up.
+pub(crate) fn rotate_cspace(
+    cspace: &ColorSpace,
+    width: usize,
+    height: usize,
+    rotation: Rotation,
+) -> ColorSpace {
+    let ColorSpace::Bayer(pat) = cspace else {
+        return cspace.clone();
+    };
+    let (rx, ry) = pat.red_pixel();
+    // Only the parity of the coordinates matters, so pad the dimensions to keep
+    // the red pixel within the bounds without changing their parity.
+    let (x, y) = rotate_coords(rx, ry, width + 2, height + 2, rotation);
+    ColorSpace::Bayer(BayerPattern::from_red_pixel(x, y))
+}
+
+/// Transpose interleaved image data, moving whole pixels.
+///
+/// The output is `height` pixels wide and `width` pixels high.
+pub(crate) fn run_transpose<T: Copy>(
+    data: &[T],
+    width: usize,
+    height: usize,
+    channels: usize,
+) -> Vec<T> {
+    let mut out = data[..width * height * channels].to_vec();
+    for (idx, px) in data.chunks_exact(channels).take(width * height).enumerate() {
+        let (x, y) = (idx % width, idx / width);
+        let dst = (x * height + y) * channels;
+        out[dst..dst + channels].copy_from_slice(px);
+    }
+    out
+}
+
+/// Get the color space of an image after transposition.
+///
+/// The red pixel of the first 2x2 cell of a Bayer mosaic moves from `(x, y)` to
+/// `(y, x)`, so the two patterns with green on the main diagonal are swapped.
+pub(crate) fn transpose_cspace(cspace: &ColorSpace) -> ColorSpace {
+    match cspace {
+        ColorSpace::Bayer(BayerPattern::Grbg) => ColorSpace::Bayer(BayerPattern::Gbrg),
+        ColorSpace::Bayer(BayerPattern::Gbrg) => ColorSpace::Bayer(BayerPattern::Grbg),
+        cspace => cspace.clone(),
+    }
+}
+
+/// Guess the Bayer pattern of single-channel mosaic data from the mean intensities of
+/// its four 2x2 sub-lattices.
+///
+/// Both green sub-lattices sample the same color, so the diagonal whose means agree more
+/// closely is taken to be green, and the brighter of the remaining two sub-lattices is
+/// taken to be red. Returns [`None`] if the red and blue means differ by no more than
+/// `tolerance` relative to the larger of the two, or if the image is smaller than 2x2.
+pub(crate) fn guess_bayer_pattern<T: PixelStor>(
+    data: &[T],
+    width: usize,
+    height: usize,
+    tolerance: f64,
+) -> Option<BayerPattern> {
+    if width < 2 || height < 2 {
+        return None;
+    }
+    let mut sums = [[0f64; 2]; 2];
+    let mut counts = [[0usize; 2]; 2];
+    for (y, row) in data.chunks_exact(width).take(height).enumerate() {
+        for (x, &v) in row.iter().enumerate() {
+            sums[y % 2][x % 2] += v.to_f64();
+            counts[y % 2][x % 2] += 1;
+        }
+    }
+    let mean = |(x, y): (usize, usize)| sums[y][x] / counts[y][x] as f64;
+    // Relative difference of the means of two sub-lattices
+    let spread = |a, b| {
+        let (a, b) = (mean(a), mean(b));
+        let scale = a.abs().max(b.abs());
+        if scale > 0.0 {
+            (a - b).abs() / scale
+        } else {
+            0.0
+        }
+    };
+    // Green occupies either the main or the anti-diagonal of each 2x2 cell, and the
+    // other diagonal holds the red and blue sub-lattices, as (x, y)
+    let main = ((0, 0), (1, 1));
+    let anti = ((1, 0), (0, 1));
+    let (a, b) = if spread(main.0, main.1) < spread(anti.0, anti.1) {
+        anti
+    } else {
+        main
+    };
+    if spread(a, b) <= tolerance {
+        return None;
+    }
+    let (rx, ry) = if mean(a) > mean(b) { a } else { b };
+    Some(BayerPattern::from_red_pixel(rx, ry))
+}
+
+/// Get the color space of an image after selecting a region with origin `(x, y)`.
+///
+/// The Bayer pattern is shifted by the parity of the origin, which may be negative.
+pub(crate) fn crop_cspace(cspace: &ColorSpace, x: isize, y: isize) -> ColorSpace {
+    match cspace {
+        ColorSpace::Bayer(pat) => {
+            ColorSpace::Bayer(pat.shift(x.rem_euclid(2) as usize, y.rem_euclid(2) as usize))
+        }
+        cspace => cspace.clone(),
+    }
+}
+
+/// Get the number of samples of an image, checking its dimensions.
+pub(crate) fn checked_len(
+    width: usize,
+    height: usize,
+    channels: usize,
+) -> Result<usize, RefImageError> {
+    if height > u16::MAX as usize || width > u16::MAX as usize {
+        return Err(RefImageError::TooLarge);
+    }
+    if width == 0 {
+        return Err(RefImageError::ZeroWidth);
+    }
+    if height == 0 {
+        return Err(RefImageError::ZeroHeight);
+    }
+    width
+        .checked_mul(height)
+        .and_then(|n| n.checked_mul(channels))
+        .ok_or(RefImageError::TooLarge)
+}
+
+/// Copy a `width` by `height` region with origin `(x, y)` out of interleaved image
+/// data, filling the pixels that lie outside the image with `fill`.
+///
+/// The dimensions of the region are checked before the output is allocated.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn run_select_padded<T: PixelStor>(
+    data: &[T],
+    swid: usize,
+    shei: usize,
+    channels: usize,
+    x: isize,
+    y: isize,
+    width: usize,
+    height: usize,
+    fill: T,
+) -> Result<Vec<T>, RefImageError> {
+    let mut out = vec![fill; checked_len(width, height, channels)?];
+    // Columns of the region that overlap the image
+    let x0 = x.clamp(0, swid as isize);
+    let x1 = x.saturating_add(width as isize).clamp(0, swid as isize);
+    if x1 <= x0 {
+        return Ok(out);
+    }
+    let (x0, x1) = (x0 as usize, x1 as usize);
+    let dx = (x0 as isize - x) as usize;
+    for (oy, row) in out.chunks_exact_mut(width * channels).enumerate() {
+        let sy = y.saturating_add(oy as isize);
+        if sy < 0 || sy >= shei as isize {
+            continue;
+        }
+        let src = &data[(sy as usize * swid + x0) * channels..(sy as usize * swid + x1) * channels];
+        row[dx * channels..dx * channels + src.len()].copy_from_slice(src);
+    }
+    Ok(out)
+}
+
+/// Paste interleaved `src` image data into `dst` with origin `(x, y)`.
+///
+/// Only the region that overlaps the destination is written; all other destination
+/// pixels are left untouched.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn run_paste<T: Copy>(
+    src: &[T],
+    swid: usize,
+    shei: usize,
+    dst: &mut [T],
+    dwid: usize,
+    dhei: usize,
+    channels: usize,
+    x: usize,
+    y: usize,
+) {
+    if x >= dwid || y >= dhei {
+        return;
+    }
+    let wid = swid.min(dwid - x) * channels;
+    for (sy, row) in src
+        .chunks_exact(swid * channels)
+        .take(shei.min(dhei - y))
+        .enumerate()
+    {
+        let start = ((y + sy) * dwid + x) * channels;
+        dst[start..start + wid].copy_from_slice(&row[..wid]);
+    }
+}
+
+/// Description of the color space of images produced by `to_hsv`.
+pub(crate) const HSV_DESCRIPTION: &str = "HSV";
+
+/// Convert interleaved RGB data to interleaved HSV data.
+///
+/// The RGB values are scaled to `[0, 1]` using the valid range of the type. The hue
+/// is in degrees, in `[0, 360)`, and the saturation and value are in `[0, 1]`.
+pub(crate) fn run_rgb_to_hsv<T: PixelStor>(data: &[T]) -> Vec<f32> {
+    let min = T::DEFAULT_MIN_VALUE.to_f32();
+    let scale = T::DEFAULT_MAX_VALUE.to_f32() - min;
+    data.chunks_exact(3)
+        .flat_map(|px| {
+            let [r, g, b] = [px[0], px[1], px[2]].map(|v| (v.to_f32() - min) / scale);
+            let max = r.max(g).max(b);
+            let delta = max - r.min(g).min(b);
+            let hue = if delta <= 0.0 {
+                0.0
+            } else if max == r {
+                60.0 * ((g - b) / delta)
+            } else if max == g {
+                60.0 * ((b - r) / delta + 2.0)
+            } else {
+                60.0 * ((r - g) / delta + 4.0)
+            };
+            let sat = if max <= 0.0 { 0.0 } else { delta / max };
+            [wrap_hue(hue), sat, max]
+        })
+        .collect()
+}
+
+/// Convert interleaved HSV data, as produced by [`run_rgb_to_hsv`], to interleaved
+/// RGB data in `[0, 1]`.
+pub(crate) fn run_hsv_to_rgb(data: &[f32]) -> Vec<f32> {
+    data.chunks_exact(3)
+        .flat_map(|px| {
+            let hue = wrap_hue(px[0]) / 60.0;
+            let (sat, val) = (px[1].clamp(0.0, 1.0), px[2].clamp(0.0, 1.0));
+            let chroma = val * sat;
+            let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+            let (r, g, b) = match hue as u8 {
+                0 => (chroma, x, 0.0),
+                1 => (x, chroma, 0.0),
+                2 => (0.0, chroma, x),
+                3 => (0.0, x, chroma),
+                4 => (x, 0.0, chroma),
+                _ => (chroma, 0.0, x),
+            };
+            let m = val - chroma;
+            [r + m, g + m, b + m]
+        })
+        .collect()
+}
+
+/// Wrap a hue in degrees to `[0, 360)`.
+fn wrap_hue(hue: f32) -> f32 {
+    let hue = hue.rem_euclid(360.0);
+    // rem_euclid can round up to 360 for tiny negative values
+    if hue >= 360.0 {
+        0.0
+    } else {
+        hue
+    }
+}
+
+/// Scale the deviation of each RGB channel from the pixel luminance by `factor`.
+pub(crate) fn run_saturation<T: PixelStor>(data: &mut [T], factor: f64) {
+    let min = T::DEFAULT_MIN_VALUE.to_f64();
+    let max = T::DEFAULT_MAX_VALUE.to_f64();
+    let saturate = |chunk: &mut [T]| {
+        let luma = chunk
+            .iter()
+            .zip(LUMA_COEFFS.iter())
+            .fold(0f64, |acc, (px, &w)| acc + (*px).to_f64() * w);
+        for px in chunk.iter_mut() {
+            let v = luma + ((*px).to_f64() - luma) * factor;
+            *px = T::from_f64(v.clamp(min, max));
+        }
+    };
+    #[cfg(not(feature = "rayon"))]
+    {
+        data.chunks_exact_mut(3).for_each(saturate);
+    }
+    #[cfg(feature = "rayon")]
+    {
+        data.par_chunks_exact_mut(3).for_each(saturate);
+    }
+}
+
+/// Apply `out = max * (in / max)^gamma` to each color channel, skipping the alpha
+/// channel, if any. The values are normalized by the valid range of the pixel type.
+pub(crate) fn run_gamma<T: PixelStor>(data: &mut [T], channels: usize, alpha: bool, gamma: f64) {
+    let min = T::DEFAULT_MIN_VALUE.to_f64();
+    let max = T::DEFAULT_MAX_VALUE.to_f64();
+    let colors = if alpha { channels - 1 } else { channels };
+    let round = !T::PIXEL_TYPE.is_float();
+    let correct = |chunk: &mut [T]| {
+        for px in chunk[..colors].iter_mut() {
+            let v = (((*px).to_f64() - min) / (max - min)).max(0.0);
+            let v = min + (max - min) * v.powf(gamma);
+            let v = if round { v.round() } else { v };
+            *px = T::from_f64(v.clamp(min, max));
+        }
+    };
+    #[cfg(not(feature = "rayon"))]
+    {
+        data.chunks_exact_mut(channels).for_each(correct);
+    }
+    #[cfg(feature = "rayon")]
+    {
+        data.par_chunks_exact_mut(channels).for_each(correct);
+    }
+}
+
+/// Replace every non-finite value with `replacement`.
+///
+/// Note: This operation is parallelized if the `rayon` feature is enabled.
+pub(crate) fn run_sanitize<T: PixelStor + Float>(data: &mut [T], replacement: T) {
+    let replace = |v: &mut T| {
+        if !v.is_finite() {
+            *v = replacement;
+        }
+    };
+    #[cfg(not(feature = "rayon"))]
+    {
+        data.iter_mut().for_each(replace);
+    }
+    #[cfg(feature = "rayon")]
+    {
+        data.par_iter_mut().for_each(replace);
+    }
+}
+
+/// Check whether any value is NaN or infinite.
+///
+/// Note: This operation is parallelized if the `rayon` feature is enabled.
+pub(crate) fn run_has_non_finite(data: &[f32]) -> bool {
+    #[cfg(not(feature = "rayon"))]
+    {
+        data.iter().any(|v| !v.is_finite())
+    }
+    #[cfg(feature = "rayon")]
+    {
+        data.par_iter().any(|v| !v.is_finite())
+    }
+}
+
+/// Apply `f` to every element in place.
+///
+/// Note: This operation is parallelized if the `rayon` feature is enabled.
+pub(crate) fn run_map_pixels<T: PixelStor, F: Fn(T) -> T + Sync>(data: &mut [T], f: F) {
+    #[cfg(not(feature = "rayon"))]
+    {
+        data.iter_mut().for_each(|v| *v = f(*v));
+    }
+    #[cfg(feature = "rayon")]
+    {
+        data.par_iter_mut().for_each(|v| *v = f(*v));
+    }
+}
+
+/// Apply `f` to every element, collecting the results.
+///
+/// Note: This operation is parallelized if the `rayon` feature is enabled.
+pub(crate) fn run_map_pixels_to<T: PixelStor, U: PixelStor, F: Fn(T) -> U + Sync>(
+    data: &[T],
+    f: F,
+) -> Vec<U> {
+    #[cfg(not(feature = "rayon"))]
+    {
+        data.iter().map(|&v| f(v)).collect()
+    }
+    #[cfg(feature = "rayon")]
+    {
+        data.par_iter().map(|&v| f(v)).collect()
+    }
+}
+
+/// Number of histogram bins used for floating point images.
+pub(crate) const FLOAT_HISTOGRAM_BINS: usize = 4096;
+
+/// Number of histogram bins that resolve every value of the pixel type.
+pub(crate) fn histogram_bins<T: PixelStor>() -> usize {
+    match T::PIXEL_TYPE {
+        PixelType::U8 | PixelType::I8 => 1 << 8,
+        PixelType::U16 | PixelType::I16 => 1 << 16,
+        _ => FLOAT_HISTOGRAM_BINS,
+    }
+}
+
+/// Find the histogram bin of a value, with the bins spanning the valid range of the type.
+pub(crate) fn histogram_bin<T: PixelStor>(value: T, bins: usize) -> usize {
+    let min = T::DEFAULT_MIN_VALUE.to_f64();
+    let max = T::DEFAULT_MAX_VALUE.to_f64();
+    let pos = (value.to_f64() - min) / (max - min) * bins as f64;
+    (pos.max(0.0) as usize).min(bins - 1)
+}
+
+/// Build the histogram of a single channel of interleaved image data.
+pub(crate) fn run_histogram<T: PixelStor>(
+    data: &[T],
+    channels: usize,
+    channel: usize,
+    bins: usize,
+) -> Vec<usize> {
+    let mut hist = vec![0usize; bins];
+    data.iter()
+        .skip(channel)
+        .step_by(channels)
+        .for_each(|&v| hist[histogram_bin(v, bins)] += 1);
+    hist
+}
+
+/// Build the histograms of all channels of interleaved image data.
+///
+/// Note: This operation is parallelized if the `rayon` feature is enabled.
+pub(crate) fn run_histograms<T: PixelStor>(
+    data: &[T],
+    channels: usize,
+    bins: usize,
+) -> Vec<Vec<u64>> {
+    let accumulate = |mut hist: Vec<Vec<u64>>, chunk: &[T]| {
+        for px in chunk.chunks_exact(channels) {
+            for (hist, &v) in hist.iter_mut().zip(px.iter()) {
+                hist[histogram_bin(v, bins)] += 1;
+            }
+        }
+        hist
+    };
+    #[cfg(not(feature = "rayon"))]
+    {
+        accumulate(vec![vec![0; bins]; channels], data)
+    }
+    #[cfg(feature = "rayon")]
+    {
+        // Chunks must contain whole pixels
+        data.par_chunks(channels * 4096)
+            .fold(|| vec![vec![0; bins]; channels], accumulate)
+            .reduce(
+                || vec![vec![0; bins]; channels],
+                |mut a, b| {
+                    a.iter_mut()
+                        .flatten()
+                        .zip(b.iter().flatten())
+                        .for_each(|(a, b)| *a += b);
+                    a
+                },
+            )
+    }
+}
+
+/// Stretch interleaved image data to [`u8`], mapping the `[low, high]` percentile
+/// window of each channel linearly to `[0, 255]`.
+///
+/// Note: This operation is parallelized if the `rayon` feature is enabled.
+///
+/// # Errors
+/// - If `low` or `high` is not in `[0, 1]`, or `low` is not less than `high`.
+pub(crate) fn run_stretch_u8<T: PixelStor>(
+    data: &[T],
+    channels: usize,
+    low: f32,
+    high: f32,
+) -> Result<Vec<u8>, &'static str> {
+    if !(0f32..=1f32).contains(&low) || !(0f32..=1f32).contains(&high) {
+        return Err("Percentiles must be between 0 and 1.");
+    }
+    if low >= high {
+        return Err("Low percentile must be less than high percentile.");
+    }
+    let windows: Vec<(f64, f64)> = (0..channels)
+        .map(|ch| {
+            let mut vals: Vec<f64> = data
+                .iter()
+                .skip(ch)
+                .step_by(channels)
+                .map(|&v| PixelStor::to_f64(v))
+                .collect();
+            vals.sort_unstable_by(f64::total_cmp);
+            let len = vals.len();
+            (
+                vals[percentile_index(low, len)],
+                vals[percentile_index(high, len)],
+            )
+        })
+        .collect();
+    let stretch = |(i, v): (usize, &T)| {
+        let (lo, hi) = windows[i % channels];
+        let v = PixelStor::to_f64(*v);
+        if hi <= lo {
+            if v <= lo {
+                0
+            } else {
+                255
+            }
+        } else {
+            (((v - lo) / (hi - lo)).clamp(0.0, 1.0) * 255.0).round() as u8
+        }
+    };
+    #[cfg(not(feature = "rayon"))]
+    {
+        Ok(data.iter().enumerate().map(stretch).collect())
+    }
+    #[cfg(feature = "rayon")]
+    {
+        Ok(data.par_iter().enumerate().map(stretch).collect())
+    }
+}
+
+/// Compute the equalization mapping of each histogram bin from its cumulative distribution.
+///
+/// Returns [`None`] if the histogram has a single occupied bin.
+pub(crate) fn equalize_map<T: PixelStor>(hist: &[usize]) -> Option<Vec<T>> {
+    let total: usize = hist.iter().sum();
+    let cdf_min = hist.iter().copied().find(|&n| n > 0)?;
+    if total == cdf_min {
+        return None;
+    }
+    let min = T::DEFAULT_MIN_VALUE.to_f64();
+    let max = T::DEFAULT_MAX_VALUE.to_f64();
+    let scale = (max - min) / (total - cdf_min) as f64;
+    let round = !T::PIXEL_TYPE.is_float();
+    let mut cdf = 0;
+    Some(
+        hist.iter()
+            .map(|&n| {
+                cdf += n;
+                let v = min + cdf.saturating_sub(cdf_min) as f64 * scale;
+                let v = if round { v.round() } else { v };
+                T::from_f64(v.clamp(min, max))
+            })
+            .collect(),
+    )
+}
+
+/// Create a normalized Gaussian kernel with standard deviation `sigma`, e.g. for
+/// [`ImageOwned::convolve_separable`](crate::ImageOwned::convolve_separable).
+///
+/// # Arguments
+/// - `sigma`: The standard deviation of the Gaussian, in pixels.
+///
+/// # Returns
+/// The kernel of length `2 * ceil(3 * sigma) + 1`, whose weights sum to 1. If `sigma`
+/// is not positive and finite, the identity kernel `[1.0]` is returned.
+pub fn gaussian_kernel(sigma: f64) -> Vec<f64> {
+    if !sigma.is_finite() || sigma <= 0.0 {
+        return vec![1.0];
+    }
+    let radius = (3.0 * sigma).ceil() as isize;
+    let kernel: Vec<f64> = (-radius..=radius)
+        .map(|i| (-((i * i) as f64) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let norm: f64 = kernel.iter().sum();
+    kernel.into_iter().map(|w| w / norm).collect()
+}
+
+/// Convolve interleaved image data with a horizontal kernel `kx` and a vertical kernel
+/// `ky`, each centered on its middle element. Each channel is convolved independently.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn run_convolve_separable(
+    data: &[f64],
+    width: usize,
+    height: usize,
+    channels: usize,
+    kx: &[f64],
+    ky: &[f64],
+    border: BorderMode,
+) -> Vec<f64> {
+    let row = width * channels;
+    let (rx, ry) = ((kx.len() / 2) as isize, (ky.len() / 2) as isize);
+    let hpass = |(src, dst): (&[f64], &mut [f64])| {
+        for x in 0..width {
+            for c in 0..channels {
+                dst[x * channels + c] = kx
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(k, w)| {
+                        let j = border.index(x as isize + k as isize - rx, width)?;
+                        Some(w * src[j * channels + c])
+                    })
+                    .sum();
+            }
+        }
+    };
+    let mut tmp = vec![0f64; data.len()];
+    #[cfg(feature = "rayon")]
+    data.par_chunks_exact(row)
+        .zip(tmp.par_chunks_exact_mut(row))
+        .for_each(hpass);
+    #[cfg(not(feature = "rayon"))]
+    data.chunks_exact(row)
+        .zip(tmp.chunks_exact_mut(row))
+        .for_each(hpass);
+    let vpass = |(y, dst): (usize, &mut [f64])| {
+        for (k, w) in ky.iter().enumerate() {
+            if let Some(j) = border.index(y as isize + k as isize - ry, height) {
+                dst.iter_mut()
+                    .zip(&tmp[j * row..(j + 1) * row])
+                    .for_each(|(d, s)| *d += w * s);
+            }
+        }
+    };
+    let mut out = vec![0f64; data.len()];
+    #[cfg(feature = "rayon")]
+    out.par_chunks_exact_mut(row).enumerate().for_each(vpass);
+    #[cfg(not(feature = "rayon"))]
+    out.chunks_exact_mut(row).enumerate().for_each(vpass);
+    out
+}
+
+/// Convolve interleaved image data with separable kernels, converting the samples to
+/// [`f64`] and clamping the result back to the valid range of the pixel type.
+///
+/// # Errors
+/// - If the color space is a Bayer mosaic.
+/// - If a kernel is empty or has an even length.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn convolve_pixels<T: PixelStor>(
+    data: &[T],
+    width: usize,
+    height: usize,
+    channels: usize,
+    cspace: &ColorSpace,
+    kx: &[f64],
+    ky: &[f64],
+    border: BorderMode,
+) -> Result<Vec<T>, &'static str> {
+    if cspace.is_bayer() {
+        return Err("Image is not debayered.");
+    }
+    if kx.len().is_multiple_of(2) || ky.len().is_multiple_of(2) {
+        return Err("Kernel length must be odd.");
+    }
+    let data: Vec<f64> = data.iter().map(|&v| v.to_f64()).collect();
+    let round = !T::PIXEL_TYPE.is_float();
+    Ok(
+        run_convolve_separable(&data, width, height, channels, kx, ky, border)
+            .into_iter()
+            .map(|v| clamp_from_f64(if round { v.round() } else { v }))
+            .collect(),
+    )
+}
+
+/// Blur interleaved image data with a separable Gaussian kernel, replicating the
+/// samples at the image border.
+pub(crate) fn gaussian_blur(
+    data: &[f64],
+    width: usize,
+    height: usize,
+    channels: usize,
+    sigma: f64,
+) -> Vec<f64> {
+    let kernel = gaussian_kernel(sigma);
+    run_convolve_separable(
+        data,
+        width,
+        height,
+        channels,
+        &kernel,
+        &kernel,
+        BorderMode::Replicate,
+    )
+}
+
+/// Map every sample of interleaved image data through a per-channel function.
+pub(crate) fn run_lut<T, F>(data: &mut [T], channels: usize, map: F)
+where
+    T: PixelStor,
+    F: Fn(usize, T) -> T + Sync,
+{
+    let apply = |chunk: &mut [T]| {
+        for (ch, px) in chunk.iter_mut().enumerate() {
+            *px = map(ch, *px);
+        }
+    };
+    #[cfg(not(feature = "rayon"))]
+    {
+        data.chunks_exact_mut(channels).for_each(apply);
+    }
+    #[cfg(feature = "rayon")]
+    {
+        data.par_chunks_exact_mut(channels).for_each(apply);
+    }
+}
+
+/// Check that a [`u16`] lookup table is either complete or can be interpolated.
+pub(crate) fn check_lut_u16(lut: &[u16]) -> Result<(), &'static str> {
+    if lut.len() < 2 || lut.len() > 65536 {
+        return Err("Lookup table must have between 2 and 65536 entries.");
+    }
+    Ok(())
+}
+
+/// Look up a [`u16`] value in a table, linearly interpolating if the table
+/// has fewer than 65536 entries.
+pub(crate) fn lookup_u16(lut: &[u16], value: u16) -> u16 {
+    if lut.len() == 65536 {
+        return lut[value as usize];
+    }
+    let max = u16::MAX as u64;
+    let pos = value as u64 * (lut.len() as u64 - 1);
+    let (idx, rem) = ((pos / max) as usize, pos % max);
+    let lo = lut[idx] as u64;
+    let hi = lut[(idx + 1).min(lut.len() - 1)] as u64;
+    ((lo * (max - rem) + hi * rem + max / 2) / max) as u16
+}
+
 impl ColorSpace {
     /// Check if the color space is a Bayer pattern.
     pub fn is_bayer(&self) -> bool {
         matches!(self, Self::Bayer(_))
     }
+
+    /// Check if the last channel of the color space is an alpha channel.
+    ///
+    /// This is the case for [`ColorSpace::GrayAlpha`] and [`ColorSpace::Rgba`] only.
+    pub fn has_alpha(&self) -> bool {
+        matches!(self, Self::GrayAlpha | Self::Rgba)
+    }
+
+    /// Get the number of channels per pixel for the color space.
+    ///
+    /// This is 1 for grayscale and Bayer pattern images, 2 for gray-alpha images, 3 for RGB
+    /// images, 4 for RGBA and CMYK images and `ch` for [`ColorSpace::Custom`]`(ch, _)`.
+    ///
+    /// # Returns
+    /// The number of channels, or [`None`] for a custom color space with zero channels.
+    pub fn channel_count(&self) -> Option<u8> {
+        match self {
+            Self::Gray | Self::Bayer(_) => Some(1),
+            Self::GrayAlpha => Some(2),
+            Self::Rgb => Some(3),
+            Self::Rgba | Self::Cmyk => Some(4),
+            Self::Custom(0, _) => None,
+            Self::Custom(ch, _) => Some(*ch),
+        }
+    }
+
+    /// Get the number of channels per pixel for the color space, or zero if it has none.
+    pub(crate) fn num_channels(&self) -> usize {
+        self.channel_count().unwrap_or(0) as usize
+    }
+
+    /// Get the canonical string representation of the color space.
+    ///
+    /// The representation is one of `GRAY`, `GRAYA`, `RGB`, `RGBA`, `CMYK`, `BGGR`, `GBRG`,
+    /// `GRBG`, `RGGB`, or `C(<channels>, <description>)` for [`ColorSpace::Custom`].
+    /// The string can be parsed back using [`ColorSpace::from_canonical_str`].
+    pub fn to_canonical_str(&self) -> String {
+        match self {
+            Self::Gray => "GRAY".to_owned(),
+            Self::GrayAlpha => "GRAYA".to_owned(),
+            Self::Rgb => "RGB".to_owned(),
+            Self::Rgba => "RGBA".to_owned(),
+            Self::Cmyk => "CMYK".to_owned(),
+            Self::Bayer(BayerPattern::Bggr) => "BGGR".to_owned(),
+            Self::Bayer(BayerPattern::Gbrg) => "GBRG".to_owned(),
+            Self::Bayer(BayerPattern::Grbg) => "GRBG".to_owned(),
+            Self::Bayer(BayerPattern::Rggb) => "RGGB".to_owned(),
+            Self::Custom(ch, desc) => format!("C({ch}, {desc})"),
+        }
+    }
+
+    /// Parse the canonical string representation of the color space,
+    /// as produced by [`ColorSpace::to_canonical_str`].
+    ///
+    /// Leading and trailing whitespace is ignored. The description of a
+    /// [`ColorSpace::Custom`] color space may contain any characters.
+    ///
+    /// # Errors
+    /// - If the string is not a valid color space representation.
+    /// - If a custom color space has zero channels.
+    pub fn from_canonical_str(val: &str) -> Result<Self, &'static str> {
+        let cspace = match val.trim() {
+            "GRAY" => Self::Gray,
+            "GRAYA" => Self::GrayAlpha,
+            "RGB" => Self::Rgb,
+            "RGBA" => Self::Rgba,
+            "CMYK" => Self::Cmyk,
+            "BGGR" => Self::Bayer(BayerPattern::Bggr),
+            "GBRG" => Self::Bayer(BayerPattern::Gbrg),
+            "GRBG" => Self::Bayer(BayerPattern::Grbg),
+            "RGGB" => Self::Bayer(BayerPattern::Rggb),
+            val => {
+                let (ch, desc) = val
+                    .strip_prefix("C(")
+                    .and_then(|val| val.strip_suffix(')'))
+                    .and_then(|val| val.split_once(", "))
+                    .ok_or("Invalid color space string.")?;
+                let ch: u8 = ch
+                    .parse()
+                    .map_err(|_| "Invalid channel count in custom color space.")?;
+                if ch == 0 {
+                    return Err("Custom color space must have at least one channel.");
+                }
+                Self::Custom(ch, desc.to_owned())
+            }
+        };
+        Ok(cspace)
+    }
 }
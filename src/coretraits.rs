@@ -1,4 +1,4 @@
-use bytemuck::NoUninit;
+use bytemuck::{NoUninit, PodCastError};
 use num_traits::{Bounded, Num, NumCast, ToPrimitive, Zero};
 #[cfg(feature = "rayon")]
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
@@ -58,8 +58,13 @@ pub trait PixelStor:
     }
 
     /// Cast the value to [`u8`], by scaling the value to the range `[0, 255]`.
+    /// NaN is cast to `0`, and out of range values saturate.
     fn cast_u8(self) -> u8 {
         let mut val: f32 = NumCast::from(self).unwrap();
+        if val.is_nan() {
+            // NaN is treated as the minimum value
+            return 0;
+        }
         let min: f32 = NumCast::from(Self::DEFAULT_MIN_VALUE).unwrap();
         let max: f32 = NumCast::from(Self::DEFAULT_MAX_VALUE).unwrap();
         val -= min;
@@ -71,6 +76,10 @@ pub trait PixelStor:
     /// Cast the value to [`u8`], by scaling the value to the range `[0, 255]`. Floors the value at the end.
     fn floor_u8(self) -> u8 {
         let mut val: f32 = NumCast::from(self).unwrap();
+        if val.is_nan() {
+            // NaN is treated as the minimum value
+            return 0;
+        }
         let min: f32 = NumCast::from(Self::DEFAULT_MIN_VALUE).unwrap();
         let max: f32 = NumCast::from(Self::DEFAULT_MAX_VALUE).unwrap();
         val -= min;
@@ -101,6 +110,9 @@ declare_pixelstor!(i32: (Self::MIN)..Self::MAX, PixelType::I32);
 declare_pixelstor!(f32: (0.0)..1.0,  PixelType::F32);
 declare_pixelstor!(f64: (0.0)..1.0, PixelType::F64);
 
+#[cfg(feature = "f16")]
+declare_pixelstor!(half::f16: (half::f16::ZERO)..half::f16::ONE, PixelType::F16);
+
 /// An `Enlargable::Larger` value should be enough to calculate
 /// the sum (average) of a few hundred or thousand Enlargeable values.
 pub trait Enlargeable: Sized + Bounded + NumCast + Copy {
@@ -248,6 +260,52 @@ impl Enlargeable for f32 {
 impl Enlargeable for f64 {
     type Larger = f64;
 }
+#[cfg(feature = "f16")]
+impl Enlargeable for half::f16 {
+    type Larger = f32;
+}
+
+/// Convert a `f64` value to `T`, clamping it to the valid range of `T`.
+///
+/// NaN is converted to the minimum value of `T`.
+pub(crate) fn clamp_from_f64<T: PixelStor>(v: f64) -> T {
+    let min = T::DEFAULT_MIN_VALUE.to_f64();
+    let max = T::DEFAULT_MAX_VALUE.to_f64();
+    if v.is_nan() {
+        T::DEFAULT_MIN_VALUE
+    } else {
+        T::from_f64(v.clamp(min, max))
+    }
+}
+
+/// Convert a value to another pixel type, mapping the valid range of `T` linearly
+/// onto the valid range of `U`.
+///
+/// Integer results are rounded to the nearest integer, and NaN is converted to the
+/// minimum value of `U`.
+pub(crate) fn convert_range<T: PixelStor, U: PixelStor>(v: T) -> U {
+    let (smin, smax) = (T::DEFAULT_MIN_VALUE.to_f64(), T::DEFAULT_MAX_VALUE.to_f64());
+    let (dmin, dmax) = (U::DEFAULT_MIN_VALUE.to_f64(), U::DEFAULT_MAX_VALUE.to_f64());
+    let v = dmin + (v.to_f64() - smin) / (smax - smin) * (dmax - dmin);
+    clamp_from_f64(if U::PIXEL_TYPE.is_float() {
+        v
+    } else {
+        v.round()
+    })
+}
+
+/// Describe a byte casting error.
+pub(crate) fn pod_cast_error(e: PodCastError) -> &'static str {
+    use PodCastError::*;
+    match e {
+        TargetAlignmentGreaterAndInputNotAligned => {
+            "Target alignment greater and input not aligned"
+        }
+        OutputSliceWouldHaveSlop => "Output slice would have slop",
+        SizeMismatch => "Size mismatch",
+        AlignmentMismatch => "Alignment mismatch",
+    }
+}
 
 /// Cast a slice of `T` to a slice of `u8`.
 #[inline(never)]
@@ -272,5 +330,22 @@ mod test {
         let v = 0.4f32;
         let u = v.cast_u8();
         assert_eq!(u, 102); // f32::round(v * 255.0) as u8);
+        assert_eq!(f32::NAN.cast_u8(), 0);
+        assert_eq!(f32::NAN.floor_u8(), 0);
+        assert_eq!(f32::INFINITY.cast_u8(), 255);
+        assert_eq!(f32::NEG_INFINITY.cast_u8(), 0);
+    }
+
+    #[cfg(feature = "f16")]
+    #[test]
+    fn test_pixelstor_f16() {
+        use crate::coretraits::{Enlargeable, PixelStor};
+        use half::f16;
+        let v = f16::from_f32(0.5);
+        assert_eq!(v.cast_u8(), 128);
+        assert_eq!(PixelStor::to_f64(v), 0.5);
+        assert_eq!(<f16 as PixelStor>::from_f64(2.0), f16::ONE);
+        assert_eq!(v.make_larger(), 0.5f32);
+        assert_eq!(f16::clamp_larger(1e6), f16::MAX);
     }
 }
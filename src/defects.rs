@@ -0,0 +1,209 @@
+//! Sensor defect correction.
+
+use std::collections::HashSet;
+
+use crate::{ImageOwned, ImageProps, PixelStor};
+
+/// Get the median of the values, reordering them in the process.
+/// For an even number of values, the mean of the two middle values is returned.
+pub(crate) fn median(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        Some((values[mid - 1] + values[mid]) / 2.0)
+    } else {
+        Some(values[mid])
+    }
+}
+
+/// Get the coordinates of the 8 neighbors of `(x, y)` that are `stride` pixels apart,
+/// and lie within the image.
+fn neighbors(
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    stride: usize,
+) -> impl Iterator<Item = (usize, usize)> {
+    (-1isize..=1)
+        .flat_map(|dy| (-1isize..=1).map(move |dx| (dx, dy)))
+        .filter(|&(dx, dy)| dx != 0 || dy != 0)
+        .filter_map(move |(dx, dy)| {
+            let nx = x.checked_add_signed(dx * stride as isize)?;
+            let ny = y.checked_add_signed(dy * stride as isize)?;
+            (nx < width && ny < height).then_some((nx, ny))
+        })
+}
+
+impl<T: PixelStor> ImageOwned<T> {
+    /// Replace known-bad pixels with the median of their valid neighbors.
+    ///
+    /// Each channel of a bad pixel is replaced by the median of the same channel of
+    /// its 8 nearest neighbors, excluding neighbors that are themselves listed as bad.
+    /// For Bayer mosaic images, only the nearest neighbors of the same color (two
+    /// pixels apart) are used. Pixels that are not listed are left unchanged, as are
+    /// bad pixels without any valid neighbors.
+    ///
+    /// # Arguments
+    /// - `bad`: The `(x, y)` coordinates of the bad pixels.
+    ///
+    /// # Errors
+    /// - If any of the coordinates lie outside the image.
+    pub fn correct_bad_pixels(&mut self, bad: &[(usize, usize)]) -> Result<(), &'static str> {
+        let (width, height) = (self.width(), self.height());
+        if bad.iter().any(|&(x, y)| x >= width || y >= height) {
+            return Err("Bad pixel coordinates out of bounds.");
+        }
+        let channels = self.channels as usize;
+        let stride = if self.cspace.is_bayer() { 2 } else { 1 };
+        let badset: HashSet<_> = bad.iter().copied().collect();
+        let mut values = Vec::with_capacity(8);
+        for &(x, y) in badset.iter() {
+            let valid: Vec<_> = neighbors(x, y, width, height, stride)
+                .filter(|px| !badset.contains(px))
+                .collect();
+            for ch in 0..channels {
+                values.clear();
+                values.extend(
+                    valid
+                        .iter()
+                        .map(|&(nx, ny)| self.data[(ny * width + nx) * channels + ch].to_f64()),
+                );
+                if let Some(val) = median(&mut values) {
+                    self.data[(y * width + x) * channels + ch] = T::from_f64(val);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Find hot or dead pixels, e.g. in a dark frame, to build a bad pixel map
+    /// for [`ImageOwned::correct_bad_pixels`].
+    ///
+    /// The residual of each pixel is its deviation from the median of its 8 nearest
+    /// neighbors. The noise of each channel is estimated robustly from the median
+    /// absolute deviation (MAD) of the residuals, and a pixel is flagged if the
+    /// residual of any channel exceeds `sigma` times the noise. For Bayer mosaic
+    /// images, only the nearest neighbors of the same color (two pixels apart) are used.
+    ///
+    /// # Arguments
+    /// - `sigma`: The detection threshold, in units of the estimated noise.
+    ///
+    /// # Returns
+    /// The `(x, y)` coordinates of the flagged pixels, in row-major order.
+    ///
+    /// # Errors
+    /// - If `sigma` is not positive and finite.
+    pub fn detect_outlier_pixels(&self, sigma: f64) -> Result<Vec<(usize, usize)>, &'static str> {
+        if !sigma.is_finite() || sigma <= 0.0 {
+            return Err("Sigma must be a positive finite number.");
+        }
+        let (width, height) = (self.width(), self.height());
+        let channels = self.channels as usize;
+        let stride = if self.cspace.is_bayer() { 2 } else { 1 };
+        let npix = width * height;
+        let mut values = Vec::with_capacity(8);
+        let mut residuals = vec![0f64; npix * channels];
+        for y in 0..height {
+            for x in 0..width {
+                for ch in 0..channels {
+                    values.clear();
+                    values.extend(
+                        neighbors(x, y, width, height, stride)
+                            .map(|(nx, ny)| self.data[(ny * width + nx) * channels + ch].to_f64()),
+                    );
+                    let idx = (y * width + x) * channels + ch;
+                    if let Some(med) = median(&mut values) {
+                        residuals[idx] = self.data[idx].to_f64() - med;
+                    }
+                }
+            }
+        }
+        // 1.4826 * MAD estimates the standard deviation of normally distributed noise
+        let thresholds: Vec<f64> = (0..channels)
+            .map(|ch| {
+                let mut res: Vec<f64> = residuals
+                    .iter()
+                    .skip(ch)
+                    .step_by(channels)
+                    .copied()
+                    .collect();
+                let center = median(&mut res).unwrap_or_default();
+                res.iter_mut().for_each(|r| *r = (*r - center).abs());
+                let mad = median(&mut res).unwrap_or_default();
+                sigma * 1.4826 * mad
+            })
+            .collect();
+        Ok((0..npix)
+            .filter(|&px| {
+                residuals[px * channels..(px + 1) * channels]
+                    .iter()
+                    .zip(thresholds.iter())
+                    .any(|(r, t)| r.abs() > *t)
+            })
+            .map(|px| (px % width, px / width))
+            .collect())
+    }
+}
+
+mod test {
+    #[test]
+    fn test_correct_bad_pixels() {
+        use crate::{BayerPattern, ColorSpace, ImageOwned};
+        let mut data = vec![10u16; 25];
+        data[12] = 60000;
+        data[13] = 0;
+        data[7] = 11;
+        let mut img = ImageOwned::from_owned(data, 5, 5, ColorSpace::Gray).unwrap();
+        img.correct_bad_pixels(&[(2, 2), (3, 2)]).unwrap();
+        assert_eq!(img.as_slice()[12], 10);
+        assert_eq!(img.as_slice()[13], 10);
+        assert_eq!(img.as_slice()[7], 11);
+        assert!(img.correct_bad_pixels(&[(5, 0)]).is_err());
+        // same-color neighbors only
+        let mut data: Vec<u8> = (0..36)
+            .map(|i| {
+                if (i / 6) % 2 == 0 && i % 2 == 0 {
+                    100
+                } else {
+                    5
+                }
+            })
+            .collect();
+        data[14] = 255;
+        let mut img =
+            ImageOwned::from_owned(data, 6, 6, ColorSpace::Bayer(BayerPattern::Rggb)).unwrap();
+        img.correct_bad_pixels(&[(2, 2)]).unwrap();
+        assert_eq!(img.as_slice()[14], 100);
+    }
+
+    #[test]
+    fn test_detect_outlier_pixels() {
+        use crate::{BayerPattern, ColorSpace, ImageOwned};
+        let mut data: Vec<u16> = (0..64).map(|i| 1000 + (i * 7919 % 13) as u16).collect();
+        data[9] = 60000;
+        data[42] = 0;
+        let mut img = ImageOwned::from_owned(data, 8, 8, ColorSpace::Gray).unwrap();
+        let bad = img.detect_outlier_pixels(5.0).unwrap();
+        assert_eq!(bad, vec![(1, 1), (2, 5)]);
+        img.correct_bad_pixels(&bad).unwrap();
+        assert!(img.detect_outlier_pixels(5.0).unwrap().is_empty());
+        assert!(img.detect_outlier_pixels(0.0).is_err());
+        // a Bayer pattern is not mistaken for outliers
+        let data: Vec<u8> = (0..64)
+            .map(|i| {
+                if (i / 8) % 2 == 0 && i % 2 == 0 {
+                    200
+                } else {
+                    20 + (i % 3) as u8
+                }
+            })
+            .collect();
+        let img =
+            ImageOwned::from_owned(data, 8, 8, ColorSpace::Bayer(BayerPattern::Rggb)).unwrap();
+        assert!(img.detect_outlier_pixels(5.0).unwrap().is_empty());
+    }
+}
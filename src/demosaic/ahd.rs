@@ -0,0 +1,405 @@
+//! Demosaicing using adaptive homogeneity-directed (AHD) interpolation.
+//!
+//! The green channel is interpolated twice, once along the rows and once along the
+//! columns, and the red and blue channels are filled in from the color differences to
+//! each green estimate. Both candidates are converted to CIELAB, and each output pixel
+//! is taken from the candidate that is more homogeneous around it, i.e. has more
+//! neighbors within a small luminance and chrominance distance. This avoids
+//! interpolating across edges, which causes the zipper artifacts of linear interpolation.
+//!
+//! ```text
+//!   green_h = (G[x - 1] + G[x + 1]) / 2 + (2 C[x] - C[x - 2] - C[x + 2]) / 4
+//!   green_v = (G[y - 1] + G[y + 1]) / 2 + (2 C[y] - C[y - 2] - C[y + 2]) / 4
+//! ```
+//!
+//! Reference: K. Hirakawa and T. W. Parks, "Adaptive homogeneity-directed demosaicing
+//! algorithm", IEEE Transactions on Image Processing, 14(3), 2005.
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::border::BorderMirror;
+use crate::coretraits::{clamp_from_f64, Enlargeable};
+use crate::demosaic::{BayerError, BayerRead, BayerResult, ColorFilterArray, RasterMut};
+use crate::{ImageOwned, ImageProps, ImageRef, PixelStor};
+
+/// Border required by the interpolation (3), the homogeneity map (1) and the
+/// homogeneity window (1), rounded up to keep the phase of the pattern.
+const PADDING: usize = 6;
+
+/// The two interpolation directions: horizontal and vertical.
+const DIRECTIONS: usize = 2;
+
+pub fn run_imagedata<T>(
+    src: &ImageRef<'_, T>,
+    cfa: ColorFilterArray,
+    dst: &mut RasterMut<'_, T>,
+) -> BayerResult<()>
+where
+    T: PixelStor + Enlargeable,
+{
+    if src.width() < 2 || src.height() < 2 {
+        return Err(BayerError::WrongResolution);
+    }
+
+    debayer(src.as_slice(), cfa, dst)
+}
+
+pub fn run_imageowned<T>(
+    src: &ImageOwned<T>,
+    cfa: ColorFilterArray,
+    dst: &mut RasterMut<'_, T>,
+) -> BayerResult<()>
+where
+    T: PixelStor + Enlargeable,
+{
+    if src.width() < 2 || src.height() < 2 {
+        return Err(BayerError::WrongResolution);
+    }
+
+    debayer(src.as_slice(), cfa, dst)
+}
+
+/// Get the color index (0 = R, 1 = G, 2 = B) of the pixel at `(x, y)`, and for
+/// green pixels, the color index of the horizontal neighbors.
+fn color_at(cfa: ColorFilterArray, x: usize, y: usize) -> (usize, usize) {
+    let cfa = if x.is_multiple_of(2) {
+        cfa
+    } else {
+        cfa.next_x()
+    };
+    let cfa = if y.is_multiple_of(2) {
+        cfa
+    } else {
+        cfa.next_y()
+    };
+    match cfa {
+        ColorFilterArray::Rggb => (0, 0),
+        ColorFilterArray::Bggr => (2, 2),
+        ColorFilterArray::Grbg => (1, 0),
+        ColorFilterArray::Gbrg => (1, 2),
+    }
+}
+
+/// Convert linear RGB in `[0, 1]` to CIELAB, with a D65 white point.
+fn rgb_to_lab([r, g, b]: [f64; 3]) -> [f64; 3] {
+    let x = (0.412453 * r + 0.357580 * g + 0.180423 * b) / 0.950456;
+    let y = 0.212671 * r + 0.715160 * g + 0.072169 * b;
+    let z = (0.019334 * r + 0.119193 * g + 0.950227 * b) / 1.088754;
+    let f = |t: f64| {
+        if t > 0.008856 {
+            t.cbrt()
+        } else {
+            7.787 * t + 16.0 / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+/// Fill a plane row by row with `f(y, row)`, where each row has `len` elements.
+fn fill_rows<V, F>(plane: &mut [V], len: usize, f: F)
+where
+    V: Send,
+    F: Fn(usize, &mut [V]) + Send + Sync,
+{
+    #[cfg(feature = "rayon")]
+    plane
+        .par_chunks_mut(len)
+        .enumerate()
+        .for_each(|(y, row)| f(y, row));
+    #[cfg(not(feature = "rayon"))]
+    plane
+        .chunks_mut(len)
+        .enumerate()
+        .for_each(|(y, row)| f(y, row));
+}
+
+/// Read the mosaic into a buffer with a mirrored border of [`PADDING`] pixels.
+fn read_padded<T: PixelStor>(r: &[T], w: usize, h: usize) -> BayerResult<Vec<f64>> {
+    let stride = 2 * PADDING + w;
+    let mut data = vec![T::zero(); stride * (2 * PADDING + h)];
+    let rdr = BorderMirror::new(w, PADDING);
+    for row in data.chunks_mut(stride).skip(PADDING).take(h) {
+        rdr.read_row(r, row)?;
+    }
+    for k in 1..=PADDING {
+        // Row -k mirrors row k, and row h - 1 + k mirrors row h - 1 - k.
+        let (top, src) = data.split_at_mut(stride * PADDING);
+        top[(stride * (PADDING - k))..(stride * (PADDING - k + 1))]
+            .copy_from_slice(&src[(stride * k)..(stride * (k + 1))]);
+        let yy = PADDING + h - 1;
+        let (src, bottom) = data.split_at_mut(stride * (yy + 1));
+        bottom[(stride * (k - 1))..(stride * k)]
+            .copy_from_slice(&src[(stride * (yy - k))..(stride * (yy - k + 1))]);
+    }
+    Ok(data.into_iter().map(|v| v.to_f64()).collect())
+}
+
+fn debayer<T>(r: &[T], cfa: ColorFilterArray, dst: &mut RasterMut<'_, T>) -> BayerResult<()>
+where
+    T: PixelStor + Enlargeable,
+{
+    let (w, h) = (dst.w, dst.h);
+    if w < 2 || h < 2 {
+        return Err(BayerError::WrongResolution);
+    }
+    if w <= PADDING || h <= PADDING {
+        // Too small to mirror the border, the homogeneity map would be meaningless anyway.
+        return crate::demosaic::linear::debayer(r, cfa, dst);
+    }
+
+    // The padding is even, so the padded mosaic starts with the same pattern.
+    let stride = 2 * PADDING + w;
+    let mosaic = read_padded(r, w, h)?;
+    let min = T::DEFAULT_MIN_VALUE.to_f64();
+    let range = T::DEFAULT_MAX_VALUE.to_f64() - min;
+
+    // Directional green estimates.
+    let green: Vec<Vec<f64>> = (0..DIRECTIONS)
+        .map(|d| {
+            let step = if d == 0 { 1 } else { stride };
+            let mut plane = mosaic.clone();
+            fill_rows(&mut plane, stride, |y, row| {
+                if y < 2 || y + 2 >= h + 2 * PADDING {
+                    return;
+                }
+                for (x, px) in row.iter_mut().enumerate().take(stride - 2).skip(2) {
+                    if color_at(cfa, x, y).0 == 1 {
+                        continue;
+                    }
+                    let i = y * stride + x;
+                    let (a, b) = (mosaic[i - step], mosaic[i + step]);
+                    let est = (a + b) / 2.0
+                        + (2.0 * mosaic[i] - mosaic[i - 2 * step] - mosaic[i + 2 * step]) / 4.0;
+                    *px = est.clamp(a.min(b), a.max(b));
+                }
+            });
+            plane
+        })
+        .collect();
+
+    // Directional RGB candidates, filled from the color differences, in CIELAB.
+    let lab: Vec<Vec<f64>> = green
+        .iter()
+        .map(|green| {
+            let mut plane = vec![0f64; 3 * mosaic.len()];
+            fill_rows(&mut plane, 3 * stride, |y, row| {
+                if y < 3 || y + 3 >= h + 2 * PADDING {
+                    return;
+                }
+                for x in 3..(stride - 3) {
+                    let rgb = rgb_at(&mosaic, green, cfa, stride, x, y);
+                    let rgb = rgb.map(|v| ((v - min) / range).clamp(0.0, 1.0));
+                    row[3 * x..3 * x + 3].copy_from_slice(&rgb_to_lab(rgb));
+                }
+            });
+            plane
+        })
+        .collect();
+
+    // Homogeneity maps: the number of neighbors within the adaptive distances.
+    let dist_l = |lab: &[f64], i: usize, j: usize| (lab[3 * i] - lab[3 * j]).abs();
+    let dist_c = |lab: &[f64], i: usize, j: usize| {
+        (lab[3 * i + 1] - lab[3 * j + 1]).powi(2) + (lab[3 * i + 2] - lab[3 * j + 2]).powi(2)
+    };
+    let mut homogeneity = vec![[0u8; DIRECTIONS]; mosaic.len()];
+    fill_rows(&mut homogeneity, stride, |y, row| {
+        if y < 4 || y + 4 >= h + 2 * PADDING {
+            return;
+        }
+        for (x, hom) in row.iter_mut().enumerate().take(stride - 4).skip(4) {
+            let i = y * stride + x;
+            let (lh, lv) = (&lab[0], &lab[1]);
+            let eps_l = dist_l(lh, i, i - 1)
+                .max(dist_l(lh, i, i + 1))
+                .min(dist_l(lv, i, i - stride).max(dist_l(lv, i, i + stride)));
+            let eps_c = dist_c(lh, i, i - 1)
+                .max(dist_c(lh, i, i + 1))
+                .min(dist_c(lv, i, i - stride).max(dist_c(lv, i, i + stride)));
+            for (d, lab) in lab.iter().enumerate() {
+                hom[d] = [i - 1, i + 1, i - stride, i + stride]
+                    .into_iter()
+                    .filter(|&j| dist_l(lab, i, j) <= eps_l && dist_c(lab, i, j) <= eps_c)
+                    .count() as u8;
+            }
+        }
+    });
+
+    // Pick the more homogeneous direction, and average the candidates on ties.
+    let round = !T::PIXEL_TYPE.is_float();
+    let out_stride = dst.stride;
+    fill_rows(dst.buf, out_stride, |y, row| {
+        if y >= h {
+            return;
+        }
+        let yy = y + PADDING;
+        for x in 0..w {
+            let xx = x + PADDING;
+            let i = yy * stride + xx;
+            let mut score = [0u32; DIRECTIONS];
+            for j in [i - stride, i, i + stride] {
+                for hom in &homogeneity[j - 1..=j + 1] {
+                    score[0] += hom[0] as u32;
+                    score[1] += hom[1] as u32;
+                }
+            }
+            let candidate = |d: usize| rgb_at(&mosaic, &green[d], cfa, stride, xx, yy);
+            let rgb = match score[0].cmp(&score[1]) {
+                std::cmp::Ordering::Greater => candidate(0),
+                std::cmp::Ordering::Less => candidate(1),
+                std::cmp::Ordering::Equal => {
+                    let (a, b) = (candidate(0), candidate(1));
+                    [
+                        (a[0] + b[0]) / 2.0,
+                        (a[1] + b[1]) / 2.0,
+                        (a[2] + b[2]) / 2.0,
+                    ]
+                }
+            };
+            for (px, v) in row[3 * x..3 * x + 3].iter_mut().zip(rgb) {
+                *px = clamp_from_f64(if round { v.round() } else { v });
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Get the RGB value at `(x, y)` of the padded mosaic, filled from the color
+/// differences to the given green estimate.
+fn rgb_at(
+    mosaic: &[f64],
+    green: &[f64],
+    cfa: ColorFilterArray,
+    stride: usize,
+    x: usize,
+    y: usize,
+) -> [f64; 3] {
+    let i = y * stride + x;
+    let diff = |j: usize| mosaic[j] - green[j];
+    let g = green[i];
+    let mut rgb = [g; 3];
+    match color_at(cfa, x, y) {
+        (1, hc) => {
+            rgb[hc] = g + (diff(i - 1) + diff(i + 1)) / 2.0;
+            rgb[2 - hc] = g + (diff(i - stride) + diff(i + stride)) / 2.0;
+        }
+        (c, _) => {
+            rgb[c] = mosaic[i];
+            rgb[2 - c] = g
+                + (diff(i - stride - 1)
+                    + diff(i - stride + 1)
+                    + diff(i + stride - 1)
+                    + diff(i + stride + 1))
+                    / 4.0;
+        }
+    }
+    rgb
+}
+
+#[cfg(test)]
+mod tests {
+    use super::debayer;
+    use crate::demosaic::{ColorFilterArray, RasterMut};
+
+    /// Sample an RGB image with the given pattern, listed as the colors of the
+    /// top-left, top-right, bottom-left and bottom-right pixels.
+    fn mosaic(rgb: &[u8], w: usize, cfa: ColorFilterArray) -> Vec<u8> {
+        let colors = match cfa {
+            ColorFilterArray::Rggb => [0, 1, 1, 2],
+            ColorFilterArray::Bggr => [2, 1, 1, 0],
+            ColorFilterArray::Grbg => [1, 0, 2, 1],
+            ColorFilterArray::Gbrg => [1, 2, 0, 1],
+        };
+        (0..rgb.len() / 3)
+            .map(|i| {
+                let (x, y) = (i % w, i / w);
+                rgb[3 * i + colors[2 * (y % 2) + x % 2]]
+            })
+            .collect()
+    }
+
+    /// Sum of squared differences to the reference.
+    fn energy(a: &[u8], b: &[u8]) -> f64 {
+        a.iter()
+            .zip(b)
+            .map(|(&a, &b)| (a as f64 - b as f64).powi(2))
+            .sum()
+    }
+
+    #[test]
+    fn test_flat() {
+        const IMG_W: usize = 11;
+        const IMG_H: usize = 8;
+        let rgb = [200u8, 100, 50].repeat(IMG_W * IMG_H);
+        for cfa in [
+            ColorFilterArray::Rggb,
+            ColorFilterArray::Bggr,
+            ColorFilterArray::Grbg,
+            ColorFilterArray::Gbrg,
+        ] {
+            let src = mosaic(&rgb, IMG_W, cfa);
+            let mut buf = [0u8; 3 * IMG_W * IMG_H];
+            let res = debayer(&src, cfa, &mut RasterMut::new(IMG_W, IMG_H, &mut buf));
+            assert!(res.is_ok());
+            assert_eq!(&buf[..], &rgb[..], "{cfa:?}");
+        }
+    }
+
+    #[test]
+    fn test_gradient_artifacts() {
+        // A gray gradient with a sharp vertical edge and a sharp horizontal edge,
+        // where linear interpolation produces zipper artifacts.
+        const IMG_W: usize = 24;
+        const IMG_H: usize = 20;
+        let rgb: Vec<u8> = (0..IMG_W * IMG_H)
+            .flat_map(|i| {
+                let (x, y) = (i % IMG_W, i / IMG_W);
+                let v = 40 + 4 * x + 2 * y + if x >= 11 { 80 } else { 0 };
+                let v = if y >= 13 { v / 3 } else { v };
+                [v as u8; 3]
+            })
+            .collect();
+        for cfa in [
+            ColorFilterArray::Rggb,
+            ColorFilterArray::Bggr,
+            ColorFilterArray::Grbg,
+            ColorFilterArray::Gbrg,
+        ] {
+            let src = mosaic(&rgb, IMG_W, cfa);
+            let mut ahd = [0u8; 3 * IMG_W * IMG_H];
+            debayer(&src, cfa, &mut RasterMut::new(IMG_W, IMG_H, &mut ahd)).unwrap();
+            let mut linear = [0u8; 3 * IMG_W * IMG_H];
+            crate::demosaic::linear::debayer(
+                &src,
+                cfa,
+                &mut RasterMut::new(IMG_W, IMG_H, &mut linear),
+            )
+            .unwrap();
+            let (ahd, linear) = (energy(&ahd, &rgb), energy(&linear, &rgb));
+            assert!(ahd < linear / 4.0, "{cfa:?}: AHD {ahd}, linear {linear}");
+        }
+    }
+
+    #[test]
+    fn test_small() {
+        // Images too small for the homogeneity map fall back to linear interpolation.
+        let src = [229u8, 67, 95, 146, 232, 51, 229, 241, 169];
+        let mut ahd = [0u8; 27];
+        debayer(
+            &src,
+            ColorFilterArray::Rggb,
+            &mut RasterMut::new(3, 3, &mut ahd),
+        )
+        .unwrap();
+        let mut linear = [0u8; 27];
+        crate::demosaic::linear::debayer(
+            &src,
+            ColorFilterArray::Rggb,
+            &mut RasterMut::new(3, 3, &mut linear),
+        )
+        .unwrap();
+        assert_eq!(ahd, linear);
+    }
+}
@@ -27,7 +27,7 @@ use crate::coretraits::{do_div_float, do_prod, get_mean, large_to_f64, Enlargeab
 use crate::demosaic::{BayerError, BayerRead, BayerResult, ColorFilterArray, RasterMut};
 
 #[cfg(feature = "rayon")]
-use crate::demosaic::border_mirror::BorderMirror;
+use crate::border::BorderMirror;
 use crate::{ImageOwned, ImageProps, ImageRef, PixelStor};
 
 const PADDING: usize = 3;
@@ -272,7 +272,7 @@ fn debayer_serial<T>(r: &[T], cfa: ColorFilterArray, dst: &mut RasterMut<'_, T>)
 where
     T: PixelStor + Enlargeable,
 {
-    use super::border_mirror::BorderMirror;
+    use crate::border::BorderMirror;
 
     let (w, h) = (dst.w, dst.h);
     let mut prv3 = vec![T::zero(); 2 * PADDING + w];
@@ -15,8 +15,8 @@
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 
+use crate::border::BorderReplicate;
 use crate::coretraits::{get_mean, Enlargeable};
-use crate::demosaic::border_replicate::*;
 use crate::demosaic::{BayerError, BayerRead, BayerResult, ColorFilterArray, RasterMut};
 use crate::{ImageOwned, ImageProps, ImageRef, PixelStor};
 
@@ -110,7 +110,11 @@ macro_rules! apply_kernel_row {
 /* Rayon                                                        */
 /*--------------------------------------------------------------*/
 
-fn debayer<T>(r: &[T], cfa: ColorFilterArray, dst: &mut RasterMut<'_, T>) -> BayerResult<()>
+pub(super) fn debayer<T>(
+    r: &[T],
+    cfa: ColorFilterArray,
+    dst: &mut RasterMut<'_, T>,
+) -> BayerResult<()>
 where
     T: PixelStor + Enlargeable,
 {
@@ -0,0 +1,398 @@
+//! Demosaicing using gradient-corrected linear interpolation (Malvar-He-Cutler).
+//!
+//! ```text
+//!   green_kernel = (1 / 16) *
+//!       [  0  0 -2  0  0
+//!       ;  0  0  4  0  0
+//!       ; -2  4  8  4 -2
+//!       ;  0  0  4  0  0
+//!       ;  0  0 -2  0  0 ];
+//!
+//!   red/blue_kernel (green pixel, red/blue row) = (1 / 16) *
+//!       [  0  0  1  0  0
+//!       ;  0 -2  0 -2  0
+//!       ; -2  8 10  8 -2
+//!       ;  0 -2  0 -2  0
+//!       ;  0  0  1  0  0 ];
+//!
+//!   red/blue_kernel (green pixel, red/blue column) = transpose of the above
+//!
+//!   red/blue_kernel (blue/red pixel) = (1 / 16) *
+//!       [  0  0 -3  0  0
+//!       ;  0  4  0  4  0
+//!       ; -3  0 12  0 -3
+//!       ;  0  4  0  4  0
+//!       ;  0  0 -3  0  0 ];
+//! ```
+//!
+//! Reference: H. S. Malvar, L. He and R. Cutler, "High-quality linear interpolation
+//! for demosaicing of Bayer-patterned color images", ICASSP 2004.
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::border::BorderMirror;
+use crate::coretraits::{clamp_from_f64, Enlargeable};
+use crate::demosaic::{BayerError, BayerRead, BayerResult, ColorFilterArray, RasterMut};
+use crate::{ImageOwned, ImageProps, ImageRef, PixelStor};
+
+const PADDING: usize = 2;
+
+pub fn run_imagedata<T>(
+    src: &ImageRef<'_, T>,
+    cfa: ColorFilterArray,
+    dst: &mut RasterMut<'_, T>,
+) -> BayerResult<()>
+where
+    T: PixelStor + Enlargeable,
+{
+    if src.width() < 2 || src.height() < 2 {
+        return Err(BayerError::WrongResolution);
+    }
+
+    debayer(src.as_slice(), cfa, dst)
+}
+
+pub fn run_imageowned<T>(
+    src: &ImageOwned<T>,
+    cfa: ColorFilterArray,
+    dst: &mut RasterMut<'_, T>,
+) -> BayerResult<()>
+where
+    T: PixelStor + Enlargeable,
+{
+    if src.width() < 2 || src.height() < 2 {
+        return Err(BayerError::WrongResolution);
+    }
+
+    debayer(src.as_slice(), cfa, dst)
+}
+
+/// Divide the weighted sum by the kernel normalization of 16, and clamp it to the
+/// valid range of `T`.
+fn norm16<T: PixelStor>(x: f64) -> T {
+    let x = x / 16.0;
+    clamp_from_f64(if T::PIXEL_TYPE.is_float() {
+        x
+    } else {
+        x.round()
+    })
+}
+
+macro_rules! apply_kernel_row {
+    ($T:ident; $row:ident,
+            $prv2:expr, $prv1:expr, $curr:expr, $nxt1:expr, $nxt2:expr,
+            $cfa:expr, $w:expr) => {{
+        let (mut i, cfa_c, cfa_g) =
+            if $cfa == ColorFilterArray::Bggr || $cfa == ColorFilterArray::Rggb {
+                (0, $cfa, $cfa.next_x())
+            } else {
+                apply_kernel_g!($T; $row, $prv2, $prv1, $curr, $nxt1, $nxt2, $cfa, 0);
+                (1, $cfa.next_x(), $cfa)
+            };
+
+        while i + 1 < $w {
+            apply_kernel_c!($T; $row, $prv2, $prv1, $curr, $nxt1, $nxt2, cfa_c, i);
+            apply_kernel_g!($T; $row, $prv2, $prv1, $curr, $nxt1, $nxt2, cfa_g, i + 1);
+            i += 2;
+        }
+
+        if i < $w {
+            apply_kernel_c!($T; $row, $prv2, $prv1, $curr, $nxt1, $nxt2, cfa_c, i);
+        }
+    }}
+}
+
+macro_rules! apply_kernel_c {
+    ($T:ident; $row:ident,
+            $prv2:expr, $prv1:expr, $curr:expr, $nxt1:expr, $nxt2:expr,
+            $cfa:expr, $i:expr) => {{
+        // current = B/R, diagonal = R/B.
+        let (c, d) = if $cfa == ColorFilterArray::Bggr {
+            (2, 0)
+        } else {
+            (0, 2)
+        };
+        let j = $i + PADDING;
+        let px = |v| PixelStor::to_f64(v);
+
+        let center = px($curr[j]);
+        let cross1 = px($prv1[j]) + px($curr[j - 1]) + px($curr[j + 1]) + px($nxt1[j]);
+        let cross2 = px($prv2[j]) + px($curr[j - 2]) + px($curr[j + 2]) + px($nxt2[j]);
+        let diag = px($prv1[j - 1]) + px($prv1[j + 1]) + px($nxt1[j - 1]) + px($nxt1[j + 1]);
+
+        $row[3 * $i + c] = $curr[j];
+        $row[3 * $i + 1] = norm16(8.0 * center + 4.0 * cross1 - 2.0 * cross2);
+        $row[3 * $i + d] = norm16(12.0 * center + 4.0 * diag - 3.0 * cross2);
+    }};
+}
+
+macro_rules! apply_kernel_g {
+    ($T:ident; $row:ident,
+            $prv2:expr, $prv1:expr, $curr:expr, $nxt1:expr, $nxt2:expr,
+            $cfa:expr, $i:expr) => {{
+        // horizontal = B/R, vertical = R/G.
+        let (h, v) = if $cfa == ColorFilterArray::Gbrg {
+            (2, 0)
+        } else {
+            (0, 2)
+        };
+        let j = $i + PADDING;
+        let px = |v| PixelStor::to_f64(v);
+
+        let center = px($curr[j]);
+        let h1 = px($curr[j - 1]) + px($curr[j + 1]);
+        let h2 = px($curr[j - 2]) + px($curr[j + 2]);
+        let v1 = px($prv1[j]) + px($nxt1[j]);
+        let v2 = px($prv2[j]) + px($nxt2[j]);
+        let diag = px($prv1[j - 1]) + px($prv1[j + 1]) + px($nxt1[j - 1]) + px($nxt1[j + 1]);
+
+        $row[3 * $i + h] = norm16(10.0 * center + 8.0 * h1 - 2.0 * h2 - 2.0 * diag + v2);
+        $row[3 * $i + 1] = $curr[j];
+        $row[3 * $i + v] = norm16(10.0 * center + 8.0 * v1 - 2.0 * v2 - 2.0 * diag + h2);
+    }};
+}
+
+/*--------------------------------------------------------------*/
+/* Rayon                                                        */
+/*--------------------------------------------------------------*/
+
+fn debayer<T>(r: &[T], cfa: ColorFilterArray, dst: &mut RasterMut<'_, T>) -> BayerResult<()>
+where
+    T: PixelStor + Enlargeable,
+{
+    if dst.w < 2 || dst.h < 2 {
+        return Err(BayerError::WrongResolution);
+    }
+
+    if dst.w <= PADDING || dst.h <= PADDING {
+        // Too small to mirror the border.
+        return crate::demosaic::linear::debayer(r, cfa, dst);
+    }
+
+    #[cfg(feature = "rayon")]
+    {
+        debayer_parallel(r, cfa, dst)
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        debayer_serial(r, cfa, dst)
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[inline(never)]
+fn debayer_parallel<T>(
+    r: &[T],
+    cfa: ColorFilterArray,
+    dst: &mut RasterMut<'_, T>,
+) -> BayerResult<()>
+where
+    T: PixelStor + Enlargeable,
+{
+    let (w, h) = (dst.w, dst.h);
+    let mut data = vec![T::zero(); (2 * PADDING + w) * (2 * PADDING + h)];
+
+    // Read all data.
+    {
+        let stride = 2 * PADDING + w;
+        let rdr = BorderMirror::new(w, PADDING);
+        for row in data.chunks_mut(stride).skip(PADDING).take(h) {
+            rdr.read_row(r, row)?;
+        }
+
+        {
+            let (top, src) = data.split_at_mut(stride * PADDING);
+            top[0..stride].copy_from_slice(&src[(stride * 2)..(stride * 3)]);
+            top[stride..(stride * 2)].copy_from_slice(&src[stride..(stride * 2)]);
+        }
+
+        {
+            let (src, bottom) = data.split_at_mut(stride * (h + PADDING));
+            let yy = PADDING + h;
+            bottom[0..stride].copy_from_slice(&src[(stride * (yy - 2))..(stride * (yy - 1))]);
+            bottom[stride..(stride * 2)]
+                .copy_from_slice(&src[(stride * (yy - 3))..(stride * (yy - 2))]);
+        }
+    }
+
+    dst.buf
+        .par_chunks_mut(dst.stride)
+        .enumerate()
+        .for_each(|(y, row)| {
+            let stride = 2 * PADDING + w;
+            let prv2 = &data[(stride * (PADDING + y - 2))..(stride * (PADDING + y - 1))];
+            let prv1 = &data[(stride * (PADDING + y - 1))..(stride * (PADDING + y))];
+            let curr = &data[(stride * (PADDING + y))..(stride * (PADDING + y + 1))];
+            let nxt1 = &data[(stride * (PADDING + y + 1))..(stride * (PADDING + y + 2))];
+            let nxt2 = &data[(stride * (PADDING + y + 2))..(stride * (PADDING + y + 3))];
+            let cfa_y = if y % 2 == 0 { cfa } else { cfa.next_y() };
+
+            apply_kernel_row!(T; row, prv2, prv1, curr, nxt1, nxt2, cfa_y, w);
+        });
+
+    Ok(())
+}
+
+/*--------------------------------------------------------------*/
+/* Naive                                                        */
+/*--------------------------------------------------------------*/
+
+#[cfg(not(feature = "rayon"))]
+#[inline(never)]
+fn debayer_serial<T>(r: &[T], cfa: ColorFilterArray, dst: &mut RasterMut<'_, T>) -> BayerResult<()>
+where
+    T: PixelStor + Enlargeable,
+{
+    let (w, h) = (dst.w, dst.h);
+    let mut prv2 = vec![T::zero(); 2 * PADDING + w];
+    let mut prv1 = vec![T::zero(); 2 * PADDING + w];
+    let mut curr = vec![T::zero(); 2 * PADDING + w];
+    let mut nxt1 = vec![T::zero(); 2 * PADDING + w];
+    let mut nxt2 = vec![T::zero(); 2 * PADDING + w];
+    let mut cfa = cfa;
+
+    let rdr = BorderMirror::new(w, PADDING);
+    rdr.read_row(r, &mut curr)?;
+    rdr.read_row(r, &mut nxt1)?;
+    rdr.read_row(r, &mut nxt2)?;
+
+    prv1.copy_from_slice(&nxt1);
+    prv2.copy_from_slice(&nxt2);
+
+    {
+        // y = 0.
+        let row = dst.borrow_row_mut(0);
+        apply_kernel_row!(T; row, nxt2, nxt1, curr, nxt1, nxt2, cfa, w);
+        cfa = cfa.next_y();
+    }
+
+    for y in 1..(h - 2) {
+        rotate!(prv2 <- prv1 <- curr <- nxt1 <- nxt2);
+        rdr.read_row(r, &mut nxt2)?;
+
+        let row = dst.borrow_row_mut(y);
+        apply_kernel_row!(T; row, prv2, prv1, curr, nxt1, nxt2, cfa, w);
+        cfa = cfa.next_y();
+    }
+
+    {
+        // y = h - 2.
+        let row = dst.borrow_row_mut(h - 2);
+        apply_kernel_row!(T; row, prv1, curr, nxt1, nxt2, nxt1, cfa, w);
+        cfa = cfa.next_y();
+    }
+
+    {
+        // y = h - 1.
+        let row = dst.borrow_row_mut(h - 1);
+        apply_kernel_row!(T; row, curr, nxt1, nxt2, nxt1, curr, cfa, w);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::debayer;
+    use crate::demosaic::{ColorFilterArray, RasterMut};
+
+    #[test]
+    fn test_even() {
+        // Input of the cubic tests. The expected output is a regression snapshot of this
+        // implementation, not an independent reference.
+        let src = [
+            229, 67, 95, 146, 232, 51, 229, 241, 169, 161, 15, 52, 45, 175, 98, 197, 127, 183, 253,
+            97, 199, 239, 54, 166, 32, 68, 98, 3, 97, 222, 87, 123, 153, 126, 47, 211, 171, 203,
+            27, 185, 105, 210, 165, 200, 141, 135, 202, 5, 122, 187, 177, 122, 220, 112, 62, 18,
+            25, 80, 132, 169, 104, 233, 75, 117,
+        ];
+
+        let expected = [
+            229, 177, 250, 154, 67, 124, 95, 0, 0, 237, 146, 111, 232, 98, 152, 208, 51, 136, 229,
+            165, 251, 255, 241, 255, 198, 169, 213, 214, 134, 161, 121, 15, 35, 160, 53, 52, 166,
+            45, 70, 189, 115, 175, 101, 98, 154, 164, 166, 197, 127, 94, 43, 242, 183, 183, 253,
+            166, 173, 224, 97, 38, 199, 130, 129, 218, 239, 255, 54, 111, 124, 78, 166, 194, 61,
+            32, 3, 113, 89, 68, 120, 98, 15, 68, 60, 3, 140, 97, 68, 198, 213, 222, 0, 87, 124, 12,
+            112, 123, 153, 131, 189, 110, 126, 143, 47, 79, 14, 151, 211, 164, 171, 187, 176, 132,
+            203, 220, 27, 144, 83, 31, 185, 110, 138, 105, 160, 178, 181, 210, 121, 165, 211, 207,
+            195, 200, 200, 141, 141, 98, 150, 135, 107, 202, 99, 0, 91, 5, 122, 108, 119, 194, 187,
+            189, 177, 169, 191, 190, 122, 170, 220, 151, 231, 148, 112, 158, 62, 86, 99, 0, 18, 0,
+            34, 25, 0, 84, 89, 80, 149, 132, 134, 192, 115, 169, 204, 104, 199, 212, 148, 233, 37,
+            75, 192, 61, 46, 117,
+        ];
+
+        const IMG_W: usize = 8;
+        const IMG_H: usize = 8;
+        let mut buf = [0u8; 3 * IMG_W * IMG_H];
+
+        let res = debayer(
+            &src,
+            ColorFilterArray::Rggb,
+            &mut RasterMut::new(IMG_W, IMG_H, &mut buf),
+        );
+        assert!(res.is_ok());
+        assert_eq!(&buf[..], &expected[..]);
+    }
+
+    #[test]
+    fn test_odd() {
+        // Input of the cubic tests. The expected output is a regression snapshot of this
+        // implementation, not an independent reference.
+        let src = [
+            229, 67, 95, 146, 232, 51, 229, 241, 169, 161, 15, 52, 45, 175, 98, 197, 127, 183, 253,
+            97, 199, 239, 54, 166, 32, 68, 98, 3, 97, 222, 87, 123, 153, 126, 47, 211, 171, 203,
+            27, 185, 105, 210, 165, 200, 141, 135, 202, 5, 122,
+        ];
+
+        let expected = [
+            229, 220, 255, 101, 67, 74, 95, 92, 29, 210, 146, 15, 232, 88, 48, 193, 51, 8, 229,
+            120, 55, 208, 241, 243, 188, 200, 169, 115, 161, 102, 139, 110, 15, 221, 52, 0, 224,
+            91, 45, 255, 175, 116, 98, 195, 76, 105, 197, 121, 127, 174, 63, 229, 183, 67, 253,
+            138, 104, 225, 97, 90, 199, 95, 74, 106, 239, 85, 63, 180, 54, 94, 166, 41, 143, 127,
+            32, 160, 68, 35, 184, 88, 98, 30, 3, 51, 97, 217, 103, 110, 222, 121, 87, 157, 39, 96,
+            123, 11, 153, 128, 70, 96, 126, 125, 47, 61, 19, 127, 211, 175, 171, 242, 171, 135,
+            203, 114, 103, 133, 27, 239, 185, 97, 147, 142, 105, 180, 210, 171, 165, 229, 206, 160,
+            200, 158, 141, 188, 103, 149, 135, 0, 202, 157, 111, 65, 5, 0, 122, 106, 103,
+        ];
+
+        const IMG_W: usize = 7;
+        const IMG_H: usize = 7;
+        let mut buf = [0u8; 3 * IMG_W * IMG_H];
+
+        let res = debayer(
+            &src,
+            ColorFilterArray::Rggb,
+            &mut RasterMut::new(IMG_W, IMG_H, &mut buf),
+        );
+        assert!(res.is_ok());
+        assert_eq!(&buf[..], &expected[..]);
+    }
+
+    #[test]
+    fn test_grbg() {
+        // Input of the cubic tests. The expected output is a regression snapshot of this
+        // implementation, not an independent reference.
+        let src = [
+            229, 67, 95, 146, 232, 51, 229, 241, 169, 161, 15, 52, 45, 175, 98,
+        ];
+
+        let expected = [
+            74, 229, 88, 67, 189, 137, 14, 95, 218, 146, 169, 205, 195, 232, 209, 0, 128, 51, 122,
+            229, 220, 161, 168, 241, 190, 169, 219, 131, 147, 161, 0, 15, 0, 52, 110, 117, 40, 45,
+            153, 175, 143, 235, 170, 98, 85,
+        ];
+
+        const IMG_W: usize = 5;
+        const IMG_H: usize = 3;
+        let mut buf = [0u8; 3 * IMG_W * IMG_H];
+
+        let res = debayer(
+            &src,
+            ColorFilterArray::Grbg,
+            &mut RasterMut::new(IMG_W, IMG_H, &mut buf),
+        );
+        assert!(res.is_ok());
+        assert_eq!(&buf[..], &expected[..]);
+    }
+}
@@ -1,23 +1,25 @@
 mod bayer;
-mod border_mirror;
-mod border_none;
-mod border_replicate;
 mod errcode;
 #[macro_use]
 mod none;
 mod raster;
 #[macro_use]
 mod rotate;
+mod ahd;
 mod cubic;
 mod linear;
+mod malvar;
 mod nearestneighbour;
 
 pub use bayer::{BayerRead, ColorFilterArray};
 pub use errcode::BayerError;
 pub use errcode::BayerResult;
+pub use raster::RowWriter;
 
 use crate::coretraits::Enlargeable;
+use crate::ColorSpace;
 use crate::ImageOwned;
+use crate::ImageProps;
 use crate::ImageRef;
 use crate::PixelStor;
 #[allow(unused_imports)]
@@ -44,6 +46,12 @@ pub enum DemosaicMethod {
     Linear,
     /// Cubic interpolation.
     Cubic,
+    /// Gradient-corrected linear interpolation (Malvar-He-Cutler), which reduces
+    /// color fringes at edges at a cost close to that of cubic interpolation.
+    Malvar,
+    /// Adaptive homogeneity-directed interpolation, which interpolates along edges
+    /// instead of across them. This is the slowest method.
+    Ahd,
 }
 
 pub(crate) fn run_demosaic_imagedata<T>(
@@ -60,6 +68,8 @@ where
         DemosaicMethod::Nearest => crate::demosaic::nearestneighbour::run_imagedata(r, cfa, dst),
         DemosaicMethod::Linear => crate::demosaic::linear::run_imagedata(r, cfa, dst),
         DemosaicMethod::Cubic => crate::demosaic::cubic::run_imagedata(r, cfa, dst),
+        DemosaicMethod::Malvar => crate::demosaic::malvar::run_imagedata(r, cfa, dst),
+        DemosaicMethod::Ahd => crate::demosaic::ahd::run_imagedata(r, cfa, dst),
     }
 }
 
@@ -77,6 +87,8 @@ where
         DemosaicMethod::Nearest => crate::demosaic::nearestneighbour::run_imageowned(r, cfa, dst),
         DemosaicMethod::Linear => crate::demosaic::linear::run_imageowned(r, cfa, dst),
         DemosaicMethod::Cubic => crate::demosaic::cubic::run_imageowned(r, cfa, dst),
+        DemosaicMethod::Malvar => crate::demosaic::malvar::run_imageowned(r, cfa, dst),
+        DemosaicMethod::Ahd => crate::demosaic::ahd::run_imageowned(r, cfa, dst),
     }
 }
 
@@ -102,9 +114,80 @@ where
     /// - [`DemosaicMethod::Nearest`]: Nearest neighbour interpolation.
     /// - [`DemosaicMethod::Linear`]: Linear interpolation.
     /// - [`DemosaicMethod::Cubic`]: Cubic interpolation.
+    /// - [`DemosaicMethod::Malvar`]: Gradient-corrected linear interpolation.
+    /// - [`DemosaicMethod::Ahd`]: Adaptive homogeneity-directed interpolation.
     ///
     /// # Errors
     /// - If the image is not a Bayer pattern image.
     /// - If the image is not a single channel image.
     fn debayer(&self, alg: DemosaicMethod) -> Result<Self::Output, BayerError>;
+
+    #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+    #[cfg(feature = "rayon")]
+    /// Debayer the image, running any parallel work inside the supplied thread pool.
+    ///
+    /// This allows the parallelism of each operation to be capped, e.g. when several
+    /// frames are processed concurrently. See [`Debayer::debayer`] for details.
+    ///
+    /// # Arguments
+    /// - `alg`: The demosaicing algorithm to use.
+    /// - `pool`: The [`rayon::ThreadPool`] to run the operation in.
+    fn debayer_in_pool(
+        &self,
+        alg: DemosaicMethod,
+        pool: &rayon::ThreadPool,
+    ) -> Result<Self::Output, BayerError>
+    where
+        Self: Sync,
+        Self::Output: Send,
+    {
+        pool.install(|| self.debayer(alg))
+    }
+}
+
+/// Evaluate the quality of a demosaicing algorithm against a known reference.
+///
+/// The Bayer mosaic image is debayered using the specified algorithm, and the peak
+/// signal-to-noise ratio (PSNR, in dB) of the result is calculated against the
+/// reference RGB image. The peak signal is the full range of the pixel type.
+///
+/// # Arguments
+/// - `reference`: The reference RGB image.
+/// - `bayered`: The Bayer mosaic image of the same scene.
+/// - `alg`: The demosaicing algorithm to evaluate.
+///
+/// # Returns
+/// The PSNR in dB. If the debayered image is identical to the reference, the PSNR is
+/// [`f64::INFINITY`].
+///
+/// # Errors
+/// - If the mosaic image is not a Bayer pattern image.
+/// - If the dimensions of the reference and mosaic images do not match.
+/// - If the reference image is not an RGB image.
+pub fn demosaic_psnr<T>(
+    reference: &ImageOwned<T>,
+    bayered: &ImageRef<T>,
+    alg: DemosaicMethod,
+) -> Result<f64, BayerError>
+where
+    T: PixelStor + Enlargeable,
+{
+    if reference.width() != bayered.width() || reference.height() != bayered.height() {
+        return Err(BayerError::WrongResolution);
+    }
+    if reference.color_space() != ColorSpace::Rgb {
+        return Err(BayerError::WrongDepth);
+    }
+    let debayered = bayered.debayer(alg)?;
+    let sqerr = debayered
+        .as_slice()
+        .iter()
+        .zip(reference.as_slice().iter())
+        .fold(0f64, |acc, (&a, &b)| {
+            let diff = a.to_f64() - b.to_f64();
+            acc + diff * diff
+        });
+    let mse = sqerr / reference.len() as f64;
+    let peak = T::DEFAULT_MAX_VALUE.to_f64() - T::DEFAULT_MIN_VALUE.to_f64();
+    Ok(10.0 * (peak * peak / mse).log10())
 }
@@ -4,7 +4,7 @@ use crate::demosaic::RasterMut;
 use crate::demosaic::{BayerError, BayerRead, BayerResult, ColorFilterArray};
 use crate::{ImageOwned, ImageProps, ImageRef, PixelStor};
 
-use super::border_replicate::BorderReplicate;
+use crate::border::BorderReplicate;
 
 const PADDING: usize = 1;
 
@@ -3,7 +3,7 @@ use crate::demosaic::RasterMut;
 use crate::demosaic::{BayerError, BayerRead, BayerResult, ColorFilterArray};
 use crate::{ImageOwned, ImageProps, ImageRef, PixelStor};
 
-use super::border_none::BorderNone;
+use crate::border::BorderNone;
 
 pub fn run_imagedata<T>(
     src: &ImageRef<'_, T>,
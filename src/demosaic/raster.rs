@@ -3,6 +3,132 @@ use crate::PixelStor;
 
 use super::RasterMut;
 
+/// Row-addressed mutable access to interleaved image data.
+///
+/// This wraps a mutable slice with the geometry of an image, so that a destination
+/// image can be filled scanline-by-scanline without computing offsets by hand.
+///
+/// # Usage
+/// ```
+/// use refimage::RowWriter;
+///
+/// let mut buf = vec![0u8; 3 * 2 * 3];
+/// let mut dst = RowWriter::new(&mut buf, 3, 2, 3).unwrap();
+/// for y in 0..dst.height() {
+///     dst.row_mut(y).unwrap().fill(y as u8 + 1);
+/// }
+/// assert_eq!(&buf[..9], &[1; 9]);
+/// assert_eq!(&buf[9..], &[2; 9]);
+/// ```
+#[derive(Debug)]
+pub struct RowWriter<'a, T: PixelStor> {
+    buf: &'a mut [T],
+    width: usize,
+    height: usize,
+    channels: usize,
+    stride: usize,
+}
+
+impl<'a, T: PixelStor> RowWriter<'a, T> {
+    /// Create a new [`RowWriter`] over tightly packed rows.
+    ///
+    /// # Arguments
+    /// - `buf`: The destination buffer.
+    /// - `width`: The width of the image, in pixels.
+    /// - `height`: The height of the image, in pixels.
+    /// - `channels`: The number of channels per pixel.
+    ///
+    /// # Errors
+    /// - If any of the dimensions is zero.
+    /// - If the buffer is too small for the image.
+    pub fn new(
+        buf: &'a mut [T],
+        width: usize,
+        height: usize,
+        channels: usize,
+    ) -> Result<Self, &'static str> {
+        let stride = width.checked_mul(channels).ok_or("Image size overflow.")?;
+        Self::with_stride(buf, width, height, channels, stride)
+    }
+
+    /// Create a new [`RowWriter`] with a custom row stride.
+    ///
+    /// # Arguments
+    /// - `buf`: The destination buffer.
+    /// - `width`: The width of the image, in pixels.
+    /// - `height`: The height of the image, in pixels.
+    /// - `channels`: The number of channels per pixel.
+    /// - `stride`: The distance between the starts of consecutive rows, in elements.
+    ///
+    /// # Errors
+    /// - If any of the dimensions is zero.
+    /// - If the stride is smaller than a row.
+    /// - If the buffer is too small for the image.
+    pub fn with_stride(
+        buf: &'a mut [T],
+        width: usize,
+        height: usize,
+        channels: usize,
+        stride: usize,
+    ) -> Result<Self, &'static str> {
+        if width == 0 || height == 0 || channels == 0 {
+            return Err("Image dimensions must be non-zero.");
+        }
+        let row = width.checked_mul(channels).ok_or("Image size overflow.")?;
+        if stride < row {
+            return Err("Stride is smaller than the row length.");
+        }
+        let len = stride
+            .checked_mul(height - 1)
+            .and_then(|len| len.checked_add(row))
+            .ok_or("Image size overflow.")?;
+        if buf.len() < len {
+            return Err("Buffer is too small for the image.");
+        }
+        Ok(Self {
+            buf,
+            width,
+            height,
+            channels,
+            stride,
+        })
+    }
+
+    /// Get the width of the image, in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get the height of the image, in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Get the number of channels per pixel.
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Get the distance between the starts of consecutive rows, in elements.
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Borrow a row of the image mutably.
+    ///
+    /// The row contains `width * channels` elements.
+    ///
+    /// # Errors
+    /// - If `y` is out of bounds.
+    pub fn row_mut(&mut self, y: usize) -> Result<&mut [T], &'static str> {
+        if y >= self.height {
+            return Err("Row index out of bounds.");
+        }
+        let start = self.stride * y;
+        Ok(&mut self.buf[start..start + self.width * self.channels])
+    }
+}
+
 impl<'a, T: PixelStor> RasterMut<'a, T> {
     /// Allocate a new raster for the given destination buffer slice.
     pub fn new(w: usize, h: usize, buf: &'a mut [T]) -> Self {
@@ -81,4 +207,18 @@ mod tests {
 
         assert_eq!(&buf[0..3 * IMG_W * IMG_H], &expected[..]);
     }
+
+    #[test]
+    fn test_row_writer() {
+        use super::RowWriter;
+        let mut buf = [0u16; 10];
+        assert!(RowWriter::with_stride(&mut buf, 3, 3, 1, 2).is_err());
+        assert!(RowWriter::with_stride(&mut buf, 2, 3, 1, 5).is_err());
+        let mut dst = RowWriter::with_stride(&mut buf, 2, 3, 1, 4).unwrap();
+        for y in 0..dst.height() {
+            dst.row_mut(y).unwrap().fill(y as u16 + 1);
+        }
+        assert!(dst.row_mut(3).is_err());
+        assert_eq!(buf, [1, 1, 0, 0, 2, 2, 0, 0, 3, 3]);
+    }
 }
@@ -8,6 +8,14 @@ macro_rules! rotate {
         $v1 = $v2;
         $v2 = rot;
     }};
+    ($v0:ident <- $v1:ident <- $v2:ident <- $v3:ident <- $v4:ident) => {{
+        let rot = $v0;
+        $v0 = $v1;
+        $v1 = $v2;
+        $v2 = $v3;
+        $v3 = $v4;
+        $v4 = rot;
+    }};
     ($v0:ident <- $v1:ident <- $v2:ident <- $v3:ident <- $v4:ident <- $v5:ident <- $v6:ident) => {{
         let rot = $v0;
         $v0 = $v1;
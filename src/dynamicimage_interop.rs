@@ -1,9 +1,11 @@
 //! Image interop
-use image::ImageBuffer;
+use std::time::{Duration, SystemTime};
+
+use image::{Frames, ImageBuffer, ImageDecoder, ImageEncoder, Luma, Rgb};
 
 use crate::{
     ColorSpace, DynamicImage, DynamicImageRef, GenericImage, GenericImageOwned, GenericImageRef,
-    ImageProps,
+    ImageProps, FRAME_DELAY_KEY,
 };
 
 #[cfg_attr(docsrs, doc(cfg(feature = "image")))]
@@ -30,6 +32,7 @@ impl<'a> TryFrom<DynamicImageRef<'a>> for DynamicImage {
                         .ok_or("Could not create Gray16 image")?,
                 )),
                 F32(_) => Err("Gray32F not supported"),
+                I16(_) | U32(_) => Err("Pixel type not supported"),
             },
             ColorSpace::Rgb => match value {
                 U8(data) => Ok(DynamicImage::ImageRgb8(
@@ -44,6 +47,34 @@ impl<'a> TryFrom<DynamicImageRef<'a>> for DynamicImage {
                     ImageBuffer::from_vec(width, height, data.into_vec())
                         .ok_or("Could not create Rgb32F image")?,
                 )),
+                I16(_) | U32(_) => Err("Pixel type not supported"),
+            },
+            ColorSpace::GrayAlpha => match value {
+                U8(data) => Ok(DynamicImage::ImageLumaA8(
+                    ImageBuffer::from_vec(width, height, data.into_vec())
+                        .ok_or("Could not create GrayAlpha8 image")?,
+                )),
+                U16(data) => Ok(DynamicImage::ImageLumaA16(
+                    ImageBuffer::from_vec(width, height, data.into_vec())
+                        .ok_or("Could not create GrayAlpha16 image")?,
+                )),
+                F32(_) => Err("GrayAlpha32F not supported"),
+                I16(_) | U32(_) => Err("Pixel type not supported"),
+            },
+            ColorSpace::Rgba => match value {
+                U8(data) => Ok(DynamicImage::ImageRgba8(
+                    ImageBuffer::from_vec(width, height, data.into_vec())
+                        .ok_or("Could not create Rgba8 image")?,
+                )),
+                U16(data) => Ok(DynamicImage::ImageRgba16(
+                    ImageBuffer::from_vec(width, height, data.into_vec())
+                        .ok_or("Could not create Rgba16 image")?,
+                )),
+                F32(data) => Ok(DynamicImage::ImageRgba32F(
+                    ImageBuffer::from_vec(width, height, data.into_vec())
+                        .ok_or("Could not create Rgba32F image")?,
+                )),
+                I16(_) | U32(_) => Err("Pixel type not supported"),
             },
             _ => Err("Unsupported color space"),
         }
@@ -80,6 +111,36 @@ impl TryFrom<DynamicImage> for DynamicImageOwned {
                 ImageOwned::new(data.into_raw(), wid.into(), hei.into(), ColorSpace::Rgb)
                     .map_err(|_| "Could not create DynamicImageOwned from ImageRgb32F")?,
             )),
+            DynamicImage::ImageLumaA8(data) => Ok(DynamicImageOwned::U8(
+                ImageOwned::new(
+                    data.into_raw(),
+                    wid.into(),
+                    hei.into(),
+                    ColorSpace::GrayAlpha,
+                )
+                .map_err(|_| "Could not create DynamicImageOwned from ImageLumaA8")?,
+            )),
+            DynamicImage::ImageRgba8(data) => Ok(DynamicImageOwned::U8(
+                ImageOwned::new(data.into_raw(), wid.into(), hei.into(), ColorSpace::Rgba)
+                    .map_err(|_| "Could not create DynamicImageOwned from ImageRgba8")?,
+            )),
+            DynamicImage::ImageLumaA16(data) => Ok(DynamicImageOwned::U16(
+                ImageOwned::new(
+                    data.into_raw(),
+                    wid.into(),
+                    hei.into(),
+                    ColorSpace::GrayAlpha,
+                )
+                .map_err(|_| "Could not create DynamicImageOwned from ImageLumaA16")?,
+            )),
+            DynamicImage::ImageRgba16(data) => Ok(DynamicImageOwned::U16(
+                ImageOwned::new(data.into_raw(), wid.into(), hei.into(), ColorSpace::Rgba)
+                    .map_err(|_| "Could not create DynamicImageOwned from ImageRgba16")?,
+            )),
+            DynamicImage::ImageRgba32F(data) => Ok(DynamicImageOwned::F32(
+                ImageOwned::new(data.into_raw(), wid.into(), hei.into(), ColorSpace::Rgba)
+                    .map_err(|_| "Could not create DynamicImageOwned from ImageRgba32F")?,
+            )),
             _ => Err("Unknown image type"),
         }
     }
@@ -109,6 +170,7 @@ impl TryFrom<DynamicImageOwned> for DynamicImage {
                         .ok_or("Could not create Gray16 image")?,
                 )),
                 F32(_) => Err("Gray32F not supported"),
+                I16(_) | U32(_) => Err("Pixel type not supported"),
             },
             ColorSpace::Rgb => match value {
                 U8(data) => Ok(DynamicImage::ImageRgb8(
@@ -123,12 +185,242 @@ impl TryFrom<DynamicImageOwned> for DynamicImage {
                     ImageBuffer::from_vec(width, height, data.into_vec())
                         .ok_or("Could not create Rgb32F image")?,
                 )),
+                I16(_) | U32(_) => Err("Pixel type not supported"),
+            },
+            ColorSpace::GrayAlpha => match value {
+                U8(data) => Ok(DynamicImage::ImageLumaA8(
+                    ImageBuffer::from_vec(width, height, data.into_vec())
+                        .ok_or("Could not create GrayAlpha8 image")?,
+                )),
+                U16(data) => Ok(DynamicImage::ImageLumaA16(
+                    ImageBuffer::from_vec(width, height, data.into_vec())
+                        .ok_or("Could not create GrayAlpha16 image")?,
+                )),
+                F32(_) => Err("GrayAlpha32F not supported"),
+                I16(_) | U32(_) => Err("Pixel type not supported"),
+            },
+            ColorSpace::Rgba => match value {
+                U8(data) => Ok(DynamicImage::ImageRgba8(
+                    ImageBuffer::from_vec(width, height, data.into_vec())
+                        .ok_or("Could not create Rgba8 image")?,
+                )),
+                U16(data) => Ok(DynamicImage::ImageRgba16(
+                    ImageBuffer::from_vec(width, height, data.into_vec())
+                        .ok_or("Could not create Rgba16 image")?,
+                )),
+                F32(data) => Ok(DynamicImage::ImageRgba32F(
+                    ImageBuffer::from_vec(width, height, data.into_vec())
+                        .ok_or("Could not create Rgba32F image")?,
+                )),
+                I16(_) | U32(_) => Err("Pixel type not supported"),
             },
             _ => Err("Unsupported color space"),
         }
     }
 }
 
+#[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+/// Move the data of a 16-bit grayscale [`ImageBuffer`] into an [`ImageOwned`], preserving
+/// the bit depth.
+impl TryFrom<ImageBuffer<Luma<u16>, Vec<u16>>> for ImageOwned<u16> {
+    type Error = &'static str;
+
+    fn try_from(data: ImageBuffer<Luma<u16>, Vec<u16>>) -> Result<Self, Self::Error> {
+        let (width, height) = data.dimensions();
+        ImageOwned::new(
+            data.into_raw(),
+            width as usize,
+            height as usize,
+            ColorSpace::Gray,
+        )
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+/// Move the data of a 16-bit RGB [`ImageBuffer`] into an [`ImageOwned`], preserving
+/// the bit depth.
+impl TryFrom<ImageBuffer<Rgb<u16>, Vec<u16>>> for ImageOwned<u16> {
+    type Error = &'static str;
+
+    fn try_from(data: ImageBuffer<Rgb<u16>, Vec<u16>>) -> Result<Self, Self::Error> {
+        let (width, height) = data.dimensions();
+        ImageOwned::new(
+            data.into_raw(),
+            width as usize,
+            height as usize,
+            ColorSpace::Rgb,
+        )
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+/// Move the data of a 16-bit grayscale [`ImageOwned`] into an [`ImageBuffer`], without
+/// an intermediate [`DynamicImage`].
+impl TryFrom<ImageOwned<u16>> for ImageBuffer<Luma<u16>, Vec<u16>> {
+    type Error = &'static str;
+
+    fn try_from(value: ImageOwned<u16>) -> Result<Self, Self::Error> {
+        if value.color_space() != ColorSpace::Gray {
+            return Err("Image is not grayscale");
+        }
+        let (width, height) = (value.width() as u32, value.height() as u32);
+        ImageBuffer::from_raw(width, height, value.into_vec())
+            .ok_or("Could not create Gray16 image")
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+/// Move the data of a 16-bit RGB [`ImageOwned`] into an [`ImageBuffer`], without an
+/// intermediate [`DynamicImage`].
+impl TryFrom<ImageOwned<u16>> for ImageBuffer<Rgb<u16>, Vec<u16>> {
+    type Error = &'static str;
+
+    fn try_from(value: ImageOwned<u16>) -> Result<Self, Self::Error> {
+        if value.color_space() != ColorSpace::Rgb {
+            return Err("Image is not RGB");
+        }
+        let (width, height) = (value.width() as u32, value.height() as u32);
+        ImageBuffer::from_raw(width, height, value.into_vec()).ok_or("Could not create Rgb16 image")
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+impl DynamicImageOwned {
+    /// Move the data into an [`image::GrayImage`] without an intermediate [`DynamicImage`].
+    ///
+    /// # Errors
+    /// - If the image is not a `u8` grayscale image.
+    pub fn into_luma_image(self) -> Result<image::GrayImage, &'static str> {
+        if self.color_space() != ColorSpace::Gray {
+            return Err("Image is not grayscale");
+        }
+        let (width, height) = (self.width() as u32, self.height() as u32);
+        match self {
+            DynamicImageOwned::U8(data) => ImageBuffer::from_raw(width, height, data.into_vec())
+                .ok_or("Could not create Gray8 image"),
+            _ => Err("Image is not of type u8"),
+        }
+    }
+
+    /// Move the data into an [`image::RgbImage`] without an intermediate [`DynamicImage`].
+    ///
+    /// # Errors
+    /// - If the image is not a `u8` RGB image.
+    pub fn into_rgb_image(self) -> Result<image::RgbImage, &'static str> {
+        if self.color_space() != ColorSpace::Rgb {
+            return Err("Image is not RGB");
+        }
+        let (width, height) = (self.width() as u32, self.height() as u32);
+        match self {
+            DynamicImageOwned::U8(data) => ImageBuffer::from_raw(width, height, data.into_vec())
+                .ok_or("Could not create Rgb8 image"),
+            _ => Err("Image is not of type u8"),
+        }
+    }
+
+    /// Move the data into an [`image::RgbaImage`] without an intermediate [`DynamicImage`].
+    ///
+    /// # Errors
+    /// - If the image is not a `u8` RGBA image.
+    pub fn into_rgba_image(self) -> Result<image::RgbaImage, &'static str> {
+        if self.color_space() != ColorSpace::Rgba {
+            return Err("Image is not RGBA");
+        }
+        let (width, height) = (self.width() as u32, self.height() as u32);
+        match self {
+            DynamicImageOwned::U8(data) => ImageBuffer::from_raw(width, height, data.into_vec())
+                .ok_or("Could not create Rgba8 image"),
+            _ => Err("Image is not of type u8"),
+        }
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+impl GenericImageOwned {
+    /// Convert the frames of an animated image into a sequence of [`GenericImageOwned`].
+    ///
+    /// The first frame is timestamped with `base_time`, and each subsequent frame is
+    /// timestamped with the sum of the delays of the preceding frames. The delay of each
+    /// frame is stored with the [`FRAME_DELAY_KEY`] key.
+    ///
+    /// # Note
+    /// - Frames are decoded as RGBA, and the alpha channel is discarded.
+    ///
+    /// # Arguments
+    /// - `frames`: The frames of the animated image, e.g. from [`image::AnimationDecoder::into_frames`].
+    /// - `base_time`: The timestamp of the first frame.
+    ///
+    /// # Errors
+    /// - If a frame could not be decoded.
+    pub fn from_frames(
+        frames: Frames<'_>,
+        base_time: SystemTime,
+    ) -> Result<Vec<GenericImageOwned>, &'static str> {
+        let mut tstamp = base_time;
+        frames
+            .map(|frame| {
+                let frame = frame.map_err(|_| "Could not decode frame")?;
+                let delay = Duration::from(frame.delay());
+                let data = DynamicImage::ImageRgba8(frame.into_buffer()).into_rgb8();
+                let image = DynamicImageOwned::try_from(DynamicImage::ImageRgb8(data))?;
+                let mut img = GenericImageOwned::new(tstamp, image);
+                img.insert_key(FRAME_DELAY_KEY, (delay, "Display duration of the frame"))?;
+                tstamp += delay;
+                Ok(img)
+            })
+            .collect()
+    }
+
+    /// Decode an image with an [`ImageDecoder`], e.g. a PNG or JPEG decoder.
+    ///
+    /// The ICC color profile embedded in the image, if any, is stored with
+    /// [`GenericImageOwned::set_icc_profile`].
+    ///
+    /// # Arguments
+    /// - `decoder`: The image decoder.
+    /// - `tstamp`: The timestamp of the image.
+    ///
+    /// # Errors
+    /// - If the ICC profile or the image could not be decoded.
+    /// - If the pixel type of the decoded image is not supported.
+    pub fn from_decoder(
+        mut decoder: impl ImageDecoder,
+        tstamp: SystemTime,
+    ) -> Result<GenericImageOwned, &'static str> {
+        let icc = decoder
+            .icc_profile()
+            .map_err(|_| "Could not read ICC profile")?;
+        let image = DynamicImage::from_decoder(decoder).map_err(|_| "Could not decode image")?;
+        let mut img = GenericImageOwned::new(tstamp, image.try_into()?);
+        if let Some(icc) = icc {
+            img.set_icc_profile(icc);
+        }
+        Ok(img)
+    }
+
+    /// Encode the image with an [`ImageEncoder`], e.g. a PNG or JPEG encoder.
+    ///
+    /// The ICC color profile of the image, if any, is embedded if the encoder supports it.
+    /// The rest of the metadata is not written.
+    ///
+    /// # Arguments
+    /// - `encoder`: The image encoder.
+    ///
+    /// # Errors
+    /// - If the image can not be converted to a [`DynamicImage`].
+    /// - If the image could not be encoded.
+    pub fn write_with_encoder(&self, mut encoder: impl ImageEncoder) -> Result<(), &'static str> {
+        if let Some(icc) = self.icc_profile() {
+            // Formats without ICC support reject the profile, the image is written without it.
+            let _ = encoder.set_icc_profile(icc.to_vec());
+        }
+        let image: DynamicImage = self.image.clone().try_into()?;
+        image
+            .write_with_encoder(encoder)
+            .map_err(|_| "Could not encode image")
+    }
+}
+
 impl TryFrom<GenericImageOwned> for DynamicImage {
     type Error = &'static str;
 
@@ -187,4 +479,145 @@ mod test {
             .expect("Failed to convert DynamicImage to DynamicImageOwned");
         assert_eq!(_d.width(), 128);
     }
+
+    #[test]
+    fn test_into_rgb_image() {
+        use super::DynamicImageOwned;
+        use crate::{ColorSpace, ImageOwned};
+        let data: Vec<u8> = (0..18).collect();
+        let a = ImageOwned::new(data, 3, 2, ColorSpace::Rgb).expect("Failed to create ImageOwned");
+        let b = DynamicImageOwned::from(a);
+        assert!(b.clone().into_luma_image().is_err());
+        assert!(b.clone().into_rgba_image().is_err());
+        let c = b.into_rgb_image().expect("Failed to convert to RgbImage");
+        assert_eq!(c.dimensions(), (3, 2));
+        assert_eq!(c.get_pixel(1, 0).0, [3, 4, 5]);
+
+        let data: Vec<u8> = (0..8).collect();
+        let a = ImageOwned::new(data.clone(), 2, 1, ColorSpace::Custom(4, "RGBN".into())).unwrap();
+        assert!(DynamicImageOwned::from(a).into_rgba_image().is_err());
+        let a = ImageOwned::new(data, 2, 1, ColorSpace::Rgba).unwrap();
+        let c = DynamicImageOwned::from(a.clone())
+            .into_rgba_image()
+            .unwrap();
+        assert_eq!(c.get_pixel(1, 0).0, [4, 5, 6, 7]);
+        let d = image::DynamicImage::ImageRgba8(c);
+        assert_eq!(DynamicImageOwned::try_from(d).unwrap(), a.into());
+    }
+
+    #[test]
+    fn test_image_buffer_u16() {
+        use crate::{ColorSpace, ImageOwned, ImageProps};
+        use image::{ImageBuffer, Luma, Rgb};
+        let data: Vec<u16> = vec![0, 300, 4095, 65535, 1, 12345];
+        let buf = ImageBuffer::<Luma<u16>, _>::from_raw(3, 2, data.clone()).unwrap();
+        let img = ImageOwned::try_from(buf).unwrap();
+        assert_eq!((img.width(), img.height()), (3, 2));
+        assert_eq!(img.color_space(), ColorSpace::Gray);
+        assert_eq!(img.as_slice(), data.as_slice());
+        assert!(ImageBuffer::<Rgb<u16>, _>::try_from(img.clone()).is_err());
+        let buf = ImageBuffer::<Luma<u16>, _>::try_from(img).unwrap();
+        assert_eq!(buf.get_pixel(1, 1).0, [1]);
+        assert_eq!(buf.into_raw(), data);
+
+        let buf = ImageBuffer::<Rgb<u16>, _>::from_raw(2, 1, data.clone()).unwrap();
+        let img = ImageOwned::try_from(buf).unwrap();
+        assert_eq!((img.width(), img.height()), (2, 1));
+        assert_eq!(img.color_space(), ColorSpace::Rgb);
+        let buf = ImageBuffer::<Rgb<u16>, _>::try_from(img).unwrap();
+        assert_eq!(buf.get_pixel(1, 0).0, [65535, 1, 12345]);
+    }
+
+    #[test]
+    fn test_from_frames() {
+        use crate::{GenericImageOwned, ImageProps, FRAME_DELAY_KEY};
+        use image::{Delay, Frame, Frames, RgbaImage};
+        use std::time::{Duration, SystemTime};
+        let frames = (0..3u8).map(|i| {
+            let buf = RgbaImage::from_pixel(4, 2, image::Rgba([i, i, i, 255]));
+            Ok(Frame::from_parts(
+                buf,
+                0,
+                0,
+                Delay::from_numer_denom_ms(100, 1),
+            ))
+        });
+        let base = SystemTime::UNIX_EPOCH;
+        let imgs = GenericImageOwned::from_frames(Frames::new(Box::new(frames)), base).unwrap();
+        assert_eq!(imgs.len(), 3);
+        assert_eq!(imgs[2].get_timestamp(), base + Duration::from_millis(200));
+        assert_eq!(imgs[2].channels(), 3);
+        assert_eq!(imgs[2].get_image().as_slice_u8().unwrap()[0], 2);
+        let delay: Duration = imgs[1]
+            .get_key(FRAME_DELAY_KEY)
+            .unwrap()
+            .get_value()
+            .clone()
+            .try_into()
+            .unwrap();
+        assert_eq!(delay, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_icc_passthrough() {
+        use crate::{ColorSpace, GenericImageOwned, ImageOwned};
+        use image::{
+            error::UnsupportedError, ColorType, ExtendedColorType, ImageDecoder, ImageEncoder,
+            ImageResult,
+        };
+        use std::time::SystemTime;
+
+        struct Decoder(Option<Vec<u8>>);
+        impl ImageDecoder for Decoder {
+            fn dimensions(&self) -> (u32, u32) {
+                (2, 1)
+            }
+            fn color_type(&self) -> ColorType {
+                ColorType::L8
+            }
+            fn icc_profile(&mut self) -> ImageResult<Option<Vec<u8>>> {
+                Ok(self.0.clone())
+            }
+            fn read_image(self, buf: &mut [u8]) -> ImageResult<()> {
+                buf.copy_from_slice(&[7, 8]);
+                Ok(())
+            }
+            fn read_image_boxed(self: Box<Self>, buf: &mut [u8]) -> ImageResult<()> {
+                (*self).read_image(buf)
+            }
+        }
+
+        struct Encoder<'a>(&'a mut Option<Vec<u8>>, &'a mut Vec<u8>);
+        impl ImageEncoder for Encoder<'_> {
+            fn write_image(
+                self,
+                buf: &[u8],
+                _: u32,
+                _: u32,
+                _: ExtendedColorType,
+            ) -> ImageResult<()> {
+                self.1.extend_from_slice(buf);
+                Ok(())
+            }
+            fn set_icc_profile(&mut self, icc_profile: Vec<u8>) -> Result<(), UnsupportedError> {
+                *self.0 = Some(icc_profile);
+                Ok(())
+            }
+        }
+
+        let img = GenericImageOwned::from_decoder(Decoder(Some(vec![1, 2, 3])), SystemTime::now())
+            .unwrap();
+        assert_eq!(img.icc_profile(), Some([1u8, 2, 3].as_slice()));
+        assert_eq!(img.get_image().as_slice_u8().unwrap(), [7, 8]);
+        let img = GenericImageOwned::from_decoder(Decoder(None), SystemTime::now()).unwrap();
+        assert!(img.icc_profile().is_none());
+
+        let data = ImageOwned::from_owned(vec![4u8, 5], 2, 1, ColorSpace::Gray).unwrap();
+        let mut img = GenericImageOwned::new(SystemTime::now(), data.into());
+        img.set_icc_profile(vec![9, 9]);
+        let (mut icc, mut buf) = (None, Vec::new());
+        img.write_with_encoder(Encoder(&mut icc, &mut buf)).unwrap();
+        assert_eq!(icc, Some(vec![9, 9]));
+        assert_eq!(buf, [4, 5]);
+    }
 }
@@ -1,8 +1,161 @@
 use crate::imagetraits::ImageProps;
 use crate::{ColorSpace, DynamicImageOwned, DynamicImageRef, ImageOwned, PixelType};
 use crate::{Deserializer, Serializer};
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::SerializeTuple;
 use serde::{Deserialize, Serialize};
 
+/// Magic tag that precedes the version of the serialized image format.
+const SERIAL_MAGIC: [u8; 4] = *b"RIMG";
+
+/// Current version of the serialized image format.
+const SERIAL_VERSION: u16 = 1;
+
+/// A [`SerialImage`] prefixed with a magic tag and the version of the serialization format.
+///
+/// The magic tag and the version are read first, and the rest of the data is parsed
+/// according to the version. Data without the magic tag, e.g. written by unversioned
+/// earlier writers, and unknown versions are rejected instead of being misparsed. The
+/// unversioned format is read by [`DynamicImageOwned::deserialize_v0`].
+struct VersionedImage(SerialImage);
+
+impl Serialize for VersionedImage {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut tup = serializer.serialize_tuple(3)?;
+        tup.serialize_element(&SERIAL_MAGIC)?;
+        tup.serialize_element(&SERIAL_VERSION)?;
+        tup.serialize_element(&self.0)?;
+        tup.end()
+    }
+}
+
+struct VersionedImageVisitor;
+
+impl<'de> Visitor<'de> for VersionedImageVisitor {
+    type Value = VersionedImage;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a magic tag and format version followed by serialized image data")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let magic: [u8; 4] = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        if magic != SERIAL_MAGIC {
+            return Err(de::Error::custom(
+                "Missing image serialization magic tag, data from earlier unversioned writers \
+                 can be read with `DynamicImageOwned::deserialize_v0`",
+            ));
+        }
+        let version: u16 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        match version {
+            1 => seq
+                .next_element::<SerialImage>()?
+                .map(VersionedImage)
+                .ok_or_else(|| de::Error::invalid_length(2, &self)),
+            _ => Err(de::Error::custom(format!(
+                "Unsupported image serialization format version {version}"
+            ))),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionedImage {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple(3, VersionedImageVisitor)
+    }
+}
+
+/// Compression codec of the pixel data of a [`SerialImage`].
+///
+/// Serialized as a single byte, whose value `0` coincides with `false` of the boolean
+/// flag used by earlier writers. Human-readable formats also accept the boolean flag.
+///
+/// Images are written with zstd compression if the `serde_zstd` feature is enabled, and
+/// uncompressed otherwise. Compressed data can only be read if the reader has the same
+/// feature enabled; otherwise deserialization fails with an error naming the feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    /// The data is not compressed.
+    None = 0,
+    /// The data is compressed with zstd.
+    Zstd = 2,
+}
+
+impl TryFrom<u64> for Compression {
+    type Error = String;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Compression::None),
+            2 => Ok(Compression::Zstd),
+            _ => Err(format!("Unknown image compression codec {value}")),
+        }
+    }
+}
+
+impl Serialize for Compression {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+struct CompressionVisitor;
+
+impl Visitor<'_> for CompressionVisitor {
+    type Value = Compression;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a compression codec byte or a compression flag")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        if v {
+            Err(E::custom("Compressed image data is not supported"))
+        } else {
+            Ok(Compression::None)
+        }
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Compression::try_from(v).map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        u64::try_from(v)
+            .map_err(|_| E::custom(format!("Unknown image compression codec {v}")))
+            .and_then(|v| self.visit_u64(v))
+    }
+}
+
+impl<'de> Deserialize<'de> for Compression {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(CompressionVisitor)
+        } else {
+            deserializer.deserialize_u8(CompressionVisitor)
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 struct SerialImage {
     width: u16,
@@ -10,11 +163,57 @@ struct SerialImage {
     channels: u8,
     cspace: ColorSpace,
     pixeltype: i8,
-    compressed: bool,
+    compressed: Compression,
     data: Vec<u8>,
     crc: u32,
 }
 
+impl SerialImage {
+    /// Compress the pixel data with the codec enabled in this build.
+    ///
+    /// The checksum is that of the uncompressed data, and is not changed.
+    #[cfg(feature = "serde_zstd")]
+    fn compress(mut self) -> Result<Self, &'static str> {
+        if self.compressed == Compression::None {
+            self.data =
+                zstd::bulk::compress(&self.data, 0).map_err(|_| "Could not compress image data")?;
+            self.compressed = Compression::Zstd;
+        }
+        Ok(self)
+    }
+
+    /// Compress the pixel data with the codec enabled in this build.
+    #[cfg(not(feature = "serde_zstd"))]
+    fn compress(self) -> Result<Self, &'static str> {
+        Ok(self)
+    }
+
+    /// Decompress the pixel data in-place.
+    ///
+    /// # Errors
+    /// - If the codec of the data is not enabled, or the data is invalid.
+    /// - If the decompressed data would be larger than `max_len` bytes.
+    fn decompress(&mut self, max_len: usize) -> Result<(), &'static str> {
+        match self.compressed {
+            Compression::None => {}
+            #[cfg(feature = "serde_zstd")]
+            Compression::Zstd => {
+                self.data = zstd::bulk::decompress(&self.data, max_len)
+                    .map_err(|_| "Could not decompress zstd image data")?;
+            }
+            #[cfg(not(feature = "serde_zstd"))]
+            Compression::Zstd => {
+                let _ = max_len;
+                return Err(
+                    "Image data is compressed with zstd, enable the `serde_zstd` feature to read it",
+                );
+            }
+        }
+        self.compressed = Compression::None;
+        Ok(())
+    }
+}
+
 impl<'a> TryFrom<&'a DynamicImageRef<'a>> for SerialImage {
     type Error = &'static str;
 
@@ -27,7 +226,7 @@ impl<'a> TryFrom<&'a DynamicImageRef<'a>> for SerialImage {
         let data = data.as_raw_u8();
         let out = data.to_vec();
         let crc = crc32fast::hash(data);
-        let compressed = false;
+        let compressed = Compression::None;
 
         Ok(SerialImage {
             width: width as _,
@@ -48,8 +247,9 @@ impl Serialize for DynamicImageRef<'_> {
         S: Serializer,
     {
         SerialImage::try_from(self)
+            .and_then(SerialImage::compress)
             .map_err(|_| serde::ser::Error::custom("Could not serialize DynamicImageRef"))
-            .and_then(|img| img.serialize(serializer))
+            .and_then(|img| VersionedImage(img).serialize(serializer))
     }
 }
 
@@ -65,7 +265,7 @@ impl TryFrom<&DynamicImageOwned> for SerialImage {
         let data = data.as_raw_u8();
         let out = data.to_vec();
         let crc = crc32fast::hash(data);
-        let compressed = false;
+        let compressed = Compression::None;
         Ok(SerialImage {
             width: width as _,
             height: height as _,
@@ -83,15 +283,23 @@ impl TryFrom<SerialImage> for DynamicImageOwned {
     type Error = &'static str;
 
     fn try_from(data: SerialImage) -> Result<Self, Self::Error> {
+        DynamicImageOwned::from_serial(data, true)
+    }
+}
+
+impl DynamicImageOwned {
+    /// Build the image from its serialized form, optionally verifying the data checksum.
+    fn from_serial(mut data: SerialImage, verify_crc: bool) -> Result<Self, &'static str> {
         let width = data.width;
         let height = data.height;
         let channels = data.channels;
+        let pixeltype: PixelType = data.pixeltype.try_into()?;
+        let len = usize::from(width) * usize::from(height) * usize::from(channels);
+        data.decompress(len * pixeltype.size_bytes())?;
         let cspace = data.cspace;
-        let pixeltype = data.pixeltype.try_into()?;
         #[allow(unused_mut)]
         let mut out = data.data;
-        let crc = crc32fast::hash(&out);
-        if data.crc != crc {
+        if verify_crc && data.crc != crc32fast::hash(&out) {
             return Err("Invalid data checksum");
         }
         match pixeltype {
@@ -115,6 +323,32 @@ impl TryFrom<SerialImage> for DynamicImageOwned {
                 }
                 Ok(DynamicImageOwned::U16(img))
             }
+            PixelType::I16 => {
+                let data = u8_slice_as_i16(&out).map_err(|_| "Could not cast u8 slice as i16")?;
+                let img = ImageOwned::new(
+                    data.as_slice().to_vec(),
+                    width.into(),
+                    height.into(),
+                    cspace,
+                )?;
+                if img.channels() != channels {
+                    return Err("Data length does not match image size.");
+                }
+                Ok(DynamicImageOwned::I16(img))
+            }
+            PixelType::U32 => {
+                let data = u8_slice_as_u32(&out).map_err(|_| "Could not cast u8 slice as u32")?;
+                let img = ImageOwned::new(
+                    data.as_slice().to_vec(),
+                    width.into(),
+                    height.into(),
+                    cspace,
+                )?;
+                if img.channels() != channels {
+                    return Err("Data length does not match image size.");
+                }
+                Ok(DynamicImageOwned::U32(img))
+            }
             PixelType::F32 => {
                 let data = u8_slice_as_f32(&out).map_err(|_| "Could not cast u8 slice as f32")?;
                 let img = ImageOwned::new(
@@ -139,8 +373,9 @@ impl Serialize for DynamicImageOwned {
         S: Serializer,
     {
         SerialImage::try_from(self)
+            .and_then(SerialImage::compress)
             .map_err(|_| serde::ser::Error::custom("Could not serialize DynamicImageOwned"))
-            .and_then(|img| img.serialize(serializer))
+            .and_then(|img| VersionedImage(img).serialize(serializer))
     }
 }
 
@@ -149,13 +384,86 @@ impl<'de> Deserialize<'de> for DynamicImageOwned {
     where
         D: Deserializer<'de>,
     {
-        SerialImage::deserialize(deserializer).and_then(|img| {
-            DynamicImageOwned::try_from(img)
-                .map_err(|_| serde::de::Error::custom("Could not deserialize DynamicImageOwned"))
+        VersionedImage::deserialize(deserializer).and_then(|VersionedImage(img)| {
+            DynamicImageOwned::try_from(img).map_err(|e| {
+                de::Error::custom(format!("Could not deserialize DynamicImageOwned: {e}"))
+            })
         })
     }
 }
 
+impl DynamicImageOwned {
+    /// Deserialize an image without verifying the checksum of its data.
+    ///
+    /// The serialized format is the same as that of the [`Deserialize`] implementation,
+    /// and for valid input the result is identical. Skipping the checksum saves hashing
+    /// the whole image, and is only appropriate for trusted input, e.g. a local cache
+    /// written by [`Serialize`]. Corrupted data is not detected.
+    ///
+    /// # Example
+    /// ```
+    /// use refimage::{ColorSpace, DynamicImageOwned, ImageOwned};
+    /// let img = ImageOwned::from_owned(vec![1u16, 2, 3, 4], 2, 2, ColorSpace::Gray).unwrap();
+    /// let img = DynamicImageOwned::from(img);
+    /// let json = serde_json::to_string(&img).unwrap();
+    /// let mut de = serde_json::Deserializer::from_str(&json);
+    /// let out = DynamicImageOwned::deserialize_unchecked(&mut de).unwrap();
+    /// assert_eq!(out, img);
+    /// ```
+    ///
+    /// # Errors
+    /// - If the data is not a serialized image, or the image is invalid.
+    pub fn deserialize_unchecked<'de, D>(deserializer: D) -> Result<DynamicImageOwned, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        VersionedImage::deserialize(deserializer).and_then(|VersionedImage(img)| {
+            DynamicImageOwned::from_serial(img, false).map_err(|e| {
+                de::Error::custom(format!("Could not deserialize DynamicImageOwned: {e}"))
+            })
+        })
+    }
+}
+
+impl DynamicImageOwned {
+    /// Deserialize an image written by the unversioned format of earlier releases.
+    ///
+    /// Earlier releases serialized images without the magic tag and format version that
+    /// now precede the image data, and such data is rejected by the [`Deserialize`]
+    /// implementation. This reads the unversioned layout, which does not support
+    /// compression, and verifies the checksum of the data.
+    ///
+    /// # Example
+    /// ```
+    /// use bincode::Options;
+    /// use refimage::DynamicImageOwned;
+    /// // A 2x1 grayscale u8 image written by an unversioned release
+    /// let blob = [
+    ///     2, 0, 1, 0, 1, 0, 0, 0, 0, 8, 0, 2, 0, 0, 0, 0, 0, 0, 0, 7, 8, 10, 12, 67, 0,
+    /// ];
+    /// let opts = bincode::options().with_fixint_encoding();
+    /// let mut de = bincode::Deserializer::from_slice(&blob, opts);
+    /// let img = DynamicImageOwned::deserialize_v0(&mut de).unwrap();
+    /// assert_eq!(img.as_slice_u8(), Some([7u8, 8].as_slice()));
+    /// ```
+    ///
+    /// # Errors
+    /// - If the data is not an unversioned serialized image, or the image is invalid.
+    pub fn deserialize_v0<'de, D>(deserializer: D) -> Result<DynamicImageOwned, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let img = SerialImage::deserialize(deserializer)?;
+        if img.compressed != Compression::None {
+            return Err(de::Error::custom(
+                "Unversioned image data can not be compressed",
+            ));
+        }
+        DynamicImageOwned::from_serial(img, true)
+            .map_err(|e| de::Error::custom(format!("Could not deserialize DynamicImageOwned: {e}")))
+    }
+}
+
 enum DtypeContainer<'a, T> {
     Slice(&'a [T]),
     Vec(Vec<T>),
@@ -220,8 +528,292 @@ fn u8_slice_as_u16(buf: &[u8]) -> ByteResult<DtypeContainer<u16>> {
     }
 }
 
+fn u8_slice_as_i16(buf: &[u8]) -> ByteResult<DtypeContainer<'_, i16>> {
+    let res = bytemuck::try_cast_slice(buf);
+    match res {
+        Ok(slc) => Ok(DtypeContainer::<i16>::Slice(slc)),
+        Err(err) => match err {
+            bytemuck::PodCastError::TargetAlignmentGreaterAndInputNotAligned => {
+                // If the buffer is not aligned for an i16 slice, copy the buffer into a new Vec<i16>
+                let vec = buf
+                    .chunks_exact(2)
+                    .map(|chunk| i16::from_ne_bytes([chunk[0], chunk[1]]))
+                    .collect();
+                Ok(DtypeContainer::Vec(vec))
+            }
+            _ => Err(err.to_string()),
+        },
+    }
+}
+
+fn u8_slice_as_u32(buf: &[u8]) -> ByteResult<DtypeContainer<'_, u32>> {
+    let res = bytemuck::try_cast_slice(buf);
+    match res {
+        Ok(slc) => Ok(DtypeContainer::<u32>::Slice(slc)),
+        Err(err) => match err {
+            bytemuck::PodCastError::TargetAlignmentGreaterAndInputNotAligned => {
+                // If the buffer is not aligned for a u32 slice, copy the buffer into a new Vec<u32>
+                let vec = buf
+                    .chunks_exact(4)
+                    .map(|chunk| u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                    .collect();
+                Ok(DtypeContainer::Vec(vec))
+            }
+            _ => Err(err.to_string()),
+        },
+    }
+}
+
 mod test {
 
+    #[test]
+    fn test_versioned_roundtrip() {
+        use crate::{ColorSpace, DynamicImageOwned, ImageOwned};
+        let img = ImageOwned::from_owned(vec![1u16, 2, 3, 4, 5, 6], 3, 2, ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let img = DynamicImageOwned::from(img);
+        let v1 = super::SerialImage::try_from(&img)
+            .and_then(super::SerialImage::compress)
+            .unwrap();
+        let blob = bincode::serialize(&(*b"RIMG", 1u16, v1)).unwrap();
+        assert_eq!(blob, bincode::serialize(&img).unwrap());
+        let de: DynamicImageOwned = bincode::deserialize(&blob).unwrap();
+        assert_eq!(de, img);
+        let json = serde_json::to_string(&img).unwrap();
+        let de: DynamicImageOwned = serde_json::from_str(&json).unwrap();
+        assert_eq!(de, img);
+        let mut blob = blob;
+        blob[4..6].copy_from_slice(&2u16.to_le_bytes());
+        let err = bincode::deserialize::<DynamicImageOwned>(&blob).unwrap_err();
+        assert!(err.to_string().contains("version 2"));
+        // Unversioned data whose width would parse as version 1 is not misread
+        let img = ImageOwned::from_owned(vec![1u16, 2], 1, 2, ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let legacy = super::SerialImage::try_from(&DynamicImageOwned::from(img)).unwrap();
+        let blob = bincode::serialize(&legacy).unwrap();
+        assert_eq!(blob[..2], 1u16.to_le_bytes());
+        let err = bincode::deserialize::<DynamicImageOwned>(&blob).unwrap_err();
+        assert!(err.to_string().contains("magic tag"));
+        let json = serde_json::to_string(&legacy).unwrap();
+        assert!(serde_json::from_str::<DynamicImageOwned>(&json).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_v0() {
+        use crate::{BayerPattern, ColorSpace, DynamicImageOwned, ImageOwned, ImageProps};
+        use bincode::Options;
+        // Written by the unversioned serializer of earlier releases
+        let blob: [u8; 35] = [
+            3, 0, 2, 0, 1, 0, 0, 0, 0, 16, 0, 12, 0, 0, 0, 0, 0, 0, 0, 1, 0, 2, 0, 3, 0, 4, 0, 5,
+            0, 6, 0, 124, 72, 31, 144,
+        ];
+        let json = r#"{"width":3,"height":2,"channels":1,"cspace":"Gray","pixeltype":16,"compressed":false,"data":[1,0,2,0,3,0,4,0,5,0,6,0],"crc":2417969276}"#;
+        let bayer: [u8; 31] = [
+            2, 0, 2, 0, 1, 1, 0, 0, 0, 3, 0, 0, 0, 8, 0, 4, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4, 205,
+            251, 60, 182,
+        ];
+        let opts = || bincode::options().with_fixint_encoding();
+        let img = ImageOwned::from_owned(vec![1u16, 2, 3, 4, 5, 6], 3, 2, ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let img = DynamicImageOwned::from(img);
+        let err = bincode::deserialize::<DynamicImageOwned>(&blob).unwrap_err();
+        assert!(err.to_string().contains("deserialize_v0"));
+        let mut de = bincode::Deserializer::from_slice(&blob, opts());
+        assert_eq!(DynamicImageOwned::deserialize_v0(&mut de).unwrap(), img);
+        let mut de = serde_json::Deserializer::from_str(json);
+        assert_eq!(DynamicImageOwned::deserialize_v0(&mut de).unwrap(), img);
+        let mut de = bincode::Deserializer::from_slice(&bayer, opts());
+        let out = DynamicImageOwned::deserialize_v0(&mut de).unwrap();
+        assert_eq!(out.color_space(), ColorSpace::Bayer(BayerPattern::Rggb));
+        assert_eq!(out.as_slice_u8(), Some([1u8, 2, 3, 4].as_slice()));
+        // The checksum is verified
+        let mut blob = blob;
+        blob[20] ^= 0xff;
+        let mut de = bincode::Deserializer::from_slice(&blob, opts());
+        assert!(DynamicImageOwned::deserialize_v0(&mut de).is_err());
+        // Versioned data is not read as unversioned
+        let blob = bincode::serialize(&img).unwrap();
+        let mut de = bincode::Deserializer::from_slice(&blob, opts());
+        assert!(DynamicImageOwned::deserialize_v0(&mut de).is_err());
+    }
+
+    #[test]
+    fn test_compression_codec() {
+        use crate::{ColorSpace, DynamicImageOwned, ImageOwned};
+        let img = ImageOwned::from_owned(vec![1u16, 2, 3, 4, 5, 6], 3, 2, ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let img = DynamicImageOwned::from(img);
+        // Magic, version, width, height, channels, color space and pixel type precede the codec
+        let pos = 4 + 2 + 2 + 2 + 1 + 4 + 1;
+        let mut blob = bincode::serialize(&img).unwrap();
+        assert_eq!(blob[pos], if cfg!(feature = "serde_zstd") { 2 } else { 0 });
+        // Images written with the boolean compression flag still deserialize
+        let serial = super::SerialImage::try_from(&img).unwrap();
+        let json = serde_json::to_string(&(*b"RIMG", 1u16, serial))
+            .unwrap()
+            .replace("\"compressed\":0", "\"compressed\":false");
+        assert!(json.contains("false"));
+        let de: DynamicImageOwned = serde_json::from_str(&json).unwrap();
+        assert_eq!(de, img);
+        let err =
+            serde_json::from_str::<DynamicImageOwned>(&json.replace("false", "true")).unwrap_err();
+        assert!(err.to_string().contains("not supported"));
+        blob[pos] = 1;
+        let err = bincode::deserialize::<DynamicImageOwned>(&blob).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Unknown image compression codec 1"));
+    }
+
+    #[test]
+    fn test_zstd_codec() {
+        use super::{Compression, SerialImage};
+        use crate::{ColorSpace, DynamicImageOwned, ImageOwned};
+        let img = ImageOwned::from_owned((0..4096u16).collect(), 64, 64, ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let img = DynamicImageOwned::from(img);
+        let raw = SerialImage::try_from(&img).unwrap();
+        let mut serial = SerialImage::try_from(&img).unwrap();
+        serial.compressed = Compression::Zstd;
+        serial.data = vec![0; 16];
+        let blob = bincode::serialize(&(*b"RIMG", 1u16, serial)).unwrap();
+        let res = bincode::deserialize::<DynamicImageOwned>(&blob);
+        if cfg!(feature = "serde_zstd") {
+            let blob = bincode::serialize(&img).unwrap();
+            assert!(blob.len() < raw.data.len());
+            let de: DynamicImageOwned = bincode::deserialize(&blob).unwrap();
+            assert_eq!(de, img);
+            let json = serde_json::to_string(&img).unwrap();
+            let de: DynamicImageOwned = serde_json::from_str(&json).unwrap();
+            assert_eq!(de, img);
+            assert!(res.unwrap_err().to_string().contains("decompress"));
+        } else {
+            assert!(res.unwrap_err().to_string().contains("serde_zstd"));
+        }
+    }
+
+    #[test]
+    fn test_deserialize_unchecked() {
+        use crate::{ColorSpace, DynamicImageOwned, ImageOwned};
+        use bincode::Options;
+        let opts = || bincode::options().with_fixint_encoding();
+        let img = ImageOwned::from_owned(vec![1u16, 2, 3, 4, 5, 6], 3, 2, ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let img = DynamicImageOwned::from(img);
+        let blob = bincode::serialize(&img).unwrap();
+        let mut de = bincode::Deserializer::from_slice(&blob, opts());
+        assert_eq!(
+            DynamicImageOwned::deserialize_unchecked(&mut de).unwrap(),
+            img
+        );
+        // Corrupt the last pixel of uncompressed data, which precedes the checksum
+        let serial = super::SerialImage::try_from(&img).unwrap();
+        let blob = bincode::serialize(&(*b"RIMG", 1u16, serial)).unwrap();
+        let mut blob = blob;
+        let pos = blob.len() - 5;
+        blob[pos] ^= 0xff;
+        assert!(bincode::deserialize::<DynamicImageOwned>(&blob).is_err());
+        let mut de = bincode::Deserializer::from_slice(&blob, opts());
+        let out = DynamicImageOwned::deserialize_unchecked(&mut de).unwrap();
+        assert_eq!(out.as_slice_u16().unwrap()[5], 6 ^ 0xff00);
+    }
+
+    #[test]
+    fn test_pixeltype_roundtrip() {
+        use super::SerialImage;
+        use crate::{ColorSpace, DynamicImageOwned, ImageOwned, ImageProps, PixelType};
+        #[allow(unused_mut)]
+        let mut types = vec![
+            PixelType::U8,
+            PixelType::U16,
+            PixelType::U32,
+            PixelType::U64,
+            PixelType::I8,
+            PixelType::I16,
+            PixelType::I32,
+            PixelType::I64,
+            PixelType::F32,
+            PixelType::F64,
+        ];
+        #[cfg(feature = "f16")]
+        types.push(PixelType::F16);
+        let img = ImageOwned::from_owned(vec![1u8, 2, 3, 4], 2, 2, ColorSpace::Gray).unwrap();
+        let img = DynamicImageOwned::from(img);
+        for &pt in &types {
+            assert_eq!(PixelType::try_from(pt as i8), Ok(pt));
+            let blob = bincode::serialize(&pt).unwrap();
+            assert_eq!(bincode::deserialize::<PixelType>(&blob).unwrap(), pt);
+            let json = serde_json::to_string(&pt).unwrap();
+            assert_eq!(serde_json::from_str::<PixelType>(&json).unwrap(), pt);
+            // The discriminant survives the `pixeltype` field of the serialized image
+            let mut serial = SerialImage::try_from(&img).unwrap();
+            serial.pixeltype = pt as i8;
+            let blob = bincode::serialize(&(*b"RIMG", 1u16, serial)).unwrap();
+            let (_, _, serial): ([u8; 4], u16, SerialImage) = bincode::deserialize(&blob).unwrap();
+            assert_eq!(PixelType::try_from(serial.pixeltype), Ok(pt));
+        }
+        // Every byte that is not a discriminant is rejected
+        let valid = (i8::MIN..=i8::MAX)
+            .filter(|&v| PixelType::try_from(v).is_ok())
+            .count();
+        assert_eq!(valid, types.len());
+        assert!(PixelType::try_from(0i8).is_err());
+        assert!(PixelType::try_from(-127i8).is_err());
+        assert!(PixelType::I32.is_signed() && !PixelType::I32.is_float());
+        assert!(PixelType::F32.is_signed() && PixelType::F32.is_float());
+        assert!(!PixelType::U16.is_signed());
+
+        for (byte, msg) in [
+            (0, "Invalid value for PixelType"),
+            (64, "Invalid pixel type."),
+        ] {
+            let mut serial = SerialImage::try_from(&img).unwrap();
+            serial.pixeltype = byte;
+            let blob = bincode::serialize(&(*b"RIMG", 1u16, serial)).unwrap();
+            let err = bincode::deserialize::<DynamicImageOwned>(&blob).unwrap_err();
+            assert!(err.to_string().contains(msg));
+        }
+
+        let imgs: Vec<DynamicImageOwned> = vec![
+            ImageOwned::from_owned(vec![0u8; 4], 2, 2, ColorSpace::Gray)
+                .unwrap()
+                .into(),
+            ImageOwned::from_owned(vec![0u16; 4], 2, 2, ColorSpace::Gray)
+                .unwrap()
+                .into(),
+            ImageOwned::from_owned(vec![0i16; 4], 2, 2, ColorSpace::Gray)
+                .unwrap()
+                .into(),
+            ImageOwned::from_owned(vec![0u32; 4], 2, 2, ColorSpace::Gray)
+                .unwrap()
+                .into(),
+            ImageOwned::from_owned(vec![0f32; 4], 2, 2, ColorSpace::Gray)
+                .unwrap()
+                .into(),
+        ];
+        for img in imgs {
+            let serial = SerialImage::try_from(&img).unwrap();
+            assert_eq!(PixelType::try_from(serial.pixeltype), Ok(img.pixel_type()));
+        }
+    }
+
+    #[test]
+    fn test_i16_u32_roundtrip() {
+        use crate::{ColorSpace, DynamicImageOwned, ImageOwned};
+        let img = ImageOwned::from_owned(vec![-3i16, -2, -1, 0, 1, 2], 3, 2, ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let img = DynamicImageOwned::from(img);
+        let blob = bincode::serialize(&img).unwrap();
+        let de: DynamicImageOwned = bincode::deserialize(&blob).unwrap();
+        assert_eq!(de, img);
+        let img = ImageOwned::from_owned(vec![0u32, 1, 65536, u32::MAX], 2, 2, ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let img = DynamicImageOwned::from(img);
+        let json = serde_json::to_string(&img).unwrap();
+        let de: DynamicImageOwned = serde_json::from_str(&json).unwrap();
+        assert_eq!(de, img);
+    }
+
     #[test]
     fn generate_pycode_dynamicimagedata() {
         use serde_reflection::{Tracer, TracerConfig};
@@ -1,11 +1,12 @@
 use std::num::NonZeroUsize;
 use std::time::Duration;
 
+use crate::{coretraits::convert_range, Debayer, DynamicImageRef};
 use crate::{
-    BayerError, CalcOptExp, ColorSpace, DemosaicMethod, DynamicImageOwned, ImageOwned, ImageProps,
-    OptimumExposure, PixelType, SelectRoi, ToLuma,
+    BayerError, CalcOptExp, ColorSpace, DemosaicMethod, DynamicImageOwned, ExposureOutcome, Flip,
+    ImageMap, ImageOwned, ImageProps, MutImageData, OptimumExposure, PixelType, ResizeFilter,
+    Rotation, SelectRoi, ToLuma,
 };
-use crate::{Debayer, DynamicImageRef};
 
 macro_rules! dynamic_map(
     ($dynimage: expr, $image: pat => $action: expr) => ({
@@ -13,6 +14,8 @@ macro_rules! dynamic_map(
         match $dynimage {
             U8($image) => U8($action),
             U16($image) => U16($action),
+            I16($image) => I16($action),
+            U32($image) => U32($action),
             F32($image) => F32($action),
         }
     });
@@ -21,6 +24,8 @@ macro_rules! dynamic_map(
         match $dynimage {
             DynamicImageOwned::U8($image) => $action,
             DynamicImageOwned::U16($image) => $action,
+            DynamicImageOwned::I16($image) => $action,
+            DynamicImageOwned::U32($image) => $action,
             DynamicImageOwned::F32($image) => $action,
         }
     );
@@ -53,6 +58,8 @@ impl ImageProps for DynamicImageOwned {
         match self {
             DynamicImageOwned::U8(_) => self.clone(),
             DynamicImageOwned::U16(data) => DynamicImageOwned::U8(data.cast_u8()),
+            DynamicImageOwned::I16(data) => DynamicImageOwned::U8(data.cast_u8()),
+            DynamicImageOwned::U32(data) => DynamicImageOwned::U8(data.cast_u8()),
             DynamicImageOwned::F32(data) => DynamicImageOwned::U8(data.cast_u8()),
         }
     }
@@ -73,6 +80,8 @@ impl Debayer for DynamicImageOwned {
         match self {
             U8(image) => Ok(U8(image.debayer(alg)?)),
             U16(image) => Ok(U16(image.debayer(alg)?)),
+            I16(image) => Ok(I16(image.debayer(alg)?)),
+            U32(image) => Ok(U32(image.debayer(alg)?)),
             F32(image) => Ok(F32(image.debayer(alg)?)),
         }
     }
@@ -84,6 +93,8 @@ impl ToLuma for DynamicImageOwned {
         match self {
             U8(image) => image.to_luma(),
             U16(image) => image.to_luma(),
+            I16(image) => image.to_luma(),
+            U32(image) => image.to_luma(),
             F32(image) => image.to_luma(),
         }
     }
@@ -93,6 +104,8 @@ impl ToLuma for DynamicImageOwned {
         match self {
             U8(image) => image.to_luma_custom(coeffs),
             U16(image) => image.to_luma_custom(coeffs),
+            I16(image) => image.to_luma_custom(coeffs),
+            U32(image) => image.to_luma_custom(coeffs),
             F32(image) => image.to_luma_custom(coeffs),
         }
     }
@@ -105,6 +118,12 @@ macro_rules! select_roi {
             DynamicImageOwned::U16(data) => {
                 DynamicImageOwned::U16(data.select_roi($x, $y, $w, $h)?)
             }
+            DynamicImageOwned::I16(data) => {
+                DynamicImageOwned::I16(data.select_roi($x, $y, $w, $h)?)
+            }
+            DynamicImageOwned::U32(data) => {
+                DynamicImageOwned::U32(data.select_roi($x, $y, $w, $h)?)
+            }
             DynamicImageOwned::F32(data) => {
                 DynamicImageOwned::F32(data.select_roi($x, $y, $w, $h)?)
             }
@@ -112,6 +131,18 @@ macro_rules! select_roi {
     };
 }
 
+impl Flip for DynamicImageOwned {
+    type Output = DynamicImageOwned;
+
+    fn flip_h(&self) -> Self::Output {
+        dynamic_map!(self, ref image, { image.flip_h().into() })
+    }
+
+    fn flip_v(&self) -> Self::Output {
+        dynamic_map!(self, ref image, { image.flip_v().into() })
+    }
+}
+
 impl SelectRoi for DynamicImageOwned {
     type Output = DynamicImageOwned;
 
@@ -134,9 +165,79 @@ impl DynamicImageOwned {
         match self {
             DynamicImageOwned::U8(data) => DynamicImageOwned::U8(data),
             DynamicImageOwned::U16(data) => DynamicImageOwned::U8(data.cast_u8()),
+            DynamicImageOwned::I16(data) => DynamicImageOwned::U8(data.cast_u8()),
+            DynamicImageOwned::U32(data) => DynamicImageOwned::U8(data.cast_u8()),
             DynamicImageOwned::F32(data) => DynamicImageOwned::U8(data.cast_u8()),
         }
     }
+
+    #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+    #[cfg(feature = "rayon")]
+    /// Convert the image to a [`DynamicImageOwned`] with [`u8`] pixel type, running the
+    /// conversion inside the supplied thread pool.
+    ///
+    /// See [`Self::into_u8`] for details.
+    pub fn into_u8_in_pool(self, pool: &rayon::ThreadPool) -> DynamicImageOwned {
+        pool.install(|| self.into_u8())
+    }
+
+    /// Convert the image to a [`DynamicImageOwned`] with [`u8`] pixel type, stretching
+    /// the contrast between the given percentiles of each channel.
+    ///
+    /// See [`ImageOwned::into_u8_stretched`] for details.
+    ///
+    /// # Errors
+    /// - If `low_pct` or `high_pct` is not in `[0, 1]`, or `low_pct` is not less than `high_pct`.
+    pub fn into_u8_stretched(
+        &self,
+        low_pct: f32,
+        high_pct: f32,
+    ) -> Result<DynamicImageOwned, &'static str> {
+        dynamic_map!(self, ref image, {
+            Ok(DynamicImageOwned::U8(
+                image.into_u8_stretched(low_pct, high_pct)?,
+            ))
+        })
+    }
+
+    /// Convert the image to a [`DynamicImageOwned`] with the given pixel type.
+    ///
+    /// The valid range of the source pixel type is mapped linearly onto the valid range
+    /// of the target pixel type, e.g. `65535u16` is converted to `1.0f32` and `255u8`.
+    /// Integer results are rounded to the nearest integer, and NaN is converted to the
+    /// minimum value of the target type. Converting to the same pixel type clones the image.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    ///
+    /// # Arguments
+    /// - `target`: The pixel type of the output ([`PixelType::U8`], [`PixelType::U16`],
+    ///   [`PixelType::I16`], [`PixelType::U32`] or [`PixelType::F32`]).
+    ///
+    /// # Errors
+    /// - If the target pixel type is not supported by [`DynamicImageOwned`].
+    pub fn convert_to(&self, target: PixelType) -> Result<DynamicImageOwned, &'static str> {
+        if PixelType::from(self) == target {
+            return Ok(self.clone());
+        }
+        Ok(match target {
+            PixelType::U8 => dynamic_map!(self, ref image, {
+                image.map_pixels_to(convert_range::<_, u8>).into()
+            }),
+            PixelType::U16 => dynamic_map!(self, ref image, {
+                image.map_pixels_to(convert_range::<_, u16>).into()
+            }),
+            PixelType::I16 => dynamic_map!(self, ref image, {
+                image.map_pixels_to(convert_range::<_, i16>).into()
+            }),
+            PixelType::U32 => dynamic_map!(self, ref image, {
+                image.map_pixels_to(convert_range::<_, u32>).into()
+            }),
+            PixelType::F32 => dynamic_map!(self, ref image, {
+                image.map_pixels_to(convert_range::<_, f32>).into()
+            }),
+            _ => return Err("Pixel type not supported"),
+        })
+    }
 }
 
 impl From<&DynamicImageOwned> for PixelType {
@@ -144,6 +245,8 @@ impl From<&DynamicImageOwned> for PixelType {
         match data {
             DynamicImageOwned::U8(_) => PixelType::U8,
             DynamicImageOwned::U16(_) => PixelType::U16,
+            DynamicImageOwned::I16(_) => PixelType::I16,
+            DynamicImageOwned::U32(_) => PixelType::U32,
             DynamicImageOwned::F32(_) => PixelType::F32,
         }
     }
@@ -151,7 +254,7 @@ impl From<&DynamicImageOwned> for PixelType {
 
 macro_rules! tryfrom_dynimgdata_imgdata {
     ($type:ty, $variant:path) => {
-        impl<'a> TryFrom<DynamicImageOwned> for ImageOwned<$type> {
+        impl TryFrom<DynamicImageOwned> for ImageOwned<$type> {
             type Error = &'static str;
 
             fn try_from(data: DynamicImageOwned) -> Result<Self, Self::Error> {
@@ -166,6 +269,8 @@ macro_rules! tryfrom_dynimgdata_imgdata {
 
 tryfrom_dynimgdata_imgdata!(u8, DynamicImageOwned::U8);
 tryfrom_dynimgdata_imgdata!(u16, DynamicImageOwned::U16);
+tryfrom_dynimgdata_imgdata!(i16, DynamicImageOwned::I16);
+tryfrom_dynimgdata_imgdata!(u32, DynamicImageOwned::U32);
 tryfrom_dynimgdata_imgdata!(f32, DynamicImageOwned::F32);
 
 macro_rules! from_imgdata_dynimg {
@@ -180,14 +285,238 @@ macro_rules! from_imgdata_dynimg {
 
 from_imgdata_dynimg!(u8, DynamicImageOwned::U8);
 from_imgdata_dynimg!(u16, DynamicImageOwned::U16);
+from_imgdata_dynimg!(i16, DynamicImageOwned::I16);
+from_imgdata_dynimg!(u32, DynamicImageOwned::U32);
 from_imgdata_dynimg!(f32, DynamicImageOwned::F32);
 
+#[cfg(feature = "f16")]
+#[cfg_attr(docsrs, doc(cfg(feature = "f16")))]
+impl From<ImageOwned<half::f16>> for DynamicImageOwned {
+    /// Half-precision data is converted to [`f32`], which is supported by all
+    /// serialization and FITS backends.
+    fn from(data: ImageOwned<half::f16>) -> Self {
+        let ImageOwned {
+            data,
+            width,
+            height,
+            channels,
+            cspace,
+        } = data;
+        DynamicImageOwned::F32(ImageOwned {
+            data: data.into_iter().map(half::f16::to_f32).collect(),
+            width,
+            height,
+            channels,
+            cspace,
+        })
+    }
+}
+
 impl DynamicImageOwned {
+    /// Divide this image by a flat field, and multiply by `scale`.
+    ///
+    /// See [`ImageOwned::flat_divide`] for details.
+    ///
+    /// # Errors
+    /// - If the pixel types of the images do not match.
+    /// - If the width, height, number of channels or color space of the images do not match.
+    /// - If `scale` is not finite.
+    pub fn flat_divide(&self, flat: &Self, scale: f64) -> Result<Self, &'static str> {
+        use DynamicImageOwned::*;
+        match (self, flat) {
+            (U8(img), U8(flat)) => Ok(img.flat_divide(flat, scale)?.into()),
+            (U16(img), U16(flat)) => Ok(img.flat_divide(flat, scale)?.into()),
+            (I16(img), I16(flat)) => Ok(img.flat_divide(flat, scale)?.into()),
+            (U32(img), U32(flat)) => Ok(img.flat_divide(flat, scale)?.into()),
+            (F32(img), F32(flat)) => Ok(img.flat_divide(flat, scale)?.into()),
+            _ => Err("Image pixel types do not match."),
+        }
+    }
+
+    /// Convert an RGB image to the HSV (hue, saturation, value) color space.
+    ///
+    /// See [`ImageOwned::to_hsv`] for details.
+    ///
+    /// # Errors
+    /// - If the image is not an RGB image.
+    pub fn to_hsv(&self) -> Result<ImageOwned<f32>, &'static str> {
+        dynamic_map!(self, ref image, { image.to_hsv() })
+    }
+
+    /// Rotate the image by a multiple of 90 degrees, clockwise.
+    ///
+    /// See [`ImageOwned::rotate`] for details.
+    pub fn rotate(&self, rotation: Rotation) -> DynamicImageOwned {
+        dynamic_map!(self, ref image, { image.rotate(rotation).into() })
+    }
+
+    /// Split the image into one [`ColorSpace::Gray`] image per channel.
+    ///
+    /// See [`ImageOwned::split_channels`] for details.
+    pub fn split_channels(&self) -> Vec<DynamicImageOwned> {
+        dynamic_map!(self, ref image, {
+            image
+                .split_channels()
+                .into_iter()
+                .map(DynamicImageOwned::from)
+                .collect()
+        })
+    }
+
+    /// Merge single-channel images into one image, e.g. the output of
+    /// [`DynamicImageOwned::split_channels`].
+    ///
+    /// See [`ImageOwned::merge_channels`] for details.
+    ///
+    /// # Errors
+    /// - If the images do not have the same pixel type.
+    /// - If the number of images does not match the number of channels of `cspace`.
+    /// - If an image has more than one channel.
+    /// - If the images do not have the same dimensions.
+    pub fn merge_channels(
+        channels: &[DynamicImageOwned],
+        cspace: ColorSpace,
+    ) -> Result<DynamicImageOwned, &'static str> {
+        macro_rules! merge {
+            ($variant:path) => {{
+                let planes = channels
+                    .iter()
+                    .map(|img| match img {
+                        $variant(img) => Ok(img),
+                        _ => Err("Pixel types of the channels do not match."),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok($variant(ImageOwned::merge_planes(&planes, cspace)?))
+            }};
+        }
+        match channels.first().ok_or("No channels to merge.")? {
+            DynamicImageOwned::U8(_) => merge!(DynamicImageOwned::U8),
+            DynamicImageOwned::U16(_) => merge!(DynamicImageOwned::U16),
+            DynamicImageOwned::I16(_) => merge!(DynamicImageOwned::I16),
+            DynamicImageOwned::U32(_) => merge!(DynamicImageOwned::U32),
+            DynamicImageOwned::F32(_) => merge!(DynamicImageOwned::F32),
+        }
+    }
+
+    /// Transpose the image, i.e. swap its rows and columns.
+    ///
+    /// See [`ImageOwned::transpose`] for details.
+    pub fn transpose(&self) -> DynamicImageOwned {
+        dynamic_map!(self, ref image, { image.transpose().into() })
+    }
+
+    /// Resize the image to `new_w` by `new_h` pixels.
+    ///
+    /// See [`ImageOwned::resize`] for details.
+    ///
+    /// # Errors
+    /// - If the image is a Bayer mosaic image, since resampling destroys the mosaic.
+    /// - If the resized image is too large.
+    pub fn resize(
+        &self,
+        new_w: NonZeroUsize,
+        new_h: NonZeroUsize,
+        filter: ResizeFilter,
+    ) -> Result<DynamicImageOwned, &'static str> {
+        dynamic_map!(self, ref image, {
+            Ok(image.resize(new_w, new_h, filter)?.into())
+        })
+    }
+
+    /// Compute the histograms of all channels of the image.
+    ///
+    /// See [`ImageOwned::histograms`] for details.
+    ///
+    /// # Errors
+    /// - If `bins` is zero.
+    pub fn histograms(&self, bins: usize) -> Result<Vec<Vec<u64>>, &'static str> {
+        dynamic_map!(self, ref image, { image.histograms(bins) })
+    }
+
+    /// Select a region of interest from the image, padding the parts of the region
+    /// that lie outside the image.
+    ///
+    /// The `fill` value is converted to the pixel type of the image, clamped to its
+    /// valid range. See [`ImageOwned::select_roi_padded`] for details.
+    ///
+    /// # Errors
+    /// - If the ROI is larger than 65535x65535 pixels.
+    pub fn select_roi_padded(
+        &self,
+        x: isize,
+        y: isize,
+        width: NonZeroUsize,
+        height: NonZeroUsize,
+        fill: f64,
+    ) -> Result<DynamicImageOwned, &'static str> {
+        dynamic_map!(self, ref image, {
+            Ok(image
+                .select_roi_padded(x, y, width, height, crate::coretraits::clamp_from_f64(fill))?
+                .into())
+        })
+    }
+
+    /// Apply gamma correction to the image in-place.
+    ///
+    /// See [`MutImageData::apply_gamma`] for details.
+    ///
+    /// # Errors
+    /// - If `gamma` is not a positive finite number.
+    pub fn apply_gamma(&mut self, gamma: f64) -> Result<(), &'static str> {
+        dynamic_map!(self, ref mut image, { image.apply_gamma(gamma) })
+    }
+
+    /// Replace every NaN and infinite value in the image with `replacement`.
+    ///
+    /// This is a no-op for integer images. See [`ImageOwned::sanitize`] for details.
+    pub fn sanitize(&mut self, replacement: f32) {
+        if let Self::F32(image) = self {
+            image.sanitize(replacement);
+        }
+    }
+
+    /// Check whether the image contains any NaN or infinite values.
+    ///
+    /// This is always `false` for integer images.
+    pub fn has_non_finite(&self) -> bool {
+        match self {
+            Self::F32(image) => image.has_non_finite(),
+            _ => false,
+        }
+    }
+
+    /// Apply a pixel type generic transformation to the image data.
+    ///
+    /// The transformation is dispatched to the underlying [`ImageOwned`], regardless
+    /// of the pixel type.
+    ///
+    /// # Arguments
+    /// - `f`: The transformation to apply, see [`ImageMap`].
+    pub fn map_image<F: ImageMap>(&self, mut f: F) -> Result<DynamicImageOwned, &'static str> {
+        dynamic_map!(self, ref image, { f.map(image) })
+    }
+
     /// Get the data as a slice of `u8`, regardless of the underlying type.
     pub fn as_raw_u8(&self) -> &[u8] {
         dynamic_map!(self, ref image, { image.as_u8_slice() })
     }
 
+    /// Get the length of the image data in bytes.
+    ///
+    /// This is [`len`](ImageProps::len) times the size of the pixel type, i.e. the
+    /// length of the slice returned by [`Self::as_raw_u8`].
+    pub fn byte_len(&self) -> usize {
+        self.len() * self.pixel_type().size_bytes()
+    }
+
+    /// Get the distance between the starts of consecutive rows of the image data in bytes.
+    ///
+    /// The rows are contiguous, hence this is `width * channels` times the size of the
+    /// pixel type.
+    pub fn row_byte_stride(&self) -> usize {
+        self.width() * self.channels() as usize * self.pixel_type().size_bytes()
+    }
+
     /// Get the data as a slice of `u8`, regardless of the underlying type.
     pub fn as_raw_u8_checked(&self) -> Option<&[u8]> {
         dynamic_map!(self, ref image, { image.as_u8_slice_checked() })
@@ -225,6 +554,38 @@ impl DynamicImageOwned {
         }
     }
 
+    /// Get the data as a slice of `i16`.
+    pub fn as_slice_i16(&self) -> Option<&[i16]> {
+        match self {
+            DynamicImageOwned::I16(data) => Some(data.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// Get the data as a mutable slice of `i16`.
+    pub fn as_mut_slice_i16(&mut self) -> Option<&mut [i16]> {
+        match self {
+            DynamicImageOwned::I16(data) => Some(data.as_mut_slice()),
+            _ => None,
+        }
+    }
+
+    /// Get the data as a slice of `u32`.
+    pub fn as_slice_u32(&self) -> Option<&[u32]> {
+        match self {
+            DynamicImageOwned::U32(data) => Some(data.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// Get the data as a mutable slice of `u32`.
+    pub fn as_mut_slice_u32(&mut self) -> Option<&mut [u32]> {
+        match self {
+            DynamicImageOwned::U32(data) => Some(data.as_mut_slice()),
+            _ => None,
+        }
+    }
+
     /// Get the data as a slice of `f32`.
     pub fn as_slice_f32(&self) -> Option<&[f32]> {
         match self {
@@ -247,6 +608,8 @@ impl From<&DynamicImageRef<'_>> for DynamicImageOwned {
         match data {
             DynamicImageRef::U8(data) => DynamicImageOwned::U8(data.into()),
             DynamicImageRef::U16(data) => DynamicImageOwned::U16(data.into()),
+            DynamicImageRef::I16(data) => DynamicImageOwned::I16(data.into()),
+            DynamicImageRef::U32(data) => DynamicImageOwned::U32(data.into()),
             DynamicImageRef::F32(data) => DynamicImageOwned::F32(data.into()),
         }
     }
@@ -254,21 +617,46 @@ impl From<&DynamicImageRef<'_>> for DynamicImageOwned {
 
 impl CalcOptExp for DynamicImageOwned {
     fn calc_opt_exp(
-        mut self,
+        self,
         eval: &OptimumExposure,
         exposure: Duration,
         bin: u8,
     ) -> Result<(Duration, u16), &'static str> {
+        self.eval_opt_exp(eval, exposure, bin)
+            .map(ExposureOutcome::into_inner)
+    }
+
+    fn eval_opt_exp(
+        mut self,
+        eval: &OptimumExposure,
+        exposure: Duration,
+        bin: u8,
+    ) -> Result<ExposureOutcome, &'static str> {
         use DynamicImageOwned::*;
         match self {
-            U8(ref mut img) =>{let len = img.len(); eval.calculate(img.as_mut_slice(), len, exposure, bin)},
-            U16(ref mut img) => {let len = img.len(); eval.calculate(img.as_mut_slice(), len, exposure, bin)},
+            U8(ref mut img) =>{let len = img.len(); eval.evaluate(img.as_mut_slice(), len, exposure, bin)},
+            U16(ref mut img) => {let len = img.len(); eval.evaluate(img.as_mut_slice(), len, exposure, bin)},
+            I16(ref mut img) => {let len = img.len(); eval.evaluate(img.as_mut_slice(), len, exposure, bin)},
+            U32(ref mut img) => {let len = img.len(); eval.evaluate(img.as_mut_slice(), len, exposure, bin)},
             F32(_) => Err("Floating point images are not supported for this operation, since Ord is not implemented for floating point types."),
         }
     }
 }
 
 mod test {
+    #[cfg(feature = "f16")]
+    #[test]
+    fn test_from_f16() {
+        use crate::{ColorSpace, DynamicImageOwned, ImageOwned, ImageProps, PixelType};
+        use half::f16;
+        let data = [0.0f32, 0.25, 0.5, 1.0].map(f16::from_f32).to_vec();
+        let img = ImageOwned::from_owned(data, 2, 2, ColorSpace::Gray).unwrap();
+        assert_eq!(img.pixel_type(), PixelType::F16);
+        let img = DynamicImageOwned::from(img);
+        assert_eq!(img.pixel_type(), PixelType::F32);
+        assert_eq!(img.as_slice_f32(), Some(&[0.0, 0.25, 0.5, 1.0][..]));
+    }
+
     #[test]
     fn test_optimum_exposure() {
         use crate::CalcOptExp;
@@ -285,4 +673,115 @@ mod test {
         let res = img.calc_opt_exp(&opt_exp, exp, bin).unwrap();
         assert_eq!(res, (exp, bin as u16));
     }
+
+    #[test]
+    fn test_try_into_image_owned() {
+        use crate::{ColorSpace, DynamicImageOwned, ImageOwned};
+        let img = ImageOwned::from_owned(vec![1u16, 2, 3, 4], 2, 2, ColorSpace::Gray).unwrap();
+        let dynimg = DynamicImageOwned::from(img.clone());
+        let blob = bincode::serialize(&dynimg).unwrap();
+        let de: DynamicImageOwned = bincode::deserialize(&blob).unwrap();
+        assert_eq!(ImageOwned::<u16>::try_from(de), Ok(img));
+        assert!(ImageOwned::<f32>::try_from(dynimg.clone()).is_err());
+        assert!(ImageOwned::<u8>::try_from(dynimg).is_err());
+    }
+
+    #[test]
+    fn test_convert_to() {
+        use crate::{ColorSpace, DynamicImageOwned, ImageOwned, PixelType};
+        let img = ImageOwned::from_owned(vec![0u16, 32768, 65535], 3, 1, ColorSpace::Gray).unwrap();
+        let img = DynamicImageOwned::from(img);
+        let f = img.convert_to(PixelType::F32).unwrap();
+        let px = f.as_slice_f32().unwrap();
+        assert_eq!((px[0], px[2]), (0.0, 1.0));
+        assert!((px[1] - 32768.0 / 65535.0).abs() < 1e-7);
+        assert_eq!(
+            img.convert_to(PixelType::U8)
+                .unwrap()
+                .as_slice_u8()
+                .unwrap(),
+            &[0, 128, 255]
+        );
+        assert_eq!(
+            img.convert_to(PixelType::I16)
+                .unwrap()
+                .as_slice_i16()
+                .unwrap(),
+            &[i16::MIN, 0, i16::MAX]
+        );
+        assert_eq!(
+            f.convert_to(PixelType::U32)
+                .unwrap()
+                .as_slice_u32()
+                .unwrap(),
+            &[0, 2147516415, u32::MAX]
+        );
+        assert_eq!(
+            f.convert_to(PixelType::U16)
+                .unwrap()
+                .as_slice_u16()
+                .unwrap(),
+            &[0, 32768, 65535]
+        );
+        assert_eq!(img.convert_to(PixelType::U16).unwrap(), img);
+        assert_eq!(
+            img.convert_to(PixelType::F64),
+            Err("Pixel type not supported")
+        );
+    }
+
+    #[test]
+    fn test_split_merge_channels() {
+        use crate::{ColorSpace, DynamicImageOwned, ImageOwned, ImageProps};
+        let data: Vec<u16> = (0..12).collect();
+        let img = ImageOwned::from_owned(data, 2, 2, ColorSpace::Rgb).unwrap();
+        let img = DynamicImageOwned::from(img);
+        let planes = img.split_channels();
+        assert_eq!(planes.len(), 3);
+        assert!(planes.iter().all(|p| p.color_space() == ColorSpace::Gray));
+        assert_eq!(planes[1].as_slice_u16().unwrap(), &[1, 4, 7, 10]);
+        let merged = DynamicImageOwned::merge_channels(&planes, ColorSpace::Rgb).unwrap();
+        assert_eq!(merged, img);
+
+        assert!(DynamicImageOwned::merge_channels(&planes, ColorSpace::Cmyk).is_err());
+        assert!(DynamicImageOwned::merge_channels(&planes[..2], ColorSpace::Rgb).is_err());
+        assert!(DynamicImageOwned::merge_channels(&[], ColorSpace::Gray).is_err());
+        let mut mixed = planes.clone();
+        mixed[2] = mixed[2].clone().into_u8();
+        assert_eq!(
+            DynamicImageOwned::merge_channels(&mixed, ColorSpace::Rgb),
+            Err("Pixel types of the channels do not match.")
+        );
+        let small = ImageOwned::from_owned(vec![0u16; 2], 2, 1, ColorSpace::Gray).unwrap();
+        let mut mismatched = planes.clone();
+        mismatched[0] = small.into();
+        assert_eq!(
+            DynamicImageOwned::merge_channels(&mismatched, ColorSpace::Rgb),
+            Err("Channel dimensions do not match.")
+        );
+        assert!(DynamicImageOwned::merge_channels(&[img], ColorSpace::Rgb).is_err());
+    }
+
+    #[test]
+    fn test_byte_geometry() {
+        use crate::{
+            ColorSpace, DynamicImageOwned, DynamicImageRef, GenericImage, GenericImageRef,
+            ImageOwned, ImageRef, PixelType,
+        };
+        assert_eq!(PixelType::U8.size_bytes(), 1);
+        assert_eq!(PixelType::I16.size_bytes(), 2);
+        assert_eq!(PixelType::F32.size_bytes(), 4);
+        assert_eq!(PixelType::F64.size_bytes(), 8);
+        let img = ImageOwned::from_owned(vec![0u16; 12], 2, 2, ColorSpace::Rgb).unwrap();
+        let img = DynamicImageOwned::from(img);
+        assert_eq!((img.byte_len(), img.row_byte_stride()), (24, 12));
+        assert_eq!(img.byte_len(), img.as_raw_u8().len());
+
+        let mut data = vec![0f32; 7];
+        let img = ImageRef::new(&mut data, 3, 2, ColorSpace::Gray).unwrap();
+        let img = DynamicImageRef::from(img);
+        assert_eq!((img.byte_len(), img.row_byte_stride()), (24, 12));
+        let img = GenericImage::from(GenericImageRef::new(std::time::SystemTime::now(), img));
+        assert_eq!((img.byte_len(), img.row_byte_stride()), (24, 12));
+    }
 }
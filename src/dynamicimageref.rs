@@ -2,8 +2,9 @@ use std::num::NonZeroUsize;
 use std::time::Duration;
 
 use crate::{
-    BayerError, CalcOptExp, ColorSpace, DemosaicMethod, DynamicImageRef, ImageProps, ImageRef,
-    OptimumExposure, PixelType, SelectRoi, ToLuma,
+    BayerError, CalcOptExp, ColorSpace, DemosaicMethod, DynamicImageRef, ExposureOutcome, Flip,
+    ImageOwned, ImageProps, ImageRef, MutImageData, OptimumExposure, PixelType, Rotation,
+    SelectRoi, ToLuma,
 };
 use crate::{Debayer, DynamicImageOwned};
 
@@ -13,6 +14,8 @@ macro_rules! dynamic_map(
         match $dynimage {
             U8($image) => U8($action),
             U16($image) => U16($action),
+            I16($image) => I16($action),
+            U32($image) => U32($action),
             F32($image) => F32($action),
         }
     });
@@ -21,6 +24,8 @@ macro_rules! dynamic_map(
         match $dynimage {
             DynamicImageRef::U8($image) => $action,
             DynamicImageRef::U16($image) => $action,
+            DynamicImageRef::I16($image) => $action,
+            DynamicImageRef::U32($image) => $action,
             DynamicImageRef::F32($image) => $action,
         }
     );
@@ -46,6 +51,104 @@ impl DynamicImageRef<'_> {
     pub fn color_space(&self) -> ColorSpace {
         dynamic_map!(self, ref image, { image.color_space() })
     }
+
+    /// Convert an RGB image to the HSV (hue, saturation, value) color space.
+    ///
+    /// See [`ImageOwned::to_hsv`] for details.
+    ///
+    /// # Errors
+    /// - If the image is not an RGB image.
+    pub fn to_hsv(&self) -> Result<ImageOwned<f32>, &'static str> {
+        dynamic_map!(self, ref image, { image.to_hsv() })
+    }
+
+    /// Rotate the image by a multiple of 90 degrees, clockwise.
+    ///
+    /// See [`ImageOwned::rotate`] for details.
+    pub fn rotate(&self, rotation: Rotation) -> DynamicImageOwned {
+        dynamic_map!(self, ref image, { image.rotate(rotation).into() })
+    }
+
+    /// Transpose the image, i.e. swap its rows and columns.
+    ///
+    /// See [`ImageOwned::transpose`] for details.
+    pub fn transpose(&self) -> DynamicImageOwned {
+        dynamic_map!(self, ref image, { image.transpose().into() })
+    }
+
+    /// Compute the histograms of all channels of the image.
+    ///
+    /// See [`ImageOwned::histograms`] for details.
+    ///
+    /// # Errors
+    /// - If `bins` is zero.
+    pub fn histograms(&self, bins: usize) -> Result<Vec<Vec<u64>>, &'static str> {
+        dynamic_map!(self, ref image, { image.histograms(bins) })
+    }
+
+    /// Select a region of interest from the image, padding the parts of the region
+    /// that lie outside the image.
+    ///
+    /// The `fill` value is converted to the pixel type of the image, clamped to its
+    /// valid range. See [`ImageOwned::select_roi_padded`] for details.
+    ///
+    /// # Errors
+    /// - If the ROI is larger than 65535x65535 pixels.
+    pub fn select_roi_padded(
+        &self,
+        x: isize,
+        y: isize,
+        width: NonZeroUsize,
+        height: NonZeroUsize,
+        fill: f64,
+    ) -> Result<DynamicImageOwned, &'static str> {
+        dynamic_map!(self, ref image, {
+            Ok(image
+                .select_roi_padded(x, y, width, height, crate::coretraits::clamp_from_f64(fill))?
+                .into())
+        })
+    }
+
+    /// Apply gamma correction to the image in-place.
+    ///
+    /// See [`MutImageData::apply_gamma`] for details.
+    ///
+    /// # Errors
+    /// - If `gamma` is not a positive finite number.
+    pub fn apply_gamma(&mut self, gamma: f64) -> Result<(), &'static str> {
+        dynamic_map!(self, ref mut image, { image.apply_gamma(gamma) })
+    }
+
+    /// Replace every NaN and infinite value in the image with `replacement`.
+    ///
+    /// This is a no-op for integer images. See [`ImageOwned::sanitize`] for details.
+    pub fn sanitize(&mut self, replacement: f32) {
+        if let Self::F32(image) = self {
+            image.sanitize(replacement);
+        }
+    }
+
+    /// Check whether the image contains any NaN or infinite values.
+    ///
+    /// This is always `false` for integer images.
+    pub fn has_non_finite(&self) -> bool {
+        match self {
+            Self::F32(image) => image.has_non_finite(),
+            _ => false,
+        }
+    }
+
+    /// Get the length of the backing slice, in elements of the underlying pixel type.
+    ///
+    /// This may be larger than the length of the image data, see [`ImageProps::len`].
+    pub fn backing_len(&self) -> usize {
+        dynamic_map!(self, ref image, { image.backing_len() })
+    }
+
+    /// Copy the image data into a [`DynamicImageOwned`].
+    pub fn to_owned(&self) -> DynamicImageOwned {
+        DynamicImageOwned::from(self)
+    }
 }
 
 impl ImageProps for DynamicImageRef<'_> {
@@ -83,6 +186,8 @@ impl ImageProps for DynamicImageRef<'_> {
         match self {
             DynamicImageRef::U8(data) => DynamicImageOwned::U8(data.into()),
             DynamicImageRef::U16(data) => DynamicImageOwned::U8(data.into_u8()),
+            DynamicImageRef::I16(data) => DynamicImageOwned::U8(data.into_u8()),
+            DynamicImageRef::U32(data) => DynamicImageOwned::U8(data.into_u8()),
             DynamicImageRef::F32(data) => DynamicImageOwned::U8(data.into_u8()),
         }
     }
@@ -95,6 +200,8 @@ impl Debayer for DynamicImageRef<'_> {
         match self {
             U8(image) => Ok(DynamicImageOwned::U8(image.debayer(alg)?)),
             U16(image) => Ok(DynamicImageOwned::U16(image.debayer(alg)?)),
+            I16(image) => Ok(DynamicImageOwned::I16(image.debayer(alg)?)),
+            U32(image) => Ok(DynamicImageOwned::U32(image.debayer(alg)?)),
             F32(image) => Ok(DynamicImageOwned::F32(image.debayer(alg)?)),
         }
     }
@@ -106,6 +213,8 @@ impl ToLuma for DynamicImageRef<'_> {
         match self {
             U8(image) => image.to_luma(),
             U16(image) => image.to_luma(),
+            I16(image) => image.to_luma(),
+            U32(image) => image.to_luma(),
             F32(image) => image.to_luma(),
         }
     }
@@ -115,6 +224,8 @@ impl ToLuma for DynamicImageRef<'_> {
         match self {
             U8(image) => image.to_luma_custom(coeffs),
             U16(image) => image.to_luma_custom(coeffs),
+            I16(image) => image.to_luma_custom(coeffs),
+            U32(image) => image.to_luma_custom(coeffs),
             F32(image) => image.to_luma_custom(coeffs),
         }
     }
@@ -125,6 +236,8 @@ impl From<&DynamicImageRef<'_>> for PixelType {
         match data {
             DynamicImageRef::U8(_) => PixelType::U8,
             DynamicImageRef::U16(_) => PixelType::U16,
+            DynamicImageRef::I16(_) => PixelType::I16,
+            DynamicImageRef::U32(_) => PixelType::U32,
             DynamicImageRef::F32(_) => PixelType::F32,
         }
     }
@@ -147,6 +260,8 @@ macro_rules! tryfrom_dynimgdata_imgdata {
 
 tryfrom_dynimgdata_imgdata!(u8, DynamicImageRef::U8);
 tryfrom_dynimgdata_imgdata!(u16, DynamicImageRef::U16);
+tryfrom_dynimgdata_imgdata!(i16, DynamicImageRef::I16);
+tryfrom_dynimgdata_imgdata!(u32, DynamicImageRef::U32);
 tryfrom_dynimgdata_imgdata!(f32, DynamicImageRef::F32);
 
 macro_rules! from_imgdata_dynimg {
@@ -161,6 +276,8 @@ macro_rules! from_imgdata_dynimg {
 
 from_imgdata_dynimg!(u8, DynamicImageRef::U8);
 from_imgdata_dynimg!(u16, DynamicImageRef::U16);
+from_imgdata_dynimg!(i16, DynamicImageRef::I16);
+from_imgdata_dynimg!(u32, DynamicImageRef::U32);
 from_imgdata_dynimg!(f32, DynamicImageRef::F32);
 
 macro_rules! select_roi {
@@ -168,11 +285,25 @@ macro_rules! select_roi {
         match $dynimage {
             DynamicImageRef::U8(data) => DynamicImageOwned::U8(data.select_roi($x, $y, $w, $h)?),
             DynamicImageRef::U16(data) => DynamicImageOwned::U16(data.select_roi($x, $y, $w, $h)?),
+            DynamicImageRef::I16(data) => DynamicImageOwned::I16(data.select_roi($x, $y, $w, $h)?),
+            DynamicImageRef::U32(data) => DynamicImageOwned::U32(data.select_roi($x, $y, $w, $h)?),
             DynamicImageRef::F32(data) => DynamicImageOwned::F32(data.select_roi($x, $y, $w, $h)?),
         }
     };
 }
 
+impl Flip for DynamicImageRef<'_> {
+    type Output = DynamicImageOwned;
+
+    fn flip_h(&self) -> Self::Output {
+        dynamic_map!(self, ref image, { image.flip_h().into() })
+    }
+
+    fn flip_v(&self) -> Self::Output {
+        dynamic_map!(self, ref image, { image.flip_v().into() })
+    }
+}
+
 impl SelectRoi for DynamicImageRef<'_> {
     type Output = DynamicImageOwned;
 
@@ -193,6 +324,22 @@ impl DynamicImageRef<'_> {
         dynamic_map!(self, ref image, { image.as_u8_slice() })
     }
 
+    /// Get the length of the image data in bytes.
+    ///
+    /// This is [`len`](ImageProps::len) times the size of the pixel type, i.e. the
+    /// length of the slice returned by [`Self::as_raw_u8`].
+    pub fn byte_len(&self) -> usize {
+        self.len() * self.pixel_type().size_bytes()
+    }
+
+    /// Get the distance between the starts of consecutive rows of the image data in bytes.
+    ///
+    /// The rows are contiguous, hence this is `width * channels` times the size of the
+    /// pixel type.
+    pub fn row_byte_stride(&self) -> usize {
+        self.width() * self.channels() as usize * self.pixel_type().size_bytes()
+    }
+
     /// Get the data as a slice of [`u8`], regardless of the underlying type.
     pub fn as_raw_u8_checked(&self) -> Option<&[u8]> {
         dynamic_map!(self, ref image, { image.as_u8_slice_checked() })
@@ -246,6 +393,54 @@ impl DynamicImageRef<'_> {
         }
     }
 
+    /// Get the data as a slice of [`i16`].
+    ///
+    /// # Note
+    /// The returned slice is not guaranteed to have the same length as the image.
+    /// Use [`len`](DynamicImageRef::len) to get the length of the image.
+    pub fn as_slice_i16(&self) -> Option<&[i16]> {
+        match self {
+            DynamicImageRef::I16(data) => Some(data.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// Get the data as a mutable slice of [`i16`].
+    ///
+    /// # Note
+    /// The returned slice is not guaranteed to have the same length as the image.
+    /// Use [`len`](DynamicImageRef::len) to get the length of the image.
+    pub fn as_mut_slice_i16(&mut self) -> Option<&mut [i16]> {
+        match self {
+            DynamicImageRef::I16(data) => Some(data.as_mut_slice()),
+            _ => None,
+        }
+    }
+
+    /// Get the data as a slice of [`u32`].
+    ///
+    /// # Note
+    /// The returned slice is not guaranteed to have the same length as the image.
+    /// Use [`len`](DynamicImageRef::len) to get the length of the image.
+    pub fn as_slice_u32(&self) -> Option<&[u32]> {
+        match self {
+            DynamicImageRef::U32(data) => Some(data.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// Get the data as a mutable slice of [`u32`].
+    ///
+    /// # Note
+    /// The returned slice is not guaranteed to have the same length as the image.
+    /// Use [`len`](DynamicImageRef::len) to get the length of the image.
+    pub fn as_mut_slice_u32(&mut self) -> Option<&mut [u32]> {
+        match self {
+            DynamicImageRef::U32(data) => Some(data.as_mut_slice()),
+            _ => None,
+        }
+    }
+
     /// Get the data as a slice of [`f32`].
     ///
     /// # Note
@@ -278,28 +473,98 @@ impl DynamicImageRef<'_> {
         match self {
             U8(data) => DynamicImageOwned::U8(data.into()),
             U16(data) => DynamicImageOwned::U8(data.into_u8()),
+            I16(data) => DynamicImageOwned::U8(data.into_u8()),
+            U32(data) => DynamicImageOwned::U8(data.into_u8()),
             F32(data) => DynamicImageOwned::U8(data.into_u8()),
         }
     }
+
+    #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+    #[cfg(feature = "rayon")]
+    /// Convert the image to a [`DynamicImageOwned`] with [`u8`] pixel type, running the
+    /// conversion inside the supplied thread pool.
+    ///
+    /// See [`Self::into_u8`] for details.
+    pub fn into_u8_in_pool(&self, pool: &rayon::ThreadPool) -> DynamicImageOwned {
+        pool.install(|| self.into_u8())
+    }
+
+    /// Convert the image to a [`DynamicImageOwned`] with [`u8`] pixel type, stretching
+    /// the contrast between the given percentiles of each channel.
+    ///
+    /// See [`ImageOwned::into_u8_stretched`] for details.
+    ///
+    /// # Errors
+    /// - If `low_pct` or `high_pct` is not in `[0, 1]`, or `low_pct` is not less than `high_pct`.
+    pub fn into_u8_stretched(
+        &self,
+        low_pct: f32,
+        high_pct: f32,
+    ) -> Result<DynamicImageOwned, &'static str> {
+        dynamic_map!(self, ref image, {
+            Ok(DynamicImageOwned::U8(
+                image.into_u8_stretched(low_pct, high_pct)?,
+            ))
+        })
+    }
 }
 
 impl CalcOptExp for DynamicImageRef<'_> {
     fn calc_opt_exp(
-        mut self,
+        self,
         eval: &OptimumExposure,
         exposure: Duration,
         bin: u8,
     ) -> Result<(Duration, u16), &'static str> {
+        self.eval_opt_exp(eval, exposure, bin)
+            .map(ExposureOutcome::into_inner)
+    }
+
+    fn eval_opt_exp(
+        mut self,
+        eval: &OptimumExposure,
+        exposure: Duration,
+        bin: u8,
+    ) -> Result<ExposureOutcome, &'static str> {
         use DynamicImageRef::*;
         match self {
-            U8(ref mut img) => {let len = img.len(); eval.calculate(img.as_mut_slice(), len, exposure, bin)},
-            U16(ref mut img) => {let len = img.len(); eval.calculate(img.as_mut_slice(), len, exposure, bin)},
+            U8(ref mut img) => {let len = img.len(); eval.evaluate(img.as_mut_slice(), len, exposure, bin)},
+            U16(ref mut img) => {let len = img.len(); eval.evaluate(img.as_mut_slice(), len, exposure, bin)},
+            I16(ref mut img) => {let len = img.len(); eval.evaluate(img.as_mut_slice(), len, exposure, bin)},
+            U32(ref mut img) => {let len = img.len(); eval.evaluate(img.as_mut_slice(), len, exposure, bin)},
             F32(_) => Err("Floating point images are not supported for this operation, since Ord is not implemented for floating point types."),
         }
     }
 }
 
 mod test {
+    #[test]
+    fn test_backing_len() {
+        use crate::ImageProps;
+        let mut data = vec![0u16; 10];
+        let img = crate::ImageRef::new(data.as_mut_slice(), 4, 2, crate::ColorSpace::Gray)
+            .expect("Failed to create ImageRef");
+        let img = crate::DynamicImageRef::from(img);
+        assert_eq!(img.len(), 8);
+        assert_eq!(img.backing_len(), 10);
+    }
+
+    #[test]
+    fn test_pixel_count() {
+        use crate::ImageProps;
+        let mut data = vec![0u8; 4 * 2 * 3];
+        let img = crate::ImageRef::new(data.as_mut_slice(), 4, 2, crate::ColorSpace::Rgb)
+            .expect("Failed to create ImageRef");
+        assert_eq!((img.pixel_count(), img.aspect_ratio()), (8, 2.0));
+        let img = crate::DynamicImageRef::from(img);
+        assert_eq!(img.pixel_count(), 8);
+        assert_eq!(img.len(), 24);
+        assert_eq!(img.aspect_ratio(), 2.0);
+        let img = crate::GenericImageRef::new(std::time::SystemTime::now(), img);
+        let img = crate::GenericImage::from(img);
+        assert_eq!((img.pixel_count(), img.aspect_ratio()), (8, 2.0));
+    }
+
     #[test]
     fn test_optimum_exposure() {
         use crate::CalcOptExp;
@@ -0,0 +1,167 @@
+//! Encoding of images to arbitrary writers.
+use std::io::Write;
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{DynamicImageOwned, DynamicImageRef, GenericImage, GenericImageOwned, GenericImageRef};
+
+/// Formats supported by [`ImageEncoder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ImageFormat {
+    /// Serialized image data (and metadata, if present) using [`bincode`].
+    ///
+    /// The output can be deserialized into a [`DynamicImageOwned`] or a
+    /// [`GenericImageOwned`] using `bincode::deserialize`.
+    Bincode,
+    /// Portable Network Graphics. Metadata is not stored.
+    ///
+    /// Requires the `png` feature of the `image` crate to be enabled.
+    #[cfg(feature = "image")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+    Png,
+    /// JPEG. Only 8-bit images are supported, and metadata is not stored.
+    ///
+    /// Requires the `jpeg` feature of the `image` crate to be enabled.
+    #[cfg(feature = "image")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+    Jpeg,
+}
+
+/// Errors that can occur while encoding an image with [`ImageEncoder`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum EncodeError {
+    /// Error writing to the output.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// Error serializing the image.
+    #[error("Serialization error: {0}")]
+    Serialize(#[from] bincode::Error),
+    /// Error encoding the image.
+    #[cfg(feature = "image")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+    #[error("Encoding error: {0}")]
+    Image(#[from] image::ImageError),
+    /// The image can not be represented in the requested format.
+    #[error("{0}")]
+    Message(&'static str),
+}
+
+/// Trait to write images to arbitrary [`Write`] sinks, such as sockets,
+/// in-memory buffers or compressed streams.
+///
+/// FITS output requires a file path, and is available through [`FitsWrite`](crate::FitsWrite)
+/// instead.
+pub trait ImageEncoder {
+    /// Encode the image in the given format, and write it to `w`.
+    ///
+    /// # Arguments
+    /// - `w`: The writer to write the encoded image to.
+    /// - `format`: The [`ImageFormat`] to encode the image in.
+    ///
+    /// # Errors
+    /// - If the image can not be represented in the requested format.
+    /// - If encoding the image fails.
+    /// - If writing to `w` fails.
+    fn encode<W: Write>(&self, w: &mut W, format: ImageFormat) -> Result<(), EncodeError>;
+}
+
+/// Encode an image, converting it to a [`DynamicImageOwned`] only if the format
+/// requires it.
+fn encode_with<W, S, F>(
+    img: &S,
+    owned: F,
+    w: &mut W,
+    format: ImageFormat,
+) -> Result<(), EncodeError>
+where
+    W: Write,
+    S: Serialize,
+    F: FnOnce() -> DynamicImageOwned,
+{
+    #[cfg(not(feature = "image"))]
+    let _ = owned;
+    match format {
+        ImageFormat::Bincode => Ok(bincode::serialize_into(w, img)?),
+        #[cfg(feature = "image")]
+        ImageFormat::Png => write_image(owned(), w, image::ImageFormat::Png),
+        #[cfg(feature = "image")]
+        ImageFormat::Jpeg => write_image(owned(), w, image::ImageFormat::Jpeg),
+    }
+}
+
+#[cfg(feature = "image")]
+fn write_image<W: Write>(
+    img: DynamicImageOwned,
+    w: &mut W,
+    format: image::ImageFormat,
+) -> Result<(), EncodeError> {
+    let img = image::DynamicImage::try_from(img).map_err(EncodeError::Message)?;
+    // The image encoders require a seekable writer, so encode to memory first.
+    let mut buf = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut buf, format)?;
+    w.write_all(buf.get_ref())?;
+    Ok(())
+}
+
+impl ImageEncoder for DynamicImageRef<'_> {
+    fn encode<W: Write>(&self, w: &mut W, format: ImageFormat) -> Result<(), EncodeError> {
+        encode_with(self, || self.to_owned(), w, format)
+    }
+}
+
+impl ImageEncoder for DynamicImageOwned {
+    fn encode<W: Write>(&self, w: &mut W, format: ImageFormat) -> Result<(), EncodeError> {
+        encode_with(self, || self.clone(), w, format)
+    }
+}
+
+impl ImageEncoder for GenericImageRef<'_> {
+    fn encode<W: Write>(&self, w: &mut W, format: ImageFormat) -> Result<(), EncodeError> {
+        encode_with(self, || self.image.to_owned(), w, format)
+    }
+}
+
+impl ImageEncoder for GenericImageOwned {
+    fn encode<W: Write>(&self, w: &mut W, format: ImageFormat) -> Result<(), EncodeError> {
+        encode_with(self, || self.image.clone(), w, format)
+    }
+}
+
+impl ImageEncoder for GenericImage<'_> {
+    fn encode<W: Write>(&self, w: &mut W, format: ImageFormat) -> Result<(), EncodeError> {
+        match self {
+            GenericImage::Ref(img) => img.encode(w, format),
+            GenericImage::Own(img) => img.encode(w, format),
+        }
+    }
+}
+
+mod test {
+    #[test]
+    fn test_encode_bincode() {
+        use crate::{
+            ColorSpace, GenericImage, GenericImageOwned, GenericImageRef, ImageEncoder,
+            ImageFormat, ImageRef,
+        };
+        use std::time::SystemTime;
+        let mut data = vec![1u16, 2, 3, 4, 5, 6];
+        let img = ImageRef::new(&mut data, 3, 2, ColorSpace::Gray).unwrap();
+        let mut img = GenericImageRef::new(SystemTime::now(), img.into());
+        img.insert_key("CAMERA", "Canon EOS 5D Mark IV").unwrap();
+        let mut buf = Vec::new();
+        img.encode(&mut buf, ImageFormat::Bincode).unwrap();
+        let de: GenericImageOwned = bincode::deserialize(&buf).unwrap();
+        assert_eq!(de.get_key("CAMERA"), img.get_key("CAMERA"));
+        let mut buf = Vec::new();
+        GenericImage::from(img)
+            .encode(&mut buf, ImageFormat::Bincode)
+            .unwrap();
+        assert_eq!(bincode::deserialize::<GenericImageOwned>(&buf).unwrap(), de);
+        let mut buf = Vec::new();
+        de.encode(&mut buf, ImageFormat::Bincode).unwrap();
+        assert_eq!(bincode::deserialize::<GenericImageOwned>(&buf).unwrap(), de);
+    }
+}
@@ -0,0 +1,99 @@
+//! Errors of image construction.
+
+use std::fmt::Display;
+
+use bytemuck::PodCastError;
+
+use crate::coretraits::pod_cast_error;
+
+/// Error returned when an image can not be created from the given data, dimensions
+/// and color space, e.g. by [`ImageOwned::from_owned`](crate::ImageOwned::from_owned)
+/// or [`ImageRef::new`](crate::ImageRef::new).
+///
+/// The [`Display`] messages are the same as the string errors of earlier versions, and
+/// the error converts into a `&'static str` with [`From`], so that it can be propagated
+/// with `?` from functions that return string errors.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefImageError {
+    /// The width or height exceeds 65535 pixels, or the image size overflows.
+    TooLarge,
+    /// The data is empty.
+    EmptyData,
+    /// The width is zero.
+    ZeroWidth,
+    /// The height is zero.
+    ZeroHeight,
+    /// The color space has no channels, i.e. [`ColorSpace::Custom`](crate::ColorSpace::Custom)
+    /// with zero channels.
+    NoChannels,
+    /// The number of channels does not match the color space.
+    ChannelMismatch {
+        /// The number of channels of the color space.
+        expected: u8,
+        /// The number of channels of the data.
+        found: u8,
+    },
+    /// There is not enough data for the image.
+    LengthMismatch,
+    /// The byte data could not be cast to the pixel type.
+    ByteCast(PodCastError),
+}
+
+impl RefImageError {
+    /// Get the error message.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::TooLarge => "Image too large.",
+            Self::EmptyData => "Data is empty",
+            Self::ZeroWidth => "Width is zero",
+            Self::ZeroHeight => "Height is zero",
+            Self::NoChannels => "Color space has no channels.",
+            Self::ChannelMismatch { .. } => "Number of channels does not match the color space.",
+            Self::LengthMismatch => "Not enough data for image.",
+            Self::ByteCast(e) => pod_cast_error(*e),
+        }
+    }
+}
+
+impl Display for RefImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::error::Error for RefImageError {}
+
+impl From<RefImageError> for &'static str {
+    fn from(err: RefImageError) -> Self {
+        err.as_str()
+    }
+}
+
+impl From<PodCastError> for RefImageError {
+    fn from(err: PodCastError) -> Self {
+        Self::ByteCast(err)
+    }
+}
+
+mod test {
+    #[test]
+    fn test_error_messages() {
+        use super::RefImageError;
+        use bytemuck::PodCastError;
+        let err = RefImageError::ChannelMismatch {
+            expected: 3,
+            found: 1,
+        };
+        assert_eq!(
+            err.to_string(),
+            "Number of channels does not match the color space."
+        );
+        let msg: &'static str = RefImageError::LengthMismatch.into();
+        assert_eq!(msg, "Not enough data for image.");
+        assert_eq!(
+            RefImageError::from(PodCastError::SizeMismatch),
+            RefImageError::ByteCast(PodCastError::SizeMismatch)
+        );
+    }
+}
@@ -1,22 +1,27 @@
 use std::{
+    collections::HashMap,
     fmt::Display,
+    num::NonZeroUsize,
+    ops::Range,
     path::{Path, PathBuf},
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, Utc};
 pub use fitsio::errors::Error as FitsError;
 use fitsio::{
-    hdu::FitsHdu,
-    images::{ImageDescription, ImageType, WriteImage},
+    hdu::{FitsHdu, HduInfo},
+    images::{ImageDescription, ImageType, ReadImage, WriteImage},
+    tables::{ColumnDataType, ColumnDescription},
     FitsFile,
 };
 
 use crate::{
+    coreimpls::crop_cspace,
     genericimageref::GenericImageRef,
-    metadata::{GenericValue, TIMESTAMP_KEY},
-    BayerPattern, ColorSpace, DynamicImageOwned, DynamicImageRef, GenericImage, GenericImageOwned,
-    GenericLineItem, ImageOwned, ImageProps, ImageRef, PixelStor, PixelType,
+    metadata::{crop_values, GenericValue, TIMESTAMP_KEY},
+    ColorSpace, DynamicImageOwned, DynamicImageRef, GenericImage, GenericImageOwned,
+    GenericLineItem, ImageOwned, ImageProps, ImageRef, PixelStor, PixelType, Rect,
 };
 
 #[derive(Debug, Clone, PartialEq, Hash)]
@@ -118,6 +123,37 @@ pub trait FitsWrite {
         overwrite: bool,
     ) -> Result<PathBuf, FitsError>;
 
+    #[cfg_attr(docsrs, doc(cfg(feature = "fitsio")))]
+    /// Write the image, with metadata, to a FITS file, quantizing [`f32`] image data
+    /// to 16-bit integers.
+    ///
+    /// The scale factors are derived from the minimum and maximum finite values of the
+    /// image, and are stored in the `BSCALE` and `BZERO` keywords, so that readers can
+    /// reconstruct the floating point values as `BZERO + BSCALE * value`. Images with
+    /// other pixel types are written as in [`FitsWrite::write_fits`].
+    ///
+    /// The default implementation does not quantize, and writes the image with
+    /// [`FitsWrite::write_fits`].
+    ///
+    /// # Arguments
+    /// - `path`: The path to write the FITS file to.
+    /// - `compress`: The compression algorithm to use ([`FitsCompression`]).
+    /// - `overwrite`: Whether to overwrite the file if it already exists.
+    ///
+    /// # Returns
+    /// The path to the written FITS file.
+    ///
+    /// # Errors
+    /// This function returns errors from the FITS library if the file could not be written.
+    fn write_fits_scaled<T: AsRef<Path>>(
+        &self,
+        path: T,
+        compress: FitsCompression,
+        overwrite: bool,
+    ) -> Result<PathBuf, FitsError> {
+        self.write_fits(path, compress, overwrite)
+    }
+
     #[cfg_attr(docsrs, doc(cfg(feature = "fitsio")))]
     /// Append the image, with metadata, to an existing FITS file.
     /// This method creates a new image HDU in the file.
@@ -128,6 +164,653 @@ pub trait FitsWrite {
     /// # Errors
     /// This function returns errors from the FITS library if the image could not be appended.
     fn append_fits(&self, fitsfile: &mut FitsFile) -> Result<(), FitsError>;
+
+    #[cfg_attr(docsrs, doc(cfg(feature = "fitsio")))]
+    /// Write the image, with metadata, to a FITS file, followed by a binary table
+    /// extension containing the tabular metadata in `table`.
+    ///
+    /// # Arguments
+    /// - `path`: The path to write the FITS file to.
+    /// - `compress`: The compression algorithm to use ([`FitsCompression`]).
+    /// - `overwrite`: Whether to overwrite the file if it already exists.
+    /// - `table`: The tabular metadata to write ([`MetaTable`]).
+    ///
+    /// # Returns
+    /// The path to the written FITS file.
+    ///
+    /// # Errors
+    /// This function returns errors from the FITS library if the file could not be written.
+    fn write_fits_with_table<T: AsRef<Path>>(
+        &self,
+        path: T,
+        compress: FitsCompression,
+        overwrite: bool,
+        table: &MetaTable,
+    ) -> Result<PathBuf, FitsError> {
+        let path = self.write_fits(path, compress, overwrite)?;
+        let mut fptr = FitsFile::edit(&path)?;
+        table.append_fits(&mut fptr)?;
+        Ok(path)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "fitsio")))]
+/// Tabular metadata, written to a FITS file as a binary table (`BINTABLE`) extension.
+///
+/// The table consists of named columns of [`GenericValue`]s. All columns must have the
+/// same number of rows, and all values in a column must be of the same type.
+///
+/// # Valid Types
+/// The valid types for the column values are:
+/// - [`u8`] | [`u16`] | [`u32`] | [`u64`]
+/// - [`i8`] | [`i16`] | [`i32`] | [`i64`]
+/// - [`f32`] | [`f64`]
+/// - [`std::time::Duration`] (stored as seconds, [`f64`])
+/// - [`String`]
+///
+/// # Usage
+/// ```no_run
+/// use refimage::{GenericValue, MetaTable};
+///
+/// let mut table = MetaTable::new("SOURCES");
+/// table
+///     .add_column("X", vec![GenericValue::F32(10.5), GenericValue::F32(20.25)])
+///     .unwrap();
+/// table
+///     .add_column("FLUX", vec![GenericValue::U32(1200), GenericValue::U32(800)])
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetaTable {
+    name: String,
+    columns: Vec<(String, Vec<GenericValue>)>,
+}
+
+impl MetaTable {
+    /// Create a new, empty [`MetaTable`].
+    ///
+    /// # Arguments
+    /// - `name`: The name of the table extension (`EXTNAME`).
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            columns: Vec::new(),
+        }
+    }
+
+    /// Add a column to the table.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the column.
+    /// - `values`: The values in the column.
+    ///
+    /// # Errors
+    /// - If the column name is empty, or a column with the same name exists.
+    /// - If the column is empty.
+    /// - If the column does not have the same number of rows as the existing columns.
+    /// - If the values in the column are not of the same type.
+    /// - If the values are not of a supported type.
+    pub fn add_column(
+        &mut self,
+        name: &str,
+        values: Vec<GenericValue>,
+    ) -> Result<(), &'static str> {
+        if name.is_empty() {
+            return Err("Column name cannot be empty");
+        }
+        if self
+            .columns
+            .iter()
+            .any(|(n, _)| n.eq_ignore_ascii_case(name))
+        {
+            return Err("Column already exists");
+        }
+        let first = values.first().ok_or("Column cannot be empty")?;
+        if let Some((_, col)) = self.columns.first() {
+            if col.len() != values.len() {
+                return Err("Column length does not match table length");
+            }
+        }
+        if values
+            .iter()
+            .any(|v| std::mem::discriminant(v) != std::mem::discriminant(first))
+        {
+            return Err("Column values must be of the same type");
+        }
+        column_type(&values)?;
+        self.columns.push((name.to_owned(), values));
+        Ok(())
+    }
+
+    /// Get the name of the table.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the number of rows in the table.
+    pub fn len(&self) -> usize {
+        self.columns.first().map(|(_, col)| col.len()).unwrap_or(0)
+    }
+
+    /// Check if the table has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Append the table as a binary table extension to an existing FITS file.
+    fn append_fits(&self, fptr: &mut FitsFile) -> Result<(), FitsError> {
+        if self.columns.is_empty() {
+            return Err(FitsError::Message("Table has no columns".to_owned()));
+        }
+        let descs = self
+            .columns
+            .iter()
+            .map(|(name, values)| {
+                let (typ, repeat) =
+                    column_type(values).map_err(|e| FitsError::Message(e.into()))?;
+                ColumnDescription::new(name)
+                    .with_type(typ)
+                    .that_repeats(repeat)
+                    .create()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let hdu = fptr.create_table(self.name.clone(), &descs)?;
+        for (name, values) in self.columns.iter() {
+            write_column(&hdu, fptr, name, values)?;
+        }
+        Ok(())
+    }
+}
+
+/// Get the FITS column type and repeat count for a column of values.
+fn column_type(values: &[GenericValue]) -> Result<(ColumnDataType, usize), &'static str> {
+    let typ = match values.first().ok_or("Column cannot be empty")? {
+        GenericValue::U8(_) => ColumnDataType::Byte,
+        GenericValue::U16(_) => ColumnDataType::UnsignedShort,
+        GenericValue::U32(_) => ColumnDataType::UnsignedLong,
+        GenericValue::U64(_) => ColumnDataType::UnsignedLongLong,
+        GenericValue::I8(_) => ColumnDataType::SignedByte,
+        GenericValue::I16(_) => ColumnDataType::Short,
+        GenericValue::I32(_) => ColumnDataType::Int,
+        GenericValue::I64(_) => ColumnDataType::LongLong,
+        GenericValue::F32(_) => ColumnDataType::Float,
+        GenericValue::F64(_) | GenericValue::Duration(_) => ColumnDataType::Double,
+        GenericValue::String(_) => {
+            let width = values
+                .iter()
+                .filter_map(|v| v.get_value_string())
+                .map(|v| v.len())
+                .max()
+                .unwrap_or(1)
+                .max(1);
+            return Ok((ColumnDataType::String, width));
+        }
+        _ => return Err("Unsupported column type"),
+    };
+    Ok((typ, 1))
+}
+
+/// Write a column of values to a binary table HDU.
+fn write_column(
+    hdu: &FitsHdu,
+    fptr: &mut FitsFile,
+    name: &str,
+    values: &[GenericValue],
+) -> Result<(), FitsError> {
+    macro_rules! write_col {
+        ($getter:ident) => {{
+            let col: Vec<_> = values.iter().filter_map(|v| v.$getter()).collect();
+            hdu.write_col(fptr, name, &col)?;
+        }};
+    }
+    match values.first() {
+        Some(GenericValue::U8(_)) => write_col!(get_value_u8),
+        Some(GenericValue::U16(_)) => write_col!(get_value_u16),
+        Some(GenericValue::U32(_)) => write_col!(get_value_u32),
+        Some(GenericValue::U64(_)) => write_col!(get_value_u64),
+        Some(GenericValue::I8(_)) => write_col!(get_value_i8),
+        Some(GenericValue::I16(_)) => write_col!(get_value_i16),
+        Some(GenericValue::I32(_)) => write_col!(get_value_i32),
+        Some(GenericValue::I64(_)) => write_col!(get_value_i64),
+        Some(GenericValue::F32(_)) => write_col!(get_value_f32),
+        Some(GenericValue::F64(_)) => write_col!(get_value_f64),
+        Some(GenericValue::Duration(_)) => {
+            let col: Vec<f64> = values
+                .iter()
+                .filter_map(|v| v.get_value_duration())
+                .map(|v| v.as_secs_f64())
+                .collect();
+            hdu.write_col(fptr, name, &col)?;
+        }
+        Some(GenericValue::String(_)) => {
+            let col: Vec<String> = values
+                .iter()
+                .filter_map(|v| v.get_value_string())
+                .map(|v| v.to_owned())
+                .collect();
+            hdu.write_col(fptr, name, &col)?;
+        }
+        _ => return Err(FitsError::Message("Unsupported column type".to_owned())),
+    }
+    Ok(())
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "fitsio")))]
+/// Trait for reading objects from FITS files.
+pub trait FitsRead: Sized {
+    #[cfg_attr(docsrs, doc(cfg(feature = "fitsio")))]
+    /// Read the first image in a FITS file.
+    ///
+    /// The image is read from the first HDU that contains image data, which is the
+    /// primary HDU for uncompressed files and the `IMAGE` extension for compressed
+    /// files. The color space is reconstructed from the `COLOR_SPACE` keyword, and all
+    /// other keywords are read into the metadata. Durations and timestamps that were
+    /// split into seconds (`_S`) and nanoseconds (`_NS`) keywords are reassembled.
+    ///
+    /// Since FITS headers do not preserve the width of numeric values, integer
+    /// metadata is read as [`i64`] and real metadata as [`f64`]. Images with pixel
+    /// types other than [`u8`], [`u16`], [`i16`], [`u32`] and [`f32`], and scaled
+    /// [`i16`] images, are read as [`f32`].
+    ///
+    /// # Arguments
+    /// - `path`: The path to the FITS file.
+    ///
+    /// # Errors
+    /// - If the file does not contain image data.
+    /// - If the color space does not match the image dimensions.
+    /// - Errors from the FITS library if the file could not be read.
+    fn read_fits<T: AsRef<Path>>(path: T) -> Result<Self, FitsError>;
+
+    #[cfg_attr(docsrs, doc(cfg(feature = "fitsio")))]
+    /// Read a region of interest (ROI) of the first image in a FITS file.
+    ///
+    /// Only the pixels in the requested region are read from the file. If the source
+    /// image is a Bayer mosaic, the Bayer pattern is shifted according to the origin
+    /// of the ROI.
+    /// The metadata is read as in [`FitsRead::read_fits`], and the sub-frame origin
+    /// and WCS reference pixel are updated according to the origin of the ROI.
+    ///
+    /// # Arguments
+    /// - `path`: The path to the FITS file.
+    /// - `roi`: The region of interest. The width and height are clipped to the image bounds.
+    ///
+    /// # Errors
+    /// - If the file does not contain image data.
+    /// - If the ROI is empty or completely out of bounds.
+    /// - If the pixel type of the image is not supported.
+    /// - Errors from the FITS library if the file could not be read.
+    fn read_fits_roi<T: AsRef<Path>>(path: T, roi: Rect) -> Result<Self, FitsError>;
+}
+
+impl FitsRead for GenericImageOwned {
+    fn read_fits<T: AsRef<Path>>(path: T) -> Result<Self, FitsError> {
+        let max = NonZeroUsize::MAX;
+        let (image, metadata) = read_image_hdu(path.as_ref(), 0, 0, max, max)?;
+        Ok(GenericImageOwned { metadata, image })
+    }
+
+    fn read_fits_roi<T: AsRef<Path>>(path: T, roi: Rect) -> Result<Self, FitsError> {
+        let (width, height) = NonZeroUsize::new(roi.width)
+            .zip(NonZeroUsize::new(roi.height))
+            .ok_or_else(|| FitsError::Message("ROI is empty.".to_owned()))?;
+        let (image, mut metadata) = read_image_hdu(path.as_ref(), roi.x, roi.y, width, height)?;
+        crop_values(&mut metadata, roi.x, roi.y);
+        Ok(GenericImageOwned { metadata, image })
+    }
+}
+
+/// Read a region of the first image in a FITS file, along with its header.
+fn read_image_hdu(
+    path: &Path,
+    x: usize,
+    y: usize,
+    width: NonZeroUsize,
+    height: NonZeroUsize,
+) -> Result<(DynamicImageOwned, HashMap<String, GenericLineItem>), FitsError> {
+    let mut fptr = FitsFile::open(path)?;
+    let hdu = find_image_hdu(&mut fptr)?;
+    let (shape, image_type) = match hdu.info {
+        HduInfo::ImageInfo {
+            ref shape,
+            image_type,
+        } => (shape.clone(), image_type),
+        _ => unreachable!(), // guaranteed by find_image_hdu
+    };
+    let (swid, shei, channels) = match shape[..] {
+        [h, w] => (w, h, 1),
+        [h, w, c] => (w, h, c),
+        _ => {
+            return Err(FitsError::Message(format!(
+                "Unsupported image dimensions: {:?}",
+                shape
+            )))
+        }
+    };
+    if x >= swid || y >= shei {
+        return Err(FitsError::Message("ROI is out of bounds.".to_owned()));
+    }
+    let wid = width.get().min(swid - x);
+    let hei = height.get().min(shei - y);
+
+    let cspace = read_cspace(&hdu, &mut fptr, channels)?;
+    let cspace = crop_cspace(&cspace, x as isize, y as isize);
+
+    let yrange = y..y + hei;
+    let xrange = x..x + wid;
+    let crange = 0..channels;
+    // Ranges are in FITS axis order, i.e. fastest varying axis first.
+    let ranges: &[&Range<usize>] = if shape.len() > 2 {
+        &[&crange, &xrange, &yrange]
+    } else {
+        &[&xrange, &yrange]
+    };
+
+    let image = match image_type {
+        ImageType::UnsignedByte => {
+            DynamicImageOwned::U8(read_region(&hdu, &mut fptr, ranges, wid, hei, cspace)?)
+        }
+        ImageType::UnsignedShort => {
+            DynamicImageOwned::U16(read_region(&hdu, &mut fptr, ranges, wid, hei, cspace)?)
+        }
+        ImageType::Short if !is_scaled(&hdu, &mut fptr) => {
+            DynamicImageOwned::I16(read_region(&hdu, &mut fptr, ranges, wid, hei, cspace)?)
+        }
+        ImageType::UnsignedLong => {
+            DynamicImageOwned::U32(read_region(&hdu, &mut fptr, ranges, wid, hei, cspace)?)
+        }
+        // The remaining types are converted to f32 by the FITS library, which
+        // also applies the BSCALE and BZERO keywords (e.g. for scaled images).
+        ImageType::Float
+        | ImageType::Double
+        | ImageType::Byte
+        | ImageType::Short
+        | ImageType::Long
+        | ImageType::LongLong => {
+            DynamicImageOwned::F32(read_region(&hdu, &mut fptr, ranges, wid, hei, cspace)?)
+        }
+    };
+
+    let metadata = read_metadata(&hdu, &mut fptr)?;
+    Ok((image, metadata))
+}
+
+/// Check whether the image HDU has a linear scaling applied through the `BSCALE`
+/// and `BZERO` keywords.
+fn is_scaled(hdu: &FitsHdu, fptr: &mut FitsFile) -> bool {
+    let bscale = hdu.read_key::<f64>(fptr, "BSCALE").unwrap_or(1.0);
+    let bzero = hdu.read_key::<f64>(fptr, "BZERO").unwrap_or(0.0);
+    bscale != 1.0 || bzero != 0.0
+}
+
+/// Find the first HDU in the FITS file that contains image data.
+fn find_image_hdu(fptr: &mut FitsFile) -> Result<FitsHdu, FitsError> {
+    for hdu in fptr.iter() {
+        if let HduInfo::ImageInfo { ref shape, .. } = hdu.info {
+            if !shape.is_empty() {
+                return Ok(hdu);
+            }
+        }
+    }
+    Err(FitsError::Message(
+        "Could not find image data in FITS file".to_owned(),
+    ))
+}
+
+/// Read a region of the image HDU into an [`ImageOwned`].
+fn read_region<T: PixelStor>(
+    hdu: &FitsHdu,
+    fptr: &mut FitsFile,
+    ranges: &[&Range<usize>],
+    width: usize,
+    height: usize,
+    cspace: ColorSpace,
+) -> Result<ImageOwned<T>, FitsError>
+where
+    Vec<T>: ReadImage,
+{
+    let data: Vec<T> = hdu.read_region(fptr, ranges)?;
+    ImageOwned::new(data, width, height, cspace).map_err(|e| FitsError::Message(e.to_owned()))
+}
+
+/// Read the color space of the image HDU, falling back to the image dimensions.
+fn read_cspace(
+    hdu: &FitsHdu,
+    fptr: &mut FitsFile,
+    channels: usize,
+) -> Result<ColorSpace, FitsError> {
+    let cspace = hdu
+        .read_key::<String>(fptr, "COLOR_SPACE")
+        .ok()
+        .and_then(|val| ColorSpace::from_canonical_str(&val).ok());
+    let cspace = match (cspace, channels) {
+        (Some(cspace), _) => cspace,
+        (None, 1) => ColorSpace::Gray,
+        (None, 3) => ColorSpace::Rgb,
+        (None, ch) => ColorSpace::Custom(ch as _, "UNKNOWN".to_owned()),
+    };
+    if cspace.num_channels() != channels {
+        return Err(FitsError::Message(
+            "Color space does not match the number of channels".to_owned(),
+        ));
+    }
+    Ok(cspace)
+}
+
+/// Keywords that describe the structure of the HDU, or that are written by
+/// [`FitsWrite`] itself, and are therefore not part of the image metadata.
+const RESERVED_KEYS: &[&str] = &[
+    "SIMPLE",
+    "XTENSION",
+    "BITPIX",
+    "EXTEND",
+    "EXTNAME",
+    "PCOUNT",
+    "GCOUNT",
+    "TFIELDS",
+    "THEAP",
+    "BZERO",
+    "BSCALE",
+    "BLANK",
+    "CHECKSUM",
+    "DATASUM",
+    "COMMENT",
+    "HISTORY",
+    "DATE-OBS",
+    "COLOR_SPACE",
+    "COMPRESSED_IMAGE",
+    "COMPRESSION_ALGO",
+];
+
+/// Check whether a keyword is reserved by the FITS standard, by the tiled image
+/// compression convention or by [`FitsWrite`].
+fn is_reserved_key(key: &str) -> bool {
+    // NAXISn, TTYPEn, TFORMn etc. and all Z keywords of compressed images.
+    const INDEXED: &[&str] = &["NAXIS", "TTYPE", "TFORM", "TUNIT", "TDIM", "TSCAL", "TZERO"];
+    key.is_empty()
+        || RESERVED_KEYS.contains(&key)
+        || INDEXED.iter().any(|prefix| {
+            key.strip_prefix(prefix)
+                .is_some_and(|idx| idx.chars().all(|c| c.is_ascii_digit()))
+        })
+        || is_zkey(key)
+}
+
+/// Check whether a keyword belongs to the tiled image compression convention.
+fn is_zkey(key: &str) -> bool {
+    const ZKEYS: &[&str] = &[
+        "ZIMAGE", "ZCMPTYPE", "ZBITPIX", "ZNAXIS", "ZTILE", "ZNAME", "ZVAL", "ZMASKCMP", "ZSIMPLE",
+        "ZTENSION", "ZEXTEND", "ZBLOCKED", "ZPCOUNT", "ZGCOUNT", "ZHECKSUM", "ZDATASUM",
+        "ZQUANTIZ", "ZDITHER0", "ZBLANK", "ZSCALE", "ZZERO",
+    ];
+    ZKEYS.iter().any(|prefix| {
+        key.strip_prefix(prefix)
+            .is_some_and(|idx| idx.chars().all(|c| c.is_ascii_digit()))
+    })
+}
+
+/// Read all header cards of the image HDU as `(keyword, value, comment)` triplets.
+fn read_cards(
+    hdu: &FitsHdu,
+    fptr: &mut FitsFile,
+) -> Result<Vec<(String, String, String)>, FitsError> {
+    use fitsio::sys::{ffghsp, ffgkyn, FLEN_COMMENT, FLEN_KEYWORD, FLEN_VALUE};
+    use std::ffi::{c_char, CStr};
+
+    // Make the image HDU the current HDU
+    fptr.hdu(hdu.number)?;
+    let mut status = 0;
+    let mut nkeys = 0;
+    let mut nmore = 0;
+    // SAFETY: The file pointer is valid for the lifetime of `fptr`.
+    unsafe { ffghsp(fptr.as_raw(), &mut nkeys, &mut nmore, &mut status) };
+    fitsio::errors::check_status(status)?;
+
+    let mut cards = Vec::with_capacity(nkeys as _);
+    for idx in 1..=nkeys {
+        let mut name = [0 as c_char; FLEN_KEYWORD as usize];
+        let mut value = [0 as c_char; FLEN_VALUE as usize];
+        let mut comment = [0 as c_char; FLEN_COMMENT as usize];
+        // SAFETY: The buffers are sized according to the cfitsio limits, and are
+        // NUL-terminated by cfitsio.
+        let (name, value, comment) = unsafe {
+            ffgkyn(
+                fptr.as_raw(),
+                idx,
+                name.as_mut_ptr(),
+                value.as_mut_ptr(),
+                comment.as_mut_ptr(),
+                &mut status,
+            );
+            (
+                CStr::from_ptr(name.as_ptr()).to_string_lossy().into_owned(),
+                CStr::from_ptr(value.as_ptr())
+                    .to_string_lossy()
+                    .into_owned(),
+                CStr::from_ptr(comment.as_ptr())
+                    .to_string_lossy()
+                    .into_owned(),
+            )
+        };
+        fitsio::errors::check_status(status)?;
+        cards.push((name, value, comment));
+    }
+    Ok(cards)
+}
+
+/// Parse the value of a header card into a [`GenericValue`].
+///
+/// FITS headers do not store the width of numeric values, hence integers are
+/// parsed as [`i64`] (or [`u64`] if they do not fit), and real numbers as [`f64`].
+/// Logical values are returned as the strings `T` and `F`.
+fn parse_card_value(value: &str) -> Option<GenericValue> {
+    let value = value.trim();
+    if value.is_empty() {
+        None
+    } else if let Some(val) = value.strip_prefix('\'') {
+        let val = val.strip_suffix('\'').unwrap_or(val);
+        Some(GenericValue::String(
+            val.replace("''", "'").trim_end().to_owned(),
+        ))
+    } else if let Ok(val) = value.parse::<i64>() {
+        Some(GenericValue::I64(val))
+    } else if let Ok(val) = value.parse::<u64>() {
+        Some(GenericValue::U64(val))
+    } else if let Ok(val) = value.replace(['D', 'd'], "E").parse::<f64>() {
+        Some(GenericValue::F64(val))
+    } else {
+        Some(GenericValue::String(value.to_owned()))
+    }
+}
+
+/// Read the metadata of the image HDU.
+///
+/// All non-reserved keywords are read. Durations and timestamps, which are split
+/// into pairs of seconds (`_S`) and nanoseconds (`_NS`) keywords on export, are
+/// reassembled. If the image does not have a timestamp, the `DATE-OBS` keyword is
+/// used instead, falling back to the UNIX epoch.
+fn read_metadata(
+    hdu: &FitsHdu,
+    fptr: &mut FitsFile,
+) -> Result<HashMap<String, GenericLineItem>, FitsError> {
+    let cards = read_cards(hdu, fptr)?;
+
+    let lookup: HashMap<&str, (&str, &str)> = cards
+        .iter()
+        .map(|(name, value, comment)| (name.as_str(), (value.as_str(), comment.as_str())))
+        .collect();
+    let as_u64 = |key: &str| {
+        lookup
+            .get(key)
+            .and_then(|(val, _)| val.trim().parse::<u64>().ok())
+    };
+
+    let mut metadata = HashMap::new();
+    let mut consumed = Vec::new();
+    // Reassemble split durations and timestamps
+    for (name, _, comment) in cards.iter() {
+        let Some(base) = name.strip_suffix("_S") else {
+            continue;
+        };
+        let nskey = format!("{base}_NS");
+        let (Some(secs), Some(nsecs)) = (as_u64(name), as_u64(&nskey)) else {
+            continue;
+        };
+        let duration = Duration::new(secs, nsecs as _);
+        let (value, comment) =
+            if comment.ends_with("s from EPOCH") || comment.ends_with("(s from EPOCH)") {
+                let cmt = comment.strip_suffix(" (s from EPOCH)");
+                (GenericValue::SystemTime(UNIX_EPOCH + duration), cmt)
+            } else {
+                (
+                    GenericValue::Duration(duration),
+                    comment.strip_suffix(" (s)"),
+                )
+            };
+        metadata.insert(
+            base.to_owned(),
+            GenericLineItem {
+                value,
+                comment: comment.map(|c| c.to_owned()),
+            },
+        );
+        consumed.extend([base.to_owned(), name.clone(), nskey]);
+    }
+    for (name, value, comment) in cards.iter() {
+        if is_reserved_key(name) || consumed.contains(name) {
+            continue;
+        }
+        if let Some(value) = parse_card_value(value) {
+            metadata.insert(
+                name.clone(),
+                GenericLineItem {
+                    value,
+                    comment: (!comment.is_empty()).then(|| comment.clone()),
+                },
+            );
+        }
+    }
+
+    if !matches!(
+        metadata.get(TIMESTAMP_KEY).map(|v| &v.value),
+        Some(GenericValue::SystemTime(_))
+    ) {
+        let tstamp = lookup
+            .get("DATE-OBS")
+            .and_then(|(val, _)| parse_card_value(val))
+            .and_then(|val| {
+                NaiveDateTime::parse_from_str(val.get_value_string()?, "%Y-%m-%dT%H:%M:%S%.f").ok()
+            })
+            .map(|dt| dt.and_utc().into())
+            .unwrap_or(UNIX_EPOCH);
+        metadata.insert(
+            TIMESTAMP_KEY.to_string(),
+            GenericLineItem {
+                value: tstamp.into(),
+                comment: Some("Timestamp of the image".to_owned()),
+            },
+        );
+    }
+    Ok(metadata)
 }
 
 #[cfg_attr(docsrs, doc(cfg(feature = "fitsio")))]
@@ -148,26 +831,321 @@ pub fn create_fits<T: AsRef<Path>>(
     compress: FitsCompression,
     overwrite: bool,
 ) -> Result<FitsFile, FitsError> {
-    let mut path: PathBuf = path.as_ref().into();
+    let (_, path) = fits_paths(path.as_ref(), &compress, overwrite)?;
+    FitsFile::create(path).open()
+}
+
+/// Get the paths of a FITS file to be created, removing an existing file if `overwrite`
+/// is set.
+///
+/// Returns the path with the default `.fits` extension, which is reported to the caller,
+/// and the path with the extension of the compression algorithm, which is passed to the
+/// FITS library to create the file.
+fn fits_paths(
+    path: &Path,
+    compress: &FitsCompression,
+    overwrite: bool,
+) -> Result<(PathBuf, PathBuf), FitsError> {
+    let mut path = PathBuf::from(path);
     path.set_extension((FitsCompression::None).extension()); // Default extension
     if overwrite && path.exists() {
         // There seems to be a bug in FITSIO, overwrite() the way called here does nothing
         std::fs::remove_file(&path)?;
     }
+    let fpath = path.clone();
     path.set_extension(compress.extension());
-    FitsFile::create(path).open()
+    Ok((fpath, path))
+}
+
+/// Create a FITS file at `path` with an image HDU described by `desc`.
+///
+/// Uncompressed images are stored in the primary HDU. Compressed images are stored in
+/// an `IMAGE` extension, and the primary HDU records the compression algorithm.
+fn create_image_hdu(
+    path: PathBuf,
+    compress: &FitsCompression,
+    desc: &ImageDescription,
+) -> Result<(FitsHdu, FitsFile), FitsError> {
+    let mut fptr = FitsFile::create(path);
+    if *compress == FitsCompression::None {
+        fptr = fptr.with_custom_primary(desc);
+    }
+    let mut fptr = fptr.open()?;
+
+    let hdu = if *compress == FitsCompression::None {
+        fptr.primary_hdu()?
+    } else {
+        let hdu = fptr.primary_hdu()?;
+        hdu.write_key(&mut fptr, "COMPRESSED_IMAGE", "T")?;
+        hdu.write_key(&mut fptr, "COMPRESSION_ALGO", compress.to_string())?;
+        fptr.create_image("IMAGE", desc)?
+    };
+    Ok((hdu, fptr))
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "fitsio")))]
+/// Write a stack of frames to a FITS file as a single data cube, e.g. a time series.
+///
+/// Unlike appending each frame with [`FitsWrite::append_fits`], all frames are stored in
+/// one image HDU, with an extra outermost axis (`NAXIS3` for single-channel frames,
+/// `NAXIS4` for multi-channel frames) equal to the number of frames. The metadata of
+/// the first frame is written to the HDU.
+///
+/// # Arguments
+/// - `frames`: The frames to write.
+/// - `path`: The path to write the FITS file to.
+/// - `compress`: The compression algorithm to use ([`FitsCompression`]).
+/// - `overwrite`: Whether to overwrite the file if it already exists.
+///
+/// # Returns
+/// The path to the written FITS file.
+///
+/// # Errors
+/// - If there are no frames.
+/// - If the frames do not have the same dimensions, pixel type and color space.
+/// - Errors from the FITS library if the file could not be written.
+pub fn write_fits_cube<T: AsRef<Path>>(
+    frames: &[GenericImageOwned],
+    path: T,
+    compress: FitsCompression,
+    overwrite: bool,
+) -> Result<PathBuf, FitsError> {
+    let first = frames
+        .first()
+        .ok_or(FitsError::Message("No frames to write".to_owned()))?;
+    if frames[1..].iter().any(|frame| {
+        frame.width() != first.width()
+            || frame.height() != first.height()
+            || frame.pixel_type() != first.pixel_type()
+            || frame.color_space() != first.color_space()
+    }) {
+        return Err(FitsError::Message(
+            "Frames must have the same dimensions, pixel type and color space".to_owned(),
+        ));
+    }
+    let path = path.as_ref();
+    if path.exists() && path.is_dir() {
+        return Err(FitsError::Message("Path is a directory".to_string()));
+    }
+    let datestamp = first
+        .get_key(TIMESTAMP_KEY)
+        .ok_or(FitsError::Message(
+            "Could not find timestamp in metadata".to_owned(),
+        ))?
+        .get_value()
+        .get_value_systemtime()
+        .ok_or(FitsError::Message(
+            "Could not convert timestamp to SystemTime".to_owned(),
+        ))?;
+    let datestamp = systemtime_to_utc(datestamp)?;
+    let datestamp = datestamp.format("%Y-%m-%dT%H:%M:%S%.6f").to_string();
+
+    let (width, height, channels) = (first.width(), first.height(), first.channels() as usize);
+    let dimensions = [frames.len(), height, width, channels];
+    let desc = ImageDescription {
+        data_type: first.pixel_type().into(),
+        dimensions: if channels > 1 {
+            &dimensions
+        } else {
+            &dimensions[..3]
+        },
+    };
+
+    let (fpath, path) = fits_paths(path, &compress, overwrite)?;
+    let (hdu, mut fptr) = create_image_hdu(path, &compress, &desc)?;
+
+    let len = first.len();
+    for (i, frame) in frames.iter().enumerate() {
+        let (start, end) = (i * len, (i + 1) * len);
+        match frame.get_image() {
+            DynamicImageOwned::U8(img) => hdu.write_section(&mut fptr, start, end, img.as_slice()),
+            DynamicImageOwned::U16(img) => hdu.write_section(&mut fptr, start, end, img.as_slice()),
+            DynamicImageOwned::I16(img) => hdu.write_section(&mut fptr, start, end, img.as_slice()),
+            DynamicImageOwned::U32(img) => hdu.write_section(&mut fptr, start, end, img.as_slice()),
+            DynamicImageOwned::F32(img) => hdu.write_section(&mut fptr, start, end, img.as_slice()),
+        }?;
+    }
+
+    let lineitem = GenericLineItem {
+        value: GenericValue::String(datestamp),
+        comment: Some("Date and time of FITS file data".to_string()),
+    };
+    lineitem.write_key("DATE-OBS", &hdu, &mut fptr)?;
+
+    let lineitem = GenericLineItem {
+        value: first.color_space().into(),
+        comment: Some("Color space of the image".to_string()),
+    };
+    lineitem.write_key("COLOR_SPACE", &hdu, &mut fptr)?;
+
+    for (name, value) in first.iter_metadata_sorted() {
+        value.write_key(name, &hdu, &mut fptr)?;
+    }
+    Ok(fpath)
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "fitsio")))]
+/// Writer for FITS images that are too large to hold in memory.
+///
+/// The image HDU is created with the declared dimensions, and the image data is written
+/// incrementally, in blocks of whole rows, e.g. while assembling a mosaic.
+///
+/// # Usage
+/// ```no_run
+/// use refimage::{ColorSpace, FitsCompression, FitsImageWriter};
+///
+/// let mut writer =
+///     FitsImageWriter::<u16>::create("mosaic.fits", 4, 1000, ColorSpace::Gray, FitsCompression::None, true)
+///         .unwrap();
+/// for y in (0..1000).step_by(100) {
+///     let rows = vec![0u16; 4 * 100]; // e.g. read from a tile
+///     writer.write_rows(y, &rows).unwrap();
+/// }
+/// let path = writer.finish().unwrap();
+/// ```
+pub struct FitsImageWriter<T: PixelStor + WriteImage> {
+    fptr: FitsFile,
+    hdu: FitsHdu,
+    path: PathBuf,
+    width: usize,
+    height: usize,
+    channels: usize,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: PixelStor + WriteImage> FitsImageWriter<T> {
+    /// Create a FITS file with an image HDU of the given dimensions.
+    ///
+    /// # Arguments
+    /// - `path`: The path to write the FITS file to.
+    /// - `width`: The width of the image.
+    /// - `height`: The height of the image.
+    /// - `cspace`: The color space of the image ([`ColorSpace`]).
+    /// - `compress`: The compression algorithm to use ([`FitsCompression`]).
+    /// - `overwrite`: Whether to overwrite the file if it already exists.
+    ///
+    /// # Errors
+    /// - If the width or height is zero.
+    /// - Errors from the FITS library if the file could not be created.
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        width: usize,
+        height: usize,
+        cspace: ColorSpace,
+        compress: FitsCompression,
+        overwrite: bool,
+    ) -> Result<Self, FitsError> {
+        if width == 0 || height == 0 {
+            return Err(FitsError::Message(
+                "Image dimensions must be non-zero".to_owned(),
+            ));
+        }
+        let channels = cspace.num_channels();
+        let desc = ImageDescription {
+            data_type: T::PIXEL_TYPE.into(),
+            dimensions: if channels > 1 {
+                &[height, width, channels]
+            } else {
+                &[height, width]
+            },
+        };
+
+        let (fpath, path) = fits_paths(path.as_ref(), &compress, overwrite)?;
+        let (hdu, mut fptr) = create_image_hdu(path, &compress, &desc)?;
+
+        let lineitem = GenericLineItem {
+            value: cspace.into(),
+            comment: Some("Color space of the image".to_string()),
+        };
+        lineitem.write_key("COLOR_SPACE", &hdu, &mut fptr)?;
+
+        Ok(Self {
+            fptr,
+            hdu,
+            path: fpath,
+            width,
+            height,
+            channels,
+            _type: std::marker::PhantomData,
+        })
+    }
+
+    /// Write a metadata key to the image HDU.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the key.
+    /// - `value`: The value of the key.
+    /// - `comment`: An optional comment for the key.
+    ///
+    /// # Errors
+    /// Errors from the FITS library if the key could not be written.
+    pub fn write_key<V: Into<GenericValue>>(
+        &mut self,
+        name: &str,
+        value: V,
+        comment: Option<&str>,
+    ) -> Result<(), FitsError> {
+        let lineitem = GenericLineItem {
+            value: value.into(),
+            comment: comment.map(|c| c.to_owned()),
+        };
+        lineitem.write_key(name, &self.hdu, &mut self.fptr)
+    }
+
+    /// Write whole rows of image data, starting at row `y0`.
+    ///
+    /// The data is interleaved (`RGBRGB...`), and must contain a whole number of rows.
+    ///
+    /// # Arguments
+    /// - `y0`: The first row to write.
+    /// - `data`: The image data of the rows.
+    ///
+    /// # Errors
+    /// - If `data` does not contain a whole number of rows.
+    /// - If the rows extend beyond the height of the image.
+    /// - Errors from the FITS library if the data could not be written.
+    pub fn write_rows(&mut self, y0: usize, data: &[T]) -> Result<(), FitsError> {
+        let stride = self.width * self.channels;
+        if !data.len().is_multiple_of(stride) {
+            return Err(FitsError::Message(
+                "Data must contain a whole number of rows".to_owned(),
+            ));
+        }
+        let rows = data.len() / stride;
+        if !matches!(y0.checked_add(rows), Some(end) if end <= self.height) {
+            return Err(FitsError::Message(
+                "Rows extend beyond the image".to_owned(),
+            ));
+        }
+        let start = y0 * stride;
+        self.hdu
+            .write_section(&mut self.fptr, start, start + data.len(), data)
+    }
+
+    /// Close the FITS file.
+    ///
+    /// All rows should be written before closing the file; rows that were not
+    /// written have unspecified values.
+    ///
+    /// # Returns
+    /// The path to the written FITS file.
+    pub fn finish(self) -> Result<PathBuf, FitsError> {
+        let Self { fptr, path, .. } = self;
+        drop(fptr);
+        Ok(path)
+    }
 }
 
 macro_rules! impl_fitswrite {
     ($t:ty) => {
-        impl FitsWrite for $t {
-            fn write_fits<T: AsRef<Path>>(
+        impl $t {
+            fn write_fits_inner(
                 &self,
-                path: T,
+                path: &Path,
                 compress: FitsCompression,
                 overwrite: bool,
+                scaled: bool,
             ) -> Result<PathBuf, FitsError> {
-                let path = path.as_ref();
                 if path.exists() && path.is_dir() {
                     return Err(FitsError::Message("Path is a directory".to_string()));
                 }
@@ -186,17 +1164,13 @@ macro_rules! impl_fitswrite {
 
                 let datestamp = datestamp.format("%Y-%m-%dT%H:%M:%S%.6f").to_string();
 
-                let mut path = PathBuf::from(path);
-                path.set_extension((FitsCompression::None).extension()); // Default extension
-                if overwrite && path.exists() {
-                    // There seems to be a bug in FITSIO, overwrite() the way called here does nothing
-                    std::fs::remove_file(&path)?;
-                }
-
-                let fpath = path.clone();
-                path.set_extension(compress.extension());
+                let (fpath, path) = fits_paths(path, &compress, overwrite)?;
 
-                let (hdu, mut fptr) = self.get_image().write_fits(path, compress)?;
+                let (hdu, mut fptr) = if scaled {
+                    self.get_image().write_fits_scaled(path, compress)?
+                } else {
+                    self.get_image().write_fits(path, compress)?
+                };
 
                 let lineitem = GenericLineItem {
                     value: GenericValue::String(datestamp),
@@ -210,11 +1184,31 @@ macro_rules! impl_fitswrite {
                 };
                 lineitem.write_key("COLOR_SPACE", &hdu, &mut fptr)?;
 
-                for (name, value) in self.get_metadata().iter() {
+                for (name, value) in self.iter_metadata_sorted() {
                     value.write_key(name, &hdu, &mut fptr)?;
                 }
                 Ok(fpath)
             }
+        }
+
+        impl FitsWrite for $t {
+            fn write_fits<T: AsRef<Path>>(
+                &self,
+                path: T,
+                compress: FitsCompression,
+                overwrite: bool,
+            ) -> Result<PathBuf, FitsError> {
+                self.write_fits_inner(path.as_ref(), compress, overwrite, false)
+            }
+
+            fn write_fits_scaled<T: AsRef<Path>>(
+                &self,
+                path: T,
+                compress: FitsCompression,
+                overwrite: bool,
+            ) -> Result<PathBuf, FitsError> {
+                self.write_fits_inner(path.as_ref(), compress, overwrite, true)
+            }
 
             fn append_fits(&self, fitsfile: &mut FitsFile) -> Result<(), FitsError> {
                 let datestamp = self
@@ -245,7 +1239,7 @@ macro_rules! impl_fitswrite {
                 };
                 lineitem.write_key("COLOR_SPACE", &hdu, fitsfile)?;
 
-                for (name, value) in self.get_metadata().iter() {
+                for (name, value) in self.iter_metadata_sorted() {
                     value.write_key(name, &hdu, fitsfile)?;
                 }
                 Ok(())
@@ -270,6 +1264,18 @@ impl FitsWrite for GenericImage<'_> {
         }
     }
 
+    fn write_fits_scaled<T: AsRef<Path>>(
+        &self,
+        path: T,
+        compress: FitsCompression,
+        overwrite: bool,
+    ) -> Result<PathBuf, FitsError> {
+        match self {
+            GenericImage::Ref(image) => image.write_fits_scaled(path, compress, overwrite),
+            GenericImage::Own(image) => image.write_fits_scaled(path, compress, overwrite),
+        }
+    }
+
     fn append_fits(&self, fitsfile: &mut FitsFile) -> Result<(), FitsError> {
         match self {
             GenericImage::Ref(image) => image.append_fits(fitsfile),
@@ -288,16 +1294,32 @@ impl DynamicImageRef<'_> {
         match self {
             U8(data) => data.write_fits(path, compress, PixelType::U8),
             U16(data) => data.write_fits(path, compress, PixelType::U16),
+            I16(data) => data.write_fits(path, compress, PixelType::I16),
+            U32(data) => data.write_fits(path, compress, PixelType::U32),
             F32(data) => data.write_fits(path, compress, PixelType::F32),
         }
     }
 
+    /// Write the image data to a FITS file, quantizing [`f32`] data to 16 bits.
+    fn write_fits_scaled(
+        &self,
+        path: PathBuf,
+        compress: FitsCompression,
+    ) -> Result<(FitsHdu, FitsFile), FitsError> {
+        match self {
+            DynamicImageRef::F32(data) => data.to_owned().write_fits_scaled(path, compress),
+            _ => self.write_fits(path, compress),
+        }
+    }
+
     /// Append the image data to an existing FITS file.
     fn write_hdu(&self, fptr: &mut FitsFile) -> Result<FitsHdu, FitsError> {
         use DynamicImageRef::*;
         match self {
             U8(data) => data.write_hdu(fptr),
             U16(data) => data.write_hdu(fptr),
+            I16(data) => data.write_hdu(fptr),
+            U32(data) => data.write_hdu(fptr),
             F32(data) => data.write_hdu(fptr),
         }
     }
@@ -313,16 +1335,32 @@ impl DynamicImageOwned {
         match self {
             U8(data) => data.write_fits(path, compress),
             U16(data) => data.write_fits(path, compress),
+            I16(data) => data.write_fits(path, compress),
+            U32(data) => data.write_fits(path, compress),
             F32(data) => data.write_fits(path, compress),
         }
     }
 
+    /// Write the image data to a FITS file, quantizing [`f32`] data to 16 bits.
+    fn write_fits_scaled(
+        &self,
+        path: PathBuf,
+        compress: FitsCompression,
+    ) -> Result<(FitsHdu, FitsFile), FitsError> {
+        match self {
+            DynamicImageOwned::F32(data) => data.write_fits_scaled(path, compress),
+            _ => self.write_fits(path, compress),
+        }
+    }
+
     /// Append the image data to an existing FITS file.
     fn write_hdu(&self, fptr: &mut FitsFile) -> Result<FitsHdu, FitsError> {
         use DynamicImageOwned::*;
         match self {
             U8(data) => data.write_hdu(fptr),
             U16(data) => data.write_hdu(fptr),
+            I16(data) => data.write_hdu(fptr),
+            U32(data) => data.write_hdu(fptr),
             F32(data) => data.write_hdu(fptr),
         }
     }
@@ -345,22 +1383,7 @@ impl<T: PixelStor + WriteImage> ImageRef<'_, T> {
             },
         };
 
-        let mut fptr = FitsFile::create(path);
-
-        if compress == FitsCompression::None {
-            fptr = fptr.with_custom_primary(&desc);
-        }
-        let mut fptr = fptr.open()?;
-
-        let hdu = if compress == FitsCompression::None {
-            fptr.primary_hdu()?
-        } else {
-            let hdu = fptr.primary_hdu()?;
-            hdu.write_key(&mut fptr, "COMPRESSED_IMAGE", "T")?;
-            hdu.write_key(&mut fptr, "COMPRESSION_ALGO", compress.to_string())?;
-            fptr.create_image("IMAGE", &desc)?
-        };
-
+        let (hdu, mut fptr) = create_image_hdu(path, &compress, &desc)?;
         hdu.write_image(&mut fptr, self.as_slice())?;
         Ok((hdu, fptr))
     }
@@ -398,22 +1421,7 @@ impl<T: PixelStor + WriteImage> ImageOwned<T> {
             },
         };
 
-        let mut fptr = FitsFile::create(path);
-
-        if compress == FitsCompression::None {
-            fptr = fptr.with_custom_primary(&desc);
-        }
-        let mut fptr = fptr.open()?;
-
-        let hdu = if compress == FitsCompression::None {
-            fptr.primary_hdu()?
-        } else {
-            let hdu = fptr.primary_hdu()?;
-            hdu.write_key(&mut fptr, "COMPRESSED_IMAGE", "T")?;
-            hdu.write_key(&mut fptr, "COMPRESSION_ALGO", compress.to_string())?;
-            fptr.create_image("IMAGE", &desc)?
-        };
-
+        let (hdu, mut fptr) = create_image_hdu(path, &compress, &desc)?;
         hdu.write_image(&mut fptr, self.as_slice())?;
         Ok((hdu, fptr))
     }
@@ -435,6 +1443,53 @@ impl<T: PixelStor + WriteImage> ImageOwned<T> {
     }
 }
 
+impl ImageOwned<f32> {
+    /// Write the image data to a FITS file as 16-bit integers, with the
+    /// `BSCALE` and `BZERO` keywords to reconstruct the floating point values.
+    fn write_fits_scaled(
+        &self,
+        path: PathBuf,
+        compress: FitsCompression,
+    ) -> Result<(FitsHdu, FitsFile), FitsError> {
+        let (data, bscale, bzero) = quantize_f32(self.as_slice());
+        let img = ImageOwned::new(data, self.width(), self.height(), self.cspace.clone())
+            .map_err(|err| FitsError::Message(err.to_owned()))?;
+        let (hdu, mut fptr) = img.write_fits(path, compress)?;
+        hdu.write_key(&mut fptr, "BSCALE", (bscale, "Physical value scale factor"))?;
+        hdu.write_key(&mut fptr, "BZERO", (bzero, "Physical value offset"))?;
+        Ok((hdu, fptr))
+    }
+}
+
+/// Quantize floating point data to [`i16`], spanning the range of the finite values.
+///
+/// Returns the quantized data, and the `BSCALE` and `BZERO` values such that
+/// `value = BZERO + BSCALE * quantized`.
+fn quantize_f32(data: &[f32]) -> (Vec<i16>, f64, f64) {
+    let (min, max) = data
+        .iter()
+        .filter(|v| v.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v as f64), hi.max(v as f64))
+        });
+    let (min, max) = if min > max { (0.0, 0.0) } else { (min, max) };
+    let (bscale, bzero) = if max > min {
+        let bscale = (max - min) / u16::MAX as f64;
+        (bscale, min - i16::MIN as f64 * bscale)
+    } else {
+        (1.0, min)
+    };
+    let out = data
+        .iter()
+        .map(|&v| {
+            ((v as f64 - bzero) / bscale)
+                .round()
+                .clamp(i16::MIN as f64, i16::MAX as f64) as i16
+        })
+        .collect();
+    (out, bscale, bzero)
+}
+
 pub(crate) trait WriteKey {
     fn write_key(&self, key: &str, hdu: &FitsHdu, fptr: &mut FitsFile) -> Result<(), FitsError>;
 }
@@ -466,7 +1521,7 @@ impl WriteKey for GenericLineItem {
                 GenericValue::I64(v) => hdu.write_key(fptr, key, (*v, cmt.as_str())),
                 GenericValue::F64(v) => hdu.write_key(fptr, key, (*v, cmt.as_str())),
                 GenericValue::ColorSpace(color_space) => {
-                    hdu.write_key(fptr, key, (str_from_cspace(color_space), cmt.as_str()))
+                    hdu.write_key(fptr, key, (color_space.to_canonical_str(), cmt.as_str()))
                 }
                 GenericValue::Duration(duration) => {
                     let key_ = format!("{key}_S");
@@ -477,6 +1532,8 @@ impl WriteKey for GenericLineItem {
                     hdu.write_key(fptr, &key_, (duration.subsec_nanos(), cmt_.as_str()))?;
                     hdu.write_key(fptr, key, (duration.as_secs_f64(), cmt.as_str()))
                 }
+                // Binary blobs can not be stored in header cards.
+                GenericValue::Bytes(_) => Ok(()),
             }
         } else {
             match &self.value {
@@ -502,7 +1559,7 @@ impl WriteKey for GenericLineItem {
                 GenericValue::I64(v) => hdu.write_key(fptr, key, *v),
                 GenericValue::F64(v) => hdu.write_key(fptr, key, *v),
                 GenericValue::ColorSpace(color_space) => {
-                    hdu.write_key(fptr, key, str_from_cspace(color_space))
+                    hdu.write_key(fptr, key, color_space.to_canonical_str())
                 }
                 GenericValue::Duration(duration) => {
                     let key_ = format!("{key}_S");
@@ -511,6 +1568,8 @@ impl WriteKey for GenericLineItem {
                     hdu.write_key(fptr, &key_, (duration.subsec_nanos(), "(ns)"))?;
                     hdu.write_key(fptr, key, (duration.as_secs_f64(), "s"))
                 }
+                // Binary blobs can not be stored in header cards.
+                GenericValue::Bytes(_) => Ok(()),
             }
         }
     }
@@ -522,6 +1581,8 @@ impl From<PixelType> for ImageType {
             PixelType::U8 => ImageType::UnsignedByte,
             PixelType::U16 => ImageType::UnsignedShort,
             PixelType::F32 => ImageType::Float,
+            #[cfg(feature = "f16")]
+            PixelType::F16 => ImageType::Float, // FITS does not support half precision
             PixelType::I8 => ImageType::Byte,
             PixelType::I16 => ImageType::Short,
             PixelType::I32 => ImageType::Long,
@@ -533,19 +1594,6 @@ impl From<PixelType> for ImageType {
     }
 }
 
-fn str_from_cspace(cspace: &ColorSpace) -> String {
-    let val = match cspace {
-        ColorSpace::Gray => "GRAY",
-        ColorSpace::Rgb => "RGB",
-        ColorSpace::Bayer(BayerPattern::Bggr) => "BGGR",
-        ColorSpace::Bayer(BayerPattern::Gbrg) => "GBRG",
-        ColorSpace::Bayer(BayerPattern::Grbg) => "GRBG",
-        ColorSpace::Bayer(BayerPattern::Rggb) => "RGGB",
-        ColorSpace::Custom(ch, desc) => &format!("C({ch}, {desc})"),
-    };
-    val.to_string()
-}
-
 fn systemtime_to_utc(stime: SystemTime) -> Result<DateTime<Utc>, FitsError> {
     let timestamp = stime
         .duration_since(UNIX_EPOCH)
@@ -588,6 +1636,20 @@ mod test {
         false
     }
 
+    #[test]
+    fn test_quantize_f32() {
+        let data = [0.0f32, 0.5, 1.0, f32::NAN];
+        let (out, bscale, bzero) = super::quantize_f32(&data);
+        assert_eq!(out[0], i16::MIN);
+        assert_eq!(out[2], i16::MAX);
+        for (&q, &v) in out.iter().zip(data.iter()).take(3) {
+            assert!((bzero + bscale * q as f64 - v as f64).abs() <= bscale);
+        }
+        let (out, bscale, bzero) = super::quantize_f32(&[2.0f32; 4]);
+        assert_eq!(out, vec![0; 4]);
+        assert_eq!(bzero + bscale * out[0] as f64, 2.0);
+    }
+
     #[test]
     fn test_fitsio() {
         use crate::{FitsCompression, FitsWrite};
@@ -636,4 +1698,278 @@ mod test {
         drop(fitsfile);
         std::fs::remove_file("test_multi.fits").unwrap();
     }
+
+    #[test]
+    fn test_fitsio_scaled() {
+        use crate::{FitsCompression, FitsWrite};
+        use fitsio::FitsFile;
+        let data: Vec<f32> = (0..6).map(|v| v as f32 * 0.25 - 0.5).collect();
+        let img = crate::ImageOwned::from_owned(data.clone(), 3, 2, crate::ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let img = crate::DynamicImageOwned::from(img);
+        let img = crate::GenericImageOwned::new(std::time::SystemTime::now(), img);
+        let path = img
+            .write_fits_scaled("test_scaled.fits", FitsCompression::None, true)
+            .expect("Could not write FITS file");
+        let mut fptr = FitsFile::open(&path).expect("Could not open FITS file");
+        let hdu = fptr.primary_hdu().unwrap();
+        let bitpix: i64 = hdu.read_key(&mut fptr, "BITPIX").unwrap();
+        assert_eq!(bitpix, 16);
+        let bscale: f64 = hdu.read_key(&mut fptr, "BSCALE").unwrap();
+        let read: Vec<f32> = hdu.read_image(&mut fptr).unwrap();
+        drop(fptr);
+        std::fs::remove_file(&path).unwrap();
+        for (a, b) in read.iter().zip(data.iter()) {
+            assert!(((a - b) as f64).abs() <= bscale);
+        }
+    }
+
+    #[test]
+    fn test_fitsio_scaled_default() {
+        use crate::{FitsCompression, FitsWrite, GenericImageOwned};
+        use fitsio::{errors::Error as FitsError, FitsFile};
+        use std::path::{Path, PathBuf};
+        // An implementor that only provides the required methods
+        struct Frame(GenericImageOwned);
+        impl FitsWrite for Frame {
+            fn write_fits<T: AsRef<Path>>(
+                &self,
+                path: T,
+                compress: FitsCompression,
+                overwrite: bool,
+            ) -> Result<PathBuf, FitsError> {
+                self.0.write_fits(path, compress, overwrite)
+            }
+
+            fn append_fits(&self, fitsfile: &mut FitsFile) -> Result<(), FitsError> {
+                self.0.append_fits(fitsfile)
+            }
+        }
+        let img = crate::ImageOwned::from_owned(vec![0.5f32; 6], 3, 2, crate::ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let img = GenericImageOwned::new(std::time::SystemTime::now(), img.into());
+        let path = Frame(img)
+            .write_fits_scaled("test_scaled_default.fits", FitsCompression::None, true)
+            .expect("Could not write FITS file");
+        let mut fptr = FitsFile::open(&path).expect("Could not open FITS file");
+        let hdu = fptr.primary_hdu().unwrap();
+        let bitpix: i64 = hdu.read_key(&mut fptr, "BITPIX").unwrap();
+        drop(fptr);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(bitpix, -32);
+    }
+
+    #[test]
+    fn test_fits_image_writer() {
+        use crate::{ColorSpace, FitsCompression, FitsImageWriter, FitsRead, ImageProps};
+        let mut writer = FitsImageWriter::<u16>::create(
+            "test_writer.fits",
+            3,
+            4,
+            ColorSpace::Gray,
+            FitsCompression::None,
+            true,
+        )
+        .expect("Could not create FITS file");
+        writer
+            .write_key("CAMERA", "Mosaic".to_owned(), None)
+            .unwrap();
+        writer.write_rows(2, &[6, 7, 8, 9, 10, 11]).unwrap();
+        writer.write_rows(0, &[0, 1, 2]).unwrap();
+        writer.write_rows(1, &[3, 4, 5]).unwrap();
+        assert!(writer.write_rows(0, &[0, 1]).is_err());
+        assert!(writer.write_rows(3, &[0; 6]).is_err());
+        assert!(writer.write_rows(usize::MAX, &[0; 3]).is_err());
+        let path = writer.finish().unwrap();
+        let roi = crate::Rect {
+            x: 0,
+            y: 0,
+            width: 3,
+            height: 4,
+        };
+        let img =
+            crate::GenericImageOwned::read_fits_roi(&path, roi).expect("Could not read FITS file");
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(img.width(), 3);
+        assert_eq!(img.height(), 4);
+        assert_eq!(
+            img.as_slice_u16().unwrap(),
+            &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]
+        );
+    }
+
+    #[test]
+    fn test_write_fits_cube() {
+        use crate::{ColorSpace, FitsCompression, GenericImageOwned, ImageOwned};
+        use fitsio::{hdu::HduInfo, FitsFile};
+        use std::time::SystemTime;
+        let frame = |v: u16, cspace: ColorSpace| {
+            let ch = cspace.channel_count().unwrap() as u16;
+            let img =
+                ImageOwned::from_owned((0..6 * ch).map(|i| v + i).collect(), 3, 2, cspace).unwrap();
+            GenericImageOwned::new(SystemTime::now(), img.into())
+        };
+        let mut frames: Vec<_> = [0, 100, 200]
+            .into_iter()
+            .map(|v| frame(v, ColorSpace::Gray))
+            .collect();
+        frames[0].set_camera("ZWO ASI178MM").unwrap();
+        let path = super::write_fits_cube(&frames, "test_cube.fits", FitsCompression::None, true)
+            .expect("Could not write FITS cube");
+        let mut fptr = FitsFile::open(&path).unwrap();
+        let hdu = fptr.primary_hdu().unwrap();
+        let HduInfo::ImageInfo { shape, .. } = &hdu.info else {
+            panic!("Not an image HDU");
+        };
+        assert_eq!(shape, &[3, 2, 3]);
+        let data: Vec<u16> = hdu.read_image(&mut fptr).unwrap();
+        assert_eq!(&data[..6], frames[0].as_slice_u16().unwrap());
+        assert_eq!(&data[12..], frames[2].as_slice_u16().unwrap());
+        let camera: String = hdu.read_key(&mut fptr, "CAMERA").unwrap();
+        assert_eq!(camera, "ZWO ASI178MM");
+        drop(fptr);
+        std::fs::remove_file(&path).unwrap();
+
+        let frames = [frame(0, ColorSpace::Rgb), frame(1000, ColorSpace::Rgb)];
+        let path = super::write_fits_cube(&frames, "test_cube.fits", FitsCompression::None, true)
+            .expect("Could not write FITS cube");
+        let mut fptr = FitsFile::open(&path).unwrap();
+        let hdu = fptr.primary_hdu().unwrap();
+        let HduInfo::ImageInfo { shape, .. } = &hdu.info else {
+            panic!("Not an image HDU");
+        };
+        assert_eq!(shape, &[2, 2, 3, 3]);
+        drop(fptr);
+        std::fs::remove_file(&path).unwrap();
+
+        let mixed = [frame(0, ColorSpace::Gray), frame(0, ColorSpace::Rgb)];
+        assert!(
+            super::write_fits_cube(&mixed, "test_cube.fits", FitsCompression::None, true).is_err()
+        );
+        assert!(
+            super::write_fits_cube(&[], "test_cube.fits", FitsCompression::None, true).is_err()
+        );
+    }
+
+    #[test]
+    fn test_fitsio_read() {
+        use super::UNIX_EPOCH;
+        use crate::{FitsCompression, FitsRead, FitsWrite, GenericValue, ImageProps};
+        use std::time::{Duration, SystemTime};
+        let data: Vec<u16> = (0..36).map(|v| v * 1000).collect();
+        let img = crate::ImageOwned::from_owned(data, 4, 3, crate::ColorSpace::Rgb)
+            .expect("Failed to create ImageOwned");
+        let mut img = crate::GenericImageOwned::new(SystemTime::now(), img.into());
+        img.insert_key("CAMERA", ("Canon EOS 5D Mark IV", "Camera name"))
+            .unwrap();
+        img.insert_key("OBSERVER", "O'Brien".to_owned()).unwrap();
+        img.insert_key("LONG_KEY_NAME", -42i64).unwrap();
+        img.insert_key("TEMP", (-10.5f64, "Sensor temperature"))
+            .unwrap();
+        img.insert_key(
+            "EXPOSURE",
+            (Duration::from_micros(1_075_400), "Exposure time"),
+        )
+        .unwrap();
+        img.insert_key("DELAY", Duration::from_nanos(1_500))
+            .unwrap();
+        img.insert_key(
+            "TRIGGER",
+            UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789),
+        )
+        .unwrap();
+        for (compress, path) in [
+            (FitsCompression::None, "test_read.fits"),
+            (FitsCompression::Rice, "test_read_rice.fits"),
+        ] {
+            img.write_fits(path, compress, true)
+                .expect("Could not write FITS file");
+            let read = crate::GenericImageOwned::read_fits(path).expect("Could not read FITS file");
+            std::fs::remove_file(path).unwrap();
+            assert_eq!(read.width(), 4);
+            assert_eq!(read.height(), 3);
+            assert_eq!(read.color_space(), crate::ColorSpace::Rgb);
+            assert_eq!(read.as_slice_u16(), img.as_slice_u16());
+            assert_eq!(read.get_timestamp(), img.get_timestamp());
+            assert_eq!(read.get_metadata().len(), img.get_metadata().len());
+            for (key, item) in img.get_metadata().iter() {
+                assert_eq!(
+                    read.get_key(key).map(|v| v.get_value()),
+                    Some(item.get_value())
+                );
+            }
+            assert_eq!(
+                read.get_key("CAMERA").unwrap().get_comment(),
+                Some("Camera name")
+            );
+            assert_eq!(read.get_key("DELAY").unwrap().get_comment(), None);
+            assert!(matches!(
+                read.get_key("TRIGGER").unwrap().get_value(),
+                GenericValue::SystemTime(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_fitsio_read_roi() {
+        use crate::{BayerPattern, FitsCompression, FitsRead, FitsWrite, ImageProps};
+        let data: Vec<u16> = (0..24).collect();
+        let img = crate::ImageOwned::from_owned(data, 6, 4, BayerPattern::Rggb.into())
+            .expect("Failed to create ImageOwned");
+        let img = crate::DynamicImageOwned::from(img);
+        let img = crate::GenericImageOwned::new(std::time::SystemTime::now(), img);
+        img.write_fits("test_roi.fits", FitsCompression::None, true)
+            .expect("Could not write FITS file");
+        let rect = crate::Rect {
+            x: 1,
+            y: 2,
+            width: 3,
+            height: 4,
+        };
+        let roi = crate::GenericImageOwned::read_fits_roi("test_roi.fits", rect)
+            .expect("Could not read FITS file");
+        let empty = crate::Rect { width: 0, ..rect };
+        assert!(crate::GenericImageOwned::read_fits_roi("test_roi.fits", empty).is_err());
+        std::fs::remove_file("test_roi.fits").unwrap();
+        assert_eq!(roi.width(), 3);
+        assert_eq!(roi.height(), 2);
+        assert_eq!(roi.color_space(), BayerPattern::Grbg.into());
+        assert_eq!(roi.as_slice_u16().unwrap(), &[13, 14, 15, 19, 20, 21]);
+        assert_eq!(roi.get_timestamp(), img.get_timestamp());
+    }
+
+    #[test]
+    fn test_fitsio_table() {
+        use crate::{FitsCompression, FitsWrite, GenericValue, MetaTable};
+        let mut data = vec![1u8, 2, 3, 4, 5, 6];
+        let img = crate::ImageRef::new(&mut data, 3, 2, crate::ColorSpace::Gray)
+            .expect("Failed to create ImageRef");
+        let img = crate::GenericImageRef::new(std::time::SystemTime::now(), img.into());
+        let mut table = MetaTable::new("SOURCES");
+        table
+            .add_column("X", vec![GenericValue::F32(1.5), GenericValue::F32(2.5)])
+            .unwrap();
+        table
+            .add_column(
+                "NAME",
+                vec![
+                    GenericValue::String("A".into()),
+                    GenericValue::String("BC".into()),
+                ],
+            )
+            .unwrap();
+        assert!(table
+            .add_column("Y", vec![GenericValue::F32(1.5), GenericValue::U8(2)])
+            .is_err());
+        assert!(table.add_column("Y", vec![GenericValue::F32(1.5)]).is_err());
+        let path = img
+            .write_fits_with_table("test_table.fits", FitsCompression::None, true, &table)
+            .expect("Could not write FITS file");
+        let mut fptr = fitsio::FitsFile::open(&path).expect("Could not open FITS file");
+        let hdu = fptr.hdu("SOURCES").expect("Could not find table");
+        let col: Vec<f32> = hdu.read_col(&mut fptr, "X").unwrap();
+        assert_eq!(col, vec![1.5, 2.5]);
+        drop(fptr);
+        std::fs::remove_file(path).unwrap();
+    }
 }
@@ -0,0 +1,126 @@
+//! Focus metrics.
+
+use crate::{coreimpls::LUMA_COEFFS, ColorSpace, ImageOwned, ImageProps, PixelStor};
+
+/// The sharpness metric computed by [`ImageOwned::sharpness`].
+///
+/// All metrics are computed on the luminance of the image, and are normalized by the
+/// number of terms, so that scores of regions of different sizes can be compared.
+/// Higher scores indicate a sharper image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusMetric {
+    /// Variance of the 4-neighbor Laplacian.
+    LaplacianVariance,
+    /// Mean squared difference between pixels two columns apart.
+    BrennerGradient,
+    /// Mean squared magnitude of the Sobel gradient.
+    TenengradSobel,
+}
+
+impl<T: PixelStor> ImageOwned<T> {
+    /// Score the sharpness of the image, e.g. for autofocus.
+    ///
+    /// RGB images are reduced to luminance using the same coefficients as
+    /// [`crate::ToLuma::to_luma`] before the metric is computed.
+    ///
+    /// # Arguments
+    /// - `metric`: The sharpness metric to compute.
+    ///
+    /// # Errors
+    /// - If the image is not a grayscale or RGB image.
+    /// - If the image is smaller than 3x3 pixels.
+    pub fn sharpness(&self, metric: FocusMetric) -> Result<f64, &'static str> {
+        let (w, h) = (self.width(), self.height());
+        if w < 3 || h < 3 {
+            return Err("Image must be at least 3x3 pixels.");
+        }
+        let luma: Vec<f64> = match self.cspace {
+            ColorSpace::Gray => self
+                .as_slice()
+                .iter()
+                .map(|&v| PixelStor::to_f64(v))
+                .collect(),
+            ColorSpace::Rgb => self
+                .as_slice()
+                .chunks_exact(3)
+                .map(|px| {
+                    px.iter()
+                        .zip(LUMA_COEFFS.iter())
+                        .fold(0f64, |acc, (v, &w)| acc + PixelStor::to_f64(*v) * w)
+                })
+                .collect(),
+            ColorSpace::Bayer(_) => return Err("Image is not debayered."),
+            ColorSpace::Custom(_, _) => return Err("Custom color space not supported."),
+            ColorSpace::Cmyk => return Err("CMYK color space not supported."),
+            ColorSpace::GrayAlpha | ColorSpace::Rgba => {
+                return Err("Alpha color spaces not supported.")
+            }
+        };
+        let px = |x: usize, y: usize| luma[y * w + x];
+        let interior = || (1..h - 1).flat_map(move |y| (1..w - 1).map(move |x| (x, y)));
+        let score = match metric {
+            FocusMetric::LaplacianVariance => {
+                let n = ((w - 2) * (h - 2)) as f64;
+                let (sum, sumsq) = interior()
+                    .map(|(x, y)| {
+                        px(x - 1, y) + px(x + 1, y) + px(x, y - 1) + px(x, y + 1) - 4.0 * px(x, y)
+                    })
+                    .fold((0f64, 0f64), |(s, sq), l| (s + l, sq + l * l));
+                let mean = sum / n;
+                sumsq / n - mean * mean
+            }
+            FocusMetric::BrennerGradient => {
+                let n = ((w - 2) * h) as f64;
+                (0..h)
+                    .flat_map(|y| (0..w - 2).map(move |x| (x, y)))
+                    .map(|(x, y)| (px(x + 2, y) - px(x, y)).powi(2))
+                    .sum::<f64>()
+                    / n
+            }
+            FocusMetric::TenengradSobel => {
+                let n = ((w - 2) * (h - 2)) as f64;
+                interior()
+                    .map(|(x, y)| {
+                        let gx = px(x + 1, y - 1) + 2.0 * px(x + 1, y) + px(x + 1, y + 1)
+                            - px(x - 1, y - 1)
+                            - 2.0 * px(x - 1, y)
+                            - px(x - 1, y + 1);
+                        let gy = px(x - 1, y + 1) + 2.0 * px(x, y + 1) + px(x + 1, y + 1)
+                            - px(x - 1, y - 1)
+                            - 2.0 * px(x, y - 1)
+                            - px(x + 1, y - 1);
+                        gx * gx + gy * gy
+                    })
+                    .sum::<f64>()
+                    / n
+            }
+        };
+        Ok(score.max(0.0))
+    }
+}
+
+mod test {
+    #[test]
+    fn test_sharpness() {
+        use super::FocusMetric;
+        use crate::{ColorSpace, ImageOwned};
+        let flat = ImageOwned::from_owned(vec![100u8; 25], 5, 5, ColorSpace::Gray).unwrap();
+        let edge: Vec<u8> = (0..25).map(|i| if i % 5 < 2 { 0 } else { 200 }).collect();
+        let edge = ImageOwned::from_owned(edge, 5, 5, ColorSpace::Gray).unwrap();
+        for metric in [
+            FocusMetric::LaplacianVariance,
+            FocusMetric::BrennerGradient,
+            FocusMetric::TenengradSobel,
+        ] {
+            assert_eq!(flat.sharpness(metric).unwrap(), 0.0);
+            assert!(edge.sharpness(metric).unwrap() > 0.0);
+        }
+        // Each row has two of three column pairs straddling the edge.
+        assert_eq!(
+            edge.sharpness(FocusMetric::BrennerGradient).unwrap(),
+            200.0 * 200.0 * 2.0 / 3.0
+        );
+        let small = ImageOwned::from_owned(vec![0u8; 4], 2, 2, ColorSpace::Gray).unwrap();
+        assert!(small.sharpness(FocusMetric::BrennerGradient).is_err());
+    }
+}
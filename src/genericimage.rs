@@ -8,8 +8,8 @@ use crate::imagetraits::ImageProps;
 use crate::metadata::InsertValue;
 use crate::{genericimageowned::GenericImageOwned, genericimageref::GenericImageRef};
 use crate::{
-    BayerError, CalcOptExp, ColorSpace, Debayer, DemosaicMethod, GenericLineItem, OptimumExposure,
-    PixelType, SelectRoi, ToLuma,
+    BayerError, CalcOptExp, ColorSpace, Debayer, DemosaicMethod, ExposureOutcome, Flip,
+    GenericLineItem, OptimumExposure, PixelType, SelectRoi, ToLuma,
 };
 
 #[derive(Debug, PartialEq, Serialize)]
@@ -60,11 +60,70 @@ impl GenericImage<'_> {
         dynamic_map!(self, ref image, { image.get_timestamp() })
     }
 
+    /// Set the timestamp of the image, replacing the timestamp stored in the metadata
+    /// under [`crate::TIMESTAMP_KEY`].
+    pub fn set_timestamp(&mut self, tstamp: SystemTime) {
+        dynamic_map!(self, ref mut image, { image.set_timestamp(tstamp) })
+    }
+
     /// Get the exposure time of the image.
     pub fn get_exposure(&self) -> Option<Duration> {
         dynamic_map!(self, ref image, { image.get_exposure() })
     }
 
+    /// Set the exposure time of the image.
+    ///
+    /// The exposure is stored in the metadata under [`crate::EXPOSURE_KEY`], replacing
+    /// any existing value.
+    pub fn set_exposure(&mut self, exposure: Duration) {
+        dynamic_map!(self, ref mut image, { image.set_exposure(exposure) })
+    }
+
+    /// Get the sensor gain of the image.
+    pub fn get_gain(&self) -> Option<f64> {
+        dynamic_map!(self, ref image, { image.get_gain() })
+    }
+
+    /// Set the sensor gain of the image.
+    ///
+    /// The gain is stored in the metadata under [`crate::GAIN_KEY`], replacing any
+    /// existing value.
+    pub fn set_gain(&mut self, gain: f64) {
+        dynamic_map!(self, ref mut image, { image.set_gain(gain) })
+    }
+
+    /// Get the name of the camera that captured the image.
+    pub fn get_camera(&self) -> Option<&str> {
+        dynamic_map!(self, ref image, { image.get_camera() })
+    }
+
+    /// Set the name of the camera that captured the image.
+    ///
+    /// The name is stored in the metadata under [`crate::CAMERANAME_KEY`], replacing
+    /// any existing value.
+    ///
+    /// # Errors
+    /// - If the name is empty or longer than 4096 characters.
+    pub fn set_camera(&mut self, camera: &str) -> Result<(), &'static str> {
+        dynamic_map!(self, ref mut image, { image.set_camera(camera) })
+    }
+
+    /// Get the name of the program that generated the image.
+    pub fn get_program(&self) -> Option<&str> {
+        dynamic_map!(self, ref image, { image.get_program() })
+    }
+
+    /// Set the name of the program that generated the image.
+    ///
+    /// The name is stored in the metadata under [`crate::PROGRAMNAME_KEY`], replacing
+    /// any existing value.
+    ///
+    /// # Errors
+    /// - If the name is empty or longer than 4096 characters.
+    pub fn set_program(&mut self, program: &str) -> Result<(), &'static str> {
+        dynamic_map!(self, ref mut image, { image.set_program(program) })
+    }
+
     /// Insert a metadata value into the [`GenericImage`].
     ///
     /// # Arguments
@@ -90,6 +149,22 @@ impl GenericImage<'_> {
         dynamic_map!(self, ref mut image, { image.insert_key(name, value) })
     }
 
+    /// Insert a metadata value into the image, unless a value with the same name is
+    /// already present.
+    ///
+    /// See [`GenericImageOwned::try_insert_key`] for details.
+    ///
+    /// # Errors
+    /// - `Err("Key already exists")` if a value with the same name is present.
+    /// - Any error returned by [`GenericImage::insert_key`].
+    pub fn try_insert_key<T: InsertValue>(
+        &mut self,
+        name: &str,
+        value: T,
+    ) -> Result<(), &'static str> {
+        dynamic_map!(self, ref mut image, { image.try_insert_key(name, value) })
+    }
+
     /// Remove a metadata value from the [`GenericImageOwned`].
     ///
     /// # Arguments
@@ -139,6 +214,18 @@ impl GenericImage<'_> {
         dynamic_map!(self, ref image, { image.get_metadata() })
     }
 
+    /// Edit the metadata in bulk, restoring the timestamp afterwards if the closure
+    /// removed it. See [`GenericImageOwned::with_metadata_mut`] for details.
+    ///
+    /// # Errors
+    /// - If a key is empty or longer than 80 characters, or if two keys differ only in case.
+    pub fn with_metadata_mut<F: FnOnce(&mut HashMap<String, GenericLineItem>)>(
+        &mut self,
+        f: F,
+    ) -> Result<(), &'static str> {
+        dynamic_map!(self, ref mut image, { image.with_metadata_mut(f) })
+    }
+
     /// Get a specific metadata value by name.
     ///
     /// Returns the first metadata value with the given name.
@@ -148,6 +235,29 @@ impl GenericImage<'_> {
     pub fn get_key(&self, name: &str) -> Option<&GenericLineItem> {
         dynamic_map!(self, ref image, { image.get_key(name) })
     }
+
+    /// Increment an integer metadata value in-place, e.g. a frame sequence number.
+    ///
+    /// See [`GenericImageOwned::increment_key`] for details.
+    pub fn increment_key(&mut self, name: &str, by: i64) -> Result<i64, &'static str> {
+        dynamic_map!(self, ref mut image, { image.increment_key(name, by) })
+    }
+
+    /// Set the ICC color profile of the image.
+    ///
+    /// The profile is stored in the metadata under [`crate::ICC_PROFILE_KEY`], replacing
+    /// any existing profile, and is preserved across serialization.
+    ///
+    /// # Note
+    /// The ICC profile is not written to FITS files.
+    pub fn set_icc_profile(&mut self, profile: Vec<u8>) {
+        dynamic_map!(self, ref mut image, { image.set_icc_profile(profile) })
+    }
+
+    /// Get the ICC color profile of the image, if present.
+    pub fn icc_profile(&self) -> Option<&[u8]> {
+        dynamic_map!(self, ref image, { image.icc_profile() })
+    }
 }
 
 impl ImageProps for GenericImage<'_> {
@@ -271,6 +381,24 @@ impl<'a: 'b, 'b> GenericImage<'a> {
     }
 }
 
+impl Flip for GenericImage<'_> {
+    type Output = GenericImage<'static>;
+
+    fn flip_h(&self) -> Self::Output {
+        match self {
+            GenericImage::Ref(image) => image.flip_h().into(),
+            GenericImage::Own(image) => image.flip_h().into(),
+        }
+    }
+
+    fn flip_v(&self) -> Self::Output {
+        match self {
+            GenericImage::Ref(image) => image.flip_v().into(),
+            GenericImage::Own(image) => image.flip_v().into(),
+        }
+    }
+}
+
 impl SelectRoi for GenericImage<'_> {
     type Output = GenericImage<'static>;
 
@@ -295,19 +423,63 @@ impl CalcOptExp for GenericImage<'_> {
         exposure: Duration,
         bin: u8,
     ) -> Result<(Duration, u16), &'static str> {
+        self.eval_opt_exp(eval, exposure, bin)
+            .map(ExposureOutcome::into_inner)
+    }
+
+    fn eval_opt_exp(
+        self,
+        eval: &OptimumExposure,
+        exposure: Duration,
+        bin: u8,
+    ) -> Result<ExposureOutcome, &'static str> {
         match self {
-            GenericImage::Ref(img) => img.calc_opt_exp(eval, exposure, bin),
-            GenericImage::Own(img) => img.calc_opt_exp(eval, exposure, bin),
+            GenericImage::Ref(img) => img.eval_opt_exp(eval, exposure, bin),
+            GenericImage::Own(img) => img.eval_opt_exp(eval, exposure, bin),
         }
     }
 }
 
 impl GenericImage<'_> {
+    /// Compute the histograms of all channels of the image.
+    ///
+    /// See [`ImageOwned::histograms`](crate::ImageOwned::histograms) for details.
+    ///
+    /// # Errors
+    /// - If `bins` is zero.
+    pub fn histograms(&self, bins: usize) -> Result<Vec<Vec<u64>>, &'static str> {
+        dynamic_map!(self, ref image, { image.image.histograms(bins) })
+    }
+
+    /// Compute the minimum, maximum, mean, standard deviation and median of each
+    /// channel of the image.
+    ///
+    /// See [`ImageOwned::statistics`](crate::ImageOwned::statistics) for details.
+    pub fn statistics(&self) -> Vec<crate::ChannelStats> {
+        dynamic_map!(self, ref image, { image.image.statistics() })
+    }
+
     /// Get the data as a slice of [`u8`], regardless of the underlying type.
     pub fn as_raw_u8(&self) -> &[u8] {
         dynamic_map!(self, ref image, { image.image.as_raw_u8() })
     }
 
+    /// Get the length of the image data in bytes.
+    ///
+    /// This is [`len`](ImageProps::len) times the size of the pixel type, i.e. the
+    /// length of the slice returned by [`Self::as_raw_u8`].
+    pub fn byte_len(&self) -> usize {
+        self.len() * self.pixel_type().size_bytes()
+    }
+
+    /// Get the distance between the starts of consecutive rows of the image data in bytes.
+    ///
+    /// The rows are contiguous, hence this is `width * channels` times the size of the
+    /// pixel type.
+    pub fn row_byte_stride(&self) -> usize {
+        self.width() * self.channels() as usize * self.pixel_type().size_bytes()
+    }
+
     /// Get the data as a slice of [`u8`], regardless of the underlying type.
     pub fn as_raw_u8_checked(&self) -> Option<&[u8]> {
         dynamic_map!(self, ref image, { image.image.as_raw_u8_checked() })
@@ -8,9 +8,14 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     genericimageref::GenericImageRef,
-    metadata::{name_check, InsertValue},
-    BayerError, CalcOptExp, Debayer, DemosaicMethod, DynamicImageOwned, GenericLineItem,
-    ImageProps, OptimumExposure, SelectRoi, EXPOSURE_KEY, TIMESTAMP_KEY,
+    metadata::{
+        crop_values, edit_values, flip_values, increment_value, merge_from, merge_values,
+        name_check, InsertValue,
+    },
+    BayerError, CalcOptExp, Debayer, DemosaicMethod, Deserializer, DynamicImageOwned,
+    ExposureOutcome, Flip, GenericLineItem, ImageMap, ImageProps, MergePolicy, OptimumExposure,
+    ResizeFilter, SelectRoi, Serializer, CAMERANAME_KEY, EXPOSURE_KEY, GAIN_KEY, ICC_PROFILE_KEY,
+    PROGRAMNAME_KEY, TIMESTAMP_KEY,
 };
 
 #[allow(unused_imports)]
@@ -82,6 +87,20 @@ impl GenericImageOwned {
             .unwrap() // Safe to unwrap, as the timestamp key is always inserted
     }
 
+    /// Set the timestamp of the image, e.g. to correct the capture time after the fact.
+    ///
+    /// The timestamp stored in the metadata under [`TIMESTAMP_KEY`] is replaced, which
+    /// [`Self::insert_key`] and [`Self::remove_key`] do not allow.
+    pub fn set_timestamp(&mut self, tstamp: SystemTime) {
+        self.metadata.insert(
+            TIMESTAMP_KEY.to_string(),
+            GenericLineItem {
+                value: tstamp.into(),
+                comment: Some("Timestamp of the image".to_owned()),
+            },
+        );
+    }
+
     /// Get the exposure time of the image.
     pub fn get_exposure(&self) -> Option<Duration> {
         self.metadata
@@ -89,6 +108,76 @@ impl GenericImageOwned {
             .and_then(|x| x.get_value().clone().try_into().ok())
     }
 
+    /// Set the exposure time of the image.
+    ///
+    /// The exposure is stored in the metadata under [`EXPOSURE_KEY`], replacing any
+    /// existing value.
+    pub fn set_exposure(&mut self, exposure: Duration) {
+        self.metadata.insert(
+            EXPOSURE_KEY.to_string(),
+            GenericLineItem {
+                value: exposure.into(),
+                comment: Some("Exposure time".to_owned()),
+            },
+        );
+    }
+
+    /// Get the sensor gain of the image.
+    pub fn get_gain(&self) -> Option<f64> {
+        self.metadata
+            .get(GAIN_KEY)
+            .and_then(|x| x.get_value().get_value_f64())
+    }
+
+    /// Set the sensor gain of the image.
+    ///
+    /// The gain is stored in the metadata under [`GAIN_KEY`], replacing any existing value.
+    pub fn set_gain(&mut self, gain: f64) {
+        self.metadata.insert(
+            GAIN_KEY.to_string(),
+            GenericLineItem {
+                value: gain.into(),
+                comment: Some("Sensor gain".to_owned()),
+            },
+        );
+    }
+
+    /// Get the name of the camera that captured the image.
+    pub fn get_camera(&self) -> Option<&str> {
+        self.metadata
+            .get(CAMERANAME_KEY)
+            .and_then(|x| x.get_value().get_value_string())
+    }
+
+    /// Set the name of the camera that captured the image.
+    ///
+    /// The name is stored in the metadata under [`CAMERANAME_KEY`], replacing any
+    /// existing value.
+    ///
+    /// # Errors
+    /// - If the name is empty or longer than 4096 characters.
+    pub fn set_camera(&mut self, camera: &str) -> Result<(), &'static str> {
+        self.insert_key(CAMERANAME_KEY, (camera, "Camera name"))
+    }
+
+    /// Get the name of the program that generated the image.
+    pub fn get_program(&self) -> Option<&str> {
+        self.metadata
+            .get(PROGRAMNAME_KEY)
+            .and_then(|x| x.get_value().get_value_string())
+    }
+
+    /// Set the name of the program that generated the image.
+    ///
+    /// The name is stored in the metadata under [`PROGRAMNAME_KEY`], replacing any
+    /// existing value.
+    ///
+    /// # Errors
+    /// - If the name is empty or longer than 4096 characters.
+    pub fn set_program(&mut self, program: &str) -> Result<(), &'static str> {
+        self.insert_key(PROGRAMNAME_KEY, (program, "Program name"))
+    }
+
     /// Insert a metadata value into the [`GenericImageOwned`].
     ///
     /// # Arguments
@@ -117,6 +206,31 @@ impl GenericImageOwned {
         T::insert_key_go(self, name, value)
     }
 
+    /// Insert a metadata value into the [`GenericImageOwned`], unless a value with the same
+    /// name is already present.
+    ///
+    /// Unlike [`Self::insert_key`], an existing value is never overwritten. Since keys
+    /// are case-insensitive, e.g. `"camera"` and `"CAMERA"` name the same value.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the metadata value. The name must be non-empty and less than 80 characters.
+    /// - `value`: The value to insert. See [`Self::insert_key`] for the valid types.
+    ///
+    /// # Errors
+    /// - `Err("Key already exists")` if a value with the same name is present, including
+    ///   the timestamp key.
+    /// - Any error returned by [`Self::insert_key`].
+    pub fn try_insert_key<T: InsertValue>(
+        &mut self,
+        name: &str,
+        value: T,
+    ) -> Result<(), &'static str> {
+        if self.metadata.contains_key(&name.to_uppercase()) {
+            return Err("Key already exists");
+        }
+        self.insert_key(name, value)
+    }
+
     /// Remove a metadata value from the [`GenericImageOwned`].
     ///
     /// # Arguments
@@ -176,6 +290,45 @@ impl GenericImageOwned {
         &self.metadata
     }
 
+    /// Edit the metadata in bulk, e.g. to rename or strip a set of keys.
+    ///
+    /// The closure gets mutable access to the metadata map. If the closure removes the
+    /// timestamp stored under [`TIMESTAMP_KEY`], or replaces it with a value that is not
+    /// a timestamp, the previous timestamp is restored afterwards. Keys are converted to
+    /// uppercase afterwards, as in [`Self::insert_key`].
+    ///
+    /// # Arguments
+    /// - `f`: The closure to apply to the metadata.
+    ///
+    /// # Errors
+    /// - If a key is empty or longer than 80 characters.
+    /// - If two keys differ only in case.
+    ///
+    /// The metadata is not modified on error.
+    pub fn with_metadata_mut<F: FnOnce(&mut HashMap<String, GenericLineItem>)>(
+        &mut self,
+        f: F,
+    ) -> Result<(), &'static str> {
+        edit_values(&mut self.metadata, f)
+    }
+
+    /// Iterate over the contained metadata in ascending order of the keys.
+    ///
+    /// Unlike iterating over [`Self::get_metadata`], the order is the same across
+    /// runs, e.g. for reproducible FITS output.
+    ///
+    /// # Returns
+    /// An iterator over the names and values of the metadata.
+    pub fn iter_metadata_sorted(&self) -> impl Iterator<Item = (&str, &GenericLineItem)> {
+        let mut items: Vec<_> = self
+            .metadata
+            .iter()
+            .map(|(name, item)| (name.as_str(), item))
+            .collect();
+        items.sort_unstable_by_key(|&(name, _)| name);
+        items.into_iter()
+    }
+
     /// Get a specific metadata value by name.
     ///
     /// Returns the first metadata value with the given name.
@@ -187,6 +340,123 @@ impl GenericImageOwned {
         self.metadata.get(name)
     }
 
+    /// Increment an integer metadata value in-place, e.g. a frame sequence number.
+    ///
+    /// The stored type and comment of the value are preserved.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the metadata value.
+    /// - `by`: The amount to add to the value (may be negative).
+    ///
+    /// # Returns
+    /// The new value.
+    ///
+    /// # Errors
+    /// - If the key is not found.
+    /// - If the value is not an integer.
+    /// - If the result overflows the stored type, or does not fit in an [`i64`].
+    pub fn increment_key(&mut self, name: &str, by: i64) -> Result<i64, &'static str> {
+        increment_value(&mut self.metadata, name, by)
+    }
+
+    /// Serialize only the metadata of the image, without the pixel data.
+    ///
+    /// This is useful to write a small header sidecar file, which can be read back
+    /// with [`GenericImageOwned::merge_metadata`].
+    ///
+    /// # Arguments
+    /// - `serializer`: The serializer to use, e.g. from `bincode` or `serde_json`.
+    pub fn serialize_metadata<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.metadata.serialize(serializer)
+    }
+
+    /// Deserialize metadata written by [`GenericImageOwned::serialize_metadata`], and merge
+    /// it into the metadata of the image.
+    ///
+    /// Values with the same key as an existing value replace the existing value.
+    ///
+    /// # Arguments
+    /// - `deserializer`: The deserializer to use, e.g. from `bincode` or `serde_json`.
+    ///
+    /// # Errors
+    /// - If the metadata could not be deserialized.
+    /// - If any key is invalid, or the timestamp key does not hold a timestamp.
+    ///   In this case, the metadata of the image is left unchanged.
+    pub fn merge_metadata<'de, D: Deserializer<'de>>(
+        &mut self,
+        deserializer: D,
+    ) -> Result<(), D::Error> {
+        let incoming = HashMap::<String, GenericLineItem>::deserialize(deserializer)?;
+        merge_values(&mut self.metadata, incoming).map_err(serde::de::Error::custom)
+    }
+
+    /// Deserialize an image written by the unversioned format of earlier releases.
+    ///
+    /// See [`DynamicImageOwned::deserialize_v0`] for details.
+    ///
+    /// # Arguments
+    /// - `deserializer`: The deserializer to use, e.g. from `bincode` or `serde_json`.
+    ///
+    /// # Errors
+    /// - If the data is not an unversioned serialized image, or the image is invalid.
+    pub fn deserialize_v0<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct GenericImageOwnedV0 {
+            metadata: HashMap<String, GenericLineItem>,
+            #[serde(deserialize_with = "DynamicImageOwned::deserialize_v0")]
+            image: DynamicImageOwned,
+        }
+        let GenericImageOwnedV0 { metadata, image } =
+            GenericImageOwnedV0::deserialize(deserializer)?;
+        Ok(Self { metadata, image })
+    }
+
+    /// Merge the metadata of another image into the metadata of this image,
+    /// e.g. to assemble the header of a processed frame from its calibration frames.
+    ///
+    /// Keys that are only present in `other` are inserted. Keys present in both images
+    /// with different values are resolved according to `policy`. The timestamp of this
+    /// image is never replaced.
+    ///
+    /// # Arguments
+    /// - `other`: The image to merge the metadata from.
+    /// - `policy`: The [`MergePolicy`] to resolve key collisions.
+    ///
+    /// # Errors
+    /// - If `policy` is [`MergePolicy::Error`] and any key collides, the sorted list of
+    ///   colliding keys is returned, and the metadata of the image is left unchanged.
+    pub fn merge_metadata_from(
+        &mut self,
+        other: &GenericImageOwned,
+        policy: MergePolicy,
+    ) -> Result<(), Vec<String>> {
+        merge_from(&mut self.metadata, &other.metadata, policy)
+    }
+
+    /// Set the ICC color profile of the image.
+    ///
+    /// The profile is stored in the metadata under [`ICC_PROFILE_KEY`], replacing any
+    /// existing profile, and is preserved across serialization.
+    ///
+    /// # Note
+    /// The ICC profile is not written to FITS files.
+    pub fn set_icc_profile(&mut self, profile: Vec<u8>) {
+        self.metadata.insert(
+            ICC_PROFILE_KEY.to_string(),
+            GenericLineItem {
+                value: profile.into(),
+                comment: Some("ICC color profile".to_owned()),
+            },
+        );
+    }
+
+    /// Get the ICC color profile of the image, if present.
+    pub fn icc_profile(&self) -> Option<&[u8]> {
+        self.metadata
+            .get(ICC_PROFILE_KEY)
+            .and_then(|x| x.get_value().get_value_bytes())
+    }
+
     /// Convert the image to a [`GenericImageOwned`] with [`u8`] pixel type.
     ///
     /// Note: This operation is parallelized if the `rayon` feature is enabled.
@@ -197,6 +467,86 @@ impl GenericImageOwned {
             image: img,
         }
     }
+
+    #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+    #[cfg(feature = "rayon")]
+    /// Convert the image to a [`GenericImageOwned`] with [`u8`] pixel type, running the
+    /// conversion inside the supplied thread pool.
+    ///
+    /// See [`Self::into_u8`] for details.
+    pub fn into_u8_in_pool(self, pool: &rayon::ThreadPool) -> GenericImageOwned {
+        pool.install(|| self.into_u8())
+    }
+
+    /// Convert the image to a [`GenericImageOwned`] with [`u8`] pixel type, stretching
+    /// the contrast between the given percentiles of each channel.
+    ///
+    /// The metadata is copied to the output. See
+    /// [`ImageOwned::into_u8_stretched`](crate::ImageOwned::into_u8_stretched) for details.
+    ///
+    /// # Errors
+    /// - If `low_pct` or `high_pct` is not in `[0, 1]`, or `low_pct` is not less than `high_pct`.
+    pub fn into_u8_stretched(
+        &self,
+        low_pct: f32,
+        high_pct: f32,
+    ) -> Result<GenericImageOwned, &'static str> {
+        Ok(GenericImageOwned {
+            metadata: self.metadata.clone(),
+            image: self.image.into_u8_stretched(low_pct, high_pct)?,
+        })
+    }
+
+    /// Create a [`u8`] thumbnail of the image that fits in a `max_w` by `max_h` box.
+    ///
+    /// The image is scaled down preserving its aspect ratio, and is never scaled up.
+    /// Bayer mosaic images are debayered ([`DemosaicMethod::Nearest`]) before they are
+    /// resized with [`ResizeFilter::Nearest`]. The metadata is copied to the output
+    /// unchanged, i.e. coordinate metadata refers to the original image.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    ///
+    /// # Arguments
+    /// - `max_w`: The maximum width of the thumbnail.
+    /// - `max_h`: The maximum height of the thumbnail.
+    ///
+    /// # Errors
+    /// - If `max_w` or `max_h` is zero.
+    /// - If the Bayer mosaic image can not be debayered.
+    pub fn thumbnail(&self, max_w: u32, max_h: u32) -> Result<GenericImageOwned, &'static str> {
+        if max_w == 0 || max_h == 0 {
+            return Err("Thumbnail size must be non-zero.");
+        }
+        let debayered;
+        let image = if self.image.color_space().is_bayer() {
+            debayered = self
+                .image
+                .debayer(DemosaicMethod::Nearest)
+                .map_err(|_| "Could not debayer image.")?;
+            &debayered
+        } else {
+            &self.image
+        };
+        let (width, height) = (image.width(), image.height());
+        let scale = (max_w as f64 / width as f64)
+            .min(max_h as f64 / height as f64)
+            .min(1.0);
+        let image = if scale < 1.0 {
+            let new_w = ((width as f64 * scale).round() as usize).clamp(1, max_w as usize);
+            let new_h = ((height as f64 * scale).round() as usize).clamp(1, max_h as usize);
+            image.resize(
+                NonZeroUsize::new(new_w).unwrap(),
+                NonZeroUsize::new(new_h).unwrap(),
+                ResizeFilter::Nearest,
+            )?
+        } else {
+            image.clone()
+        };
+        Ok(GenericImageOwned {
+            metadata: self.metadata.clone(),
+            image: image.into_u8(),
+        })
+    }
 }
 
 impl Debayer for GenericImageOwned {
@@ -211,6 +561,28 @@ impl Debayer for GenericImageOwned {
     }
 }
 
+impl Flip for GenericImageOwned {
+    type Output = GenericImageOwned;
+
+    fn flip_h(&self) -> Self::Output {
+        let mut meta = self.metadata.clone();
+        flip_values(&mut meta, self.width(), true);
+        GenericImageOwned {
+            metadata: meta,
+            image: self.image.flip_h(),
+        }
+    }
+
+    fn flip_v(&self) -> Self::Output {
+        let mut meta = self.metadata.clone();
+        flip_values(&mut meta, self.height(), false);
+        GenericImageOwned {
+            metadata: meta,
+            image: self.image.flip_v(),
+        }
+    }
+}
+
 impl SelectRoi for GenericImageOwned {
     type Output = GenericImageOwned;
 
@@ -222,7 +594,8 @@ impl SelectRoi for GenericImageOwned {
         height: NonZeroUsize,
     ) -> Result<Self::Output, &'static str> {
         let img = self.image.select_roi(x, y, width, height)?;
-        let meta = self.metadata.clone();
+        let mut meta = self.metadata.clone();
+        crop_values(&mut meta, x, y);
         Ok(Self::Output {
             metadata: meta,
             image: img,
@@ -288,6 +661,21 @@ impl GenericImageOwned {
             image: img,
         })
     }
+
+    /// Apply a pixel type generic transformation to the image data.
+    ///
+    /// This function copies the metadata of the current image, and replaces the underlying
+    /// image data with the result of the transformation. Unlike [`GenericImageOwned::operate`],
+    /// the transformation operates on the underlying [`crate::ImageOwned`] directly.
+    ///
+    /// # Arguments
+    /// - `f`: The transformation to apply, see [`ImageMap`].
+    pub fn map_image<F: ImageMap>(&self, f: F) -> Result<Self, &'static str> {
+        Ok(GenericImageOwned {
+            metadata: self.metadata.clone(),
+            image: self.image.map_image(f)?,
+        })
+    }
 }
 
 impl<'a> From<GenericImageRef<'a>> for GenericImageOwned {
@@ -301,14 +689,26 @@ impl<'a> From<GenericImageRef<'a>> for GenericImageOwned {
 
 impl CalcOptExp for GenericImageOwned {
     fn calc_opt_exp(
-        mut self,
+        self,
         eval: &OptimumExposure,
         exposure: Duration,
         bin: u8,
     ) -> Result<(Duration, u16), &'static str> {
+        self.eval_opt_exp(eval, exposure, bin)
+            .map(ExposureOutcome::into_inner)
+    }
+
+    fn eval_opt_exp(
+        mut self,
+        eval: &OptimumExposure,
+        exposure: Duration,
+        bin: u8,
+    ) -> Result<ExposureOutcome, &'static str> {
         match &mut self.image {
-            DynamicImageOwned::U8(img) => {let len = img.len(); eval.calculate(img.as_mut_slice(), len, exposure, bin)},
-            DynamicImageOwned::U16(img) => {let len = img.len(); eval.calculate(img.as_mut_slice(), len, exposure, bin)},
+            DynamicImageOwned::U8(img) => {let len = img.len(); eval.evaluate(img.as_mut_slice(), len, exposure, bin)},
+            DynamicImageOwned::U16(img) => {let len = img.len(); eval.evaluate(img.as_mut_slice(), len, exposure, bin)},
+            DynamicImageOwned::I16(img) => {let len = img.len(); eval.evaluate(img.as_mut_slice(), len, exposure, bin)},
+            DynamicImageOwned::U32(img) => {let len = img.len(); eval.evaluate(img.as_mut_slice(), len, exposure, bin)},
             DynamicImageOwned::F32(_) => Err("Floating point images are not supported for this operation, since Ord is not implemented for floating point types."),
         }
     }
@@ -357,6 +757,88 @@ impl GenericImageOwned {
 }
 
 mod test {
+    #[test]
+    fn test_try_insert_key() {
+        use crate::{GenericImage, GenericImageRef, ImageOwned, ImageRef, TIMESTAMP_KEY};
+        use std::time::SystemTime;
+        let img = ImageOwned::from_owned(vec![0u8; 4], 2, 2, crate::ColorSpace::Gray).unwrap();
+        let mut img = crate::GenericImageOwned::new(SystemTime::now(), img.into());
+        img.try_insert_key("camera", "ZWO ASI178MM").unwrap();
+        assert_eq!(
+            img.try_insert_key("CAMERA", "ZWO ASI294MC"),
+            Err("Key already exists")
+        );
+        assert_eq!(
+            img.try_insert_key(TIMESTAMP_KEY, SystemTime::UNIX_EPOCH),
+            Err("Key already exists")
+        );
+        assert_eq!(
+            img.get_key("CAMERA")
+                .unwrap()
+                .get_value()
+                .get_value_string(),
+            Some("ZWO ASI178MM")
+        );
+        // Overwriting is still possible with insert_key
+        img.insert_key("CAMERA", "ZWO ASI294MC").unwrap();
+
+        let mut data = vec![0u8; 4];
+        let img = ImageRef::new(&mut data, 2, 2, crate::ColorSpace::Gray).unwrap();
+        let mut img = GenericImageRef::new(SystemTime::now(), img.into());
+        img.try_insert_key("CAMERA", "ZWO ASI178MM").unwrap();
+        assert_eq!(img.try_insert_key("camera", 1u8), Err("Key already exists"));
+        let mut img = GenericImage::from(img);
+        assert_eq!(img.try_insert_key("Camera", 1u8), Err("Key already exists"));
+        img.try_insert_key("GAIN", 100u16).unwrap();
+    }
+
+    #[test]
+    fn test_set_timestamp() {
+        use crate::{GenericImage, GenericImageRef, ImageOwned, ImageRef, TIMESTAMP_KEY};
+        use std::time::{Duration, SystemTime};
+        let img = ImageOwned::from_owned(vec![0u8; 4], 2, 2, crate::ColorSpace::Gray).unwrap();
+        let mut img = crate::GenericImageOwned::new(SystemTime::UNIX_EPOCH, img.into());
+        let tstamp = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        img.set_timestamp(tstamp);
+        assert_eq!(img.get_timestamp(), tstamp);
+        let line = img.get_key(TIMESTAMP_KEY).unwrap();
+        assert_eq!(line.get_comment(), Some("Timestamp of the image"));
+
+        let mut data = vec![0u8; 4];
+        let img = ImageRef::new(&mut data, 2, 2, crate::ColorSpace::Gray).unwrap();
+        let mut img = GenericImageRef::new(SystemTime::UNIX_EPOCH, img.into());
+        img.set_timestamp(tstamp);
+        assert_eq!(img.get_timestamp(), tstamp);
+        let mut img = GenericImage::from(img);
+        img.set_timestamp(SystemTime::UNIX_EPOCH);
+        assert_eq!(img.get_timestamp(), SystemTime::UNIX_EPOCH);
+    }
+
+    #[test]
+    fn test_map_image() {
+        use crate::{
+            DynamicImageOwned, Enlargeable, ImageMap, ImageOwned, ImageProps, PixelStor, PixelType,
+        };
+        struct ToU8;
+        impl ImageMap for ToU8 {
+            fn map<T>(&mut self, img: &ImageOwned<T>) -> Result<DynamicImageOwned, &'static str>
+            where
+                T: PixelStor + Enlargeable,
+                DynamicImageOwned: From<ImageOwned<T>>,
+            {
+                Ok(img.cast_u8().into())
+            }
+        }
+        let img = ImageOwned::from_owned(vec![0u16, 65535], 2, 1, crate::ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let mut img = crate::GenericImageOwned::new(std::time::SystemTime::now(), img.into());
+        img.insert_key("CAMERA", "Test").unwrap();
+        let out = img.map_image(ToU8).unwrap();
+        assert_eq!(out.pixel_type(), PixelType::U8);
+        assert_eq!(out.get_image().as_slice_u8(), Some(&[0u8, 255][..]));
+        assert_eq!(out.get_metadata(), img.get_metadata());
+    }
+
     #[test]
     fn test_optimum_exposure() {
         use crate::CalcOptExp;
@@ -374,4 +856,247 @@ mod test {
         let res = img.calc_opt_exp(&opt_exp, exp, bin).unwrap();
         assert_eq!(res, (exp, bin as u16));
     }
+
+    #[test]
+    fn test_typed_setters() {
+        let img = crate::ImageOwned::from_owned(vec![0u8, 1, 2, 3], 2, 2, crate::ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let mut img = crate::GenericImageOwned::new(std::time::SystemTime::now(), img.into());
+        assert_eq!(img.get_exposure(), None);
+        img.set_exposure(std::time::Duration::from_millis(250));
+        img.set_gain(1.5);
+        img.set_camera("ZWO ASI178MM").unwrap();
+        img.set_program("refimage").unwrap();
+        assert!(img.set_camera("").is_err());
+        let serialized = bincode::serialize(&img).unwrap();
+        let img: crate::GenericImageOwned = bincode::deserialize(&serialized).unwrap();
+        assert_eq!(
+            img.get_exposure(),
+            Some(std::time::Duration::from_millis(250))
+        );
+        assert_eq!(img.get_gain(), Some(1.5));
+        assert_eq!(img.get_camera(), Some("ZWO ASI178MM"));
+        assert_eq!(img.get_program(), Some("refimage"));
+    }
+
+    #[test]
+    fn test_metadata_sidecar() {
+        let img = crate::ImageOwned::from_owned(vec![0u8, 1, 2, 3], 2, 2, crate::ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let mut src = crate::GenericImageOwned::new(std::time::SystemTime::now(), img.into());
+        src.set_camera("ZWO ASI178MM").unwrap();
+        src.set_gain(2.0);
+        let mut sidecar = Vec::new();
+        src.serialize_metadata(&mut serde_json::Serializer::new(&mut sidecar))
+            .unwrap();
+        let img = crate::ImageOwned::from_owned(vec![4u8, 5, 6, 7], 2, 2, crate::ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let mut dst = crate::GenericImageOwned::new(std::time::SystemTime::UNIX_EPOCH, img.into());
+        dst.set_gain(1.0);
+        dst.insert_key("SEQ", 3u32).unwrap();
+        dst.merge_metadata(&mut serde_json::Deserializer::from_slice(&sidecar))
+            .unwrap();
+        assert_eq!(dst.get_camera(), Some("ZWO ASI178MM"));
+        assert_eq!(dst.get_gain(), Some(2.0));
+        assert_eq!(dst.get_timestamp(), src.get_timestamp());
+        assert!(dst.get_key("SEQ").is_some());
+        let bad = br#"{"TIMESTAMP": {"value": {"U8": 1}, "comment": null}}"#;
+        assert!(dst
+            .merge_metadata(&mut serde_json::Deserializer::from_slice(bad))
+            .is_err());
+        assert_eq!(dst.get_timestamp(), src.get_timestamp());
+    }
+
+    #[test]
+    fn test_deserialize_v0() {
+        use bincode::Options;
+        use std::time::{Duration, SystemTime};
+        // A 2x1 grayscale u8 image with a timestamp of one second after the epoch,
+        // written by the unversioned serializer of earlier releases
+        let blob: [u8; 97] = [
+            1, 0, 0, 0, 0, 0, 0, 0, 9, 0, 0, 0, 0, 0, 0, 0, 84, 73, 77, 69, 83, 84, 65, 77, 80, 12,
+            0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 22, 0, 0, 0, 0, 0, 0, 0, 84, 105, 109,
+            101, 115, 116, 97, 109, 112, 32, 111, 102, 32, 116, 104, 101, 32, 105, 109, 97, 103,
+            101, 2, 0, 1, 0, 1, 0, 0, 0, 0, 8, 0, 2, 0, 0, 0, 0, 0, 0, 0, 7, 8, 10, 12, 67, 0,
+        ];
+        assert!(bincode::deserialize::<crate::GenericImageOwned>(&blob).is_err());
+        let mut de =
+            bincode::Deserializer::from_slice(&blob, bincode::options().with_fixint_encoding());
+        let img = crate::GenericImageOwned::deserialize_v0(&mut de).unwrap();
+        assert_eq!(
+            img.get_timestamp(),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1)
+        );
+        assert_eq!(img.get_image().as_slice_u8(), Some([7u8, 8].as_slice()));
+    }
+
+    #[test]
+    fn test_merge_metadata_from() {
+        use crate::MergePolicy;
+        let new = |tstamp, gain| {
+            let img =
+                crate::ImageOwned::from_owned(vec![0u8, 1, 2, 3], 2, 2, crate::ColorSpace::Gray)
+                    .expect("Failed to create ImageOwned");
+            let mut img = crate::GenericImageOwned::new(tstamp, img.into());
+            img.set_camera("ZWO ASI178MM").unwrap();
+            img.set_gain(gain);
+            img
+        };
+        let light = std::time::SystemTime::now();
+        let mut dark = new(std::time::SystemTime::UNIX_EPOCH, 2.0);
+        dark.insert_key("DARKTEMP", -10.0f32).unwrap();
+        let mut img = new(light, 1.0);
+        assert_eq!(
+            img.merge_metadata_from(&dark, MergePolicy::Error),
+            Err(vec!["GAIN".to_owned()])
+        );
+        assert!(img.get_key("DARKTEMP").is_none());
+        img.merge_metadata_from(&dark, MergePolicy::KeepSelf)
+            .unwrap();
+        assert_eq!(img.get_gain(), Some(1.0));
+        assert_eq!(img.get_timestamp(), light);
+        assert!(img.get_key("DARKTEMP").is_some());
+        img.merge_metadata_from(&dark, MergePolicy::TakeOther)
+            .unwrap();
+        assert_eq!(img.get_gain(), Some(2.0));
+        assert_eq!(img.get_timestamp(), light);
+    }
+
+    #[test]
+    fn test_select_roi_metadata() {
+        use crate::{SelectRoi, SUBFRAME_X_KEY, SUBFRAME_Y_KEY};
+        use std::num::NonZeroUsize;
+        let img = crate::ImageOwned::from_owned(vec![0u8; 64], 8, 8, crate::ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let mut img = crate::GenericImageOwned::new(std::time::SystemTime::now(), img.into());
+        img.insert_key("CRPIX1", 4.5f64).unwrap();
+        img.insert_key("CRPIX2", 3i32).unwrap();
+        let four = NonZeroUsize::new(4).unwrap();
+        let roi = img.select_roi(2, 1, four, four).unwrap();
+        let roi = roi.select_roi(1, 2, four, four).unwrap();
+        let get = |key| roi.get_key(key).unwrap().get_value().get_value_u64();
+        assert_eq!(get(SUBFRAME_X_KEY), Some(3));
+        assert_eq!(get(SUBFRAME_Y_KEY), Some(3));
+        let get = |key| roi.get_key(key).unwrap().get_value().get_value_f64();
+        assert_eq!(get("CRPIX1"), Some(1.5));
+        assert_eq!(get("CRPIX2"), Some(0.0));
+    }
+
+    #[test]
+    fn test_flip_metadata() {
+        use crate::Flip;
+        let img =
+            crate::ImageOwned::from_owned(vec![0u8, 1, 2, 3, 4, 5], 3, 2, crate::ColorSpace::Gray)
+                .expect("Failed to create ImageOwned");
+        let mut img = crate::GenericImageOwned::new(std::time::SystemTime::now(), img.into());
+        img.insert_key("CRPIX1", 1.5f64).unwrap();
+        img.insert_key("CRPIX2", 1i32).unwrap();
+        img.insert_key("CDELT1", 0.25f64).unwrap();
+        let flipped = img.flip_h();
+        assert_eq!(flipped.as_slice_u8(), Some(&[2u8, 1, 0, 5, 4, 3][..]));
+        let get = |key| flipped.get_key(key).unwrap().get_value().get_value_f64();
+        assert_eq!(get("CRPIX1"), Some(2.5));
+        assert_eq!(get("CDELT1"), Some(-0.25));
+        assert_eq!(
+            flipped
+                .get_key("CRPIX2")
+                .unwrap()
+                .get_value()
+                .get_value_i32(),
+            Some(1)
+        );
+        let flipped = img.flip_v();
+        assert_eq!(flipped.as_slice_u8(), Some(&[3u8, 4, 5, 0, 1, 2][..]));
+        let get = |key| flipped.get_key(key).unwrap().get_value().get_value_f64();
+        assert_eq!(get("CRPIX1"), Some(1.5));
+        assert_eq!(get("CRPIX2"), Some(2.0));
+    }
+
+    #[test]
+    fn test_icc_profile() {
+        let mut data = vec![0u8, 1, 2, 3, 4, 5];
+        let img = crate::ImageRef::new(data.as_mut_slice(), 3, 2, crate::ColorSpace::Gray)
+            .expect("Failed to create ImageRef");
+        let mut img = crate::GenericImageRef::new(std::time::SystemTime::now(), img.into());
+        assert_eq!(img.icc_profile(), None);
+        img.set_icc_profile(vec![0xde, 0xad, 0xbe, 0xef]);
+        let serialized = bincode::serialize(&img).unwrap();
+        let img: crate::GenericImageOwned = bincode::deserialize(&serialized).unwrap();
+        assert_eq!(img.icc_profile(), Some(&[0xde, 0xad, 0xbe, 0xef][..]));
+    }
+
+    #[test]
+    fn test_increment_key() {
+        let img = crate::ImageOwned::from_owned(vec![0u8, 1, 2, 3], 2, 2, crate::ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let mut img = crate::GenericImageOwned::new(std::time::SystemTime::now(), img.into());
+        img.insert_key("SEQ", (254u8, "Sequence number")).unwrap();
+        assert_eq!(img.increment_key("seq", 1), Ok(255));
+        assert!(img.increment_key("SEQ", 1).is_err());
+        let line = img.get_key("SEQ").unwrap();
+        assert_eq!(line.get_value(), &crate::GenericValue::U8(255));
+        assert_eq!(line.get_comment(), Some("Sequence number"));
+        assert_eq!(img.increment_key("SEQ", -5), Ok(250));
+        img.insert_key("NAME", "frame").unwrap();
+        assert!(img.increment_key("NAME", 1).is_err());
+        assert!(img.increment_key("MISSING", 1).is_err());
+    }
+
+    #[test]
+    fn test_into_u8_stretched() {
+        let mut data: Vec<u16> = (0..100).collect();
+        data[99] = u16::MAX;
+        let img = crate::ImageOwned::from_owned(data, 10, 10, crate::ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let mut img = crate::GenericImageOwned::new(std::time::SystemTime::now(), img.into());
+        img.insert_key("CAMERA", "Test").unwrap();
+        let out = img.into_u8_stretched(0.0, 0.9).unwrap();
+        let px = out.as_slice_u8().unwrap();
+        assert_eq!(
+            (px[0], px[44], px[89], px[90], px[99]),
+            (0, 126, 255, 255, 255)
+        );
+        assert!(out.get_key("CAMERA").is_some());
+        assert!(img.into_u8_stretched(0.5, 0.5).is_err());
+        assert!(img.into_u8_stretched(-0.1, 0.5).is_err());
+        assert!(img.into_u8_stretched(0.1, 1.5).is_err());
+    }
+
+    #[test]
+    fn test_iter_metadata_sorted() {
+        let img = crate::ImageOwned::from_owned(vec![0u8; 4], 2, 2, crate::ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let mut img = crate::GenericImageOwned::new(std::time::SystemTime::now(), img.into());
+        for name in ["ZETA", "ALPHA", "MID", "BETA"] {
+            img.insert_key(name, 1u8).unwrap();
+        }
+        let names: Vec<_> = img.iter_metadata_sorted().map(|(name, _)| name).collect();
+        assert_eq!(names.len(), img.get_metadata().len());
+        assert!(names.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(names.iter().position(|&n| n == "ALPHA"), Some(0));
+    }
+
+    #[test]
+    fn test_thumbnail() {
+        use crate::{BayerPattern, ColorSpace, ImageOwned, ImageProps, PixelType};
+        use std::time::SystemTime;
+        let img = ImageOwned::from_owned(vec![1000u16; 64 * 32], 64, 32, ColorSpace::Gray).unwrap();
+        let mut img = crate::GenericImageOwned::new(SystemTime::now(), img.into());
+        img.set_gain(2.0);
+        let thumb = img.thumbnail(16, 16).unwrap();
+        assert_eq!((thumb.width(), thumb.height()), (16, 8));
+        assert_eq!(thumb.pixel_type(), PixelType::U8);
+        assert_eq!(thumb.get_gain(), Some(2.0));
+        // Never scaled up
+        let thumb = img.thumbnail(128, 128).unwrap();
+        assert_eq!((thumb.width(), thumb.height()), (64, 32));
+        assert!(img.thumbnail(0, 16).is_err());
+
+        let img = ImageOwned::from_owned(vec![100u8; 40 * 20], 40, 20, BayerPattern::Rggb.into())
+            .unwrap();
+        let img = crate::GenericImageOwned::new(SystemTime::now(), img.into());
+        let thumb = img.thumbnail(10, 10).unwrap();
+        assert_eq!((thumb.width(), thumb.height()), (10, 5));
+        assert_eq!(thumb.color_space(), ColorSpace::Rgb);
+    }
 }
@@ -4,13 +4,17 @@ use std::{
     time::{Duration, SystemTime},
 };
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     genericimageowned::GenericImageOwned,
-    metadata::{name_check, InsertValue},
-    BayerError, CalcOptExp, Debayer, DemosaicMethod, DynamicImageRef, GenericLineItem, ImageProps,
-    OptimumExposure, SelectRoi, EXPOSURE_KEY, TIMESTAMP_KEY,
+    metadata::{
+        crop_values, edit_values, flip_values, increment_value, merge_values, name_check,
+        InsertValue,
+    },
+    BayerError, CalcOptExp, Debayer, DemosaicMethod, Deserializer, DynamicImageRef,
+    ExposureOutcome, Flip, GenericLineItem, ImageProps, OptimumExposure, SelectRoi, Serializer,
+    CAMERANAME_KEY, EXPOSURE_KEY, GAIN_KEY, ICC_PROFILE_KEY, PROGRAMNAME_KEY, TIMESTAMP_KEY,
 };
 
 #[allow(unused_imports)]
@@ -81,6 +85,20 @@ impl<'a> GenericImageRef<'a> {
             .unwrap() // Safe to unwrap, as the timestamp key is always inserted
     }
 
+    /// Set the timestamp of the image, e.g. to correct the capture time after the fact.
+    ///
+    /// The timestamp stored in the metadata under [`TIMESTAMP_KEY`] is replaced, which
+    /// [`Self::insert_key`] and [`Self::remove_key`] do not allow.
+    pub fn set_timestamp(&mut self, tstamp: SystemTime) {
+        self.metadata.insert(
+            TIMESTAMP_KEY.to_string(),
+            GenericLineItem {
+                value: tstamp.into(),
+                comment: Some("Timestamp of the image".to_owned()),
+            },
+        );
+    }
+
     /// Get the exposure time of the image.
     pub fn get_exposure(&self) -> Option<Duration> {
         self.metadata
@@ -88,6 +106,76 @@ impl<'a> GenericImageRef<'a> {
             .and_then(|x| x.get_value().clone().try_into().ok())
     }
 
+    /// Set the exposure time of the image.
+    ///
+    /// The exposure is stored in the metadata under [`EXPOSURE_KEY`], replacing any
+    /// existing value.
+    pub fn set_exposure(&mut self, exposure: Duration) {
+        self.metadata.insert(
+            EXPOSURE_KEY.to_string(),
+            GenericLineItem {
+                value: exposure.into(),
+                comment: Some("Exposure time".to_owned()),
+            },
+        );
+    }
+
+    /// Get the sensor gain of the image.
+    pub fn get_gain(&self) -> Option<f64> {
+        self.metadata
+            .get(GAIN_KEY)
+            .and_then(|x| x.get_value().get_value_f64())
+    }
+
+    /// Set the sensor gain of the image.
+    ///
+    /// The gain is stored in the metadata under [`GAIN_KEY`], replacing any existing value.
+    pub fn set_gain(&mut self, gain: f64) {
+        self.metadata.insert(
+            GAIN_KEY.to_string(),
+            GenericLineItem {
+                value: gain.into(),
+                comment: Some("Sensor gain".to_owned()),
+            },
+        );
+    }
+
+    /// Get the name of the camera that captured the image.
+    pub fn get_camera(&self) -> Option<&str> {
+        self.metadata
+            .get(CAMERANAME_KEY)
+            .and_then(|x| x.get_value().get_value_string())
+    }
+
+    /// Set the name of the camera that captured the image.
+    ///
+    /// The name is stored in the metadata under [`CAMERANAME_KEY`], replacing any
+    /// existing value.
+    ///
+    /// # Errors
+    /// - If the name is empty or longer than 4096 characters.
+    pub fn set_camera(&mut self, camera: &str) -> Result<(), &'static str> {
+        self.insert_key(CAMERANAME_KEY, (camera, "Camera name"))
+    }
+
+    /// Get the name of the program that generated the image.
+    pub fn get_program(&self) -> Option<&str> {
+        self.metadata
+            .get(PROGRAMNAME_KEY)
+            .and_then(|x| x.get_value().get_value_string())
+    }
+
+    /// Set the name of the program that generated the image.
+    ///
+    /// The name is stored in the metadata under [`PROGRAMNAME_KEY`], replacing any
+    /// existing value.
+    ///
+    /// # Errors
+    /// - If the name is empty or longer than 4096 characters.
+    pub fn set_program(&mut self, program: &str) -> Result<(), &'static str> {
+        self.insert_key(PROGRAMNAME_KEY, (program, "Program name"))
+    }
+
     /// Insert a metadata value into the [`GenericImageRef`].
     ///
     /// # Arguments
@@ -116,6 +204,31 @@ impl<'a> GenericImageRef<'a> {
         T::insert_key_gi(self, name, value)
     }
 
+    /// Insert a metadata value into the [`GenericImageRef`], unless a value with the same
+    /// name is already present.
+    ///
+    /// Unlike [`Self::insert_key`], an existing value is never overwritten. Since keys
+    /// are case-insensitive, e.g. `"camera"` and `"CAMERA"` name the same value.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the metadata value. The name must be non-empty and less than 80 characters.
+    /// - `value`: The value to insert. See [`Self::insert_key`] for the valid types.
+    ///
+    /// # Errors
+    /// - `Err("Key already exists")` if a value with the same name is present, including
+    ///   the timestamp key.
+    /// - Any error returned by [`Self::insert_key`].
+    pub fn try_insert_key<T: InsertValue>(
+        &mut self,
+        name: &str,
+        value: T,
+    ) -> Result<(), &'static str> {
+        if self.metadata.contains_key(&name.to_uppercase()) {
+            return Err("Key already exists");
+        }
+        self.insert_key(name, value)
+    }
+
     /// Remove a metadata value from the [`GenericImageRef`].
     ///
     /// # Arguments
@@ -175,6 +288,45 @@ impl<'a> GenericImageRef<'a> {
         &self.metadata
     }
 
+    /// Edit the metadata in bulk, e.g. to rename or strip a set of keys.
+    ///
+    /// The closure gets mutable access to the metadata map. If the closure removes the
+    /// timestamp stored under [`TIMESTAMP_KEY`], or replaces it with a value that is not
+    /// a timestamp, the previous timestamp is restored afterwards. Keys are converted to
+    /// uppercase afterwards, as in [`Self::insert_key`].
+    ///
+    /// # Arguments
+    /// - `f`: The closure to apply to the metadata.
+    ///
+    /// # Errors
+    /// - If a key is empty or longer than 80 characters.
+    /// - If two keys differ only in case.
+    ///
+    /// The metadata is not modified on error.
+    pub fn with_metadata_mut<F: FnOnce(&mut HashMap<String, GenericLineItem>)>(
+        &mut self,
+        f: F,
+    ) -> Result<(), &'static str> {
+        edit_values(&mut self.metadata, f)
+    }
+
+    /// Iterate over the contained metadata in ascending order of the keys.
+    ///
+    /// Unlike iterating over [`Self::get_metadata`], the order is the same across
+    /// runs, e.g. for reproducible FITS output.
+    ///
+    /// # Returns
+    /// An iterator over the names and values of the metadata.
+    pub fn iter_metadata_sorted(&self) -> impl Iterator<Item = (&str, &GenericLineItem)> {
+        let mut items: Vec<_> = self
+            .metadata
+            .iter()
+            .map(|(name, item)| (name.as_str(), item))
+            .collect();
+        items.sort_unstable_by_key(|&(name, _)| name);
+        items.into_iter()
+    }
+
     /// Get a specific metadata value by name.
     ///
     /// Returns the first metadata value with the given name.
@@ -186,6 +338,80 @@ impl<'a> GenericImageRef<'a> {
         self.metadata.get(name)
     }
 
+    /// Increment an integer metadata value in-place, e.g. a frame sequence number.
+    ///
+    /// The stored type and comment of the value are preserved.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the metadata value.
+    /// - `by`: The amount to add to the value (may be negative).
+    ///
+    /// # Returns
+    /// The new value.
+    ///
+    /// # Errors
+    /// - If the key is not found.
+    /// - If the value is not an integer.
+    /// - If the result overflows the stored type, or does not fit in an [`i64`].
+    pub fn increment_key(&mut self, name: &str, by: i64) -> Result<i64, &'static str> {
+        increment_value(&mut self.metadata, name, by)
+    }
+
+    /// Serialize only the metadata of the image, without the pixel data.
+    ///
+    /// This is useful to write a small header sidecar file, which can be read back
+    /// with [`GenericImageRef::merge_metadata`].
+    ///
+    /// # Arguments
+    /// - `serializer`: The serializer to use, e.g. from `bincode` or `serde_json`.
+    pub fn serialize_metadata<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.metadata.serialize(serializer)
+    }
+
+    /// Deserialize metadata written by [`GenericImageRef::serialize_metadata`], and merge
+    /// it into the metadata of the image.
+    ///
+    /// Values with the same key as an existing value replace the existing value.
+    ///
+    /// # Arguments
+    /// - `deserializer`: The deserializer to use, e.g. from `bincode` or `serde_json`.
+    ///
+    /// # Errors
+    /// - If the metadata could not be deserialized.
+    /// - If any key is invalid, or the timestamp key does not hold a timestamp.
+    ///   In this case, the metadata of the image is left unchanged.
+    pub fn merge_metadata<'de, D: Deserializer<'de>>(
+        &mut self,
+        deserializer: D,
+    ) -> Result<(), D::Error> {
+        let incoming = HashMap::<String, GenericLineItem>::deserialize(deserializer)?;
+        merge_values(&mut self.metadata, incoming).map_err(serde::de::Error::custom)
+    }
+
+    /// Set the ICC color profile of the image.
+    ///
+    /// The profile is stored in the metadata under [`ICC_PROFILE_KEY`], replacing any
+    /// existing profile, and is preserved across serialization.
+    ///
+    /// # Note
+    /// The ICC profile is not written to FITS files.
+    pub fn set_icc_profile(&mut self, profile: Vec<u8>) {
+        self.metadata.insert(
+            ICC_PROFILE_KEY.to_string(),
+            GenericLineItem {
+                value: profile.into(),
+                comment: Some("ICC color profile".to_owned()),
+            },
+        );
+    }
+
+    /// Get the ICC color profile of the image, if present.
+    pub fn icc_profile(&self) -> Option<&[u8]> {
+        self.metadata
+            .get(ICC_PROFILE_KEY)
+            .and_then(|x| x.get_value().get_value_bytes())
+    }
+
     /// Convert the image to a [`GenericImageOwned`] with [`u8`] pixel type.
     ///
     /// Note: This operation is parallelized if the `rayon` feature is enabled.
@@ -196,6 +422,16 @@ impl<'a> GenericImageRef<'a> {
             image: img,
         }
     }
+
+    #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+    #[cfg(feature = "rayon")]
+    /// Convert the image to a [`GenericImageOwned`] with [`u8`] pixel type, running the
+    /// conversion inside the supplied thread pool.
+    ///
+    /// See [`Self::into_u8`] for details.
+    pub fn into_u8_in_pool(self, pool: &rayon::ThreadPool) -> GenericImageOwned {
+        pool.install(|| self.into_u8())
+    }
 }
 
 impl Debayer for GenericImageRef<'_> {
@@ -212,19 +448,53 @@ impl Debayer for GenericImageRef<'_> {
 
 impl CalcOptExp for GenericImageRef<'_> {
     fn calc_opt_exp(
-        mut self,
+        self,
         eval: &OptimumExposure,
         exposure: Duration,
         bin: u8,
     ) -> Result<(Duration, u16), &'static str> {
+        self.eval_opt_exp(eval, exposure, bin)
+            .map(ExposureOutcome::into_inner)
+    }
+
+    fn eval_opt_exp(
+        mut self,
+        eval: &OptimumExposure,
+        exposure: Duration,
+        bin: u8,
+    ) -> Result<ExposureOutcome, &'static str> {
         match &mut self.image {
-            DynamicImageRef::U8(img) => {let len = img.len(); eval.calculate(img.as_mut_slice(), len, exposure, bin)},
-            DynamicImageRef::U16(img) => {let len = img.len(); eval.calculate(img.as_mut_slice(), len, exposure, bin)},
+            DynamicImageRef::U8(img) => {let len = img.len(); eval.evaluate(img.as_mut_slice(), len, exposure, bin)},
+            DynamicImageRef::U16(img) => {let len = img.len(); eval.evaluate(img.as_mut_slice(), len, exposure, bin)},
+            DynamicImageRef::I16(img) => {let len = img.len(); eval.evaluate(img.as_mut_slice(), len, exposure, bin)},
+            DynamicImageRef::U32(img) => {let len = img.len(); eval.evaluate(img.as_mut_slice(), len, exposure, bin)},
             DynamicImageRef::F32(_) => Err("Floating point images are not supported for this operation, since Ord is not implemented for floating point types."),
         }
     }
 }
 
+impl Flip for GenericImageRef<'_> {
+    type Output = GenericImageOwned;
+
+    fn flip_h(&self) -> Self::Output {
+        let mut meta = self.metadata.clone();
+        flip_values(&mut meta, self.width(), true);
+        GenericImageOwned {
+            metadata: meta,
+            image: self.image.flip_h(),
+        }
+    }
+
+    fn flip_v(&self) -> Self::Output {
+        let mut meta = self.metadata.clone();
+        flip_values(&mut meta, self.height(), false);
+        GenericImageOwned {
+            metadata: meta,
+            image: self.image.flip_v(),
+        }
+    }
+}
+
 impl SelectRoi for GenericImageRef<'_> {
     type Output = GenericImageOwned;
 
@@ -236,7 +506,8 @@ impl SelectRoi for GenericImageRef<'_> {
         height: NonZeroUsize,
     ) -> Result<Self::Output, &'static str> {
         let img = self.image.select_roi(x, y, width, height)?;
-        let meta = self.metadata.clone();
+        let mut meta = self.metadata.clone();
+        crop_values(&mut meta, x, y);
         Ok(Self::Output {
             metadata: meta,
             image: img,
@@ -350,6 +621,62 @@ impl GenericImageRef<'_> {
 }
 
 mod test {
+    #[test]
+    fn test_with_metadata_mut() {
+        use crate::{ColorSpace, GenericImage, GenericImageRef, ImageRef, TIMESTAMP_KEY};
+        use std::time::{Duration, SystemTime};
+        let tstamp = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let mut data = vec![0u8; 4];
+        let img = ImageRef::new(&mut data, 2, 2, ColorSpace::Gray).unwrap();
+        let mut img = GenericImageRef::new(tstamp, img.into());
+        img.insert_key("OBJECT", "M31").unwrap();
+        img.insert_key("FILTER", "Ha").unwrap();
+        img.with_metadata_mut(|meta| {
+            let keys: Vec<_> = meta.keys().cloned().collect();
+            for key in keys {
+                let line = meta.remove(&key).unwrap();
+                meta.insert(format!("X{key}"), line);
+            }
+        })
+        .unwrap();
+        assert!(img.get_key("OBJECT").is_none());
+        assert!(img.get_key("XOBJECT").is_some());
+        assert_eq!(img.get_timestamp(), tstamp);
+        assert_eq!(img.get_metadata().len(), 4);
+
+        let mut img = GenericImage::from(img);
+        img.with_metadata_mut(|meta| {
+            meta.retain(|key, _| key != "XFILTER");
+            let line = meta["XOBJECT"].clone();
+            meta.insert(TIMESTAMP_KEY.to_owned(), line);
+        })
+        .unwrap();
+        assert!(img.get_key("XFILTER").is_none());
+        assert_eq!(img.get_timestamp(), tstamp);
+
+        img.with_metadata_mut(|meta| {
+            let line = meta.remove("XOBJECT").unwrap();
+            meta.insert("camera".to_owned(), line);
+        })
+        .unwrap();
+        assert!(img.get_metadata().contains_key("CAMERA"));
+        assert!(!img.get_metadata().contains_key("camera"));
+        assert!(img.try_insert_key("Camera", "ZWO").is_err());
+
+        let before = img.get_metadata().clone();
+        let res = img.with_metadata_mut(|meta| {
+            let line = meta["CAMERA"].clone();
+            meta.insert("Camera".to_owned(), line);
+        });
+        assert!(res.is_err());
+        let res = img.with_metadata_mut(|meta| {
+            let line = meta["CAMERA"].clone();
+            meta.insert(String::new(), line);
+        });
+        assert!(res.is_err());
+        assert_eq!(img.get_metadata(), &before);
+    }
+
     #[test]
     fn test_optimum_exposure() {
         use crate::CalcOptExp;
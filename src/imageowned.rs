@@ -1,14 +1,23 @@
-use std::time::Duration;
+use std::{num::NonZeroUsize, time::Duration};
 
 use crate::{
+    alpha::without_alpha,
+    coreimpls::{
+        checked_len, crop_cspace, flip_cols, flip_cspace, flip_rows, guess_bayer_pattern,
+        rotate_cspace, run_paste, run_rotate, run_select_padded, run_stretch_u8, run_transpose,
+        transpose_cspace,
+    },
     coretraits::cast_u8,
     demosaic::{run_demosaic_imageowned, Debayer, RasterMut},
-    imagetraits::ImageProps,
-    BayerError, CalcOptExp, ColorSpace, CopyRoi, DemosaicMethod, Enlargeable, ImageRef,
-    OptimumExposure, PixelStor, PixelType, SelectRoi, ToLuma,
+    imagetraits::{ImageProps, MutImageData},
+    BayerError, BayerPattern, BorderMode, CalcOptExp, ColorSpace, CopyRoi, DemosaicMethod,
+    Enlargeable, ExposureOutcome, Flip, ImageRef, OptimumExposure, PixelStor, PixelType,
+    RefImageError, ResizeFilter, Rotation, SelectRoi, ToLuma,
 };
 use bytemuck::{AnyBitPattern, PodCastError};
-use num_traits::Zero;
+use num_traits::{NumCast, PrimInt, Zero};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 /// A structure that holds image data backed by a vector.
 ///
@@ -44,31 +53,37 @@ impl<T: PixelStor> ImageOwned<T> {
         height: usize,
         cspace: ColorSpace,
     ) -> Result<Self, &'static str> {
+        Ok(Self::try_new(data, width, height, cspace)?)
+    }
+
+    /// Validate the dimensions and color space, and create the image.
+    pub(crate) fn try_new(
+        data: Vec<T>,
+        width: usize,
+        height: usize,
+        cspace: ColorSpace,
+    ) -> Result<Self, RefImageError> {
         if height > u16::MAX as usize || width > u16::MAX as usize {
-            return Err("Image too large.");
+            return Err(RefImageError::TooLarge);
         }
         if data.is_empty() {
-            return Err("Data is empty");
+            return Err(RefImageError::EmptyData);
         }
         if width == 0 {
-            return Err("Width is zero");
+            return Err(RefImageError::ZeroWidth);
         }
         if height == 0 {
-            return Err("Height is zero");
+            return Err(RefImageError::ZeroHeight);
         }
-        let channels = match cspace {
-            ColorSpace::Gray | ColorSpace::Bayer(_) => 1,
-            ColorSpace::Rgb => 3,
-            ColorSpace::Custom(ch, _) => ch as usize,
-        };
+        let channels = cspace.channel_count().ok_or(RefImageError::NoChannels)? as usize;
         let len = data.len();
         let tot = width
             .checked_mul(height)
-            .ok_or("Image too large.")?
+            .ok_or(RefImageError::TooLarge)?
             .checked_mul(channels)
-            .ok_or("Image too large.")?;
+            .ok_or(RefImageError::TooLarge)?;
         if tot > len {
-            return Err("Not enough data for image.");
+            return Err(RefImageError::LengthMismatch);
         }
         let mut img = ImageOwned {
             data,
@@ -98,13 +113,14 @@ impl<T: PixelStor> ImageOwned<T> {
     /// - If the height is zero.
     /// - If there are too many channels for grayscale/Bayer pattern images.
     /// - If color space is RGB and number of channels is not 3.
+    /// - If the color space has no channels.
     pub fn from_ref(
         data: &[T],
         width: usize,
         height: usize,
         cspace: ColorSpace,
-    ) -> Result<Self, &'static str> {
-        Self::new(data.into(), width, height, cspace)
+    ) -> Result<Self, RefImageError> {
+        Self::try_new(data.into(), width, height, cspace)
     }
 
     /// Create a new [`ImageOwned`] from owned data.
@@ -124,13 +140,14 @@ impl<T: PixelStor> ImageOwned<T> {
     /// - If the height is zero.
     /// - If there are too many channels for grayscale/Bayer pattern images.
     /// - If color space is RGB and number of channels is not 3.
+    /// - If the color space has no channels.
     pub fn from_owned(
         data: Vec<T>,
         width: usize,
         height: usize,
         cspace: ColorSpace,
-    ) -> Result<Self, &'static str> {
-        Self::new(data, width, height, cspace)
+    ) -> Result<Self, RefImageError> {
+        Self::try_new(data, width, height, cspace)
     }
 
     /// Get the underlying data as a slice.
@@ -143,11 +160,51 @@ impl<T: PixelStor> ImageOwned<T> {
         self.data.as_mut_slice()
     }
 
+    /// Get a borrowed view of a band of full-width rows of the image.
+    ///
+    /// Full-width rows are contiguous in memory, hence no data is copied. The view has
+    /// the same width, number of channels and color space as the image.
+    ///
+    /// # Arguments
+    /// - `y`: The first row of the band.
+    /// - `height`: The number of rows in the band.
+    ///
+    /// # Errors
+    /// - If `y + height` is larger than the height of the image.
+    /// - If the image is a Bayer mosaic image and `y` is odd, since the view would not
+    ///   start on the same Bayer pattern.
+    pub fn crop_rows(
+        &mut self,
+        y: usize,
+        height: NonZeroUsize,
+    ) -> Result<ImageRef<'_, T>, &'static str> {
+        let (swid, shei, channels) = (self.width(), self.height(), self.channels());
+        crate::imageref::row_band(
+            self.data.as_mut_slice(),
+            swid,
+            shei,
+            channels,
+            &self.cspace,
+            y,
+            height,
+        )
+    }
+
+    /// Get row-addressed mutable access to the image data.
+    pub fn row_writer(&mut self) -> crate::RowWriter<'_, T> {
+        let (width, height, channels) = (self.width(), self.height(), self.channels as usize);
+        crate::RowWriter::new(self.data.as_mut_slice(), width, height, channels)
+            .expect("Image geometry is valid")
+    }
+
     /// Get the underlying data as a vector.
     ///
-    /// Note: This function returns a copy of the data.
+    /// The owned data is moved out of the image without copying.
     pub fn into_vec(self) -> Vec<T> {
-        self.data.clone()
+        let len = self.len();
+        let mut data = self.data;
+        data.truncate(len);
+        data
     }
 
     /// Get a raw pointer to the data.
@@ -184,6 +241,42 @@ impl<T: PixelStor> ImageOwned<T> {
     pub fn as_u8_slice_checked(&self) -> Option<&[u8]> {
         bytemuck::try_cast_slice(self.as_slice()).ok()
     }
+
+    /// Get the image data as raw bytes, in the layout of a FITS data array.
+    ///
+    /// Samples are stored big-endian regardless of the host. Unsigned 16, 32 and 64-bit
+    /// and signed 8-bit samples are offset as FITS requires (i.e. for use with `BZERO`
+    /// of `32768`, `2147483648`, `9223372036854775808` and `-128` respectively), and
+    /// half-precision samples are converted to [`f32`].
+    pub fn to_fits_bytes(&self) -> Vec<u8> {
+        let data = self.as_slice();
+        #[cfg(feature = "f16")]
+        if T::PIXEL_TYPE == PixelType::F16 {
+            return data
+                .iter()
+                .flat_map(|&v| PixelStor::to_f32(v).to_be_bytes())
+                .collect();
+        }
+        let offset = matches!(
+            T::PIXEL_TYPE,
+            PixelType::U16 | PixelType::U32 | PixelType::U64 | PixelType::I8
+        );
+        let mut out = Vec::with_capacity(std::mem::size_of_val(data));
+        for px in data {
+            let start = out.len();
+            let bytes = bytemuck::bytes_of(px);
+            if cfg!(target_endian = "little") {
+                out.extend(bytes.iter().rev());
+            } else {
+                out.extend_from_slice(bytes);
+            }
+            if offset {
+                // Flipping the sign bit is equivalent to subtracting BZERO
+                out[start] ^= 0x80;
+            }
+        }
+        out
+    }
 }
 
 impl<T: PixelStor> ImageProps for ImageOwned<T> {
@@ -229,6 +322,12 @@ impl<T: PixelStor> ImageProps for ImageOwned<T> {
     }
 }
 
+impl<T: PixelStor> MutImageData<T> for ImageOwned<T> {
+    fn as_mut_logical_slice(&mut self) -> &mut [T] {
+        self.data.as_mut_slice()
+    }
+}
+
 impl<T: PixelStor + AnyBitPattern> ImageOwned<T> {
     /// Create a new [`ImageOwned`] from a mutable slice of `u8` data.
     ///
@@ -242,6 +341,7 @@ impl<T: PixelStor + AnyBitPattern> ImageOwned<T> {
     /// The endianness of the data is determined by the system, and the data is assumed
     /// to be in native endianness. This function is not safe to use in a cross-platform
     /// environment.
+    /// Use [`ImageOwned::from_u8_be`] or [`ImageOwned::from_u8_le`] for data of a known byte order.
     ///
     /// # Arguments
     /// - `data`: The [`&mut [u8]`] data slice.
@@ -258,37 +358,118 @@ impl<T: PixelStor + AnyBitPattern> ImageOwned<T> {
     /// - If the data length does not match the image size.
     /// - If there are too many channels for grayscale/Bayer pattern images.
     /// - If color space is RGB and number of channels is not 3.
+    /// - If the color space has no channels.
     pub fn from_u8(
         data: &[u8],
         width: usize,
         height: usize,
         cspace: ColorSpace,
-    ) -> Result<Self, &'static str> {
-        let data = bytemuck::try_cast_slice(data).map_err(|e| {
-            use PodCastError::*;
-            match e {
-                TargetAlignmentGreaterAndInputNotAligned => {
-                    "Target alignment greater and input not aligned"
-                }
-                OutputSliceWouldHaveSlop => "Output slice would have slop",
-                SizeMismatch => "Size mismatch",
-                AlignmentMismatch => "Alignment mismatch",
-            }
-        })?;
+    ) -> Result<Self, RefImageError> {
+        let data = bytemuck::try_cast_slice(data)?;
         Self::from_ref(data, width, height, cspace)
     }
 }
 
+impl<T: PixelStor + AnyBitPattern + PrimInt> ImageOwned<T> {
+    /// Copy `u8` data into pixels, converting each pixel with `convert`.
+    fn from_u8_ordered(
+        data: &[u8],
+        width: usize,
+        height: usize,
+        cspace: ColorSpace,
+        convert: fn(T) -> T,
+    ) -> Result<Self, RefImageError> {
+        if !data.len().is_multiple_of(std::mem::size_of::<T>()) {
+            return Err(PodCastError::OutputSliceWouldHaveSlop.into());
+        }
+        let data: Vec<T> = bytemuck::pod_collect_to_vec::<u8, T>(data)
+            .into_iter()
+            .map(convert)
+            .collect();
+        Self::try_new(data, width, height, cspace)
+    }
+
+    /// Create a new [`ImageOwned`] from a slice of `u8` data in big-endian byte order.
+    ///
+    /// Images can not be larger than 65535x65535 pixels.
+    ///
+    /// The data is copied, and converted from big-endian to native byte order. Unlike
+    /// [`ImageOwned::from_u8`], which assumes native endianness, this function is safe to
+    /// use with data from other platforms or file formats. `data` need not be aligned.
+    ///
+    /// # Arguments
+    /// - `data`: The [`&[u8]`] data slice, in big-endian byte order.
+    /// - `width`: The width of the image.
+    /// - `height`: The height of the image.
+    /// - `cspace`: The color space of the image ([`ColorSpace`]).
+    ///
+    /// # Errors
+    /// - If the data length is not a multiple of the size of `T`.
+    /// - If the image is too large.
+    /// - If the data is empty.
+    /// - If the width is zero.
+    /// - If the height is zero.
+    /// - If the data length does not match the image size.
+    /// - If there are too many channels for grayscale/Bayer pattern images.
+    /// - If color space is RGB and number of channels is not 3.
+    /// - If the color space has no channels.
+    pub fn from_u8_be(
+        data: &[u8],
+        width: usize,
+        height: usize,
+        cspace: ColorSpace,
+    ) -> Result<Self, RefImageError> {
+        Self::from_u8_ordered(data, width, height, cspace, T::from_be)
+    }
+
+    /// Create a new [`ImageOwned`] from a slice of `u8` data in little-endian byte order.
+    ///
+    /// Images can not be larger than 65535x65535 pixels.
+    ///
+    /// The data is copied, and converted from little-endian to native byte order. Unlike
+    /// [`ImageOwned::from_u8`], which assumes native endianness, this function is safe to
+    /// use with data from other platforms or file formats. `data` need not be aligned.
+    ///
+    /// # Arguments
+    /// - `data`: The [`&[u8]`] data slice, in little-endian byte order.
+    /// - `width`: The width of the image.
+    /// - `height`: The height of the image.
+    /// - `cspace`: The color space of the image ([`ColorSpace`]).
+    ///
+    /// # Errors
+    /// - If the data length is not a multiple of the size of `T`.
+    /// - If the image is too large.
+    /// - If the data is empty.
+    /// - If the width is zero.
+    /// - If the height is zero.
+    /// - If the data length does not match the image size.
+    /// - If there are too many channels for grayscale/Bayer pattern images.
+    /// - If color space is RGB and number of channels is not 3.
+    /// - If the color space has no channels.
+    pub fn from_u8_le(
+        data: &[u8],
+        width: usize,
+        height: usize,
+        cspace: ColorSpace,
+    ) -> Result<Self, RefImageError> {
+        Self::from_u8_ordered(data, width, height, cspace, T::from_le)
+    }
+}
+
 impl<T: PixelStor + Enlargeable> ToLuma for ImageOwned<T> {
     fn to_luma(&mut self) -> Result<(), &'static str> {
-        self.to_luma_custom(&[0.299, 0.587, 0.114])
+        self.to_luma_custom(&crate::coreimpls::LUMA_COEFFS)
     }
 
     fn to_luma_custom(&mut self, coeffs: &[f64]) -> Result<(), &'static str> {
         // at this point, number of channels must match number of weights
         match self.cspace {
             ColorSpace::Gray => Err("Image is already grayscale."),
-            ColorSpace::Rgb | ColorSpace::Custom(_, _) => {
+            ColorSpace::Rgb
+            | ColorSpace::Rgba
+            | ColorSpace::GrayAlpha
+            | ColorSpace::Custom(_, _) => {
+                crate::coreimpls::check_luma_coeffs(self.channels.into(), coeffs)?;
                 crate::coreimpls::run_luma(
                     self.channels.into(),
                     self.data.len(),
@@ -301,114 +482,1177 @@ impl<T: PixelStor + Enlargeable> ToLuma for ImageOwned<T> {
                 self.data.truncate(len);
                 Ok(())
             }
+            ColorSpace::Cmyk => {
+                crate::coreimpls::check_luma_coeffs(3, coeffs)?;
+                crate::coreimpls::run_luma_cmyk(self.data.len(), self.data.as_mut_slice(), coeffs)?;
+                self.cspace = ColorSpace::Gray;
+                let len = self.width as usize * self.height as usize;
+                self.channels = 1;
+                self.data.truncate(len);
+                Ok(())
+            }
             ColorSpace::Bayer(_) => Err("Image is not debayered."),
         }
     }
 }
 
-impl<T: PixelStor + Zero> SelectRoi for ImageOwned<T> {
-    type Output = ImageOwned<T>;
-
-    fn select_roi(
-        &self,
-        x: usize,
-        y: usize,
-        width: std::num::NonZeroUsize,
-        height: std::num::NonZeroUsize,
-    ) -> Result<Self::Output, &'static str> {
-        let swid = self.width();
-        let shei = self.height();
-        if x >= swid || y >= shei {
-            return Err("ROI is out of bounds.");
+impl<T: PixelStor> ImageOwned<T> {
+    /// Adjust the color saturation of an RGB image in-place, preserving the luminance.
+    ///
+    /// The luminance of each pixel is calculated using the same coefficients as
+    /// [`ToLuma::to_luma`], and the deviation of each channel from the luminance is
+    /// scaled by `factor`. The result is clamped to the valid range of the pixel type.
+    ///
+    /// A `factor` of `0` yields a gray image, and a `factor` of `1` leaves the image unchanged.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    ///
+    /// # Errors
+    /// - If the image is not an RGB image.
+    /// - If `factor` is negative or not finite.
+    pub fn adjust_saturation(&mut self, factor: f64) -> Result<(), &'static str> {
+        if self.cspace != ColorSpace::Rgb {
+            return Err("Saturation can only be adjusted for RGB images.");
         }
-        let mut data = vec![T::zero(); width.get() * height.get() * self.channels as usize];
-        let wid = width.get().min(swid - x); // guaranteed to be non-zero
-        let hei = height.get().min(shei - y); // guaranteed to be non-zero
-        for h in 0..hei {
-            let src = (y + h) * swid + x;
-            let dst = h * wid;
-            let len = wid * self.channels as usize;
-            data[dst..dst + len].copy_from_slice(&self.data[src..src + len]);
+        if !factor.is_finite() || factor < 0.0 {
+            return Err("Saturation factor must be a non-negative finite number.");
         }
-        ImageOwned::new(data, width.get(), height.get(), self.cspace.clone())
+        crate::coreimpls::run_saturation(self.data.as_mut_slice(), factor);
+        Ok(())
     }
 }
 
-impl<T: PixelStor + Zero> CopyRoi for ImageOwned<T> {
-    type Output = ImageOwned<T>;
-
-    fn copy_to(&self, dest: &mut Self::Output, x: usize, y: usize) {
-        let channels = self.channels() as usize;
-        let swid = self.width();
-        let shei = self.height();
-        let dwid = dest.width();
-        let dhei = dest.height();
-        let wid = dwid.min(swid - x);
-        let hei = dhei.min(shei - y);
-        dest.data.fill(T::zero());
-        for h in 0..hei {
-            let src = (y + h) * swid + x;
-            let dst = h * dwid;
-            let len = wid * channels;
-            dest.data[dst..dst + len].copy_from_slice(&self.data[src..src + len]);
-        }
-        dest.data.truncate(dwid * dhei * channels);
+impl<T: PixelStor + Enlargeable> ImageOwned<T> {
+    /// Subtract another image from this image, e.g. to remove a dark frame.
+    ///
+    /// The difference is computed in the larger type of the pixel type (see
+    /// [`Enlargeable`]), and saturates at [`PixelStor::DEFAULT_MIN_VALUE`] instead of
+    /// wrapping around.
+    ///
+    /// # Arguments
+    /// - `other`: The image to subtract.
+    ///
+    /// # Errors
+    /// - If the width, height, number of channels or color space of the images do not match.
+    pub fn checked_sub(&self, other: &Self) -> Result<ImageOwned<T>, &'static str> {
+        crate::coreimpls::check_same_layout(self, other)?;
+        Ok(ImageOwned {
+            data: crate::coreimpls::run_checked_sub(self.as_slice(), other.as_slice()),
+            width: self.width,
+            height: self.height,
+            channels: self.channels,
+            cspace: self.cspace.clone(),
+        })
     }
 }
 
-impl<T: PixelStor + Enlargeable> Debayer for ImageOwned<T> {
-    type Output = ImageOwned<T>;
-    fn debayer(&self, alg: DemosaicMethod) -> Result<Self::Output, BayerError> {
-        let cfa = self
-            .cspace
-            .clone()
-            .try_into()
-            .map_err(BayerError::InvalidColorSpace)?;
-        if self.channels != 1 {
-            return Err(BayerError::WrongDepth);
+impl<T: PixelStor + Enlargeable> ImageOwned<T> {
+    /// Divide this image by a flat field, and multiply by `scale`.
+    ///
+    /// Each output sample is `pixel / flat * scale`, computed in [`f64`] and saturated
+    /// into the valid range of the pixel type. Integer results are rounded to the
+    /// nearest integer. Samples where the flat is zero are set to
+    /// [`PixelStor::DEFAULT_MAX_VALUE`].
+    ///
+    /// # Arguments
+    /// - `flat`: The flat field image.
+    /// - `scale`: The scale factor, typically the mean of the flat field.
+    ///
+    /// # Errors
+    /// - If the width, height, number of channels or color space of the images do not match.
+    /// - If `scale` is not finite.
+    pub fn flat_divide(&self, flat: &Self, scale: f64) -> Result<ImageOwned<T>, &'static str> {
+        crate::coreimpls::check_same_layout(self, flat)?;
+        if !scale.is_finite() {
+            return Err("Scale factor must be finite.");
         }
-        let mut dst = vec![T::zero(); self.width() * self.height() * 3];
-        let mut raster = RasterMut::new(self.width(), self.height(), &mut dst);
-        run_demosaic_imageowned(self, cfa, alg, &mut raster)?;
-        Ok(Self::Output {
-            data: dst,
+        Ok(ImageOwned {
+            data: crate::coreimpls::run_flat_divide(self.as_slice(), flat.as_slice(), scale),
             width: self.width,
             height: self.height,
-            channels: 3,
-            cspace: ColorSpace::Rgb,
+            channels: self.channels,
+            cspace: self.cspace.clone(),
         })
     }
 }
 
-impl<'a, T: PixelStor> From<&ImageRef<'a, T>> for ImageOwned<T> {
-    fn from(data: &ImageRef<'a, T>) -> Self {
-        Self {
-            data: data.data[..data.len].to_vec(),
-            width: data.width,
-            height: data.height,
-            channels: data.channels,
-            cspace: data.cspace.clone(),
+impl<T: PixelStor> ImageOwned<T> {
+    /// Create a new [`ImageOwned`] with all samples set to zero.
+    ///
+    /// The number of channels is derived from the color space.
+    ///
+    /// # Arguments
+    /// - `width`: The width of the image.
+    /// - `height`: The height of the image.
+    /// - `cspace`: The color space of the image ([`ColorSpace`]).
+    ///
+    /// # Errors
+    /// - If the image is too large.
+    /// - If the width is zero.
+    /// - If the height is zero.
+    /// - If the color space has no channels.
+    pub fn zeros(width: usize, height: usize, cspace: ColorSpace) -> Result<Self, RefImageError> {
+        Self::filled(width, height, cspace, T::zero())
+    }
+
+    /// Create a new [`ImageOwned`] with all samples set to `value`.
+    ///
+    /// The number of channels is derived from the color space.
+    ///
+    /// # Arguments
+    /// - `width`: The width of the image.
+    /// - `height`: The height of the image.
+    /// - `cspace`: The color space of the image ([`ColorSpace`]).
+    /// - `value`: The value of every sample.
+    ///
+    /// # Errors
+    /// - If the image is too large.
+    /// - If the width is zero.
+    /// - If the height is zero.
+    /// - If the color space has no channels.
+    pub fn filled(
+        width: usize,
+        height: usize,
+        cspace: ColorSpace,
+        value: T,
+    ) -> Result<Self, RefImageError> {
+        let channels = cspace.channel_count().ok_or(RefImageError::NoChannels)? as usize;
+        let len = checked_len(width, height, channels)?;
+        Self::try_new(vec![value; len], width, height, cspace)
+    }
+
+    /// Create a new [`ImageOwned`] by evaluating a function for every sample.
+    ///
+    /// The number of channels is derived from the color space, and the samples are
+    /// generated in row-major order, i.e. the same order as the image data.
+    ///
+    /// # Arguments
+    /// - `width`: The width of the image.
+    /// - `height`: The height of the image.
+    /// - `cspace`: The color space of the image ([`ColorSpace`]).
+    /// - `f`: The function that returns the value of the sample at `(x, y, channel)`.
+    ///
+    /// # Errors
+    /// - If the image is too large.
+    /// - If the width is zero.
+    /// - If the height is zero.
+    /// - If the color space has no channels.
+    ///
+    /// # Example
+    /// ```
+    /// use refimage::{ColorSpace, ImageOwned};
+    /// // A horizontal gradient
+    /// let img = ImageOwned::from_fn(4, 2, ColorSpace::Gray, |x, _, _| x as u8 * 10).unwrap();
+    /// assert_eq!(img.as_slice(), &[0, 10, 20, 30, 0, 10, 20, 30]);
+    /// ```
+    pub fn from_fn<F: Fn(usize, usize, usize) -> T>(
+        width: usize,
+        height: usize,
+        cspace: ColorSpace,
+        f: F,
+    ) -> Result<Self, RefImageError> {
+        let channels = cspace.channel_count().ok_or(RefImageError::NoChannels)? as usize;
+        let len = checked_len(width, height, channels)?;
+        let mut data = Vec::with_capacity(len);
+        for y in 0..height {
+            for x in 0..width {
+                data.extend((0..channels).map(|ch| f(x, y, ch)));
+            }
         }
+        Self::try_new(data, width, height, cspace)
     }
-}
 
-impl<T: PixelStor + Ord> CalcOptExp for ImageOwned<T> {
-    fn calc_opt_exp(
-        mut self,
-        eval: &OptimumExposure,
-        exposure: Duration,
-        bin: u8,
-    ) -> Result<(Duration, u16), &'static str> {
-        let len = self.data.len();
-        eval.calculate(self.data.as_mut_slice(), len, exposure, bin)
+    /// Get the image data in a planar layout, i.e. `RRRR...GGGG...BBBB...`.
+    ///
+    /// The image data is stored interleaved (`RGBRGB...`); this function returns
+    /// a copy of the data with all samples of each channel stored contiguously.
+    pub fn to_planar(&self) -> Vec<T> {
+        let channels = self.channels as usize;
+        let npix = self.width as usize * self.height as usize;
+        if channels == 1 {
+            return self.data[..npix].to_vec();
+        }
+        let mut out = Vec::with_capacity(npix * channels);
+        for ch in 0..channels {
+            out.extend(self.data.iter().skip(ch).step_by(channels).take(npix));
+        }
+        out
     }
-}
 
-mod test {
+    /// Create a new [`ImageOwned`] from data in a planar layout, i.e. `RRRR...GGGG...BBBB...`.
+    ///
+    /// The number of channels is derived from the color space, and the data is
+    /// converted to the interleaved layout (`RGBRGB...`) used by this crate.
+    ///
+    /// # Arguments
+    /// - `data`: The planar data slice. It is copied into the image.
+    /// - `width`: The width of the image.
+    /// - `height`: The height of the image.
+    /// - `cspace`: The color space of the image ([`ColorSpace`]).
+    ///
+    /// # Errors
+    /// - If the image is too large.
+    /// - If the data is empty.
+    /// - If the width is zero.
+    /// - If the height is zero.
+    /// - If there is not enough data for the image.
+    pub fn from_planar(
+        data: &[T],
+        width: usize,
+        height: usize,
+        cspace: ColorSpace,
+    ) -> Result<Self, RefImageError> {
+        let channels = cspace.num_channels();
+        let npix = width.checked_mul(height).ok_or(RefImageError::TooLarge)?;
+        let tot = npix.checked_mul(channels).ok_or(RefImageError::TooLarge)?;
+        if data.len() < tot {
+            return Err(RefImageError::LengthMismatch);
+        }
+        let mut out = Vec::with_capacity(tot);
+        for px in 0..npix {
+            out.extend((0..channels).map(|ch| data[ch * npix + px]));
+        }
+        Self::try_new(out, width, height, cspace)
+    }
 
-    #[test]
-    fn test_into_luma() {
-        use crate::{ColorSpace, ImageOwned, ToLuma};
+    /// Split the image into one [`ColorSpace::Gray`] image per channel.
+    ///
+    /// The channels are returned in the order they are stored, e.g. `R`, `G`, `B`.
+    pub fn split_channels(&self) -> Vec<ImageOwned<T>> {
+        let channels = self.channels as usize;
+        let npix = self.width as usize * self.height as usize;
+        (0..channels)
+            .map(|ch| ImageOwned {
+                data: self
+                    .data
+                    .iter()
+                    .skip(ch)
+                    .step_by(channels)
+                    .take(npix)
+                    .copied()
+                    .collect(),
+                width: self.width,
+                height: self.height,
+                channels: 1,
+                cspace: ColorSpace::Gray,
+            })
+            .collect()
+    }
+
+    /// Merge single-channel images into one image, e.g. the output of
+    /// [`ImageOwned::split_channels`].
+    ///
+    /// # Arguments
+    /// - `channels`: The channels of the image, in the order they are stored.
+    /// - `cspace`: The color space of the merged image ([`ColorSpace`]).
+    ///
+    /// # Errors
+    /// - If the number of images does not match the number of channels of `cspace`.
+    /// - If an image has more than one channel.
+    /// - If the images do not have the same dimensions.
+    pub fn merge_channels(
+        channels: &[ImageOwned<T>],
+        cspace: ColorSpace,
+    ) -> Result<ImageOwned<T>, &'static str> {
+        Self::merge_planes(&channels.iter().collect::<Vec<_>>(), cspace)
+    }
+
+    /// Merge borrowed single-channel images into one image.
+    ///
+    /// See [`ImageOwned::merge_channels`] for details.
+    pub(crate) fn merge_planes(
+        planes: &[&ImageOwned<T>],
+        cspace: ColorSpace,
+    ) -> Result<ImageOwned<T>, &'static str> {
+        let first = planes.first().ok_or("No channels to merge.")?;
+        let channels = cspace.channel_count().ok_or(RefImageError::NoChannels)?;
+        if planes.len() != channels as usize {
+            return Err("Number of channels does not match the color space.");
+        }
+        if planes.iter().any(|img| img.channels != 1) {
+            return Err("Channels must be single-channel images.");
+        }
+        let (width, height) = (first.width(), first.height());
+        if planes
+            .iter()
+            .any(|img| img.width() != width || img.height() != height)
+        {
+            return Err("Channel dimensions do not match.");
+        }
+        let npix = width * height;
+        let mut out = Vec::with_capacity(npix * planes.len());
+        for px in 0..npix {
+            out.extend(planes.iter().map(|img| img.data[px]));
+        }
+        ImageOwned::new(out, width, height, cspace)
+    }
+
+    /// Change the color space of the image without touching the data, e.g. to tag a
+    /// grayscale frame as a Bayer mosaic before debayering it.
+    ///
+    /// # Arguments
+    /// - `cspace`: The new color space of the image ([`ColorSpace`]).
+    ///
+    /// # Errors
+    /// - If the color space has no channels.
+    /// - If the number of channels of the color space does not match the image.
+    pub fn reinterpret_color_space(&mut self, cspace: ColorSpace) -> Result<(), &'static str> {
+        let channels = cspace.channel_count().ok_or(RefImageError::NoChannels)?;
+        if channels != self.channels {
+            return Err(RefImageError::ChannelMismatch {
+                expected: channels,
+                found: self.channels,
+            }
+            .into());
+        }
+        self.cspace = cspace;
+        Ok(())
+    }
+
+    /// Remove the alpha channel in-place, without allocating a new buffer.
+    ///
+    /// Images with a custom color space of 4 channels are treated as RGBA, and
+    /// become [`ColorSpace::Rgb`]. Images with a custom color space of 2 channels
+    /// are treated as gray-alpha, and become [`ColorSpace::Gray`]. The color samples
+    /// are compacted to the front of the buffer, which is then truncated; the
+    /// capacity of the buffer is retained.
+    ///
+    /// # Errors
+    /// - If the image does not have 2 or 4 channels.
+    pub fn remove_alpha_in_place(&mut self) -> Result<(), &'static str> {
+        let cspace = without_alpha(&self.cspace)?;
+        let channels = cspace.num_channels();
+        let src = self.channels as usize;
+        let npix = self.width as usize * self.height as usize;
+        for px in 0..npix {
+            for ch in 0..channels {
+                self.data[px * channels + ch] = self.data[px * src + ch];
+            }
+        }
+        self.data.truncate(npix * channels);
+        self.channels = channels as _;
+        self.cspace = cspace;
+        Ok(())
+    }
+}
+
+impl<T: PixelStor> ImageOwned<T> {
+    /// Guess the Bayer pattern of a raw single-channel frame.
+    ///
+    /// The mean intensities of the four 2x2 sub-lattices of the image are compared.
+    /// Both green sub-lattices sample the same color, so the diagonal whose means agree
+    /// more closely is taken to be green. The brighter of the remaining two sub-lattices
+    /// is taken to be red, which holds for typical scenes with silicon sensors. The
+    /// existing color space, if Bayer, is ignored.
+    ///
+    /// # Arguments
+    /// - `tolerance`: The relative difference (e.g. `0.05` for 5%) below which two means
+    ///   are considered indistinguishable.
+    ///
+    /// # Returns
+    /// - [`None`] if the image is not a single-channel gray or Bayer image, is smaller
+    ///   than 2x2 pixels, or if the red and blue sub-lattices can not be told apart
+    ///   within `tolerance`, e.g. for a flat frame.
+    pub fn guess_bayer_pattern(&self, tolerance: f64) -> Option<BayerPattern> {
+        match self.cspace {
+            ColorSpace::Gray | ColorSpace::Bayer(_) if self.channels() == 1 => {
+                guess_bayer_pattern(self.as_slice(), self.width(), self.height(), tolerance)
+            }
+            _ => None,
+        }
+    }
+
+    /// Apply `f` to every stored element of the image, producing an image of pixel type `U`.
+    ///
+    /// The width, height, number of channels and color space of the image are preserved.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    ///
+    /// # Arguments
+    /// - `f`: The per-element transform.
+    pub fn map_pixels_to<U: PixelStor, F: Fn(T) -> U + Sync>(&self, f: F) -> ImageOwned<U> {
+        ImageOwned {
+            data: crate::coreimpls::run_map_pixels_to(self.as_slice(), f),
+            width: self.width,
+            height: self.height,
+            channels: self.channels,
+            cspace: self.cspace.clone(),
+        }
+    }
+
+    /// Iterate over the rows of the image, from top to bottom.
+    ///
+    /// Each row is a slice of `width * channels` interleaved elements.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        let row = self.width as usize * self.channels as usize;
+        self.as_slice().chunks_exact(row)
+    }
+
+    /// Iterate mutably over the rows of the image, from top to bottom.
+    ///
+    /// Each row is a slice of `width * channels` interleaved elements.
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [T]> {
+        let row = self.width as usize * self.channels as usize;
+        self.as_mut_slice().chunks_exact_mut(row)
+    }
+
+    /// Iterate over the pixels of the image in row-major order.
+    ///
+    /// Each pixel is a slice of `channels` elements.
+    pub fn pixels(&self) -> impl Iterator<Item = &[T]> {
+        self.as_slice().chunks_exact(self.channels as usize)
+    }
+
+    /// Select a region of interest from the image, padding the parts of the region
+    /// that lie outside the image.
+    ///
+    /// Unlike [`SelectRoi::select_roi`], the region is not shrunk to the image bounds:
+    /// the origin may be negative, and the region may lie partially or wholly outside
+    /// the image. The output is always `width` by `height` pixels, and pixels outside
+    /// the image are set to `fill`. This is useful to extract fixed-size cutouts near
+    /// the edges of a frame. The Bayer pattern of the output is adjusted for the origin.
+    ///
+    /// # Arguments
+    /// - `x`: The x-coordinate of the top-left corner of the ROI.
+    /// - `y`: The y-coordinate of the top-left corner of the ROI.
+    /// - `width`: The width of the ROI.
+    /// - `height`: The height of the ROI.
+    /// - `fill`: The value of the pixels outside the image.
+    ///
+    /// # Errors
+    /// - If the ROI is larger than 65535x65535 pixels.
+    pub fn select_roi_padded(
+        &self,
+        x: isize,
+        y: isize,
+        width: NonZeroUsize,
+        height: NonZeroUsize,
+        fill: T,
+    ) -> Result<ImageOwned<T>, &'static str> {
+        ImageOwned::new(
+            run_select_padded(
+                self.as_slice(),
+                self.width(),
+                self.height(),
+                self.channels() as usize,
+                x,
+                y,
+                width.get(),
+                height.get(),
+                fill,
+            )?,
+            width.get(),
+            height.get(),
+            crop_cspace(&self.cspace, x, y),
+        )
+    }
+
+    /// Paste the image into another image, with the top-left corner of the image at
+    /// `(x, y)` in the destination, e.g. to composite tiles into a mosaic.
+    ///
+    /// Unlike [`CopyRoi::copy_to`], only the region of the destination that the image
+    /// overlaps is written, and all other destination pixels are left untouched. Parts
+    /// of the image that fall outside the destination are ignored.
+    ///
+    /// # Errors
+    /// - If the number of channels of the images do not match.
+    pub fn paste_into(
+        &self,
+        dest: &mut ImageOwned<T>,
+        x: usize,
+        y: usize,
+    ) -> Result<(), &'static str> {
+        if self.channels() != dest.channels() {
+            return Err("Number of channels does not match.");
+        }
+        let (dwid, dhei) = (dest.width(), dest.height());
+        run_paste(
+            self.as_slice(),
+            self.width(),
+            self.height(),
+            dest.as_mut_slice(),
+            dwid,
+            dhei,
+            self.channels() as usize,
+            x,
+            y,
+        );
+        Ok(())
+    }
+
+    /// Rotate the image by a multiple of 90 degrees, clockwise.
+    ///
+    /// The width and height of the output are swapped for rotations by 90 and 270
+    /// degrees. Rotation changes the phase of a Bayer mosaic, hence the Bayer pattern
+    /// of the output is adjusted accordingly.
+    ///
+    /// # Arguments
+    /// - `rotation`: The rotation to apply ([`Rotation`]).
+    pub fn rotate(&self, rotation: Rotation) -> ImageOwned<T> {
+        let (width, height) = match rotation {
+            Rotation::Rotate180 => (self.width, self.height),
+            Rotation::Rotate90 | Rotation::Rotate270 => (self.height, self.width),
+        };
+        ImageOwned {
+            data: run_rotate(
+                self.as_slice(),
+                self.width(),
+                self.height(),
+                self.channels() as usize,
+                rotation,
+            ),
+            width,
+            height,
+            channels: self.channels,
+            cspace: rotate_cspace(&self.cspace, self.width(), self.height(), rotation),
+        }
+    }
+
+    /// Transpose the image, i.e. swap its rows and columns.
+    ///
+    /// The output is `height` pixels wide and `width` pixels high, and the pixel at
+    /// `(x, y)` moves to `(y, x)`. Unlike [`Self::rotate`], the image is mirrored
+    /// about its main diagonal. The Bayer pattern of the output is adjusted to follow
+    /// the red pixels.
+    pub fn transpose(&self) -> ImageOwned<T> {
+        ImageOwned {
+            data: run_transpose(
+                self.as_slice(),
+                self.width(),
+                self.height(),
+                self.channels() as usize,
+            ),
+            width: self.height,
+            height: self.width,
+            channels: self.channels,
+            cspace: transpose_cspace(&self.cspace),
+        }
+    }
+
+    /// Convert an RGB image to the HSV (hue, saturation, value) color space.
+    ///
+    /// The pixel values are scaled to `[0, 1]` using the valid range of the pixel type.
+    /// The hue is in degrees, in `[0, 360)`, and the saturation and value are in `[0, 1]`.
+    /// The output has a [`ColorSpace::Custom`] color space with 3 channels, described as
+    /// `HSV`, and can be converted back using [`ImageOwned::to_rgb_from_hsv`].
+    ///
+    /// # Errors
+    /// - If the image is not an RGB image.
+    pub fn to_hsv(&self) -> Result<ImageOwned<f32>, &'static str> {
+        if self.cspace != ColorSpace::Rgb {
+            return Err("HSV conversion is only supported for RGB images.");
+        }
+        ImageOwned::new(
+            crate::coreimpls::run_rgb_to_hsv(self.as_slice()),
+            self.width(),
+            self.height(),
+            ColorSpace::Custom(3, crate::coreimpls::HSV_DESCRIPTION.to_owned()),
+        )
+    }
+
+    /// Convert the image to a [`ImageOwned`] with [`u8`] pixel type, stretching the
+    /// contrast between the given percentiles.
+    ///
+    /// The `low_pct` and `high_pct` percentiles are computed for each channel; values
+    /// below and above are clipped, and the window in between is linearly mapped to
+    /// `[0, 255]`. Unlike a plain min/max scaling, a few hot pixels do not dominate
+    /// the result.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    ///
+    /// # Arguments
+    /// - `low_pct`: The lower percentile, in `[0, 1]`.
+    /// - `high_pct`: The upper percentile, in `[0, 1]`.
+    ///
+    /// # Errors
+    /// - If `low_pct` or `high_pct` is not in `[0, 1]`, or `low_pct` is not less than `high_pct`.
+    pub fn into_u8_stretched(
+        &self,
+        low_pct: f32,
+        high_pct: f32,
+    ) -> Result<ImageOwned<u8>, &'static str> {
+        Ok(ImageOwned {
+            data: run_stretch_u8(self.as_slice(), self.channels() as usize, low_pct, high_pct)?,
+            width: self.width() as _,
+            height: self.height() as _,
+            cspace: self.cspace.clone(),
+            channels: self.channels(),
+        })
+    }
+}
+
+/// Fixed-point scale of the interpolation weights along each axis, for integer pixel types.
+const RESIZE_WEIGHT_SCALE: f64 = 128.0;
+
+/// Source pixel and interpolation weight for each output coordinate along one axis.
+///
+/// The output pixel centers are mapped onto the source grid, and the weight is that of
+/// the next source pixel, scaled by `scale`.
+fn resize_taps(src: usize, dst: usize, scale: f64) -> Vec<(usize, usize, f64)> {
+    let ratio = src as f64 / dst as f64;
+    (0..dst)
+        .map(|d| {
+            let pos = ((d as f64 + 0.5) * ratio - 0.5).clamp(0.0, (src - 1) as f64);
+            let p0 = pos.floor() as usize;
+            let p1 = (p0 + 1).min(src - 1);
+            let w = (pos - p0 as f64) * scale;
+            (p0, p1, if scale == 1.0 { w } else { w.round() })
+        })
+        .collect()
+}
+
+/// Resample interleaved image data to `dst_w` by `dst_h` pixels.
+fn run_resize<T: PixelStor + Enlargeable>(
+    data: &[T],
+    src_w: usize,
+    src_h: usize,
+    channels: usize,
+    dst_w: usize,
+    dst_h: usize,
+    filter: ResizeFilter,
+) -> Vec<T> {
+    let mut out = vec![T::zero(); dst_w * dst_h * channels];
+    let row_len = dst_w * channels;
+    match filter {
+        ResizeFilter::Nearest => {
+            let cols: Vec<usize> = (0..dst_w)
+                .map(|x| ((2 * x + 1) * src_w / (2 * dst_w)).min(src_w - 1))
+                .collect();
+            let fill = |(y, row): (usize, &mut [T])| {
+                let sy = ((2 * y + 1) * src_h / (2 * dst_h)).min(src_h - 1);
+                let src = &data[sy * src_w * channels..(sy + 1) * src_w * channels];
+                for (px, &sx) in row.chunks_exact_mut(channels).zip(cols.iter()) {
+                    px.copy_from_slice(&src[sx * channels..(sx + 1) * channels]);
+                }
+            };
+            #[cfg(feature = "rayon")]
+            out.par_chunks_exact_mut(row_len).enumerate().for_each(fill);
+            #[cfg(not(feature = "rayon"))]
+            out.chunks_exact_mut(row_len).enumerate().for_each(fill);
+        }
+        ResizeFilter::Bilinear => {
+            // Floating point types interpolate with exact weights, integer types in fixed point
+            let exact = <T::Larger as NumCast>::from(0.5).is_some_and(|h| h != T::Larger::zero());
+            let scale = if exact { 1.0 } else { RESIZE_WEIGHT_SCALE };
+            let cols = resize_taps(src_w, dst_w, scale);
+            let rows = resize_taps(src_h, dst_h, scale);
+            let fill = |(y, row): (usize, &mut [T])| {
+                let zero = T::Larger::zero();
+                let weight = |w: f64| -> T::Larger { NumCast::from(w).unwrap_or(zero) };
+                let full = weight(scale);
+                let total = full * full;
+                let half = if exact {
+                    zero
+                } else {
+                    weight(scale * scale / 2.0)
+                };
+                let (y0, y1, wy) = rows[y];
+                let wy1 = weight(wy);
+                let wy0 = full - wy1;
+                let top = &data[y0 * src_w * channels..(y0 + 1) * src_w * channels];
+                let bot = &data[y1 * src_w * channels..(y1 + 1) * src_w * channels];
+                for (px, &(x0, x1, wx)) in row.chunks_exact_mut(channels).zip(cols.iter()) {
+                    let (x0, x1) = (x0 * channels, x1 * channels);
+                    let wx1 = weight(wx);
+                    let wx0 = full - wx1;
+                    for (ch, v) in px.iter_mut().enumerate() {
+                        let sum = (top[x0 + ch].make_larger() * wx0
+                            + top[x1 + ch].make_larger() * wx1)
+                            * wy0
+                            + (bot[x0 + ch].make_larger() * wx0 + bot[x1 + ch].make_larger() * wx1)
+                                * wy1;
+                        let sum = if sum < zero { sum - half } else { sum + half };
+                        *v = T::clamp_larger(sum / total);
+                    }
+                }
+            };
+            #[cfg(feature = "rayon")]
+            out.par_chunks_exact_mut(row_len).enumerate().for_each(fill);
+            #[cfg(not(feature = "rayon"))]
+            out.chunks_exact_mut(row_len).enumerate().for_each(fill);
+        }
+    }
+    out
+}
+
+impl<T: PixelStor + Enlargeable> ImageOwned<T> {
+    /// Resize the image to `new_w` by `new_h` pixels.
+    ///
+    /// The output pixel centers are mapped onto the input, so that the image is scaled
+    /// about its center. Each channel is resampled independently. Bilinear interpolation
+    /// accumulates the weighted pixels in the larger type of the pixel type (see
+    /// [`Enlargeable`]), and clamps the result back to the range of the pixel type.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    ///
+    /// # Arguments
+    /// - `new_w`: The width of the resized image.
+    /// - `new_h`: The height of the resized image.
+    /// - `filter`: The resampling filter ([`ResizeFilter`]).
+    ///
+    /// # Errors
+    /// - If the image is a Bayer mosaic image, since resampling destroys the mosaic.
+    /// - If the resized image is too large.
+    pub fn resize(
+        &self,
+        new_w: NonZeroUsize,
+        new_h: NonZeroUsize,
+        filter: ResizeFilter,
+    ) -> Result<ImageOwned<T>, &'static str> {
+        if self.cspace.is_bayer() {
+            return Err("Bayer mosaic images can not be resized.");
+        }
+        let (new_w, new_h) = (new_w.get(), new_h.get());
+        if new_w > u16::MAX as usize || new_h > u16::MAX as usize {
+            return Err("Resized image is too large.");
+        }
+        ImageOwned::new(
+            run_resize(
+                self.as_slice(),
+                self.width(),
+                self.height(),
+                self.channels() as usize,
+                new_w,
+                new_h,
+                filter,
+            ),
+            new_w,
+            new_h,
+            self.cspace.clone(),
+        )
+    }
+}
+
+impl ImageOwned<f32> {
+    /// Check whether the image contains any NaN or infinite values.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    pub fn has_non_finite(&self) -> bool {
+        crate::coreimpls::run_has_non_finite(self.as_slice())
+    }
+
+    /// Convert an HSV image, as produced by [`ImageOwned::to_hsv`], to an RGB image.
+    ///
+    /// The hue is in degrees, and wraps around at 360°. The saturation and value are
+    /// clamped to `[0, 1]`.
+    ///
+    /// # Errors
+    /// - If the image does not have a [`ColorSpace::Custom`] color space with 3 channels.
+    pub fn to_rgb_from_hsv(&self) -> Result<ImageOwned<f32>, &'static str> {
+        if !matches!(self.cspace, ColorSpace::Custom(3, _)) {
+            return Err("Image is not an HSV image.");
+        }
+        ImageOwned::new(
+            crate::coreimpls::run_hsv_to_rgb(self.as_slice()),
+            self.width(),
+            self.height(),
+            ColorSpace::Rgb,
+        )
+    }
+}
+
+impl<T: PixelStor> ImageOwned<T> {
+    /// Compute the histogram of a single channel of the image.
+    ///
+    /// The bins uniformly span the valid range of the pixel type, i.e.
+    /// [`PixelStor::DEFAULT_MIN_VALUE`] to [`PixelStor::DEFAULT_MAX_VALUE`].
+    ///
+    /// # Arguments
+    /// - `channel`: The channel to compute the histogram of.
+    /// - `bins`: The number of bins in the histogram.
+    ///
+    /// # Errors
+    /// - If `channel` is out of bounds.
+    /// - If `bins` is zero.
+    pub fn histogram(&self, channel: u8, bins: usize) -> Result<Vec<usize>, &'static str> {
+        if channel >= self.channels {
+            return Err("Channel index out of bounds.");
+        }
+        if bins == 0 {
+            return Err("Number of bins must be non-zero.");
+        }
+        Ok(crate::coreimpls::run_histogram(
+            self.data.as_slice(),
+            self.channels as usize,
+            channel as usize,
+            bins,
+        ))
+    }
+
+    /// Compute the histograms of all channels of the image.
+    ///
+    /// The bins uniformly span the valid range of the pixel type, i.e.
+    /// [`PixelStor::DEFAULT_MIN_VALUE`] to [`PixelStor::DEFAULT_MAX_VALUE`], which is
+    /// `[0, 1]` for floating point images. Out of range values are counted in the
+    /// first or last bin.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    ///
+    /// # Arguments
+    /// - `bins`: The number of bins in each histogram.
+    ///
+    /// # Returns
+    /// One histogram per channel.
+    ///
+    /// # Errors
+    /// - If `bins` is zero.
+    pub fn histograms(&self, bins: usize) -> Result<Vec<Vec<u64>>, &'static str> {
+        if bins == 0 {
+            return Err("Number of bins must be non-zero.");
+        }
+        Ok(crate::coreimpls::run_histograms(
+            self.as_slice(),
+            self.channels as usize,
+            bins,
+        ))
+    }
+
+    /// Equalize the histogram of the image in-place.
+    ///
+    /// Each channel is remapped through its normalized cumulative distribution, so that
+    /// the output spans the valid range of the pixel type. Integer images use one bin per
+    /// value, while floating point images are binned into 4096 bins. Channels with a single
+    /// occupied bin are left unchanged.
+    ///
+    /// # Arguments
+    /// - `channel`: The channel to equalize. If [`None`], every channel is equalized independently.
+    ///
+    /// # Errors
+    /// - If the image is a Bayer mosaic image.
+    /// - If `channel` is out of bounds.
+    pub fn equalize(&mut self, channel: Option<u8>) -> Result<(), &'static str> {
+        if self.cspace.is_bayer() {
+            return Err("Image is not debayered.");
+        }
+        if channel.is_some_and(|ch| ch >= self.channels) {
+            return Err("Channel index out of bounds.");
+        }
+        let bins = crate::coreimpls::histogram_bins::<T>();
+        let maps = (0..self.channels)
+            .map(|ch| {
+                if channel.is_some_and(|sel| sel != ch) {
+                    return None;
+                }
+                let hist = self.histogram(ch, bins).ok()?;
+                crate::coreimpls::equalize_map::<T>(&hist)
+            })
+            .collect::<Vec<_>>();
+        crate::coreimpls::run_lut(self.data.as_mut_slice(), maps.len(), |ch, v| {
+            match &maps[ch] {
+                Some(map) => map[crate::coreimpls::histogram_bin(v, bins)],
+                None => v,
+            }
+        });
+        Ok(())
+    }
+}
+
+impl<T: PixelStor> ImageOwned<T> {
+    /// Sharpen the image in-place with an unsharp mask.
+    ///
+    /// The image is blurred with a Gaussian kernel, and `amount` times the difference
+    /// between the image and the blurred image is added back to the image. Samples where
+    /// the absolute difference does not exceed `threshold` are left unchanged, to avoid
+    /// sharpening noise. The result is clamped to the valid range of the pixel type.
+    ///
+    /// # Arguments
+    /// - `sigma`: The standard deviation of the Gaussian kernel, in pixels.
+    /// - `amount`: The strength of the sharpening.
+    /// - `threshold`: The minimum absolute difference to sharpen.
+    ///
+    /// # Errors
+    /// - If the image is a Bayer mosaic image.
+    /// - If `sigma` is not positive and finite.
+    /// - If `amount` is negative or not finite.
+    pub fn unsharp_mask(
+        &mut self,
+        sigma: f64,
+        amount: f64,
+        threshold: T,
+    ) -> Result<(), &'static str> {
+        if self.cspace.is_bayer() {
+            return Err("Image is not debayered.");
+        }
+        if !sigma.is_finite() || sigma <= 0.0 {
+            return Err("Sigma must be a positive finite number.");
+        }
+        if !amount.is_finite() || amount < 0.0 {
+            return Err("Amount must be a non-negative finite number.");
+        }
+        let (width, height, channels) = (self.width(), self.height(), self.channels as usize);
+        let len = self.len();
+        let data: Vec<f64> = self.data[..len].iter().map(|&v| v.to_f64()).collect();
+        let blurred = crate::coreimpls::gaussian_blur(&data, width, height, channels, sigma);
+        let threshold = threshold.to_f64();
+        let min = T::DEFAULT_MIN_VALUE.to_f64();
+        let max = T::DEFAULT_MAX_VALUE.to_f64();
+        self.data[..len]
+            .iter_mut()
+            .zip(data.iter().zip(blurred.iter()))
+            .for_each(|(px, (&v, &b))| {
+                let diff = v - b;
+                if diff.abs() > threshold {
+                    *px = T::from_f64((v + amount * diff).clamp(min, max));
+                }
+            });
+        Ok(())
+    }
+
+    /// Convolve the image with a horizontal kernel `kx` and a vertical kernel `ky`, e.g.
+    /// to smooth the image with a [`gaussian_kernel`](crate::gaussian_kernel).
+    ///
+    /// Each channel is convolved independently. The kernels are centered on their middle
+    /// element, and samples outside the image are handled according to `border`. The
+    /// result is clamped to the valid range of the pixel type.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    ///
+    /// # Arguments
+    /// - `kx`: The horizontal kernel, of odd length.
+    /// - `ky`: The vertical kernel, of odd length.
+    /// - `border`: The [`BorderMode`] of the convolution.
+    ///
+    /// # Errors
+    /// - If the image is a Bayer mosaic image.
+    /// - If a kernel is empty or has an even length.
+    pub fn convolve_separable(
+        &self,
+        kx: &[f64],
+        ky: &[f64],
+        border: BorderMode,
+    ) -> Result<ImageOwned<T>, &'static str> {
+        let data = crate::coreimpls::convolve_pixels(
+            &self.data[..self.len()],
+            self.width(),
+            self.height(),
+            self.channels as usize,
+            &self.cspace,
+            kx,
+            ky,
+            border,
+        )?;
+        ImageOwned::new(data, self.width(), self.height(), self.cspace.clone())
+    }
+}
+
+impl ImageOwned<u8> {
+    /// Remap every sample of the image in-place through a lookup table.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    pub fn apply_lut(&mut self, lut: &[u8; 256]) {
+        crate::coreimpls::run_lut(self.data.as_mut_slice(), 1, |_, v| lut[v as usize]);
+    }
+
+    /// Remap every sample of the image in-place through a per-channel lookup table.
+    ///
+    /// # Arguments
+    /// - `luts`: One lookup table of 256 entries for each channel.
+    ///
+    /// # Errors
+    /// - If the number of tables does not match the number of channels.
+    /// - If any table does not have 256 entries.
+    pub fn apply_lut_channels(&mut self, luts: &[&[u8]]) -> Result<(), &'static str> {
+        if luts.len() != self.channels as usize {
+            return Err("Number of lookup tables must match the number of channels.");
+        }
+        if luts.iter().any(|lut| lut.len() != 256) {
+            return Err("Lookup tables must have 256 entries.");
+        }
+        crate::coreimpls::run_lut(self.data.as_mut_slice(), luts.len(), |ch, v| {
+            luts[ch][v as usize]
+        });
+        Ok(())
+    }
+}
+
+impl ImageOwned<u16> {
+    /// Remap every sample of the image in-place through a lookup table.
+    ///
+    /// If the table has fewer than 65536 entries, it is assumed to span the full
+    /// range of [`u16`] uniformly, and intermediate values are linearly interpolated.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    ///
+    /// # Errors
+    /// - If the table has fewer than 2 or more than 65536 entries.
+    pub fn apply_lut(&mut self, lut: &[u16]) -> Result<(), &'static str> {
+        crate::coreimpls::check_lut_u16(lut)?;
+        crate::coreimpls::run_lut(self.data.as_mut_slice(), 1, |_, v| {
+            crate::coreimpls::lookup_u16(lut, v)
+        });
+        Ok(())
+    }
+
+    /// Remap every sample of the image in-place through a per-channel lookup table.
+    ///
+    /// Tables are interpolated as in [`ImageOwned::<u16>::apply_lut`].
+    ///
+    /// # Arguments
+    /// - `luts`: One lookup table for each channel.
+    ///
+    /// # Errors
+    /// - If the number of tables does not match the number of channels.
+    /// - If any table has fewer than 2 or more than 65536 entries.
+    pub fn apply_lut_channels(&mut self, luts: &[&[u16]]) -> Result<(), &'static str> {
+        if luts.len() != self.channels as usize {
+            return Err("Number of lookup tables must match the number of channels.");
+        }
+        luts.iter()
+            .try_for_each(|lut| crate::coreimpls::check_lut_u16(lut))?;
+        crate::coreimpls::run_lut(self.data.as_mut_slice(), luts.len(), |ch, v| {
+            crate::coreimpls::lookup_u16(luts[ch], v)
+        });
+        Ok(())
+    }
+}
+
+impl<T: PixelStor + Zero> SelectRoi for ImageOwned<T> {
+    type Output = ImageOwned<T>;
+
+    fn select_roi(
+        &self,
+        x: usize,
+        y: usize,
+        width: std::num::NonZeroUsize,
+        height: std::num::NonZeroUsize,
+    ) -> Result<Self::Output, &'static str> {
+        let swid = self.width();
+        let shei = self.height();
+        if x >= swid || y >= shei {
+            return Err("ROI is out of bounds.");
+        }
+        // The parts of the ROI outside the image are zero
+        let data = run_select_padded(
+            self.as_slice(),
+            swid,
+            shei,
+            self.channels as usize,
+            x as isize,
+            y as isize,
+            width.get(),
+            height.get(),
+            T::zero(),
+        )?;
+        ImageOwned::new(
+            data,
+            width.get(),
+            height.get(),
+            crop_cspace(&self.cspace, x as isize, y as isize),
+        )
+    }
+}
+
+impl<T: PixelStor> Flip for ImageOwned<T> {
+    type Output = ImageOwned<T>;
+
+    fn flip_h(&self) -> Self::Output {
+        let channels = self.channels() as usize;
+        ImageOwned {
+            data: flip_rows(self.as_slice(), self.width(), channels),
+            width: self.width,
+            height: self.height,
+            channels: self.channels,
+            cspace: flip_cspace(&self.cspace, self.width(), true),
+        }
+    }
+
+    fn flip_v(&self) -> Self::Output {
+        let channels = self.channels() as usize;
+        ImageOwned {
+            data: flip_cols(self.as_slice(), self.width(), channels),
+            width: self.width,
+            height: self.height,
+            channels: self.channels,
+            cspace: flip_cspace(&self.cspace, self.height(), false),
+        }
+    }
+}
+
+impl<T: PixelStor + Zero> CopyRoi for ImageOwned<T> {
+    type Output = ImageOwned<T>;
+
+    fn copy_to(&self, dest: &mut Self::Output, x: usize, y: usize) {
+        let channels = self.channels() as usize;
+        let swid = self.width();
+        let shei = self.height();
+        let dwid = dest.width();
+        let dhei = dest.height();
+        let wid = dwid.min(swid - x);
+        let hei = dhei.min(shei - y);
+        dest.data.fill(T::zero());
+        for h in 0..hei {
+            let src = (y + h) * swid + x;
+            let dst = h * dwid;
+            let len = wid * channels;
+            dest.data[dst..dst + len].copy_from_slice(&self.data[src..src + len]);
+        }
+        dest.data.truncate(dwid * dhei * channels);
+    }
+}
+
+impl<T: PixelStor + Enlargeable> Debayer for ImageOwned<T> {
+    type Output = ImageOwned<T>;
+    fn debayer(&self, alg: DemosaicMethod) -> Result<Self::Output, BayerError> {
+        let cfa = self
+            .cspace
+            .clone()
+            .try_into()
+            .map_err(BayerError::InvalidColorSpace)?;
+        if self.channels != 1 {
+            return Err(BayerError::WrongDepth);
+        }
+        let mut dst = vec![T::zero(); self.width() * self.height() * 3];
+        let mut raster = RasterMut::new(self.width(), self.height(), &mut dst);
+        run_demosaic_imageowned(self, cfa, alg, &mut raster)?;
+        Ok(Self::Output {
+            data: dst,
+            width: self.width,
+            height: self.height,
+            channels: 3,
+            cspace: ColorSpace::Rgb,
+        })
+    }
+}
+
+impl<'a, T: PixelStor> From<&ImageRef<'a, T>> for ImageOwned<T> {
+    fn from(data: &ImageRef<'a, T>) -> Self {
+        Self {
+            data: data.data[..data.len].to_vec(),
+            width: data.width,
+            height: data.height,
+            channels: data.channels,
+            cspace: data.cspace.clone(),
+        }
+    }
+}
+
+impl<T: PixelStor + Ord> CalcOptExp for ImageOwned<T> {
+    fn calc_opt_exp(
+        self,
+        eval: &OptimumExposure,
+        exposure: Duration,
+        bin: u8,
+    ) -> Result<(Duration, u16), &'static str> {
+        self.eval_opt_exp(eval, exposure, bin)
+            .map(ExposureOutcome::into_inner)
+    }
+
+    fn eval_opt_exp(
+        mut self,
+        eval: &OptimumExposure,
+        exposure: Duration,
+        bin: u8,
+    ) -> Result<ExposureOutcome, &'static str> {
+        let len = self.data.len();
+        eval.evaluate(self.data.as_mut_slice(), len, exposure, bin)
+    }
+}
+
+mod test {
+
+    #[test]
+    fn test_into_luma() {
+        use crate::{ColorSpace, ImageOwned, ToLuma};
         let data = vec![
             181u8, 178, 118, 183, 85, 131, 82, 143, 196, 108, 64, 33, 174, 43, 18, 236, 19, 179,
             178, 132, 14, 32, 82, 1, 185, 221, 160, 112, 67, 179, 248, 104, 31, 105, 33, 100, 73,
@@ -499,6 +1743,34 @@ mod test {
         assert_eq!(res, (exp, bin as u16));
     }
 
+    #[test]
+    fn test_paste_into() {
+        use crate::{ColorSpace, ImageOwned, ImageRef};
+        let tile = ImageOwned::from_owned(vec![1u8, 2, 3, 4], 2, 2, ColorSpace::Gray).unwrap();
+        let mut canvas = ImageOwned::from_owned(vec![9u8; 16], 4, 4, ColorSpace::Gray).unwrap();
+        tile.paste_into(&mut canvas, 1, 1).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(canvas.as_slice(), &[
+            9, 9, 9, 9,
+            9, 1, 2, 9,
+            9, 3, 4, 9,
+            9, 9, 9, 9,
+        ]);
+        // Only the overlapping region is written
+        tile.paste_into(&mut canvas, 3, 3).unwrap();
+        assert_eq!(&canvas.as_slice()[12..], &[9, 9, 9, 1]);
+        tile.paste_into(&mut canvas, 4, 0).unwrap();
+        assert_eq!(canvas.as_slice()[3], 9);
+
+        let mut data = vec![5u8, 6, 7, 8, 9, 10];
+        let tile = ImageRef::new(&mut data, 1, 2, ColorSpace::Rgb).unwrap();
+        let mut canvas = ImageOwned::from_owned(vec![0u8; 12], 2, 2, ColorSpace::Rgb).unwrap();
+        tile.paste_into(&mut canvas, 1, 0).unwrap();
+        assert_eq!(canvas.as_slice(), &[0, 0, 0, 5, 6, 7, 0, 0, 0, 8, 9, 10]);
+        let mut gray = ImageOwned::from_owned(vec![0u8; 4], 2, 2, ColorSpace::Gray).unwrap();
+        assert!(tile.paste_into(&mut gray, 0, 0).is_err());
+    }
+
     #[test]
     fn test_select_roi() {
         use crate::{CopyRoi, SelectRoi};
@@ -518,4 +1790,788 @@ mod test {
         img.copy_to(&mut roi, 1, 0);
         assert_eq!(roi.as_slice(), &[1, 2, 5, 7, 0, 0]);
     }
+
+    #[test]
+    fn test_select_roi_rgb() {
+        use crate::{ImageProps, SelectRoi};
+        use std::num::NonZero;
+
+        // Each sample is 100 * channel + 10 * y + x
+        let img = crate::ImageOwned::from_fn(3, 2, crate::ColorSpace::Rgb, |x, y, c| {
+            (100 * c + 10 * y + x) as u8
+        })
+        .expect("Failed to create ImageOwned");
+        let roi = img
+            .select_roi(1, 0, NonZero::new(2).unwrap(), NonZero::new(3).unwrap())
+            .expect("Failed to select ROI");
+        assert_eq!(roi.channels(), 3);
+        assert_eq!(
+            roi.as_slice(),
+            &[1, 101, 201, 2, 102, 202, 11, 111, 211, 12, 112, 212, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_select_roi_bayer() {
+        use crate::{BayerPattern, ImageProps, SelectRoi};
+        use std::num::NonZero;
+
+        let img = crate::ImageOwned::new(vec![0u16; 16], 4, 4, BayerPattern::Rggb.into())
+            .expect("Failed to create ImageOwned");
+        let nz = NonZero::new(2).unwrap();
+        for (x, y, pat) in [
+            (0, 0, BayerPattern::Rggb),
+            (1, 0, BayerPattern::Grbg),
+            (0, 1, BayerPattern::Gbrg),
+            (1, 1, BayerPattern::Bggr),
+            (3, 2, BayerPattern::Grbg),
+        ] {
+            let roi = img.select_roi(x, y, nz, nz).expect("Failed to select ROI");
+            assert_eq!(roi.color_space(), pat.into());
+        }
+    }
+
+    #[test]
+    fn test_center_crop() {
+        use crate::{
+            BayerPattern, ColorSpace, DynamicImageRef, GenericImage, GenericImageRef, ImageOwned,
+            ImageProps, ImageRef, SelectRoi, SUBFRAME_X_KEY,
+        };
+        use std::num::NonZeroUsize;
+        let nz = |v| NonZeroUsize::new(v).unwrap();
+        // 4x3 RGB image, each sample is 10 * y + x in all channels
+        let img = ImageOwned::from_fn(4, 3, ColorSpace::Rgb, |x, y, _| (10 * y + x) as u8).unwrap();
+        let sq = img.center_crop_square().unwrap();
+        assert_eq!((sq.width(), sq.height(), sq.channels()), (3, 3, 3));
+        assert_eq!(sq.as_slice()[..6], [0, 0, 0, 1, 1, 1]);
+        assert_eq!(sq.as_slice()[24..], [22, 22, 22]);
+        let roi = img.center_crop(nz(2), nz(1)).unwrap();
+        assert_eq!(roi.as_slice(), &[11, 11, 11, 12, 12, 12]);
+        assert!(img.center_crop(nz(5), nz(1)).is_err());
+
+        let mut data = vec![0u16; 7 * 4];
+        let img = ImageRef::new(&mut data, 7, 4, BayerPattern::Rggb.into()).unwrap();
+        let sq = img.center_crop_square().unwrap();
+        assert_eq!(sq.color_space(), BayerPattern::Grbg.into());
+
+        let img = DynamicImageRef::from(img);
+        let sq = img.center_crop_square().unwrap();
+        assert_eq!((sq.width(), sq.height()), (4, 4));
+        let img = GenericImage::from(GenericImageRef::new(std::time::SystemTime::now(), img));
+        let sq = img.center_crop(nz(3), nz(2)).unwrap();
+        assert_eq!((sq.width(), sq.height()), (3, 2));
+        let origin = sq.get_key(SUBFRAME_X_KEY).unwrap().get_value();
+        assert_eq!(origin.get_value_u64(), Some(2));
+    }
+
+    #[test]
+    fn test_adjust_saturation() {
+        let data = vec![200u8, 100, 50, 10, 20, 30];
+        let mut img = crate::ImageOwned::from_owned(data.clone(), 2, 1, crate::ColorSpace::Rgb)
+            .expect("Failed to create ImageOwned");
+        img.adjust_saturation(1.0).unwrap();
+        assert_eq!(img.as_slice(), &data[..]);
+        img.adjust_saturation(0.0).unwrap();
+        assert_eq!(img.as_slice(), &[124, 124, 124, 18, 18, 18]);
+        let mut img = crate::ImageOwned::from_owned(data, 6, 1, crate::ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        assert!(img.adjust_saturation(2.0).is_err());
+    }
+
+    #[test]
+    fn test_into_vec() {
+        let data = vec![1u8, 2, 3, 4, 5, 6];
+        let img = crate::ImageOwned::from_owned(data, 3, 2, crate::ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        let ptr = img.as_ptr();
+        let data = img.into_vec();
+        assert_eq!(data.as_ptr(), ptr);
+        assert_eq!(data, vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_apply_lut() {
+        let mut lut = [0u8; 256];
+        lut.iter_mut()
+            .enumerate()
+            .for_each(|(i, v)| *v = 255 - i as u8);
+        let mut img =
+            crate::ImageOwned::from_owned(vec![0u8, 10, 255], 3, 1, crate::ColorSpace::Gray)
+                .expect("Failed to create ImageOwned");
+        img.apply_lut(&lut);
+        assert_eq!(img.as_slice(), &[255, 245, 0]);
+        let mut img =
+            crate::ImageOwned::from_owned(vec![0u16, 32768, 65535], 3, 1, crate::ColorSpace::Gray)
+                .expect("Failed to create ImageOwned");
+        img.apply_lut(&[0, 1000]).unwrap();
+        assert_eq!(img.as_slice(), &[0, 500, 1000]);
+        assert!(img.apply_lut(&[0]).is_err());
+        let mut img = crate::ImageOwned::from_owned(
+            vec![0u16, 0, 0, 65535, 65535, 65535],
+            2,
+            1,
+            crate::ColorSpace::Rgb,
+        )
+        .expect("Failed to create ImageOwned");
+        img.apply_lut_channels(&[&[0, 10], &[0, 20], &[5, 30]])
+            .unwrap();
+        assert_eq!(img.as_slice(), &[0, 0, 5, 10, 20, 30]);
+        assert!(img.apply_lut_channels(&[&[0, 10]]).is_err());
+    }
+
+    #[test]
+    fn test_unsharp_mask() {
+        let data = vec![100u8, 100, 100, 100, 200, 200, 200, 200];
+        let mut img = crate::ImageOwned::from_owned(data.clone(), 8, 1, crate::ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        img.unsharp_mask(1.0, 1.0, 0).unwrap();
+        let out = img.as_slice();
+        assert!(out[3] < 100 && out[4] > 200);
+        assert_eq!((out[0], out[7]), (100, 200));
+        let mut img = crate::ImageOwned::from_owned(data.clone(), 8, 1, crate::ColorSpace::Gray)
+            .expect("Failed to create ImageOwned");
+        img.unsharp_mask(1.0, 1.0, 100).unwrap();
+        assert_eq!(img.as_slice(), data.as_slice());
+        assert!(img.unsharp_mask(0.0, 1.0, 0).is_err());
+        let mut img = crate::ImageOwned::from_owned(
+            data,
+            4,
+            2,
+            crate::ColorSpace::Bayer(crate::BayerPattern::Rggb),
+        )
+        .expect("Failed to create ImageOwned");
+        assert!(img.unsharp_mask(1.0, 1.0, 0).is_err());
+    }
+
+    #[test]
+    fn test_convolve_separable() {
+        use crate::{gaussian_kernel, BorderMode, ColorSpace, ImageOwned, ImageRef};
+        let kernel = gaussian_kernel(1.0);
+        assert_eq!(kernel.len(), 7);
+        assert!((kernel.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        assert_eq!(gaussian_kernel(0.0), vec![1.0]);
+
+        // A box filter along the rows of a two channel image
+        let data = vec![0u16, 90, 30, 90, 60, 90, 90, 90];
+        let img = ImageOwned::from_owned(data, 4, 1, ColorSpace::Custom(2, "GA".into())).unwrap();
+        let kx = [1.0 / 3.0; 3];
+        let out = img
+            .convolve_separable(&kx, &[1.0], BorderMode::Zero)
+            .unwrap();
+        assert_eq!(out.as_slice(), &[10, 60, 30, 90, 60, 90, 50, 60]);
+        let out = img
+            .convolve_separable(&kx, &[1.0], BorderMode::Replicate)
+            .unwrap();
+        assert_eq!(out.as_slice(), &[10, 90, 30, 90, 60, 90, 80, 90]);
+        let out = img
+            .convolve_separable(&kx, &[1.0], BorderMode::Mirror)
+            .unwrap();
+        assert_eq!(out.as_slice(), &[20, 90, 30, 90, 60, 90, 70, 90]);
+
+        // A vertical kernel, clamped to the range of the pixel type
+        let mut data = vec![200u8, 100, 200];
+        let img = ImageRef::new(&mut data, 1, 3, ColorSpace::Gray).unwrap();
+        let out = img
+            .convolve_separable(&[1.0], &[1.0, 1.0, 0.0], BorderMode::Zero)
+            .unwrap();
+        assert_eq!(out.as_slice(), &[200, 255, 255]);
+        assert!(img
+            .convolve_separable(&[0.5, 0.5], &[1.0], BorderMode::Zero)
+            .is_err());
+        assert!(img
+            .convolve_separable(&[1.0], &[], BorderMode::Zero)
+            .is_err());
+
+        let img =
+            ImageOwned::from_owned(vec![0u8; 4], 2, 2, crate::BayerPattern::Rggb.into()).unwrap();
+        assert!(img
+            .convolve_separable(&[1.0], &[1.0], BorderMode::Zero)
+            .is_err());
+    }
+
+    #[test]
+    fn test_equalize() {
+        let mut img = crate::ImageOwned::from_owned(
+            vec![10u8, 0, 10, 0, 20, 0, 20, 0],
+            4,
+            1,
+            crate::ColorSpace::Custom(2, "GrayAlpha".into()),
+        )
+        .expect("Failed to create ImageOwned");
+        assert_eq!(img.histogram(0, 4).unwrap(), vec![4, 0, 0, 0]);
+        assert!(img.histogram(2, 4).is_err());
+        img.equalize(None).unwrap();
+        assert_eq!(img.as_slice(), &[0, 0, 0, 0, 255, 0, 255, 0]);
+        let mut img =
+            crate::ImageOwned::from_owned(vec![0.2f32, 0.4, 0.6], 3, 1, crate::ColorSpace::Gray)
+                .expect("Failed to create ImageOwned");
+        img.equalize(Some(0)).unwrap();
+        assert_eq!(img.as_slice(), &[0.0, 0.5, 1.0]);
+        assert!(img.equalize(Some(1)).is_err());
+        let mut img = crate::ImageOwned::<u16>::zeros(
+            2,
+            2,
+            crate::ColorSpace::Bayer(crate::BayerPattern::Rggb),
+        )
+        .expect("Failed to create ImageOwned");
+        assert!(img.equalize(None).is_err());
+    }
+
+    #[test]
+    fn test_planar() {
+        let data = vec![1u8, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+        let img = crate::ImageOwned::from_owned(data.clone(), 2, 2, crate::ColorSpace::Rgb)
+            .expect("Failed to create ImageOwned");
+        let planar = img.to_planar();
+        assert_eq!(planar, vec![1, 4, 7, 10, 2, 5, 8, 11, 3, 6, 9, 12]);
+        let back = crate::ImageOwned::from_planar(&planar, 2, 2, crate::ColorSpace::Rgb).unwrap();
+        assert_eq!(back, img);
+        assert!(
+            crate::ImageOwned::from_planar(&planar[..6], 2, 2, crate::ColorSpace::Rgb).is_err()
+        );
+    }
+
+    #[test]
+    fn test_remove_alpha_in_place() {
+        use crate::{ColorSpace, ImageOwned, ImageProps};
+        let data = vec![1u8, 2, 3, 255, 4, 5, 6, 255, 7, 8, 9, 255, 10, 11, 12, 255];
+        let mut img = ImageOwned::from_owned(data, 2, 2, ColorSpace::Rgba).unwrap();
+        let (ptr, cap) = (img.as_ptr(), img.data.capacity());
+        img.remove_alpha_in_place().unwrap();
+        assert_eq!(img.as_slice(), &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
+        assert_eq!(img.channels(), 3);
+        assert_eq!(img.color_space(), ColorSpace::Rgb);
+        assert_eq!(img.as_ptr(), ptr);
+        assert_eq!(img.data.capacity(), cap);
+        assert!(img.remove_alpha_in_place().is_err());
+        let data = vec![1u16, 100, 2, 100];
+        let mut img = ImageOwned::from_owned(data, 2, 1, ColorSpace::GrayAlpha).unwrap();
+        img.remove_alpha_in_place().unwrap();
+        assert_eq!(img.as_slice(), &[1, 2]);
+        assert_eq!(img.color_space(), ColorSpace::Gray);
+        let mut img =
+            ImageOwned::from_owned(vec![1u8, 2], 1, 1, ColorSpace::Custom(2, "UV".into())).unwrap();
+        assert!(img.remove_alpha_in_place().is_err());
+    }
+
+    #[test]
+    fn test_to_fits_bytes() {
+        use crate::{ColorSpace, ImageOwned};
+        let img =
+            ImageOwned::from_owned(vec![0u16, 1, 0x8000, 0xffff], 2, 2, ColorSpace::Gray).unwrap();
+        assert_eq!(
+            img.to_fits_bytes(),
+            [(-32768i16), -32767, 0, 32767]
+                .iter()
+                .flat_map(|v| v.to_be_bytes())
+                .collect::<Vec<_>>()
+        );
+        let img = ImageOwned::from_owned(vec![0.5f32, -2.0], 2, 1, ColorSpace::Gray).unwrap();
+        assert_eq!(
+            img.to_fits_bytes(),
+            [0x3f, 0x00, 0x00, 0x00, 0xc0, 0x00, 0x00, 0x00]
+        );
+        let img = ImageOwned::from_owned(vec![7u8, 200], 2, 1, ColorSpace::Gray).unwrap();
+        assert_eq!(img.to_fits_bytes(), [7, 200]);
+    }
+
+    #[test]
+    fn test_checked_sub() {
+        use crate::{BayerPattern, ColorSpace, ImageOwned, ImageRef};
+        let light =
+            ImageOwned::from_owned(vec![10u16, 500, 65535, 0], 2, 2, ColorSpace::Gray).unwrap();
+        let dark = ImageOwned::from_owned(vec![20u16, 100, 1, 0], 2, 2, ColorSpace::Gray).unwrap();
+        let out = light.checked_sub(&dark).unwrap();
+        assert_eq!(out.as_slice(), &[0, 400, 65534, 0]);
+        let mut light = vec![-100i16, 32767, 0, 5];
+        let mut dark = vec![i16::MAX, -1, 0, 6];
+        let light = ImageRef::new(&mut light, 2, 2, ColorSpace::Gray).unwrap();
+        let dark = ImageRef::new(&mut dark, 2, 2, ColorSpace::Gray).unwrap();
+        let out = light.checked_sub(&dark).unwrap();
+        assert_eq!(out.as_slice(), &[i16::MIN, i16::MAX, 0, -1]);
+        let light = ImageOwned::from_owned(vec![0.5f32, 0.25], 2, 1, ColorSpace::Gray).unwrap();
+        let dark = ImageOwned::from_owned(vec![0.25f32, 0.5], 2, 1, ColorSpace::Gray).unwrap();
+        assert_eq!(light.checked_sub(&dark).unwrap().as_slice(), &[0.25, 0.0]);
+        let other = ImageOwned::from_owned(vec![0.0f32; 2], 1, 2, ColorSpace::Gray).unwrap();
+        assert_eq!(light.checked_sub(&other), Err("Image widths do not match."));
+        let other =
+            ImageOwned::from_owned(vec![0.0f32; 2], 2, 1, BayerPattern::Rggb.into()).unwrap();
+        assert_eq!(
+            light.checked_sub(&other),
+            Err("Image color spaces do not match.")
+        );
+    }
+
+    #[test]
+    fn test_flat_divide() {
+        use crate::{ColorSpace, DynamicImageOwned, ImageOwned};
+        let img =
+            ImageOwned::from_owned(vec![100u16, 200, 60000, 7], 2, 2, ColorSpace::Gray).unwrap();
+        let flat =
+            ImageOwned::from_owned(vec![50u16, 300, 100, 0], 2, 2, ColorSpace::Gray).unwrap();
+        let out = img.flat_divide(&flat, 150.0).unwrap();
+        assert_eq!(out.as_slice(), &[300, 100, 65535, 65535]);
+        assert!(img.flat_divide(&flat, f64::NAN).is_err());
+        let flat = ImageOwned::from_owned(vec![1u16; 4], 4, 1, ColorSpace::Gray).unwrap();
+        assert_eq!(
+            img.flat_divide(&flat, 1.0),
+            Err("Image widths do not match.")
+        );
+        let img = ImageOwned::from_owned(vec![0.5f32, 0.2], 2, 1, ColorSpace::Gray).unwrap();
+        let flat = ImageOwned::from_owned(vec![0.25f32, 0.8], 2, 1, ColorSpace::Gray).unwrap();
+        let out = img.flat_divide(&flat, 0.5).unwrap();
+        assert_eq!(out.as_slice(), &[1.0, 0.125]);
+        let img = DynamicImageOwned::from(img);
+        let out = img.flat_divide(&flat.clone().into(), 0.5).unwrap();
+        assert_eq!(out.as_slice_f32(), Some(&[1.0, 0.125][..]));
+        let flat = ImageOwned::from_owned(vec![1u8, 1], 2, 1, ColorSpace::Gray).unwrap();
+        assert_eq!(
+            img.flat_divide(&flat.into(), 1.0),
+            Err("Image pixel types do not match.")
+        );
+    }
+
+    #[test]
+    fn test_histograms() {
+        use crate::{ColorSpace, DynamicImageOwned, GenericImage, GenericImageOwned, ImageOwned};
+        let data = vec![0u8, 255, 64, 128, 255, 0, 191, 10, 127, 0, 254, 1];
+        let img = ImageOwned::from_owned(data, 2, 2, ColorSpace::Rgb).unwrap();
+        let hist = img.histograms(4).unwrap();
+        assert_eq!(
+            hist,
+            vec![vec![2, 0, 2, 0], vec![1, 0, 0, 3], vec![2, 2, 0, 0]]
+        );
+        assert!(img.histograms(0).is_err());
+        let img = ImageOwned::from_owned(
+            vec![-0.5f32, 0.0, 0.49, 0.5, 1.0, 2.0],
+            3,
+            2,
+            ColorSpace::Gray,
+        )
+        .unwrap();
+        assert_eq!(img.histograms(2).unwrap(), vec![vec![3, 3]]);
+        let img = GenericImage::from(GenericImageOwned::new(
+            std::time::SystemTime::now(),
+            DynamicImageOwned::from(img),
+        ));
+        assert_eq!(img.histograms(2).unwrap(), vec![vec![3, 3]]);
+        let data: Vec<u16> = (0..20000).map(|v| (v * 3) as u16).collect();
+        let img =
+            ImageOwned::from_owned(data, 100, 100, ColorSpace::Custom(2, "UV".into())).unwrap();
+        let hist = img.histograms(1).unwrap();
+        assert_eq!(hist, vec![vec![10000], vec![10000]]);
+    }
+
+    #[test]
+    fn test_rotate() {
+        use crate::{BayerPattern, ColorSpace, ImageOwned, ImageProps, ImageRef, Rotation};
+        // 3x2 RGB image, with pixel values 0..6 in the first channel
+        let data: Vec<u8> = (0..6).flat_map(|v| [v, v + 10, v + 20]).collect();
+        let img = ImageOwned::from_owned(data, 3, 2, ColorSpace::Rgb).unwrap();
+        let first = |img: &ImageOwned<u8>| {
+            img.as_slice()
+                .iter()
+                .step_by(3)
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        let rot = img.rotate(Rotation::Rotate90);
+        assert_eq!((rot.width(), rot.height()), (2, 3));
+        assert_eq!(first(&rot), &[3, 0, 4, 1, 5, 2]);
+        assert_eq!(&rot.as_slice()[..3], &[3, 13, 23]);
+        assert_eq!(first(&img.rotate(Rotation::Rotate180)), &[5, 4, 3, 2, 1, 0]);
+        assert_eq!(first(&img.rotate(Rotation::Rotate270)), &[2, 5, 1, 4, 0, 3]);
+        let mut rot = img.clone();
+        for _ in 0..4 {
+            rot = rot.rotate(Rotation::Rotate90);
+        }
+        assert_eq!(rot, img);
+        assert_eq!(
+            img.rotate(Rotation::Rotate90).rotate(Rotation::Rotate270),
+            img
+        );
+        assert_eq!(
+            img.rotate(Rotation::Rotate180).rotate(Rotation::Rotate180),
+            img
+        );
+
+        // The Bayer pattern follows the red pixels
+        let mut data = vec![0u16; 12];
+        let img = ImageRef::new(&mut data, 4, 3, BayerPattern::Rggb.into()).unwrap();
+        assert_eq!(
+            img.rotate(Rotation::Rotate90).color_space(),
+            BayerPattern::Rggb.into()
+        );
+        assert_eq!(
+            img.rotate(Rotation::Rotate180).color_space(),
+            BayerPattern::Grbg.into()
+        );
+        assert_eq!(
+            img.rotate(Rotation::Rotate270).color_space(),
+            BayerPattern::Gbrg.into()
+        );
+        let mut data = vec![0u16; 16];
+        let img = ImageRef::new(&mut data, 4, 4, BayerPattern::Rggb.into()).unwrap();
+        let mut rot = img.rotate(Rotation::Rotate90);
+        assert_eq!(rot.color_space(), BayerPattern::Grbg.into());
+        for _ in 0..3 {
+            rot = rot.rotate(Rotation::Rotate90);
+        }
+        assert_eq!(rot.color_space(), BayerPattern::Rggb.into());
+    }
+
+    #[test]
+    fn test_flip() {
+        use crate::{BayerPattern, ColorSpace, Flip, ImageOwned, ImageProps, ImageRef};
+        let mut data: Vec<u8> = (0..12).collect();
+        let img = ImageRef::new(&mut data, 2, 2, ColorSpace::Rgb).unwrap();
+        let flipped = img.flip_h();
+        assert_eq!(flipped.as_slice(), &[3, 4, 5, 0, 1, 2, 9, 10, 11, 6, 7, 8]);
+        assert_eq!(flipped.color_space(), ColorSpace::Rgb);
+        let flipped = img.flip_v();
+        assert_eq!(flipped.as_slice(), &[6, 7, 8, 9, 10, 11, 0, 1, 2, 3, 4, 5]);
+        let cspace = ColorSpace::Custom(2, "UV".to_owned());
+        let img = ImageOwned::from_owned((0..12u16).collect(), 3, 2, cspace.clone()).unwrap();
+        let flipped = img.flip_h();
+        assert_eq!(flipped.as_slice(), &[4, 5, 2, 3, 0, 1, 10, 11, 8, 9, 6, 7]);
+        assert_eq!(flipped.color_space(), cspace);
+        assert_eq!(flipped.flip_h(), img);
+        // Flipping an even dimension changes the phase of the mosaic
+        let img = ImageOwned::from_owned(vec![0u8; 12], 4, 3, BayerPattern::Rggb.into()).unwrap();
+        assert_eq!(img.flip_h().color_space(), BayerPattern::Grbg.into());
+        assert_eq!(img.flip_v().color_space(), BayerPattern::Rggb.into());
+    }
+
+    #[test]
+    fn test_hsv() {
+        use crate::{ColorSpace, ImageOwned, ImageProps};
+        let data = vec![
+            255u8, 0, 0, 0, 255, 0, 0, 0, 255, 255, 0, 128, 51, 51, 51, 255, 255, 0,
+        ];
+        let img = ImageOwned::from_owned(data.clone(), 3, 2, ColorSpace::Rgb).unwrap();
+        let hsv = img.to_hsv().unwrap();
+        assert_eq!(hsv.width(), 3);
+        assert_eq!(hsv.height(), 2);
+        let hues: Vec<f32> = hsv.as_slice().iter().step_by(3).copied().collect();
+        let expected = [0.0, 120.0, 240.0, 360.0 - 128.0 / 255.0 * 60.0, 0.0, 60.0];
+        for (h, e) in hues.iter().zip(expected.iter()) {
+            assert!((h - e).abs() < 1e-3);
+        }
+        assert_eq!(&hsv.as_slice()[12..15], &[0.0, 0.0, 0.2]);
+        let rgb = hsv.to_rgb_from_hsv().unwrap();
+        assert_eq!(rgb.color_space(), ColorSpace::Rgb);
+        for (a, &b) in rgb.as_slice().iter().zip(data.iter()) {
+            assert_eq!((a * 255.0).round() as u8, b);
+        }
+        let mut hsv = hsv;
+        hsv.as_mut_slice()[0] = 720.0;
+        assert!(hsv.to_rgb_from_hsv().unwrap().as_slice()[0] > 0.999);
+        assert!(rgb.to_rgb_from_hsv().is_err());
+        let gray = ImageOwned::from_owned(vec![0u8; 4], 2, 2, ColorSpace::Gray).unwrap();
+        assert!(gray.to_hsv().is_err());
+    }
+
+    #[test]
+    fn test_zeros() {
+        use crate::{ColorSpace, ImageOwned, ImageProps, RefImageError};
+        let img = ImageOwned::<u16>::zeros(3, 2, ColorSpace::Rgb).unwrap();
+        assert_eq!(img.channels(), 3);
+        assert_eq!(img.as_slice(), &[0; 18]);
+        let img = ImageOwned::filled(2, 2, ColorSpace::Gray, 7u8).unwrap();
+        assert_eq!(img.as_slice(), &[7; 4]);
+        assert!(ImageOwned::<u8>::zeros(0, 2, ColorSpace::Gray).is_err());
+        let empty = ColorSpace::Custom(0, "EMPTY".into());
+        assert_eq!(
+            ImageOwned::<u8>::zeros(2, 2, empty.clone()),
+            Err(RefImageError::NoChannels)
+        );
+        assert_eq!(
+            ImageOwned::filled(2, 2, empty, 1u16),
+            Err(RefImageError::NoChannels)
+        );
+    }
+
+    #[test]
+    fn test_from_fn() {
+        use crate::{ColorSpace, ImageOwned, ImageProps, RefImageError};
+        let img = ImageOwned::from_fn(3, 2, ColorSpace::Rgb, |x, y, ch| {
+            (100 * ch + 10 * y + x) as u16
+        })
+        .unwrap();
+        assert_eq!((img.width(), img.height(), img.channels()), (3, 2, 3));
+        assert_eq!(&img.as_slice()[..6], &[0, 100, 200, 1, 101, 201]);
+        assert_eq!(&img.as_slice()[15..], &[12, 112, 212]);
+        assert_eq!(
+            ImageOwned::from_fn(2, 2, ColorSpace::Custom(0, "EMPTY".into()), |_, _, _| 0u8),
+            Err(RefImageError::NoChannels)
+        );
+        assert_eq!(
+            ImageOwned::from_fn(2, 0, ColorSpace::Gray, |_, _, _| 0u8),
+            Err(RefImageError::ZeroHeight)
+        );
+    }
+
+    #[test]
+    fn test_resize() {
+        use crate::{ColorSpace, ImageOwned, ImageProps, ResizeFilter};
+        use std::num::NonZeroUsize;
+        let nz = |v| NonZeroUsize::new(v).unwrap();
+
+        let img = ImageOwned::from_owned(vec![0u8, 100, 200, 50], 2, 2, ColorSpace::Gray).unwrap();
+        let up = img.resize(nz(4), nz(4), ResizeFilter::Nearest).unwrap();
+        assert_eq!((up.width(), up.height()), (4, 4));
+        assert_eq!(
+            up.as_slice(),
+            &[0, 0, 100, 100, 0, 0, 100, 100, 200, 200, 50, 50, 200, 200, 50, 50]
+        );
+        let up = ImageOwned::from_owned(vec![0u8, 100], 2, 1, ColorSpace::Gray)
+            .unwrap()
+            .resize(nz(4), nz(1), ResizeFilter::Bilinear)
+            .unwrap();
+        assert_eq!(up.as_slice(), &[0, 25, 75, 100]);
+
+        let data: Vec<u16> = (0..16).map(|v| v * 10).collect();
+        let img = ImageOwned::from_owned(data, 4, 4, ColorSpace::Gray).unwrap();
+        let down = img.resize(nz(2), nz(2), ResizeFilter::Bilinear).unwrap();
+        assert_eq!(down.as_slice(), &[25, 45, 105, 125]);
+
+        let up = ImageOwned::from_owned(vec![0.0f32, 1.0], 2, 1, ColorSpace::Gray)
+            .unwrap()
+            .resize(nz(4), nz(1), ResizeFilter::Bilinear)
+            .unwrap();
+        assert_eq!(up.as_slice(), &[0.0, 0.25, 0.75, 1.0]);
+        let up = ImageOwned::from_owned(vec![-100i16, 100], 2, 1, ColorSpace::Gray)
+            .unwrap()
+            .resize(nz(4), nz(1), ResizeFilter::Bilinear)
+            .unwrap();
+        assert_eq!(up.as_slice(), &[-100, -50, 50, 100]);
+
+        let rgb = ImageOwned::from_owned(vec![10u8, 20, 30], 1, 1, ColorSpace::Rgb).unwrap();
+        let up = rgb.resize(nz(2), nz(2), ResizeFilter::Bilinear).unwrap();
+        assert_eq!(up.color_space(), ColorSpace::Rgb);
+        assert_eq!(up.as_slice(), &[10, 20, 30].repeat(4));
+
+        let bayer = ImageOwned::from_owned(
+            vec![0u8; 16],
+            4,
+            4,
+            ColorSpace::Bayer(crate::BayerPattern::Rggb),
+        )
+        .unwrap();
+        assert!(bayer.resize(nz(2), nz(2), ResizeFilter::Nearest).is_err());
+    }
+
+    #[test]
+    fn test_from_u8_endian() {
+        use crate::{ColorSpace, ImageOwned};
+        let be = [0x12u8, 0x34, 0xab, 0xcd, 0xff, 0xfe, 0x00, 0x01];
+        let img = ImageOwned::<u16>::from_u8_be(&be, 2, 2, ColorSpace::Gray).unwrap();
+        assert_eq!(img.as_slice(), &[0x1234, 0xabcd, 0xfffe, 0x0001]);
+        let img = ImageOwned::<u16>::from_u8_le(&be, 2, 2, ColorSpace::Gray).unwrap();
+        assert_eq!(img.as_slice(), &[0x3412, 0xcdab, 0xfeff, 0x0100]);
+        let img = ImageOwned::<i16>::from_u8_be(&be, 2, 2, ColorSpace::Gray).unwrap();
+        assert_eq!(img.as_slice()[2], -2);
+        let img = ImageOwned::<u32>::from_u8_be(&be, 2, 1, ColorSpace::Gray).unwrap();
+        assert_eq!(img.as_slice(), &[0x1234abcd, 0xfffe0001]);
+        // Unaligned input is accepted
+        let img = ImageOwned::<u16>::from_u8_be(&be[1..7], 3, 1, ColorSpace::Gray).unwrap();
+        assert_eq!(img.as_slice(), &[0x34ab, 0xcdff, 0xfe00]);
+        let img = ImageOwned::<u8>::from_u8_be(&be, 4, 2, ColorSpace::Gray).unwrap();
+        assert_eq!(img.as_slice(), &be);
+        assert!(ImageOwned::<u16>::from_u8_le(&be[..7], 2, 2, ColorSpace::Gray).is_err());
+    }
+
+    #[test]
+    fn test_guess_bayer_pattern() {
+        use crate::{BayerPattern, ColorSpace, ImageOwned, ImageRef};
+        // Values at (x % 2, y % 2) for each pattern
+        let cases = [
+            (BayerPattern::Rggb, [[200u16, 120], [120, 40]]),
+            (BayerPattern::Bggr, [[40, 120], [120, 200]]),
+            (BayerPattern::Grbg, [[120, 200], [40, 120]]),
+            (BayerPattern::Gbrg, [[120, 40], [200, 120]]),
+        ];
+        for (pattern, cell) in cases {
+            let data: Vec<u16> = (0..6)
+                .flat_map(|y| (0..6).map(move |x| cell[y % 2][x % 2] + (x + y) as u16))
+                .collect();
+            let img = ImageOwned::from_owned(data, 6, 6, ColorSpace::Gray).unwrap();
+            assert_eq!(img.guess_bayer_pattern(0.05), Some(pattern));
+        }
+        let mut data: Vec<u8> = (0..16).map(|i| 100 + (i % 2)).collect();
+        let img = ImageRef::new(&mut data, 4, 4, ColorSpace::Gray).unwrap();
+        assert_eq!(img.guess_bayer_pattern(0.05), None);
+        let img = ImageOwned::from_owned(vec![0u8; 12], 2, 2, ColorSpace::Rgb).unwrap();
+        assert_eq!(img.guess_bayer_pattern(0.05), None);
+    }
+
+    #[test]
+    fn test_apply_gamma() {
+        use crate::{ColorSpace, DynamicImageRef, ImageOwned, ImageRef, MutImageData};
+        let data: Vec<u8> = (0..=255).collect();
+        let mut img = ImageOwned::from_owned(data.clone(), 16, 16, ColorSpace::Gray).unwrap();
+        img.apply_gamma(1.0).unwrap();
+        assert_eq!(img.as_slice(), data.as_slice());
+        img.apply_gamma(0.5).unwrap();
+        assert_eq!(img.as_slice()[64], 128);
+        assert_eq!((img.as_slice()[0], img.as_slice()[255]), (0, 255));
+        assert!(img.apply_gamma(0.0).is_err());
+        assert!(img.apply_gamma(f64::NAN).is_err());
+
+        // The alpha channel is not modified
+        let mut data = vec![0.25f32, 0.25, 0.81, 0.81];
+        let img = ImageRef::new(&mut data, 2, 1, ColorSpace::GrayAlpha).unwrap();
+        let mut img = DynamicImageRef::from(img);
+        img.apply_gamma(0.5).unwrap();
+        assert_eq!(img.as_slice_f32().unwrap(), &[0.5, 0.25, 0.9, 0.81]);
+        // Every band of a custom color space is corrected
+        let mut data = vec![0.25f32, 0.25, 0.81, 0.81];
+        let mut img = ImageRef::new(&mut data, 1, 1, ColorSpace::Custom(4, "RGBN".into())).unwrap();
+        img.apply_gamma(0.5).unwrap();
+        assert_eq!(img.as_slice(), &[0.5, 0.5, 0.9, 0.9]);
+    }
+
+    #[test]
+    fn test_select_roi_padded() {
+        use crate::{BayerPattern, ColorSpace, DynamicImageOwned, ImageOwned, ImageProps};
+        use std::num::NonZeroUsize;
+        let nz = |v| NonZeroUsize::new(v).unwrap();
+        let data: Vec<u8> = (1..=9).collect();
+        let img = ImageOwned::from_owned(data, 3, 3, ColorSpace::Gray).unwrap();
+        let roi = img.select_roi_padded(-1, -1, nz(3), nz(3), 0).unwrap();
+        assert_eq!(roi.as_slice(), &[0, 0, 0, 0, 1, 2, 0, 4, 5]);
+        let roi = img.select_roi_padded(2, 1, nz(2), nz(3), 99).unwrap();
+        assert_eq!(roi.as_slice(), &[6, 99, 9, 99, 99, 99]);
+        let roi = img.select_roi_padded(5, -4, nz(2), nz(1), 7).unwrap();
+        assert_eq!(roi.as_slice(), &[7, 7]);
+        // Oversized regions are rejected before allocating, and far origins do not overflow
+        assert!(img.select_roi_padded(0, 0, nz(1 << 16), nz(1), 0).is_err());
+        assert!(img
+            .select_roi_padded(0, 0, nz(1), nz(usize::MAX), 0)
+            .is_err());
+        let roi = img.select_roi_padded(isize::MAX, isize::MAX, nz(2), nz(2), 3);
+        assert_eq!(roi.unwrap().as_slice(), &[3, 3, 3, 3]);
+
+        let data: Vec<u16> = (0..12).collect();
+        let img = ImageOwned::from_owned(data, 2, 2, ColorSpace::Rgb).unwrap();
+        let roi = img.select_roi_padded(1, 0, nz(2), nz(1), 0).unwrap();
+        assert_eq!(roi.as_slice(), &[3, 4, 5, 0, 0, 0]);
+
+        let img = ImageOwned::from_owned(vec![0u8; 16], 4, 4, BayerPattern::Rggb.into()).unwrap();
+        let roi = img.select_roi_padded(-1, 0, nz(2), nz(2), 0).unwrap();
+        assert_eq!(roi.color_space(), BayerPattern::Grbg.into());
+        let roi = DynamicImageOwned::from(img)
+            .select_roi_padded(1, -1, nz(2), nz(2), 300.0)
+            .unwrap();
+        assert_eq!(roi.color_space(), BayerPattern::Bggr.into());
+        assert_eq!(roi.as_slice_u8(), Some(&[255u8, 255, 0, 0][..]));
+    }
+
+    #[test]
+    fn test_sanitize() {
+        use crate::{ColorSpace, DynamicImageOwned, ImageOwned, MutImageData};
+        let data = vec![0.5f32, f32::NAN, f32::INFINITY, f32::NEG_INFINITY];
+        let mut img = ImageOwned::from_owned(data, 2, 2, ColorSpace::Gray).unwrap();
+        assert!(img.has_non_finite());
+        let out = DynamicImageOwned::from(img.clone()).into_u8();
+        assert_eq!(out.as_slice_u8().unwrap(), &[128, 0, 255, 0]);
+        img.sanitize(0.25);
+        assert!(!img.has_non_finite());
+        assert_eq!(img.as_slice(), &[0.5, 0.25, 0.25, 0.25]);
+
+        let mut img = DynamicImageOwned::from(
+            ImageOwned::from_owned(vec![f32::NAN; 4], 2, 2, ColorSpace::Gray).unwrap(),
+        );
+        assert!(img.has_non_finite());
+        img.sanitize(0.0);
+        assert!(!img.has_non_finite());
+        let mut img = DynamicImageOwned::from(
+            ImageOwned::from_owned(vec![7u16; 4], 2, 2, ColorSpace::Gray).unwrap(),
+        );
+        assert!(!img.has_non_finite());
+        img.sanitize(0.0);
+        assert_eq!(img.as_slice_u16().unwrap(), &[7; 4]);
+    }
+
+    #[test]
+    fn test_transpose() {
+        use crate::{
+            BayerPattern, ColorSpace, DynamicImageOwned, ImageOwned, ImageProps, ImageRef,
+        };
+        // 3x2 RGB image, with pixel values 0..6 in the first channel
+        let data: Vec<u8> = (0..6).flat_map(|v| [v, v + 10, v + 20]).collect();
+        let img = ImageOwned::from_owned(data, 3, 2, ColorSpace::Rgb).unwrap();
+        let tr = img.transpose();
+        assert_eq!((tr.width(), tr.height()), (2, 3));
+        assert_eq!(
+            tr.as_slice(),
+            &[0, 10, 20, 3, 13, 23, 1, 11, 21, 4, 14, 24, 2, 12, 22, 5, 15, 25]
+        );
+        assert_eq!(tr.transpose(), img);
+        assert_eq!(
+            DynamicImageOwned::from(img.clone()).transpose(),
+            DynamicImageOwned::from(tr)
+        );
+
+        // The Bayer pattern follows the red pixels
+        let mut data = vec![0u16; 12];
+        for (pat, expected) in [
+            (BayerPattern::Rggb, BayerPattern::Rggb),
+            (BayerPattern::Bggr, BayerPattern::Bggr),
+            (BayerPattern::Grbg, BayerPattern::Gbrg),
+            (BayerPattern::Gbrg, BayerPattern::Grbg),
+        ] {
+            let img = ImageRef::new(&mut data, 4, 3, pat.into()).unwrap();
+            let tr = img.transpose();
+            assert_eq!(tr.color_space(), expected.into());
+            assert_eq!((tr.width(), tr.height()), (3, 4));
+            assert_eq!(tr.transpose().color_space(), pat.into());
+        }
+    }
+
+    #[test]
+    fn test_rows() {
+        use crate::{ColorSpace, ImageOwned};
+        let data = vec![1u8, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+        let mut img = ImageOwned::from_owned(data, 2, 2, ColorSpace::Rgb).unwrap();
+        let rows: Vec<&[u8]> = img.rows().collect();
+        assert_eq!(rows, [&[1, 2, 3, 4, 5, 6][..], &[7, 8, 9, 10, 11, 12]]);
+        let pixels: Vec<&[u8]> = img.pixels().collect();
+        assert_eq!(pixels.len(), 4);
+        assert_eq!(pixels[3], &[10, 11, 12]);
+        for (y, row) in img.rows_mut().enumerate() {
+            row[0] = y as u8;
+        }
+        assert_eq!(img.as_slice()[0], 0);
+        assert_eq!(img.as_slice()[6], 1);
+    }
+
+    #[test]
+    fn test_luma_preset() {
+        use crate::{ColorSpace, ImageOwned, ImageProps, LumaCoefficients, ToLuma};
+        let rgb = || ImageOwned::from_owned(vec![0u8, 100, 200], 1, 1, ColorSpace::Rgb).unwrap();
+        let mut img = rgb();
+        img.to_luma_preset(LumaCoefficients::Rec709).unwrap();
+        assert_eq!(img.as_slice(), &[85]);
+        let mut img = rgb();
+        img.to_luma_preset(LumaCoefficients::Rec601).unwrap();
+        let mut expected = rgb();
+        expected.to_luma().unwrap();
+        assert_eq!(img, expected);
+        let mut img = rgb();
+        img.to_luma_preset(LumaCoefficients::Custom([0.0, 0.0, 1.0]))
+            .unwrap();
+        assert_eq!(img.as_slice(), &[200]);
+
+        let mut img = rgb();
+        assert_eq!(
+            img.to_luma_preset(LumaCoefficients::Custom([0.5, 0.5, 0.5])),
+            Err("Luma coefficients must sum to 1.")
+        );
+        assert_eq!(
+            img.to_luma_custom(&[0.5, 0.5]),
+            Err("Number of channels and weights do not match.")
+        );
+        assert_eq!(img.color_space(), ColorSpace::Rgb);
+    }
 }
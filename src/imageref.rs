@@ -1,14 +1,19 @@
-use std::time::Duration;
+use std::{num::NonZeroUsize, time::Duration};
 
 use crate::{
+    coreimpls::{
+        crop_cspace, flip_cols, flip_cspace, flip_rows, guess_bayer_pattern, rotate_cspace,
+        run_paste, run_rotate, run_select_padded, run_stretch_u8, run_transpose, transpose_cspace,
+    },
     coretraits::{cast_u8, Enlargeable},
     demosaic::{run_demosaic_imagedata, Debayer, RasterMut},
-    imagetraits::ImageProps,
-    BayerError, CalcOptExp, ColorSpace, CopyRoi, DemosaicMethod, ImageOwned, OptimumExposure,
-    PixelStor, PixelType, SelectRoi, ToLuma,
+    imagetraits::{ImageProps, MutImageData},
+    BayerError, BayerPattern, BorderMode, CalcOptExp, ColorSpace, CopyRoi, DemosaicMethod,
+    ExposureOutcome, Flip, ImageOwned, OptimumExposure, PixelStor, PixelType, Rect, RefImageError,
+    Rotation, SelectRoi, ToLuma,
 };
-use bytemuck::{AnyBitPattern, PodCastError};
-use num_traits::Zero;
+use bytemuck::AnyBitPattern;
+use num_traits::{PrimInt, Zero};
 
 /// A structure that holds image data backed by a slice or a vector.
 ///
@@ -37,38 +42,62 @@ pub struct ImageRef<'a, T: PixelStor> {
     pub(crate) cspace: ColorSpace,
 }
 
+/// Create a view of a band of full-width rows of interleaved image data.
+pub(crate) fn row_band<'b, T: PixelStor>(
+    data: &'b mut [T],
+    width: usize,
+    height: usize,
+    channels: u8,
+    cspace: &ColorSpace,
+    y: usize,
+    rows: NonZeroUsize,
+) -> Result<ImageRef<'b, T>, &'static str> {
+    if y.checked_add(rows.get()).is_none_or(|end| end > height) {
+        return Err("Row band is out of bounds.");
+    }
+    if cspace.is_bayer() && !y.is_multiple_of(2) {
+        return Err("Row band of a Bayer image must start on an even row.");
+    }
+    let stride = width * channels as usize;
+    let len = rows.get() * stride;
+    Ok(ImageRef {
+        data: &mut data[y * stride..y * stride + len],
+        len,
+        width: width as u16,
+        height: rows.get() as u16,
+        channels,
+        cspace: cspace.clone(),
+    })
+}
+
 impl<'a, T: PixelStor> ImageRef<'a, T> {
     pub(crate) fn create(
         data: &'a mut [T],
         width: usize,
         height: usize,
         cspace: ColorSpace,
-    ) -> Result<Self, &'static str> {
+    ) -> Result<Self, RefImageError> {
         if height > u16::MAX as usize || width > u16::MAX as usize {
-            return Err("Image too large.");
+            return Err(RefImageError::TooLarge);
         }
         if data.is_empty() {
-            return Err("Data is empty");
+            return Err(RefImageError::EmptyData);
         }
         if width == 0 {
-            return Err("Width is zero");
+            return Err(RefImageError::ZeroWidth);
         }
         if height == 0 {
-            return Err("Height is zero");
+            return Err(RefImageError::ZeroHeight);
         }
-        let channels = match cspace {
-            ColorSpace::Gray | ColorSpace::Bayer(_) => 1,
-            ColorSpace::Rgb => 3,
-            ColorSpace::Custom(ch, _) => ch as usize,
-        };
+        let channels = cspace.channel_count().ok_or(RefImageError::NoChannels)? as usize;
         let len = data.len();
         let tot = width
             .checked_mul(height)
-            .ok_or("Image too large.")?
+            .ok_or(RefImageError::TooLarge)?
             .checked_mul(channels)
-            .ok_or("Image too large.")?;
+            .ok_or(RefImageError::TooLarge)?;
         if tot > len {
-            return Err("Not enough data for image.");
+            return Err(RefImageError::LengthMismatch);
         }
 
         Ok(Self {
@@ -99,12 +128,49 @@ impl<'a, T: PixelStor> ImageRef<'a, T> {
     /// - If the height is zero.
     /// - If there are too many channels for grayscale/Bayer pattern images.
     /// - If color space is RGB and number of channels is not 3.
+    /// - If the color space has no channels.
     pub fn new(
         data: &'a mut [T],
         width: usize,
         height: usize,
         cspace: ColorSpace,
-    ) -> Result<Self, &'static str> {
+    ) -> Result<Self, RefImageError> {
+        Self::create(data, width, height, cspace)
+    }
+
+    /// Create a new [`ImageRef`] from a mutable slice of data, with an explicit
+    /// number of channels that is validated against the color space.
+    ///
+    /// This is useful when importing e.g. multispectral data with a
+    /// [`ColorSpace::Custom`] color space, where a mismatch between the number of
+    /// channels of the data and the color space should be caught instead of being
+    /// inferred from the color space. See [`ImageRef::new`] for the other requirements.
+    ///
+    /// # Arguments
+    /// - `data`: The data slice, at least `width * height * channels` long.
+    /// - `width`: The width of the image.
+    /// - `height`: The height of the image.
+    /// - `channels`: The number of channels of the data.
+    /// - `cspace`: The color space of the image ([`ColorSpace`]).
+    ///
+    /// # Errors
+    /// - If `channels` does not match the color space, i.e. 1 for grayscale/Bayer pattern
+    ///   images, 3 for RGB images and `ch` for [`ColorSpace::Custom`]`(ch, _)`.
+    /// - For any of the reasons listed in [`ImageRef::new`].
+    pub fn with_channels(
+        data: &'a mut [T],
+        width: usize,
+        height: usize,
+        channels: u8,
+        cspace: ColorSpace,
+    ) -> Result<Self, RefImageError> {
+        let expected = cspace.channel_count().ok_or(RefImageError::NoChannels)?;
+        if expected != channels {
+            return Err(RefImageError::ChannelMismatch {
+                expected,
+                found: channels,
+            });
+        }
         Self::create(data, width, height, cspace)
     }
 
@@ -128,12 +194,41 @@ impl<'a, T: PixelStor> ImageRef<'a, T> {
 
     /// Get the underlying data as a vector.
     ///
-    /// If the data is owned, this will return the owned data. If the data is a reference,
-    /// this will return a copy of the data.
+    /// Note: Since [`ImageRef`] borrows its data, this function always returns a copy
+    /// of the data. Use [`ImageOwned::into_vec`] to move the data out without copying.
     pub fn into_vec(self) -> Vec<T> {
         self.data[..self.len].to_vec()
     }
 
+    /// Get the length of the backing slice, in elements of `T`.
+    ///
+    /// This may be larger than the length of the image data, see [`ImageRef::len`].
+    pub fn backing_len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Get a borrowed view of a band of full-width rows of the image.
+    ///
+    /// Full-width rows are contiguous in memory, hence no data is copied. The view has
+    /// the same width, number of channels and color space as the image.
+    ///
+    /// # Arguments
+    /// - `y`: The first row of the band.
+    /// - `height`: The number of rows in the band.
+    ///
+    /// # Errors
+    /// - If `y + height` is larger than the height of the image.
+    /// - If the image is a Bayer mosaic image and `y` is odd, since the view would not
+    ///   start on the same Bayer pattern.
+    pub fn crop_rows(
+        &mut self,
+        y: usize,
+        height: NonZeroUsize,
+    ) -> Result<ImageRef<'_, T>, &'static str> {
+        let (swid, shei, channels) = (self.width(), self.height(), self.channels());
+        row_band(self.data, swid, shei, channels, &self.cspace, y, height)
+    }
+
     /// Get a raw pointer to the data.
     pub fn as_ptr(&self) -> *const T {
         self.data.as_ptr()
@@ -214,6 +309,12 @@ impl<T: PixelStor> ImageProps for ImageRef<'_, T> {
     }
 }
 
+impl<T: PixelStor> MutImageData<T> for ImageRef<'_, T> {
+    fn as_mut_logical_slice(&mut self) -> &mut [T] {
+        &mut self.data[..self.len]
+    }
+}
+
 impl<'a, T: PixelStor + AnyBitPattern> ImageRef<'a, T> {
     /// Create a new [`ImageRef`] from a mutable slice of `u8` data.
     ///
@@ -227,6 +328,7 @@ impl<'a, T: PixelStor + AnyBitPattern> ImageRef<'a, T> {
     /// The endianness of the data is determined by the system, and the data is assumed
     /// to be in native endianness. This function is not safe to use in a cross-platform
     /// environment.
+    /// Use [`ImageRef::from_u8_mut_be`] or [`ImageRef::from_u8_mut_le`] for data of a known byte order.
     ///  
     /// # Arguments
     /// - `data`: The [`&mut [u8]`] data slice.
@@ -235,7 +337,7 @@ impl<'a, T: PixelStor + AnyBitPattern> ImageRef<'a, T> {
     /// - `cspace`: The color space of the image ([`ColorSpace`]).
     ///
     /// # Errors
-    /// - Byte casting errors: [`PodCastError`].
+    /// - Byte casting errors: [`PodCastError`](bytemuck::PodCastError).
     /// - If the image is too large.
     /// - If the data is empty.
     /// - If the width is zero.
@@ -243,28 +345,151 @@ impl<'a, T: PixelStor + AnyBitPattern> ImageRef<'a, T> {
     /// - If the data length does not match the image size.
     /// - If there are too many channels for grayscale/Bayer pattern images.
     /// - If color space is RGB and number of channels is not 3.
+    /// - If the color space has no channels.
     pub fn from_u8_mut(
         data: &'a mut [u8],
         width: usize,
         height: usize,
         cspace: ColorSpace,
-    ) -> Result<Self, &'static str> {
-        let data = bytemuck::try_cast_slice_mut(data).map_err(|e| {
-            use PodCastError::*;
-            match e {
-                TargetAlignmentGreaterAndInputNotAligned => {
-                    "Target alignment greater and input not aligned"
-                }
-                OutputSliceWouldHaveSlop => "Output slice would have slop",
-                SizeMismatch => "Size mismatch",
-                AlignmentMismatch => "Alignment mismatch",
-            }
-        })?;
+    ) -> Result<Self, RefImageError> {
+        let data = bytemuck::try_cast_slice_mut(data)?;
         Self::new(data, width, height, cspace)
     }
 }
 
+impl<'a, T: PixelStor + AnyBitPattern + PrimInt> ImageRef<'a, T> {
+    /// Cast `u8` data to pixels, converting each pixel in place with `convert`.
+    fn from_u8_mut_ordered(
+        data: &'a mut [u8],
+        width: usize,
+        height: usize,
+        cspace: ColorSpace,
+        convert: fn(T) -> T,
+    ) -> Result<Self, RefImageError> {
+        let data = bytemuck::try_cast_slice_mut(data)?;
+        let img = Self::new(data, width, height, cspace)?;
+        img.data.iter_mut().for_each(|v| *v = convert(*v));
+        Ok(img)
+    }
+
+    /// Create a new [`ImageRef`] from a mutable slice of `u8` data in big-endian
+    /// byte order.
+    ///
+    /// Images can not be larger than 65535x65535 pixels.
+    ///
+    /// `data` is cast to the pixel type `T` using [`bytemuck::try_cast_slice_mut`], and
+    /// the pixels are converted from big-endian to native byte order in place, hence
+    /// `data` is modified. Unlike [`ImageRef::from_u8_mut`], which assumes native
+    /// endianness, this function is safe to use with data from other platforms or file
+    /// formats. `data` must have length (`width` * `height` * `channels` * `sizeof(T)`),
+    /// and aligned to the size of `T`.
+    ///
+    /// # Arguments
+    /// - `data`: The [`&mut [u8]`] data slice, in big-endian byte order.
+    /// - `width`: The width of the image.
+    /// - `height`: The height of the image.
+    /// - `cspace`: The color space of the image ([`ColorSpace`]).
+    ///
+    /// # Errors
+    /// - Byte casting errors: [`PodCastError`](bytemuck::PodCastError).
+    /// - If the image is too large.
+    /// - If the data is empty.
+    /// - If the width is zero.
+    /// - If the height is zero.
+    /// - If the data length does not match the image size.
+    /// - If there are too many channels for grayscale/Bayer pattern images.
+    /// - If color space is RGB and number of channels is not 3.
+    /// - If the color space has no channels.
+    ///
+    /// `data` is left unmodified on error.
+    pub fn from_u8_mut_be(
+        data: &'a mut [u8],
+        width: usize,
+        height: usize,
+        cspace: ColorSpace,
+    ) -> Result<Self, RefImageError> {
+        Self::from_u8_mut_ordered(data, width, height, cspace, T::from_be)
+    }
+
+    /// Create a new [`ImageRef`] from a mutable slice of `u8` data in little-endian
+    /// byte order.
+    ///
+    /// Images can not be larger than 65535x65535 pixels.
+    ///
+    /// `data` is cast to the pixel type `T` using [`bytemuck::try_cast_slice_mut`], and
+    /// the pixels are converted from little-endian to native byte order in place, hence
+    /// `data` is modified. Unlike [`ImageRef::from_u8_mut`], which assumes native
+    /// endianness, this function is safe to use with data from other platforms or file
+    /// formats. `data` must have length (`width` * `height` * `channels` * `sizeof(T)`),
+    /// and aligned to the size of `T`.
+    ///
+    /// # Arguments
+    /// - `data`: The [`&mut [u8]`] data slice, in little-endian byte order.
+    /// - `width`: The width of the image.
+    /// - `height`: The height of the image.
+    /// - `cspace`: The color space of the image ([`ColorSpace`]).
+    ///
+    /// # Errors
+    /// - Byte casting errors: [`PodCastError`](bytemuck::PodCastError).
+    /// - If the image is too large.
+    /// - If the data is empty.
+    /// - If the width is zero.
+    /// - If the height is zero.
+    /// - If the data length does not match the image size.
+    /// - If there are too many channels for grayscale/Bayer pattern images.
+    /// - If color space is RGB and number of channels is not 3.
+    /// - If the color space has no channels.
+    ///
+    /// `data` is left unmodified on error.
+    pub fn from_u8_mut_le(
+        data: &'a mut [u8],
+        width: usize,
+        height: usize,
+        cspace: ColorSpace,
+    ) -> Result<Self, RefImageError> {
+        Self::from_u8_mut_ordered(data, width, height, cspace, T::from_le)
+    }
+}
+
+impl ImageRef<'_, f32> {
+    /// Check whether the image contains any NaN or infinite values.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    pub fn has_non_finite(&self) -> bool {
+        crate::coreimpls::run_has_non_finite(self.as_slice())
+    }
+}
+
 impl<T: PixelStor> ImageRef<'_, T> {
+    /// Copy the image data into an [`ImageOwned`].
+    ///
+    /// Only the valid data of the image is copied, i.e. `width * height * channels` elements.
+    pub fn to_owned(&self) -> ImageOwned<T> {
+        ImageOwned::from(self)
+    }
+
+    /// Change the color space of the image without touching the data, e.g. to tag a
+    /// grayscale frame as a Bayer mosaic before debayering it.
+    ///
+    /// # Arguments
+    /// - `cspace`: The new color space of the image ([`ColorSpace`]).
+    ///
+    /// # Errors
+    /// - If the color space has no channels.
+    /// - If the number of channels of the color space does not match the image.
+    pub fn reinterpret_color_space(&mut self, cspace: ColorSpace) -> Result<(), &'static str> {
+        let channels = cspace.channel_count().ok_or(RefImageError::NoChannels)?;
+        if channels != self.channels {
+            return Err(RefImageError::ChannelMismatch {
+                expected: channels,
+                found: self.channels,
+            }
+            .into());
+        }
+        self.cspace = cspace;
+        Ok(())
+    }
+
     /// Convert the image to a [`ImageOwned`] with [`u8`] pixel type.
     ///
     /// Conversion is done by scaling the pixel values to the range `[0, 255]`.
@@ -281,24 +506,450 @@ impl<T: PixelStor> ImageRef<'_, T> {
             channels: self.channels(),
         }
     }
+
+    #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+    #[cfg(feature = "rayon")]
+    /// Convert the image to a [`ImageOwned`] with [`u8`] pixel type, running the
+    /// conversion inside the supplied thread pool.
+    ///
+    /// See [`Self::into_u8`] for details.
+    pub fn into_u8_in_pool(&self, pool: &rayon::ThreadPool) -> ImageOwned<u8> {
+        pool.install(|| self.into_u8())
+    }
+
+    /// Convert the image to a [`ImageOwned`] with [`u8`] pixel type, stretching the
+    /// contrast between the given percentiles.
+    ///
+    /// The `low_pct` and `high_pct` percentiles are computed for each channel; values
+    /// below and above are clipped, and the window in between is linearly mapped to
+    /// `[0, 255]`. Unlike a plain min/max scaling, a few hot pixels do not dominate
+    /// the result.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    ///
+    /// # Arguments
+    /// - `low_pct`: The lower percentile, in `[0, 1]`.
+    /// - `high_pct`: The upper percentile, in `[0, 1]`.
+    ///
+    /// # Errors
+    /// - If `low_pct` or `high_pct` is not in `[0, 1]`, or `low_pct` is not less than `high_pct`.
+    pub fn into_u8_stretched(
+        &self,
+        low_pct: f32,
+        high_pct: f32,
+    ) -> Result<ImageOwned<u8>, &'static str> {
+        Ok(ImageOwned {
+            data: run_stretch_u8(self.as_slice(), self.channels() as usize, low_pct, high_pct)?,
+            width: self.width() as _,
+            height: self.height() as _,
+            cspace: self.cspace.clone(),
+            channels: self.channels(),
+        })
+    }
+}
+
+impl<T: PixelStor> ImageRef<'_, T> {
+    /// Guess the Bayer pattern of a raw single-channel frame.
+    ///
+    /// The mean intensities of the four 2x2 sub-lattices of the image are compared.
+    /// Both green sub-lattices sample the same color, so the diagonal whose means agree
+    /// more closely is taken to be green. The brighter of the remaining two sub-lattices
+    /// is taken to be red, which holds for typical scenes with silicon sensors. The
+    /// existing color space, if Bayer, is ignored.
+    ///
+    /// # Arguments
+    /// - `tolerance`: The relative difference (e.g. `0.05` for 5%) below which two means
+    ///   are considered indistinguishable.
+    ///
+    /// # Returns
+    /// - [`None`] if the image is not a single-channel gray or Bayer image, is smaller
+    ///   than 2x2 pixels, or if the red and blue sub-lattices can not be told apart
+    ///   within `tolerance`, e.g. for a flat frame.
+    pub fn guess_bayer_pattern(&self, tolerance: f64) -> Option<BayerPattern> {
+        match self.cspace {
+            ColorSpace::Gray | ColorSpace::Bayer(_) if self.channels() == 1 => {
+                guess_bayer_pattern(self.as_slice(), self.width(), self.height(), tolerance)
+            }
+            _ => None,
+        }
+    }
+
+    /// Apply `f` to every stored element of the image, producing an image of pixel type `U`.
+    ///
+    /// The width, height, number of channels and color space of the image are preserved.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    ///
+    /// # Arguments
+    /// - `f`: The per-element transform.
+    pub fn map_pixels_to<U: PixelStor, F: Fn(T) -> U + Sync>(&self, f: F) -> ImageOwned<U> {
+        ImageOwned {
+            data: crate::coreimpls::run_map_pixels_to(self.as_slice(), f),
+            width: self.width,
+            height: self.height,
+            channels: self.channels,
+            cspace: self.cspace.clone(),
+        }
+    }
+
+    /// Iterate over the rows of the image, from top to bottom.
+    ///
+    /// Each row is a slice of `width * channels` interleaved elements. Data beyond
+    /// [`ImageRef::len`] is not included.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        let row = self.width as usize * self.channels as usize;
+        self.as_slice().chunks_exact(row)
+    }
+
+    /// Iterate mutably over the rows of the image, from top to bottom.
+    ///
+    /// Each row is a slice of `width * channels` interleaved elements. Data beyond
+    /// [`ImageRef::len`] is not included.
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [T]> {
+        let row = self.width as usize * self.channels as usize;
+        self.as_mut_slice().chunks_exact_mut(row)
+    }
+
+    /// Iterate over the pixels of the image in row-major order.
+    ///
+    /// Each pixel is a slice of `channels` elements.
+    pub fn pixels(&self) -> impl Iterator<Item = &[T]> {
+        self.as_slice().chunks_exact(self.channels as usize)
+    }
+
+    /// Select a region of interest from the image, padding the parts of the region
+    /// that lie outside the image.
+    ///
+    /// Unlike [`SelectRoi::select_roi`], the region is not shrunk to the image bounds:
+    /// the origin may be negative, and the region may lie partially or wholly outside
+    /// the image. The output is always `width` by `height` pixels, and pixels outside
+    /// the image are set to `fill`. This is useful to extract fixed-size cutouts near
+    /// the edges of a frame. The Bayer pattern of the output is adjusted for the origin.
+    ///
+    /// # Arguments
+    /// - `x`: The x-coordinate of the top-left corner of the ROI.
+    /// - `y`: The y-coordinate of the top-left corner of the ROI.
+    /// - `width`: The width of the ROI.
+    /// - `height`: The height of the ROI.
+    /// - `fill`: The value of the pixels outside the image.
+    ///
+    /// # Errors
+    /// - If the ROI is larger than 65535x65535 pixels.
+    pub fn select_roi_padded(
+        &self,
+        x: isize,
+        y: isize,
+        width: NonZeroUsize,
+        height: NonZeroUsize,
+        fill: T,
+    ) -> Result<ImageOwned<T>, &'static str> {
+        ImageOwned::new(
+            run_select_padded(
+                self.as_slice(),
+                self.width(),
+                self.height(),
+                self.channels() as usize,
+                x,
+                y,
+                width.get(),
+                height.get(),
+                fill,
+            )?,
+            width.get(),
+            height.get(),
+            crop_cspace(&self.cspace, x, y),
+        )
+    }
+
+    /// Paste the image into another image, with the top-left corner of the image at
+    /// `(x, y)` in the destination, e.g. to composite tiles into a mosaic.
+    ///
+    /// Unlike [`CopyRoi::copy_to`], only the region of the destination that the image
+    /// overlaps is written, and all other destination pixels are left untouched. Parts
+    /// of the image that fall outside the destination are ignored.
+    ///
+    /// # Errors
+    /// - If the number of channels of the images do not match.
+    pub fn paste_into(
+        &self,
+        dest: &mut ImageOwned<T>,
+        x: usize,
+        y: usize,
+    ) -> Result<(), &'static str> {
+        if self.channels() != dest.channels() {
+            return Err("Number of channels does not match.");
+        }
+        let (dwid, dhei) = (dest.width(), dest.height());
+        run_paste(
+            self.as_slice(),
+            self.width(),
+            self.height(),
+            dest.as_mut_slice(),
+            dwid,
+            dhei,
+            self.channels() as usize,
+            x,
+            y,
+        );
+        Ok(())
+    }
+
+    /// Rotate the image by a multiple of 90 degrees, clockwise.
+    ///
+    /// The width and height of the output are swapped for rotations by 90 and 270
+    /// degrees. Rotation changes the phase of a Bayer mosaic, hence the Bayer pattern
+    /// of the output is adjusted accordingly.
+    ///
+    /// # Arguments
+    /// - `rotation`: The rotation to apply ([`Rotation`]).
+    pub fn rotate(&self, rotation: Rotation) -> ImageOwned<T> {
+        let (width, height) = match rotation {
+            Rotation::Rotate180 => (self.width, self.height),
+            Rotation::Rotate90 | Rotation::Rotate270 => (self.height, self.width),
+        };
+        ImageOwned {
+            data: run_rotate(
+                self.as_slice(),
+                self.width(),
+                self.height(),
+                self.channels() as usize,
+                rotation,
+            ),
+            width,
+            height,
+            channels: self.channels,
+            cspace: rotate_cspace(&self.cspace, self.width(), self.height(), rotation),
+        }
+    }
+
+    /// Transpose the image, i.e. swap its rows and columns.
+    ///
+    /// The output is `height` pixels wide and `width` pixels high, and the pixel at
+    /// `(x, y)` moves to `(y, x)`. Unlike [`Self::rotate`], the image is mirrored
+    /// about its main diagonal. The Bayer pattern of the output is adjusted to follow
+    /// the red pixels.
+    pub fn transpose(&self) -> ImageOwned<T> {
+        ImageOwned {
+            data: run_transpose(
+                self.as_slice(),
+                self.width(),
+                self.height(),
+                self.channels() as usize,
+            ),
+            width: self.height,
+            height: self.width,
+            channels: self.channels,
+            cspace: transpose_cspace(&self.cspace),
+        }
+    }
+
+    /// Compute the histograms of all channels of the image.
+    ///
+    /// The bins uniformly span the valid range of the pixel type, i.e.
+    /// [`PixelStor::DEFAULT_MIN_VALUE`] to [`PixelStor::DEFAULT_MAX_VALUE`], which is
+    /// `[0, 1]` for floating point images. Out of range values are counted in the
+    /// first or last bin.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    ///
+    /// # Arguments
+    /// - `bins`: The number of bins in each histogram.
+    ///
+    /// # Returns
+    /// One histogram per channel.
+    ///
+    /// # Errors
+    /// - If `bins` is zero.
+    pub fn histograms(&self, bins: usize) -> Result<Vec<Vec<u64>>, &'static str> {
+        if bins == 0 {
+            return Err("Number of bins must be non-zero.");
+        }
+        Ok(crate::coreimpls::run_histograms(
+            self.as_slice(),
+            self.channels as usize,
+            bins,
+        ))
+    }
+
+    /// Convert an RGB image to the HSV (hue, saturation, value) color space.
+    ///
+    /// See [`ImageOwned::to_hsv`] for details.
+    ///
+    /// # Errors
+    /// - If the image is not an RGB image.
+    pub fn to_hsv(&self) -> Result<ImageOwned<f32>, &'static str> {
+        if self.cspace != ColorSpace::Rgb {
+            return Err("HSV conversion is only supported for RGB images.");
+        }
+        ImageOwned::new(
+            crate::coreimpls::run_rgb_to_hsv(self.as_slice()),
+            self.width(),
+            self.height(),
+            ColorSpace::Custom(3, crate::coreimpls::HSV_DESCRIPTION.to_owned()),
+        )
+    }
+}
+
+impl<T: PixelStor> ImageRef<'_, T> {
+    /// Remove the optical-black or overscan border of the image.
+    ///
+    /// The Bayer pattern of the output is shifted if an odd number of rows or
+    /// columns is removed from the top or the left of the image.
+    ///
+    /// # Arguments
+    /// - `top`: Number of rows to remove from the top of the image.
+    /// - `bottom`: Number of rows to remove from the bottom of the image.
+    /// - `left`: Number of columns to remove from the left of the image.
+    /// - `right`: Number of columns to remove from the right of the image.
+    ///
+    /// # Errors
+    /// - If the border is larger than the image.
+    pub fn strip_overscan(
+        &self,
+        top: usize,
+        bottom: usize,
+        left: usize,
+        right: usize,
+    ) -> Result<ImageOwned<T>, &'static str> {
+        let swid = self.width();
+        let shei = self.height();
+        let inside =
+            |a: usize, b: usize, len: usize| matches!(a.checked_add(b), Some(n) if n < len);
+        if !inside(top, bottom, shei) || !inside(left, right, swid) {
+            return Err("Overscan region is larger than the image.");
+        }
+        let channels = self.channels as usize;
+        let width = swid - left - right;
+        let height = shei - top - bottom;
+        let mut data = Vec::with_capacity(width * height * channels);
+        for row in self.data[..self.len]
+            .chunks_exact(swid * channels)
+            .skip(top)
+            .take(height)
+        {
+            data.extend_from_slice(&row[left * channels..(left + width) * channels]);
+        }
+        let cspace = crop_cspace(&self.cspace, left as isize, top as isize);
+        ImageOwned::new(data, width, height, cspace)
+    }
+
+    /// Compute the mean value of an optical-black region of the image, for bias subtraction.
+    ///
+    /// The mean is computed over all channels of the region.
+    ///
+    /// # Errors
+    /// - If the region is empty or extends outside the image.
+    pub fn overscan_bias(&self, region: Rect) -> Result<f64, &'static str> {
+        if region.width == 0 || region.height == 0 {
+            return Err("Overscan region is empty.");
+        }
+        let inside =
+            |a: usize, b: usize, len: usize| matches!(a.checked_add(b), Some(n) if n <= len);
+        if !inside(region.x, region.width, self.width())
+            || !inside(region.y, region.height, self.height())
+        {
+            return Err("Overscan region is out of bounds.");
+        }
+        let channels = self.channels as usize;
+        let sum: f64 = self.data[..self.len]
+            .chunks_exact(self.width() * channels)
+            .skip(region.y)
+            .take(region.height)
+            .flat_map(|row| &row[region.x * channels..(region.x + region.width) * channels])
+            .map(|&v| v.to_f64())
+            .sum();
+        Ok(sum / (region.width * region.height * channels) as f64)
+    }
+
+    /// Convolve the image with a horizontal kernel `kx` and a vertical kernel `ky`, e.g.
+    /// to smooth the image with a [`gaussian_kernel`](crate::gaussian_kernel).
+    ///
+    /// Each channel is convolved independently. The kernels are centered on their middle
+    /// element, and samples outside the image are handled according to `border`. The
+    /// result is clamped to the valid range of the pixel type.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    ///
+    /// # Arguments
+    /// - `kx`: The horizontal kernel, of odd length.
+    /// - `ky`: The vertical kernel, of odd length.
+    /// - `border`: The [`BorderMode`] of the convolution.
+    ///
+    /// # Errors
+    /// - If the image is a Bayer mosaic image.
+    /// - If a kernel is empty or has an even length.
+    pub fn convolve_separable(
+        &self,
+        kx: &[f64],
+        ky: &[f64],
+        border: BorderMode,
+    ) -> Result<ImageOwned<T>, &'static str> {
+        let data = crate::coreimpls::convolve_pixels(
+            self.as_slice(),
+            self.width(),
+            self.height(),
+            self.channels as usize,
+            &self.cspace,
+            kx,
+            ky,
+            border,
+        )?;
+        ImageOwned::new(data, self.width(), self.height(), self.cspace.clone())
+    }
+}
+
+impl<T: PixelStor + Enlargeable> ImageRef<'_, T> {
+    /// Subtract another image from this image, e.g. to remove a dark frame.
+    ///
+    /// The difference is computed in the larger type of the pixel type (see
+    /// [`Enlargeable`]), and saturates at [`PixelStor::DEFAULT_MIN_VALUE`] instead of
+    /// wrapping around.
+    ///
+    /// # Arguments
+    /// - `other`: The image to subtract.
+    ///
+    /// # Errors
+    /// - If the width, height, number of channels or color space of the images do not match.
+    pub fn checked_sub(&self, other: &Self) -> Result<ImageOwned<T>, &'static str> {
+        crate::coreimpls::check_same_layout(self, other)?;
+        Ok(ImageOwned {
+            data: crate::coreimpls::run_checked_sub(self.as_slice(), other.as_slice()),
+            width: self.width,
+            height: self.height,
+            channels: self.channels,
+            cspace: self.cspace.clone(),
+        })
+    }
 }
 
 impl<T: PixelStor + Enlargeable> ToLuma for ImageRef<'_, T> {
     fn to_luma(&mut self) -> Result<(), &'static str> {
-        self.to_luma_custom(&[0.299, 0.587, 0.114])
+        self.to_luma_custom(&crate::coreimpls::LUMA_COEFFS)
     }
 
     fn to_luma_custom(&mut self, coeffs: &[f64]) -> Result<(), &'static str> {
         // at this point, number of channels must match number of weights
         match self.cspace {
             ColorSpace::Gray => Ok(()),
-            ColorSpace::Rgb | ColorSpace::Custom(_, _) => {
+            ColorSpace::Rgb
+            | ColorSpace::Rgba
+            | ColorSpace::GrayAlpha
+            | ColorSpace::Custom(_, _) => {
+                crate::coreimpls::check_luma_coeffs(self.channels.into(), coeffs)?;
                 crate::coreimpls::run_luma(self.channels.into(), self.len, self.data, coeffs)?;
                 self.cspace = ColorSpace::Gray;
                 self.len = self.width as usize * self.height as usize;
                 self.channels = 1;
                 Ok(())
             }
+            ColorSpace::Cmyk => {
+                crate::coreimpls::check_luma_coeffs(3, coeffs)?;
+                crate::coreimpls::run_luma_cmyk(self.len, self.data, coeffs)?;
+                self.cspace = ColorSpace::Gray;
+                self.len = self.width as usize * self.height as usize;
+                self.channels = 1;
+                Ok(())
+            }
             ColorSpace::Bayer(_) => Err("Image is not debayered."),
         }
     }
@@ -340,16 +991,50 @@ impl<T: PixelStor + Zero> SelectRoi for ImageRef<'_, T> {
         if x >= swid || y >= shei {
             return Err("ROI is out of bounds.");
         }
-        let mut data = vec![T::zero(); width.get() * height.get() * self.channels as usize];
-        let wid = width.get().min(swid - x); // guaranteed to be non-zero
-        let hei = height.get().min(shei - y); // guaranteed to be non-zero
-        for h in 0..hei {
-            let src = (y + h) * swid + x;
-            let dst = h * wid;
-            let len = wid * self.channels as usize;
-            data[dst..dst + len].copy_from_slice(&self.data[src..src + len]);
+        // The parts of the ROI outside the image are zero
+        let data = run_select_padded(
+            self.as_slice(),
+            swid,
+            shei,
+            self.channels as usize,
+            x as isize,
+            y as isize,
+            width.get(),
+            height.get(),
+            T::zero(),
+        )?;
+        ImageOwned::new(
+            data,
+            width.get(),
+            height.get(),
+            crop_cspace(&self.cspace, x as isize, y as isize),
+        )
+    }
+}
+
+impl<T: PixelStor> Flip for ImageRef<'_, T> {
+    type Output = ImageOwned<T>;
+
+    fn flip_h(&self) -> Self::Output {
+        let channels = self.channels() as usize;
+        ImageOwned {
+            data: flip_rows(self.as_slice(), self.width(), channels),
+            width: self.width,
+            height: self.height,
+            channels: self.channels,
+            cspace: flip_cspace(&self.cspace, self.width(), true),
+        }
+    }
+
+    fn flip_v(&self) -> Self::Output {
+        let channels = self.channels() as usize;
+        ImageOwned {
+            data: flip_cols(self.as_slice(), self.width(), channels),
+            width: self.width,
+            height: self.height,
+            channels: self.channels,
+            cspace: flip_cspace(&self.cspace, self.height(), false),
         }
-        ImageOwned::new(data, width.get(), height.get(), self.cspace.clone())
     }
 }
 
@@ -382,11 +1067,101 @@ impl<T: PixelStor + Ord> CalcOptExp for ImageRef<'_, T> {
         exposure: Duration,
         bin: u8,
     ) -> Result<(Duration, u16), &'static str> {
-        eval.calculate(self.data, self.len, exposure, bin)
+        self.eval_opt_exp(eval, exposure, bin)
+            .map(ExposureOutcome::into_inner)
+    }
+
+    fn eval_opt_exp(
+        self,
+        eval: &OptimumExposure,
+        exposure: Duration,
+        bin: u8,
+    ) -> Result<ExposureOutcome, &'static str> {
+        eval.evaluate(self.data, self.len, exposure, bin)
     }
 }
 
 mod test {
+    #[test]
+    fn test_crop_rows() {
+        use crate::{BayerPattern, ColorSpace, ImageOwned, ImageProps, ImageRef};
+        use std::num::NonZeroUsize;
+        let two = NonZeroUsize::new(2).unwrap();
+        let mut data: Vec<u8> = (0..30).collect();
+        let mut img = ImageRef::new(&mut data, 2, 4, ColorSpace::Rgb).unwrap();
+        let mut band = img.crop_rows(1, two).unwrap();
+        assert_eq!((band.width(), band.height(), band.len()), (2, 2, 12));
+        assert_eq!(band.color_space(), ColorSpace::Rgb);
+        assert_eq!(band.as_slice(), &(6..18).collect::<Vec<u8>>()[..]);
+        band.as_mut_slice()[0] = 255;
+        assert!(img.crop_rows(3, two).is_err());
+        assert!(img.crop_rows(usize::MAX, two).is_err());
+        assert_eq!(data[6], 255);
+        let mut img =
+            ImageOwned::from_owned(vec![0u16; 16], 4, 4, BayerPattern::Rggb.into()).unwrap();
+        assert!(img.crop_rows(1, two).is_err());
+        let band = img.crop_rows(2, two).unwrap();
+        assert_eq!(band.color_space(), BayerPattern::Rggb.into());
+        assert_eq!(band.len(), 8);
+    }
+
+    #[test]
+    fn test_to_owned() {
+        use crate::{ColorSpace, DynamicImageOwned, DynamicImageRef, ImageRef};
+        let mut data = vec![1u8, 2, 3, 4, 5, 6, 7, 8];
+        let img =
+            ImageRef::new(&mut data, 3, 2, ColorSpace::Gray).expect("Failed to create ImageRef");
+        let own = img.to_owned();
+        assert_eq!(own.as_slice(), &[1, 2, 3, 4, 5, 6]);
+        let img = DynamicImageRef::from(img);
+        assert_eq!(img.to_owned(), DynamicImageOwned::from(own));
+    }
+
+    #[test]
+    fn test_strip_overscan() {
+        use crate::{BayerPattern, ColorSpace, ImageProps, ImageRef, Rect};
+        let mut data: Vec<u16> = (0..20).collect();
+        let img = ImageRef::new(&mut data, 5, 4, ColorSpace::Bayer(BayerPattern::Rggb))
+            .expect("Failed to create ImageRef");
+        let out = img.strip_overscan(1, 1, 1, 2).unwrap();
+        assert_eq!(out.width(), 2);
+        assert_eq!(out.height(), 2);
+        assert_eq!(out.as_slice(), &[6, 7, 11, 12]);
+        assert_eq!(out.color_space(), ColorSpace::Bayer(BayerPattern::Bggr));
+        let out = img.strip_overscan(0, 0, 1, 0).unwrap();
+        assert_eq!(out.color_space(), ColorSpace::Bayer(BayerPattern::Grbg));
+        let out = img.strip_overscan(1, 0, 0, 0).unwrap();
+        assert_eq!(out.color_space(), ColorSpace::Bayer(BayerPattern::Gbrg));
+        assert!(img.strip_overscan(2, 2, 0, 0).is_err());
+        assert!(img.strip_overscan(usize::MAX, 2, 0, 0).is_err());
+        assert!(img.strip_overscan(0, 0, 1, usize::MAX).is_err());
+        let bias = img
+            .overscan_bias(Rect {
+                x: 0,
+                y: 0,
+                width: 1,
+                height: 4,
+            })
+            .unwrap();
+        assert_eq!(bias, 7.5);
+        assert!(img
+            .overscan_bias(Rect {
+                x: 4,
+                y: 0,
+                width: 2,
+                height: 1,
+            })
+            .is_err());
+        assert!(img
+            .overscan_bias(Rect {
+                x: usize::MAX,
+                y: 0,
+                width: 2,
+                height: 1,
+            })
+            .is_err());
+    }
+
     #[test]
     fn test_into_luma() {
         use crate::{ColorSpace, ImageRef, ToLuma};
@@ -511,4 +1286,195 @@ mod test {
         img.copy_to(&mut roi, 1, 0);
         assert_eq!(roi.as_slice(), &[1, 2, 5, 7, 0, 0]);
     }
+
+    #[test]
+    fn test_select_roi_rgb() {
+        use crate::{ImageProps, SelectRoi};
+        use std::num::NonZero;
+
+        // Each sample is 100 * channel + 10 * y + x
+        let mut imgsrc: Vec<u8> = (0..2)
+            .flat_map(|y| (0..3).flat_map(move |x| (0..3).map(move |c| 100 * c + 10 * y + x)))
+            .collect();
+        let img = crate::ImageRef::new(imgsrc.as_mut_slice(), 3, 2, crate::ColorSpace::Rgb)
+            .expect("Failed to create ImageRef");
+        let roi = img
+            .select_roi(1, 0, NonZero::new(2).unwrap(), NonZero::new(3).unwrap())
+            .expect("Failed to select ROI");
+        assert_eq!(roi.channels(), 3);
+        assert_eq!(
+            roi.as_slice(),
+            &[1, 101, 201, 2, 102, 202, 11, 111, 211, 12, 112, 212, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_select_roi_bayer() {
+        use crate::{BayerPattern, ImageProps, SelectRoi};
+        use std::num::NonZero;
+
+        let mut imgsrc = vec![0u16; 16];
+        let img = crate::ImageRef::new(imgsrc.as_mut_slice(), 4, 4, BayerPattern::Rggb.into())
+            .expect("Failed to create ImageRef");
+        let nz = NonZero::new(2).unwrap();
+        for (x, y, pat) in [
+            (0, 0, BayerPattern::Rggb),
+            (1, 0, BayerPattern::Grbg),
+            (0, 1, BayerPattern::Gbrg),
+            (1, 1, BayerPattern::Bggr),
+            (3, 2, BayerPattern::Grbg),
+        ] {
+            let roi = img.select_roi(x, y, nz, nz).expect("Failed to select ROI");
+            assert_eq!(roi.color_space(), pat.into());
+        }
+    }
+
+    #[test]
+    fn test_mut_image_data() {
+        use crate::MutImageData;
+
+        fn invert<T: crate::PixelStor, I: MutImageData<T>>(img: &mut I) {
+            img.as_mut_logical_slice()
+                .iter_mut()
+                .for_each(|v| *v = T::DEFAULT_MAX_VALUE - *v);
+        }
+
+        let mut data = vec![0u8, 1, 2, 3, 4, 5, 6, 7];
+        let mut img = crate::ImageRef::new(&mut data, 3, 2, crate::ColorSpace::Gray).unwrap();
+        assert_eq!(img.dims(), (3, 2, 1));
+        invert(&mut img);
+        assert_eq!(img.as_slice(), &[255, 254, 253, 252, 251, 250]);
+        let mut img = crate::ImageOwned::from(&img);
+        invert(&mut img);
+        assert_eq!(img.as_slice(), &[0, 1, 2, 3, 4, 5]);
+        assert_eq!(data[6..], [6, 7]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_thread_pool() {
+        use crate::{BayerPattern, ColorSpace, Debayer, DemosaicMethod, ImageRef};
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let mut data: Vec<u16> = (0..64).map(|x| x * 1000).collect();
+        let img = ImageRef::new(&mut data, 8, 8, ColorSpace::Bayer(BayerPattern::Rggb))
+            .expect("Failed to create ImageRef");
+        assert_eq!(img.into_u8_in_pool(&pool), img.into_u8());
+        assert_eq!(
+            img.debayer_in_pool(DemosaicMethod::Cubic, &pool).unwrap(),
+            img.debayer(DemosaicMethod::Cubic).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_from_u8_mut_endian() {
+        use crate::{ColorSpace, ImageRef};
+        let mut data = vec![0u16; 2];
+        let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut data);
+        bytes.copy_from_slice(&[0x12, 0x34, 0xab, 0xcd]);
+        let img = ImageRef::<u16>::from_u8_mut_be(bytes, 2, 1, ColorSpace::Gray).unwrap();
+        assert_eq!(img.as_slice(), &[0x1234, 0xabcd]);
+        let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut data);
+        bytes.copy_from_slice(&[0x12, 0x34, 0xab, 0xcd]);
+        let img = ImageRef::<u16>::from_u8_mut_le(bytes, 2, 1, ColorSpace::Gray).unwrap();
+        assert_eq!(img.as_slice(), &[0x3412, 0xcdab]);
+        // The data is left unmodified on error
+        let before = data.clone();
+        let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut data);
+        assert!(ImageRef::<u16>::from_u8_mut_be(bytes, 3, 1, ColorSpace::Gray).is_err());
+        assert_eq!(data, before);
+    }
+
+    #[test]
+    fn test_map_pixels() {
+        use crate::{ColorSpace, ImageProps, ImageRef, MutImageData};
+        let mut data = vec![10u16, 20, 30, 40, 50, 60, 0];
+        let mut img = ImageRef::new(&mut data, 2, 1, ColorSpace::Rgb).unwrap();
+        img.map_pixels(|v| v.saturating_sub(25));
+        assert_eq!(img.as_slice(), &[0, 0, 5, 15, 25, 35]);
+        let out = img.map_pixels_to(|v| v as f32 / 100.0);
+        assert_eq!(out.as_slice(), &[0.0, 0.0, 0.05, 0.15, 0.25, 0.35]);
+        assert_eq!((out.width(), out.height()), (2, 1));
+        assert_eq!((out.channels(), out.color_space()), (3, ColorSpace::Rgb));
+        // Trailing data beyond the image is not touched
+        assert_eq!(data[6], 0);
+    }
+
+    #[test]
+    fn test_reinterpret_color_space() {
+        use crate::{
+            BayerPattern, ColorSpace, Debayer, DemosaicMethod, ImageOwned, ImageProps, ImageRef,
+        };
+        let mut data = vec![100u8; 16];
+        let mut img = ImageRef::new(&mut data, 4, 4, ColorSpace::Gray).unwrap();
+        assert!(img.debayer(DemosaicMethod::Linear).is_err());
+        img.reinterpret_color_space(BayerPattern::Rggb.into())
+            .unwrap();
+        assert_eq!(img.color_space(), ColorSpace::Bayer(BayerPattern::Rggb));
+        assert!(img.debayer(DemosaicMethod::Linear).is_ok());
+        assert!(img.reinterpret_color_space(ColorSpace::Rgb).is_err());
+        assert_eq!(img.color_space(), ColorSpace::Bayer(BayerPattern::Rggb));
+
+        let mut img =
+            ImageOwned::from_owned(vec![1u16; 12], 2, 2, ColorSpace::Custom(3, "XYZ".into()))
+                .unwrap();
+        img.reinterpret_color_space(ColorSpace::Rgb).unwrap();
+        assert_eq!((img.color_space(), img.channels()), (ColorSpace::Rgb, 3));
+        assert_eq!(
+            img.reinterpret_color_space(ColorSpace::Custom(0, "EMPTY".into())),
+            Err("Color space has no channels.")
+        );
+        assert_eq!(
+            img.reinterpret_color_space(ColorSpace::Cmyk),
+            Err("Number of channels does not match the color space.")
+        );
+    }
+
+    #[test]
+    fn test_with_channels() {
+        use crate::{BayerPattern, ColorSpace, ImageProps, ImageRef, RefImageError};
+        let mut data = vec![0u16; 2 * 3 * 5 + 1];
+        let img = ImageRef::with_channels(&mut data, 2, 3, 5, ColorSpace::Custom(5, "MS".into()))
+            .unwrap();
+        assert_eq!((img.channels(), img.len()), (5, 30));
+        assert_eq!(
+            ImageRef::with_channels(&mut data, 2, 3, 4, ColorSpace::Custom(5, "MS".into())),
+            Err(RefImageError::ChannelMismatch {
+                expected: 5,
+                found: 4
+            })
+        );
+        let mismatch = ImageRef::with_channels(&mut data, 2, 3, 3, BayerPattern::Rggb.into());
+        assert_eq!(
+            mismatch.unwrap_err().to_string(),
+            "Number of channels does not match the color space."
+        );
+        assert!(ImageRef::with_channels(&mut data, 2, 3, 3, ColorSpace::Rgb).is_ok());
+        assert_eq!(
+            ImageRef::with_channels(&mut data, 4, 3, 3, ColorSpace::Rgb),
+            Err(RefImageError::LengthMismatch)
+        );
+    }
+
+    #[test]
+    fn test_rows() {
+        use crate::{ColorSpace, ImageRef};
+        let mut data = vec![1u8, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13];
+        let mut img = ImageRef::new(&mut data, 2, 2, ColorSpace::Rgb).unwrap();
+        let rows: Vec<&[u8]> = img.rows().collect();
+        assert_eq!(rows, [&[1, 2, 3, 4, 5, 6][..], &[7, 8, 9, 10, 11, 12]]);
+        let pixels: Vec<&[u8]> = img.pixels().collect();
+        assert_eq!(pixels.len(), 4);
+        assert_eq!(pixels[3], &[10, 11, 12]);
+        for (y, row) in img.rows_mut().enumerate() {
+            row[0] = y as u8;
+        }
+        assert_eq!(img.as_slice()[0], 0);
+        assert_eq!(img.as_slice()[6], 1);
+        drop(img);
+        // Trailing data beyond the image is not included
+        assert_eq!(data[12], 13);
+    }
 }
@@ -1,6 +1,8 @@
 use std::num::NonZeroUsize;
 
-use crate::{BayerPattern, ColorSpace, PixelType};
+use num_traits::Float;
+
+use crate::{BayerPattern, ColorSpace, Enlargeable, PixelStor, PixelType};
 
 #[allow(unused_imports)]
 use crate::{
@@ -10,7 +12,8 @@ use crate::{
 
 /// A trait for shifting Bayer patterns.
 pub trait BayerShift {
-    /// Shift the Bayer pattern by `x` and `y` pixels.
+    /// Shift the Bayer pattern by `x` and `y` pixels, i.e. get the pattern of a region of
+    /// the mosaic with its origin at `(x, y)`.
     fn shift(&self, x: usize, y: usize) -> Self;
     /// Flip the Bayer pattern horizontally.
     fn flip_horizontal(&self) -> Self;
@@ -20,36 +23,8 @@ pub trait BayerShift {
 
 impl BayerShift for BayerPattern {
     fn shift(&self, x: usize, y: usize) -> Self {
-        match self {
-            BayerPattern::Rggb => match (x % 2, y % 2) {
-                (0, 0) => BayerPattern::Rggb,
-                (1, 0) => BayerPattern::Gbrg,
-                (0, 1) => BayerPattern::Grbg,
-                (1, 1) => BayerPattern::Bggr,
-                _ => unreachable!(),
-            },
-            BayerPattern::Gbrg => match (x % 2, y % 2) {
-                (0, 0) => BayerPattern::Gbrg,
-                (1, 0) => BayerPattern::Rggb,
-                (0, 1) => BayerPattern::Bggr,
-                (1, 1) => BayerPattern::Grbg,
-                _ => unreachable!(),
-            },
-            BayerPattern::Grbg => match (x % 2, y % 2) {
-                (0, 0) => BayerPattern::Grbg,
-                (1, 0) => BayerPattern::Bggr,
-                (0, 1) => BayerPattern::Rggb,
-                (1, 1) => BayerPattern::Gbrg,
-                _ => unreachable!(),
-            },
-            BayerPattern::Bggr => match (x % 2, y % 2) {
-                (0, 0) => BayerPattern::Bggr,
-                (1, 0) => BayerPattern::Grbg,
-                (0, 1) => BayerPattern::Gbrg,
-                (1, 1) => BayerPattern::Rggb,
-                _ => unreachable!(),
-            },
-        }
+        let (rx, ry) = self.red_pixel();
+        BayerPattern::from_red_pixel(rx + x % 2, ry + y % 2)
     }
     fn flip_horizontal(&self) -> Self {
         match self {
@@ -69,6 +44,30 @@ impl BayerShift for BayerPattern {
     }
 }
 
+/// Luminance coefficients of the RGB channels, used by [`ToLuma::to_luma_preset`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LumaCoefficients {
+    /// ITU-R BT.601 coefficients, `Y = 0.299R + 0.587G + 0.114B`, as used by
+    /// [`ToLuma::to_luma`].
+    Rec601,
+    /// ITU-R BT.709 coefficients, `Y = 0.2126R + 0.7152G + 0.0722B`, as used by most
+    /// modern displays.
+    Rec709,
+    /// Custom coefficients of the red, green and blue channels.
+    Custom([f64; 3]),
+}
+
+impl LumaCoefficients {
+    /// Get the coefficients of the red, green and blue channels.
+    pub fn weights(&self) -> [f64; 3] {
+        match self {
+            Self::Rec601 => crate::coreimpls::LUMA_COEFFS,
+            Self::Rec709 => [0.2126, 0.7152, 0.0722],
+            Self::Custom(wts) => *wts,
+        }
+    }
+}
+
 /// A trait for converting an image to a luminance image.
 ///
 /// This trait is implemented for [`ImageRef`], [`DynamicImageRef`], [`GenericImageRef`] and
@@ -79,6 +78,8 @@ pub trait ToLuma {
     ///
     /// This function uses the formula `Y = 0.299R + 0.587G + 0.114B` to calculate the
     /// corresponding luminance image.
+    /// CMYK images are converted to RGB first, with `R = (1 - C)(1 - K)` etc. on values
+    /// normalized to the valid range of the pixel type.
     ///
     /// # Errors
     /// - If the image is not debayered and is not a grayscale image.
@@ -88,12 +89,26 @@ pub trait ToLuma {
     /// Convert the image to a luminance image with custom coefficients.
     ///
     /// # Arguments
-    /// - `wts`: The weights to use for the conversion.
+    /// - `wts`: The weights to use for the conversion, one per channel. CMYK images take
+    ///   three weights, which are applied to the RGB values.
     ///
     /// # Errors
     /// - If the image is not debayered and is not a grayscale image.
     /// - If the image is not an RGB image.
+    /// - If the number of weights does not match the number of channels.
+    /// - If the weights do not sum to 1 (within `1e-3`).
     fn to_luma_custom(&mut self, coeffs: &[f64]) -> Result<(), &'static str>;
+
+    /// Convert the image to a luminance image with preset coefficients.
+    ///
+    /// # Arguments
+    /// - `coeffs`: The [`LumaCoefficients`] of the RGB channels.
+    ///
+    /// # Errors
+    /// - See [`ToLuma::to_luma_custom`].
+    fn to_luma_preset(&mut self, coeffs: LumaCoefficients) -> Result<(), &'static str> {
+        self.to_luma_custom(&coeffs.weights())
+    }
 }
 
 /// A trait for accessing the properties of an image.
@@ -121,6 +136,20 @@ pub trait ImageProps {
     /// Check if the data is empty.
     fn is_empty(&self) -> bool;
 
+    /// Get the number of pixels in the image, i.e. `width * height`.
+    fn pixel_count(&self) -> usize {
+        self.width() * self.height()
+    }
+
+    /// Get the aspect ratio of the image, i.e. `width / height`.
+    ///
+    /// # Returns
+    /// The aspect ratio of the image. If the height is zero, the result is infinite
+    /// (or `NaN` if the width is also zero).
+    fn aspect_ratio(&self) -> f64 {
+        self.width() as f64 / self.height() as f64
+    }
+
     /// Convert the image to a `u8` image.
     ///
     /// Conversion is done by scaling the pixel values to the range `[0, 255]`.
@@ -129,6 +158,119 @@ pub trait ImageProps {
     fn cast_u8(&self) -> Self::OutputU8;
 }
 
+/// A trait for mutable access to the pixel data of an image.
+///
+/// This trait is implemented for [`ImageRef`] and [`ImageOwned`], and allows in-place
+/// operations on the pixel data to be implemented once for both types. The color space
+/// of the image is available through the [`ImageProps`] supertrait.
+pub trait MutImageData<T: PixelStor>: ImageProps {
+    /// Get the pixel data of the image as a mutable slice.
+    ///
+    /// The returned slice is exactly [`ImageProps::len`] elements long, even if the
+    /// backing storage is longer.
+    fn as_mut_logical_slice(&mut self) -> &mut [T];
+
+    /// Get the dimensions of the image as `(width, height, channels)`.
+    fn dims(&self) -> (usize, usize, u8) {
+        (self.width(), self.height(), self.channels())
+    }
+
+    /// Apply `f` to every stored element of the image in-place.
+    ///
+    /// All channels, including alpha, are transformed. This is a safe alternative to
+    /// manipulating the slice returned by [`MutImageData::as_mut_logical_slice`].
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    ///
+    /// # Arguments
+    /// - `f`: The per-element transform.
+    fn map_pixels<F: Fn(T) -> T + Sync>(&mut self, f: F) {
+        crate::coreimpls::run_map_pixels(self.as_mut_logical_slice(), f);
+    }
+
+    /// Apply gamma correction to the image in-place.
+    ///
+    /// Each pixel is mapped as `out = max * (in / max)^gamma`, where `max` is the valid
+    /// maximum of the pixel type (`1.0` for floating point images). Signed images are
+    /// normalized over their full range. A `gamma` below `1` brightens the image, e.g.
+    /// `1.0 / 2.2` to encode for display, and a `gamma` above `1` darkens it. The alpha
+    /// channel of [`ColorSpace::GrayAlpha`] and [`ColorSpace::Rgba`] images is not modified.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    ///
+    /// # Errors
+    /// - If `gamma` is not a positive finite number.
+    fn apply_gamma(&mut self, gamma: f64) -> Result<(), &'static str> {
+        if !gamma.is_finite() || gamma <= 0.0 {
+            return Err("Gamma must be a positive finite number.");
+        }
+        let channels = self.channels() as usize;
+        let alpha = self.color_space().has_alpha();
+        crate::coreimpls::run_gamma(self.as_mut_logical_slice(), channels, alpha, gamma);
+        Ok(())
+    }
+
+    /// Replace every NaN and infinite value in the image with `replacement`, e.g.
+    /// before converting the image to an integer pixel type or serializing it.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    ///
+    /// # Arguments
+    /// - `replacement`: The value to replace non-finite values with.
+    fn sanitize(&mut self, replacement: T)
+    where
+        T: Float,
+    {
+        crate::coreimpls::run_sanitize(self.as_mut_logical_slice(), replacement);
+    }
+
+    /// Add `v` to every sample of the image in-place, e.g. to add a pedestal.
+    ///
+    /// The sum is computed in the larger type of the pixel type (see
+    /// [`Enlargeable`]), and saturates at the valid range of the pixel type
+    /// instead of wrapping around. All channels are modified.
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    ///
+    /// # Arguments
+    /// - `v`: The value to add.
+    fn add_scalar(&mut self, v: T)
+    where
+        T: Enlargeable,
+    {
+        crate::scalar::run_add_scalar(self.as_mut_logical_slice(), v);
+    }
+
+    /// Subtract `v` from every sample of the image in-place, e.g. to remove a
+    /// constant bias.
+    ///
+    /// The difference saturates at the valid range of the pixel type instead of
+    /// wrapping around. See [`MutImageData::add_scalar`] for details.
+    ///
+    /// # Arguments
+    /// - `v`: The value to subtract.
+    fn sub_scalar(&mut self, v: T)
+    where
+        T: Enlargeable,
+    {
+        crate::scalar::run_sub_scalar(self.as_mut_logical_slice(), v);
+    }
+
+    /// Multiply every sample of the image by `factor` in-place.
+    ///
+    /// The product is computed in [`f64`], rounded to the nearest integer for
+    /// integer images, and saturates at the valid range of the pixel type. A NaN
+    /// product is set to [`PixelStor::DEFAULT_MIN_VALUE`].
+    ///
+    /// Note: This operation is parallelized if the `rayon` feature is enabled.
+    ///
+    /// # Arguments
+    /// - `factor`: The scale factor.
+    fn mul_scalar(&mut self, factor: f64) {
+        crate::scalar::run_mul_scalar(self.as_mut_logical_slice(), factor);
+    }
+}
+
 /// A trait for selecting a region of interest (ROI) from an image.
 pub trait SelectRoi {
     /// The output type of [`SelectRoi::select_roi`].
@@ -155,6 +297,89 @@ pub trait SelectRoi {
         width: NonZeroUsize,
         height: NonZeroUsize,
     ) -> Result<Self::Output, &'static str>;
+
+    /// Select a `width` by `height` region from the center of the image.
+    ///
+    /// The origin of the region is `((image width - width) / 2, (image height - height) / 2)`,
+    /// i.e. if the margins can not be split evenly, the extra row or column is on the
+    /// bottom or right side.
+    ///
+    /// # Arguments
+    /// - `width`: The width of the region.
+    /// - `height`: The height of the region.
+    ///
+    /// # Errors
+    /// - If the region is larger than the image.
+    fn center_crop(
+        &self,
+        width: NonZeroUsize,
+        height: NonZeroUsize,
+    ) -> Result<Self::Output, &'static str>
+    where
+        Self: ImageProps,
+    {
+        let (swid, shei) = (self.width(), self.height());
+        if width.get() > swid || height.get() > shei {
+            return Err("Crop region is larger than the image.");
+        }
+        self.select_roi(
+            (swid - width.get()) / 2,
+            (shei - height.get()) / 2,
+            width,
+            height,
+        )
+    }
+
+    /// Select the largest square region from the center of the image, i.e. of side
+    /// `min(width, height)`.
+    ///
+    /// See [`SelectRoi::center_crop`] for details.
+    ///
+    /// # Errors
+    /// - If the image is empty.
+    fn center_crop_square(&self) -> Result<Self::Output, &'static str>
+    where
+        Self: ImageProps,
+    {
+        let side = NonZeroUsize::new(self.width().min(self.height())).ok_or("Image is empty.")?;
+        self.center_crop(side, side)
+    }
+}
+
+/// A trait for mirroring an image about its axes.
+///
+/// Flipping a Bayer mosaic image changes the phase of the mosaic if the flipped
+/// dimension is even, e.g. flipping an RGGB image with an even width horizontally
+/// results in a GRBG image. The Bayer pattern of the output is adjusted accordingly.
+pub trait Flip {
+    /// The output type of [`Flip::flip_h`] and [`Flip::flip_v`].
+    type Output;
+
+    /// Flip the image horizontally, i.e. mirror the image about the vertical axis.
+    fn flip_h(&self) -> Self::Output;
+
+    /// Flip the image vertically, i.e. mirror the image about the horizontal axis.
+    fn flip_v(&self) -> Self::Output;
+}
+
+/// Rotation of an image by a multiple of 90 degrees, clockwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rotation {
+    /// Rotate by 90 degrees clockwise.
+    Rotate90,
+    /// Rotate by 180 degrees.
+    Rotate180,
+    /// Rotate by 270 degrees clockwise, i.e. 90 degrees counter-clockwise.
+    Rotate270,
+}
+
+/// Filter used to resample an image in [`ImageOwned::resize`](crate::ImageOwned::resize).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResizeFilter {
+    /// Use the value of the nearest source pixel.
+    Nearest,
+    /// Interpolate linearly between the four nearest source pixels.
+    Bilinear,
 }
 
 /// A trait for copying a region of interest (ROI) from one image to another.
@@ -167,3 +392,41 @@ pub trait CopyRoi {
     /// This function will always zero out the destination image before copying the ROI.
     fn copy_to(&self, dest: &mut Self::Output, x: usize, y: usize);
 }
+
+/// A transformation of [`ImageOwned`] data that is generic over the pixel type.
+///
+/// Closures can not be generic, so this trait is used to write a single function body
+/// that is dispatched over the variants of [`DynamicImageOwned`], e.g. by
+/// [`DynamicImageOwned::map_image`] and [`GenericImageOwned::map_image`].
+///
+/// # Usage
+/// ```
+/// use refimage::{DynamicImageOwned, Enlargeable, ImageMap, ImageOwned, PixelStor, ColorSpace};
+///
+/// struct Invert;
+///
+/// impl ImageMap for Invert {
+///     fn map<T>(&mut self, img: &ImageOwned<T>) -> Result<DynamicImageOwned, &'static str>
+///     where
+///         T: PixelStor + Enlargeable,
+///         DynamicImageOwned: From<ImageOwned<T>>,
+///     {
+///         let mut out = img.clone();
+///         out.iter_mut().for_each(|v| *v = T::DEFAULT_MAX_VALUE - *v);
+///         Ok(out.into())
+///     }
+/// }
+///
+/// let img = ImageOwned::from_owned(vec![0u8, 10, 255], 3, 1, ColorSpace::Gray).unwrap();
+/// let img = DynamicImageOwned::from(img).map_image(Invert).unwrap();
+/// assert_eq!(img.as_slice_u8(), Some(&[255u8, 245, 0][..]));
+/// ```
+pub trait ImageMap {
+    /// Transform the image data.
+    ///
+    /// The output may have a different pixel type than the input.
+    fn map<T>(&mut self, img: &ImageOwned<T>) -> Result<DynamicImageOwned, &'static str>
+    where
+        T: PixelStor + Enlargeable,
+        DynamicImageOwned: From<ImageOwned<T>>;
+}
@@ -43,8 +43,10 @@
 //! # Optional Features
 //! Features are available to extend the functionalities of the core `refimage` data types:
 //! - `rayon`: Parallelizes [`GenericImageRef::to_luma`] (and similar), [`GenericImageRef::to_luma_custom`], [`GenericImageRef::into_u8`] and [`GenericImageRef::debayer`] functions (<b>enabled</b> by default).
-//! - `fitsio`: Exposes [`FitsWrite`] trait to write [`GenericImageRef`] and [`GenericImageOwned`] (<b>disabled</b> by default).
-//! - `image`: Enables [`TryFrom`] conversions between [`DynamicImage`] and [`DynamicImageRef`], [`DynamicImageOwned`] (<b>disabled</b> by default).
+//! - `fitsio`: Exposes [`FitsWrite`] trait to write [`GenericImageRef`] and [`GenericImageOwned`], and [`FitsRead`] trait to read [`GenericImageOwned`] (<b>disabled</b> by default).
+//! - `image`: Enables [`TryFrom`] conversions between [`DynamicImage`] and [`DynamicImageRef`], [`DynamicImageOwned`], and PNG and JPEG output through [`ImageEncoder`] (<b>disabled</b> by default).
+//! - `png`: Exposes `write_png` on [`DynamicImageOwned`] and [`GenericImageOwned`] to write 8- and 16-bit PNG files using the lightweight `png` crate (<b>disabled</b> by default).
+//! - `f16`: Implements [`PixelStor`] and [`Enlargeable`] for `half::f16`, with images converted to 32-bit floating point when wrapped in a [`DynamicImageOwned`] (<b>disabled</b> by default).
 //!
 
 mod coreimpls;
@@ -61,27 +63,40 @@ mod dynamicimageowned;
 #[cfg(feature = "image")]
 mod dynamicimage_interop;
 mod dynamicimage_serde;
+mod encoder;
+mod error;
 #[cfg(feature = "fitsio")]
 mod fitsio_interop;
 mod genericimage;
 mod genericimageowned;
 mod genericimageref;
+#[cfg(feature = "png")]
+mod png_interop;
 #[cfg(feature = "fitsio")]
 #[cfg_attr(docsrs, doc(cfg(feature = "fitsio")))]
-pub use fitsio_interop::{create_fits, FitsCompression, FitsError, FitsWrite};
+pub use fitsio_interop::{
+    create_fits, write_fits_cube, FitsCompression, FitsError, FitsImageWriter, FitsRead, FitsWrite,
+    MetaTable,
+};
 
+pub use encoder::{EncodeError, ImageEncoder, ImageFormat};
+pub use error::RefImageError;
 pub use genericimageowned::GenericImageOwned;
 pub use genericimageref::GenericImageRef;
 
 mod metadata;
 pub use metadata::{
-    GenericLineItem, GenericValue, CAMERANAME_KEY, EXPOSURE_KEY, PROGRAMNAME_KEY, TIMESTAMP_KEY,
+    GenericLineItem, GenericValue, MergePolicy, CAMERANAME_KEY, EXPOSURE_KEY, FRAME_DELAY_KEY,
+    GAIN_KEY, ICC_PROFILE_KEY, PROGRAMNAME_KEY, SUBFRAME_X_KEY, SUBFRAME_Y_KEY, TIMESTAMP_KEY,
 };
 
 pub use coretraits::{Enlargeable, PixelStor};
-pub use demosaic::{BayerError, Debayer, DemosaicMethod};
+pub use demosaic::{demosaic_psnr, BayerError, Debayer, DemosaicMethod, RowWriter};
 pub use genericimage::GenericImage;
-pub use imagetraits::{BayerShift, CopyRoi, ImageProps, SelectRoi, ToLuma};
+pub use imagetraits::{
+    BayerShift, CopyRoi, Flip, ImageMap, ImageProps, LumaCoefficients, MutImageData, ResizeFilter,
+    Rotation, SelectRoi, ToLuma,
+};
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "image")]
@@ -94,7 +109,39 @@ pub use imageowned::ImageOwned;
 pub use imageref::ImageRef;
 
 mod optimumexposure;
-pub use optimumexposure::{CalcOptExp, OptimumExposure, OptimumExposureBuilder};
+pub use optimumexposure::{CalcOptExp, ExposureOutcome, OptimumExposure, OptimumExposureBuilder};
+
+mod stack;
+pub use stack::{
+    stack_max, stack_mean, stack_mean_and_sigma, stack_median, stack_min, Accumulator,
+};
+
+mod motion;
+pub use motion::ChangeReport;
+
+mod bayerquad;
+pub use bayerquad::BayerQuad;
+
+pub mod border;
+pub use border::BorderMode;
+pub use coreimpls::gaussian_kernel;
+
+mod focus;
+pub use focus::FocusMetric;
+
+mod defects;
+
+mod binning;
+pub use binning::BinMode;
+
+mod statistics;
+pub use statistics::ChannelStats;
+
+mod threshold;
+
+mod scalar;
+
+mod alpha;
 
 /// Image data with a dynamic pixel type, backed by a mutable slice of data.
 ///
@@ -106,7 +153,9 @@ pub use optimumexposure::{CalcOptExp, OptimumExposure, OptimumExposureBuilder};
 /// reuse of allocated memory without re-allocation.
 ///
 /// # Note
-/// - Does not support alpha channel natively.
+/// - Does not support alpha channel natively. Use [`DynamicImageRef::add_alpha`] and
+///   [`DynamicImageRef::remove_alpha`] to convert to and from images with an alpha
+///   channel, which are represented by custom color spaces.
 /// - Internally [`DynamicImageRef`] and [`DynamicImageOwned`] serialize to the same
 ///   representation, and [`DynamicImageRef`] can be deserialized into [`DynamicImageOwned`] only.
 ///
@@ -130,6 +179,10 @@ pub enum DynamicImageRef<'a> {
     U8(ImageRef<'a, u8>),
     /// Image data with a `u16` primitive type.
     U16(ImageRef<'a, u16>),
+    /// Image data with a `i16` primitive type.
+    I16(ImageRef<'a, i16>),
+    /// Image data with a `u32` primitive type.
+    U32(ImageRef<'a, u32>),
     /// Image data with a `f32` primitive type.
     F32(ImageRef<'a, f32>),
 }
@@ -143,7 +196,9 @@ pub enum DynamicImageRef<'a> {
 /// contiguous buffer, which is backed by a vector.
 ///
 /// # Note
-/// - Does not support alpha channel natively.
+/// - Does not support alpha channel natively. Use [`DynamicImageOwned::add_alpha`] and
+///   [`DynamicImageOwned::remove_alpha`] to convert to and from images with an alpha
+///   channel, which are represented by custom color spaces.
 /// - [`DynamicImageRef`] implements [`Serialize`] and [`Deserialize`] traits, and can be
 ///   deserialized from a [`DynamicImageRef`].
 ///
@@ -167,6 +222,10 @@ pub enum DynamicImageOwned {
     U8(ImageOwned<u8>),
     /// [`ImageOwned`] with a `u16` primitive type.
     U16(ImageOwned<u16>),
+    /// [`ImageOwned`] with a `i16` primitive type.
+    I16(ImageOwned<i16>),
+    /// [`ImageOwned`] with a `u32` primitive type.
+    U32(ImageOwned<u32>),
     /// [`ImageOwned`] with a `f32` primitive type.
     F32(ImageOwned<f32>),
 }
@@ -185,8 +244,32 @@ pub enum ColorSpace {
     Bayer(BayerPattern) = 0b001,
     /// RGB image.
     Rgb = 0b100,
-    /// Custom color space.
+    /// Custom color space, with the given number of channels and a description.
+    ///
+    /// No channel of a custom color space is interpreted as an alpha channel.
     Custom(u8, String) = 0b111,
+    /// CMYK image, with the cyan, magenta, yellow and key (black) channels in order.
+    ///
+    /// The channels are interpreted as ink coverage, where the maximum value of the pixel
+    /// type is full coverage.
+    Cmyk = 0b101,
+    /// Grayscale image with an alpha channel, with the gray and alpha channels in order.
+    GrayAlpha = 0b010,
+    /// RGB image with an alpha channel, with the red, green, blue and alpha channels in order.
+    Rgba = 0b110,
+}
+
+/// A rectangular region of an image, in pixels.
+#[derive(Debug, PartialEq, Copy, Clone, Eq, Hash, Serialize, Deserialize)]
+pub struct Rect {
+    /// The column of the top-left corner of the region.
+    pub x: usize,
+    /// The row of the top-left corner of the region.
+    pub y: usize,
+    /// The width of the region.
+    pub width: usize,
+    /// The height of the region.
+    pub height: usize,
 }
 
 /// Enum to describe the Bayer pattern of the image.
@@ -231,9 +314,141 @@ pub enum PixelType {
     F32 = -32,
     /// 64-bit floating point.
     F64 = -64,
+    // Feature-gated variants are last, so that the serialized variant indices of the
+    // other variants do not depend on the enabled features.
+    /// 16-bit floating point.
+    ///
+    /// FITS does not support half-precision data, which is written as 32-bit floating point.
+    /// The discriminant is not a FITS `BITPIX` value.
+    #[cfg(feature = "f16")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "f16")))]
+    F16 = -17,
 }
 
 mod test {
+    #[test]
+    fn test_bayer_shift() {
+        use crate::{BayerPattern, BayerShift};
+        use BayerPattern::*;
+        for (pat, right, down, both) in [
+            (Rggb, Grbg, Gbrg, Bggr),
+            (Grbg, Rggb, Bggr, Gbrg),
+            (Gbrg, Bggr, Rggb, Grbg),
+            (Bggr, Gbrg, Grbg, Rggb),
+        ] {
+            assert_eq!(pat.shift(0, 2), pat);
+            assert_eq!(pat.shift(1, 0), right);
+            assert_eq!(pat.shift(3, 2), right);
+            assert_eq!(pat.shift(0, 1), down);
+            assert_eq!(pat.shift(1, 1), both);
+            assert_eq!(pat.shift(1, 0), pat.flip_horizontal());
+            assert_eq!(pat.shift(0, 1), pat.flip_vertical());
+        }
+    }
+
+    #[test]
+    fn test_cspace_canonical_str() {
+        use crate::{BayerPattern, ColorSpace};
+        for cspace in [
+            ColorSpace::Gray,
+            ColorSpace::Rgb,
+            ColorSpace::Bayer(BayerPattern::Gbrg),
+            ColorSpace::Custom(4, "multispec".to_owned()),
+            ColorSpace::Custom(2, "a, (b)".to_owned()),
+        ] {
+            let val = cspace.to_canonical_str();
+            assert_eq!(ColorSpace::from_canonical_str(&val), Ok(cspace));
+        }
+        assert_eq!(
+            ColorSpace::Custom(4, "multispec".to_owned()).to_canonical_str(),
+            "C(4, multispec)"
+        );
+        assert!(ColorSpace::from_canonical_str("C(0, none)").is_err());
+        assert!(ColorSpace::from_canonical_str("C(x, none)").is_err());
+        assert!(ColorSpace::from_canonical_str("YUV").is_err());
+    }
+
+    #[test]
+    fn test_cspace_channels() {
+        use crate::{BayerPattern, ColorSpace, ImageOwned, RefImageError};
+        assert_eq!(ColorSpace::Gray.channel_count(), Some(1));
+        assert_eq!(
+            ColorSpace::Bayer(BayerPattern::Gbrg).channel_count(),
+            Some(1)
+        );
+        assert_eq!(ColorSpace::Rgb.channel_count(), Some(3));
+        assert_eq!(ColorSpace::Custom(5, "MS".into()).channel_count(), Some(5));
+        assert_eq!(ColorSpace::Custom(0, "EMPTY".into()).channel_count(), None);
+        assert!(ColorSpace::Bayer(BayerPattern::Rggb).is_bayer());
+        assert!(!ColorSpace::Gray.is_bayer());
+        assert!(ColorSpace::Rgba.has_alpha());
+        assert!(!ColorSpace::Rgb.has_alpha());
+        assert!(!ColorSpace::Custom(4, "RGBN".into()).has_alpha());
+        assert_eq!(
+            ImageOwned::from_owned(vec![0u8; 4], 2, 2, ColorSpace::Custom(0, "EMPTY".into())),
+            Err(RefImageError::NoChannels)
+        );
+    }
+
+    #[test]
+    fn test_pixeltype_variant_index() {
+        use crate::PixelType;
+        // Serialized variant indices must not change with the enabled features
+        let index = |pixel: PixelType| bincode::serialize(&pixel).unwrap();
+        assert_eq!(index(PixelType::U8), 0u32.to_le_bytes());
+        assert_eq!(index(PixelType::F32), 8u32.to_le_bytes());
+        assert_eq!(index(PixelType::F64), 9u32.to_le_bytes());
+        #[cfg(feature = "f16")]
+        assert_eq!(index(PixelType::F16), 10u32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_cmyk() {
+        use crate::{ColorSpace, DynamicImageOwned, ImageOwned, ImageProps, ImageRef, ToLuma};
+        assert_eq!(ColorSpace::Cmyk.channel_count(), Some(4));
+        assert!(!ColorSpace::Cmyk.has_alpha());
+        assert_eq!(ColorSpace::Cmyk.to_canonical_str(), "CMYK");
+        assert_eq!(ColorSpace::from_canonical_str("CMYK"), Ok(ColorSpace::Cmyk));
+        // White, black (key), cyan and 50% gray (key)
+        let data = vec![0u8, 0, 0, 0, 0, 0, 0, 255, 255, 0, 0, 0, 0, 0, 0, 128];
+        let mut img = ImageOwned::from_owned(data.clone(), 2, 2, ColorSpace::Cmyk).unwrap();
+        assert_eq!(img.channels(), 4);
+        let blob = bincode::serialize(&DynamicImageOwned::from(img.clone())).unwrap();
+        let de: DynamicImageOwned = bincode::deserialize(&blob).unwrap();
+        assert_eq!(de.color_space(), ColorSpace::Cmyk);
+        img.to_luma().unwrap();
+        assert_eq!(img.color_space(), ColorSpace::Gray);
+        assert_eq!(img.as_slice(), &[255, 0, 179, 127]);
+
+        let mut data = vec![0.0f32, 0.0, 0.0, 0.5, 1.0];
+        let mut img = ImageRef::new(&mut data, 1, 1, ColorSpace::Cmyk).unwrap();
+        img.to_luma().unwrap();
+        assert_eq!((img.channels(), img.as_slice()), (1, &[0.5f32][..]));
+        assert!(ImageOwned::from_owned(vec![0u8; 3], 1, 1, ColorSpace::Cmyk).is_err());
+    }
+
+    #[test]
+    fn test_alpha_cspace() {
+        use crate::{ColorSpace, ImageOwned, ImageProps, ImageRef};
+        for (ch, cspace) in [(2u8, ColorSpace::GrayAlpha), (4, ColorSpace::Rgba)] {
+            assert!(cspace.has_alpha());
+            assert_eq!(cspace.channel_count(), Some(ch));
+            assert_eq!(
+                ColorSpace::from_canonical_str(&cspace.to_canonical_str()),
+                Ok(cspace.clone())
+            );
+            let mut data = vec![0u8; 3 * 2 * ch as usize];
+            let img = ImageRef::new(&mut data, 3, 2, cspace.clone()).unwrap();
+            assert_eq!((img.channels(), img.len()), (ch, 6 * ch as usize));
+            let img = ImageOwned::from_owned(data.clone(), 3, 2, cspace.clone()).unwrap();
+            assert_eq!((img.channels(), img.len()), (ch, 6 * ch as usize));
+            assert!(ImageRef::with_channels(&mut data, 3, 2, ch, cspace.clone()).is_ok());
+            assert!(ImageRef::with_channels(&mut data, 3, 2, ch - 1, cspace.clone()).is_err());
+            data.pop();
+            assert!(ImageRef::new(&mut data, 3, 2, cspace).is_err());
+        }
+    }
+
     #[test]
     fn test_debayer() {
         use crate::demosaic::Debayer;
@@ -262,6 +477,34 @@ mod test {
         assert!(a.color_space() == crate::ColorSpace::Rgb);
         assert_eq!(a.as_slice(), &expected);
     }
+
+    #[test]
+    fn test_demosaic_psnr() {
+        use crate::demosaic::Debayer;
+        let mut src = [
+            229u8, 67, 95, 146, 232, 51, 229, 241, 169, 161, 15, 52, 45, 175, 98, 197,
+        ];
+        let img = crate::ImageRef::new(
+            &mut src,
+            4,
+            4,
+            crate::ColorSpace::Bayer(crate::BayerPattern::Rggb),
+        )
+        .expect("Failed to create ImageRef");
+        let reference = img.debayer(crate::DemosaicMethod::Linear).unwrap();
+        let psnr = crate::demosaic_psnr(&reference, &img, crate::DemosaicMethod::Linear).unwrap();
+        assert!(psnr.is_infinite());
+        let psnr = crate::demosaic_psnr(&reference, &img, crate::DemosaicMethod::None).unwrap();
+        assert!(psnr.is_finite() && psnr > 0.0);
+        let custom = crate::ImageOwned::from_owned(
+            reference.as_slice().to_vec(),
+            4,
+            4,
+            crate::ColorSpace::Custom(3, "XYZ".into()),
+        )
+        .unwrap();
+        assert!(crate::demosaic_psnr(&custom, &img, crate::DemosaicMethod::Linear).is_err());
+    }
 }
 
 // Can't use the macro-call itself within the `doc` attribute. So force it to eval it as part of
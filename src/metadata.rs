@@ -1,4 +1,7 @@
-use std::time::{Duration, SystemTime};
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime},
+};
 
 use serde::{Deserialize, Serialize};
 
@@ -15,6 +18,30 @@ pub const CAMERANAME_KEY: &str = "CAMERA";
 pub const PROGRAMNAME_KEY: &str = "PROGNAME";
 /// Key for exposure time metadata of the image.
 pub const EXPOSURE_KEY: &str = "EXPOSURE";
+/// Key for the sensor gain metadata of the image.
+pub const GAIN_KEY: &str = "GAIN";
+/// Key for the ICC color profile of the image.
+///
+/// The profile is stored as a [`GenericValue::Bytes`] value, and is set using
+/// [`GenericImageOwned::set_icc_profile`] or [`GenericImageRef::set_icc_profile`].
+pub const ICC_PROFILE_KEY: &str = "ICCPROFILE";
+/// Key for the display duration of a frame of an animated image.
+///
+/// The delay is stored as a [`GenericValue::Duration`] value, and is set by
+/// `GenericImageOwned::from_frames` when the `image` feature is enabled.
+pub const FRAME_DELAY_KEY: &str = "FRAMEDELAY";
+/// Key for the column of the top-left corner of a sub-frame, in the coordinates of
+/// the full sensor.
+///
+/// The origin is accumulated by [`crate::SelectRoi::select_roi`] on
+/// [`GenericImageRef`], [`GenericImageOwned`] and [`GenericImage`].
+pub const SUBFRAME_X_KEY: &str = "XORGSUBF";
+/// Key for the row of the top-left corner of a sub-frame, in the coordinates of
+/// the full sensor.
+///
+/// The origin is accumulated by [`crate::SelectRoi::select_roi`] on
+/// [`GenericImageRef`], [`GenericImageOwned`] and [`GenericImage`].
+pub const SUBFRAME_Y_KEY: &str = "YORGSUBF";
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 /// A metadata item.
@@ -83,6 +110,10 @@ pub enum GenericValue {
     SystemTime(SystemTime),
     /// A string.
     String(String),
+    /// A binary blob, e.g. an ICC color profile.
+    ///
+    /// Binary blobs are not written to FITS files.
+    Bytes(Vec<u8>),
 }
 
 impl GenericLineItem {
@@ -121,6 +152,7 @@ impl_from_genericvalue!(ColorSpace, GenericValue::ColorSpace);
 impl_from_genericvalue!(Duration, GenericValue::Duration);
 impl_from_genericvalue!(SystemTime, GenericValue::SystemTime);
 impl_from_genericvalue!(String, GenericValue::String);
+impl_from_genericvalue!(Vec<u8>, GenericValue::Bytes);
 
 macro_rules! impl_tryinto_genericvalue {
     ($t:ty, $variant:path) => {
@@ -151,6 +183,7 @@ impl_tryinto_genericvalue!(ColorSpace, GenericValue::ColorSpace);
 impl_tryinto_genericvalue!(Duration, GenericValue::Duration);
 impl_tryinto_genericvalue!(SystemTime, GenericValue::SystemTime);
 impl_tryinto_genericvalue!(String, GenericValue::String);
+impl_tryinto_genericvalue!(Vec<u8>, GenericValue::Bytes);
 
 /// Trait to insert a metadata value into a [`GenericImageRef`].
 pub trait InsertValue {
@@ -279,6 +312,218 @@ macro_rules! insert_value_impl {
     };
 }
 
+/// Policy to resolve key collisions when merging metadata with
+/// [`GenericImageOwned::merge_metadata_from`].
+///
+/// Keys present in both images with identical values and comments are not
+/// considered to collide. The timestamp ([`TIMESTAMP_KEY`]) of the image merged into
+/// is always kept, and is never reported as a collision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Keep the existing value.
+    KeepSelf,
+    /// Replace the existing value with the incoming value.
+    TakeOther,
+    /// Fail on collision, leaving the metadata unchanged.
+    Error,
+}
+
+/// Merge metadata from another map according to a [`MergePolicy`], keeping the
+/// existing timestamp.
+///
+/// Returns the sorted list of colliding keys if the policy is [`MergePolicy::Error`].
+pub(crate) fn merge_from(
+    metadata: &mut HashMap<String, GenericLineItem>,
+    other: &HashMap<String, GenericLineItem>,
+    policy: MergePolicy,
+) -> Result<(), Vec<String>> {
+    if policy == MergePolicy::Error {
+        let mut conflicts: Vec<String> = other
+            .iter()
+            .filter(|(name, line)| {
+                name.as_str() != TIMESTAMP_KEY
+                    && metadata.get(name.as_str()).is_some_and(|old| old != *line)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        if !conflicts.is_empty() {
+            conflicts.sort();
+            return Err(conflicts);
+        }
+    }
+    for (name, line) in other.iter() {
+        if name.as_str() == TIMESTAMP_KEY {
+            continue;
+        }
+        if policy == MergePolicy::TakeOther || !metadata.contains_key(name) {
+            metadata.insert(name.clone(), line.clone());
+        }
+    }
+    Ok(())
+}
+
+/// Edit metadata in bulk with a closure, restoring the timestamp afterwards if the
+/// closure removed it or replaced it with a value that is not a timestamp.
+///
+/// The closure edits a copy of the metadata. Its keys are then validated and
+/// uppercased as in `insert_key`, and the metadata is left unchanged on error.
+pub(crate) fn edit_values<F: FnOnce(&mut HashMap<String, GenericLineItem>)>(
+    metadata: &mut HashMap<String, GenericLineItem>,
+    f: F,
+) -> Result<(), &'static str> {
+    let mut edited = metadata.clone();
+    f(&mut edited);
+    let mut checked = HashMap::with_capacity(edited.len());
+    for (name, line) in edited {
+        name_check(&name)?;
+        if checked.insert(name.to_uppercase(), line).is_some() {
+            return Err("Keys cannot differ only in case");
+        }
+    }
+    if let Some(line) = metadata.get(TIMESTAMP_KEY) {
+        let valid = checked
+            .get(TIMESTAMP_KEY)
+            .is_some_and(|x| x.get_value().get_value_systemtime().is_some());
+        if !valid {
+            checked.insert(TIMESTAMP_KEY.to_owned(), line.clone());
+        }
+    }
+    *metadata = checked;
+    Ok(())
+}
+
+/// Get a numeric metadata value as an [`f64`].
+fn as_f64(value: &GenericValue) -> Option<f64> {
+    match *value {
+        GenericValue::U8(v) => Some(v as f64),
+        GenericValue::U16(v) => Some(v as f64),
+        GenericValue::U32(v) => Some(v as f64),
+        GenericValue::U64(v) => Some(v as f64),
+        GenericValue::I8(v) => Some(v as f64),
+        GenericValue::I16(v) => Some(v as f64),
+        GenericValue::I32(v) => Some(v as f64),
+        GenericValue::I64(v) => Some(v as f64),
+        GenericValue::F32(v) => Some(v as f64),
+        GenericValue::F64(v) => Some(v),
+        _ => None,
+    }
+}
+
+/// Update coordinate metadata after cropping the image at offset `(x, y)`.
+///
+/// The sub-frame origin is accumulated, and the WCS reference pixel (`CRPIX1`,
+/// `CRPIX2`) is shifted, if present.
+pub(crate) fn crop_values(metadata: &mut HashMap<String, GenericLineItem>, x: usize, y: usize) {
+    for (key, offset, comment) in [
+        (SUBFRAME_X_KEY, x, "Sub-frame origin column"),
+        (SUBFRAME_Y_KEY, y, "Sub-frame origin row"),
+    ] {
+        let origin = metadata
+            .get(key)
+            .and_then(|line| as_f64(&line.value))
+            .unwrap_or(0.0) as u64;
+        metadata.insert(
+            key.to_owned(),
+            GenericLineItem {
+                value: GenericValue::U64(origin + offset as u64),
+                comment: Some(comment.to_owned()),
+            },
+        );
+    }
+    for (key, offset) in [("CRPIX1", x), ("CRPIX2", y)] {
+        if let Some(line) = metadata.get_mut(key) {
+            if let Some(crpix) = as_f64(&line.value) {
+                line.value = GenericValue::F64(crpix - offset as f64);
+            }
+        }
+    }
+}
+
+/// Update coordinate metadata after flipping the image along a dimension of size `extent`.
+///
+/// The WCS reference pixel (`CRPIX1` or `CRPIX2`) is mirrored, and the corresponding
+/// pixel scale (`CDELTn`, `CD1_n` and `CD2_n`) is negated, if present.
+pub(crate) fn flip_values(
+    metadata: &mut HashMap<String, GenericLineItem>,
+    extent: usize,
+    horizontal: bool,
+) {
+    let axis = if horizontal { 1 } else { 2 };
+    if let Some(line) = metadata.get_mut(&format!("CRPIX{axis}")) {
+        if let Some(crpix) = as_f64(&line.value) {
+            // FITS pixel coordinates are 1-based
+            line.value = GenericValue::F64(extent as f64 + 1.0 - crpix);
+        }
+    }
+    for key in [
+        format!("CDELT{axis}"),
+        format!("CD1_{axis}"),
+        format!("CD2_{axis}"),
+    ] {
+        if let Some(line) = metadata.get_mut(&key) {
+            if let Some(val) = as_f64(&line.value) {
+                line.value = GenericValue::F64(-val);
+            }
+        }
+    }
+}
+
+/// Merge metadata into an existing map, replacing values with the same key.
+///
+/// All keys are validated before any value is merged.
+pub(crate) fn merge_values(
+    metadata: &mut HashMap<String, GenericLineItem>,
+    incoming: HashMap<String, GenericLineItem>,
+) -> Result<(), &'static str> {
+    for (name, line) in incoming.iter() {
+        name_check(name)?;
+        if name.to_uppercase() == TIMESTAMP_KEY && line.get_value().get_value_systemtime().is_none()
+        {
+            return Err("Timestamp key must hold a timestamp");
+        }
+    }
+    metadata.extend(
+        incoming
+            .into_iter()
+            .map(|(name, line)| (name.to_uppercase(), line)),
+    );
+    Ok(())
+}
+
+/// Increment an integer metadata value in-place, preserving its type and comment.
+pub(crate) fn increment_value(
+    metadata: &mut HashMap<String, GenericLineItem>,
+    name: &str,
+    by: i64,
+) -> Result<i64, &'static str> {
+    name_check(name)?;
+    let line = metadata
+        .get_mut(&name.to_uppercase())
+        .ok_or("Key not found")?;
+    macro_rules! increment {
+        ($variant:path, $v:expr, $t:ty) => {{
+            let v = (*$v as i128)
+                .checked_add(by as i128)
+                .and_then(|v| <$t>::try_from(v).ok())
+                .ok_or("Increment overflows the stored type")?;
+            let out = i64::try_from(v).map_err(|_| "Incremented value does not fit in i64")?;
+            line.value = $variant(v);
+            Ok(out)
+        }};
+    }
+    match &line.value {
+        GenericValue::U8(v) => increment!(GenericValue::U8, v, u8),
+        GenericValue::U16(v) => increment!(GenericValue::U16, v, u16),
+        GenericValue::U32(v) => increment!(GenericValue::U32, v, u32),
+        GenericValue::U64(v) => increment!(GenericValue::U64, v, u64),
+        GenericValue::I8(v) => increment!(GenericValue::I8, v, i8),
+        GenericValue::I16(v) => increment!(GenericValue::I16, v, i16),
+        GenericValue::I32(v) => increment!(GenericValue::I32, v, i32),
+        GenericValue::I64(v) => increment!(GenericValue::I64, v, i64),
+        _ => Err("Key is not an integer"),
+    }
+}
+
 pub(crate) fn name_check(name: &str) -> Result<(), &'static str> {
     if name.is_empty() {
         Err("Key cannot be empty")
@@ -480,6 +725,14 @@ impl GenericValue {
             _ => None,
         }
     }
+
+    /// Get the binary blob metadata value.
+    pub fn get_value_bytes(&self) -> Option<&[u8]> {
+        match self {
+            GenericValue::Bytes(b) => Some(b.as_slice()),
+            _ => None,
+        }
+    }
 }
 
 mod test {
@@ -0,0 +1,97 @@
+use crate::{ImageOwned, ImageProps, PixelStor, Rect};
+
+/// The result of comparing two frames with [`ImageOwned::changed_pixels`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChangeReport {
+    /// The number of pixels that changed beyond the threshold.
+    pub count: usize,
+    /// The bounding box of the changed pixels, or `None` if no pixels changed.
+    pub bounds: Option<Rect>,
+}
+
+impl<T: PixelStor> ImageOwned<T> {
+    /// Compare this frame against a previous frame, and report the pixels that changed.
+    ///
+    /// A pixel is considered changed if the absolute difference of any of its channels
+    /// exceeds `threshold`. This is useful as a simple motion or meteor trigger for
+    /// unattended capture.
+    ///
+    /// # Arguments
+    /// - `prev`: The previous frame.
+    /// - `threshold`: The absolute difference a channel must exceed to count as changed.
+    ///
+    /// # Errors
+    /// - If the frames do not have the same dimensions or color space.
+    pub fn changed_pixels(
+        &self,
+        prev: &ImageOwned<T>,
+        threshold: T,
+    ) -> Result<ChangeReport, &'static str> {
+        if self.width() != prev.width() || self.height() != prev.height() {
+            return Err("Frames do not have the same dimensions.");
+        }
+        if self.cspace != prev.cspace {
+            return Err("Frames do not have the same color space.");
+        }
+        let threshold = PixelStor::to_f64(threshold);
+        let channels = self.channels as usize;
+        let width = self.width();
+        let mut count = 0;
+        let (mut xmin, mut ymin, mut xmax, mut ymax) = (usize::MAX, usize::MAX, 0, 0);
+        for (idx, (cur, old)) in self
+            .data
+            .chunks_exact(channels)
+            .zip(prev.data.chunks_exact(channels))
+            .enumerate()
+        {
+            let changed = cur
+                .iter()
+                .zip(old.iter())
+                .any(|(a, b)| (PixelStor::to_f64(*a) - PixelStor::to_f64(*b)).abs() > threshold);
+            if changed {
+                let (x, y) = (idx % width, idx / width);
+                count += 1;
+                xmin = xmin.min(x);
+                ymin = ymin.min(y);
+                xmax = xmax.max(x);
+                ymax = ymax.max(y);
+            }
+        }
+        let bounds = (count > 0).then(|| Rect {
+            x: xmin,
+            y: ymin,
+            width: xmax - xmin + 1,
+            height: ymax - ymin + 1,
+        });
+        Ok(ChangeReport { count, bounds })
+    }
+}
+
+mod test {
+    #[test]
+    fn test_changed_pixels() {
+        use crate::{ColorSpace, ImageOwned, Rect};
+        let prev = ImageOwned::from_owned(vec![10u8; 16], 4, 4, ColorSpace::Gray).unwrap();
+        let mut data = vec![10u8; 16];
+        data[5] = 50; // (1, 1)
+        data[11] = 12; // (3, 2), below threshold
+        data[14] = 0; // (2, 3)
+        let cur = ImageOwned::from_owned(data, 4, 4, ColorSpace::Gray).unwrap();
+        let report = cur.changed_pixels(&prev, 5).unwrap();
+        assert_eq!(report.count, 2);
+        assert_eq!(
+            report.bounds,
+            Some(Rect {
+                x: 1,
+                y: 1,
+                width: 2,
+                height: 3
+            })
+        );
+        let report = prev.changed_pixels(&prev, 0).unwrap();
+        assert_eq!(report.count, 0);
+        assert_eq!(report.bounds, None);
+        let other = ImageOwned::from_owned(vec![10u8; 8], 4, 2, ColorSpace::Gray).unwrap();
+        assert!(cur.changed_pixels(&other, 5).is_err());
+    }
+}
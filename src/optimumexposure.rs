@@ -192,6 +192,12 @@ impl OptimumExposure {
     /// # Returns
     ///  * `Ok((Duration, u16))` - The optimum exposure time and binning.
     ///
+    /// # Note
+    /// If every pixel in the image has the same value (e.g. a frame taken with
+    /// the shutter closed, or a fully saturated frame), the input exposure and
+    /// binning are returned unchanged. Use [`evaluate`](OptimumExposure::evaluate)
+    /// to detect this case.
+    ///
     /// # Errors
     ///  - Errors are returned as static string slices.
     pub fn calculate<T: PixelStor + Ord>(
@@ -201,6 +207,58 @@ impl OptimumExposure {
         exposure: Duration,
         bin: u8,
     ) -> Result<(Duration, u16), &'static str> {
+        self.evaluate(img, len, exposure, bin)
+            .map(ExposureOutcome::into_inner)
+    }
+
+    /// Find the optimum exposure time and binning to reach a target pixel value,
+    /// without modifying the image data.
+    ///
+    /// Unlike [`calculate`](OptimumExposure::calculate), which reorders `img` in place,
+    /// the pixels are copied before the percentile is selected.
+    ///
+    /// # Arguments
+    ///  * `img` - The image luminance data.
+    ///  * `exposure` - The exposure duration used to obtain this image luminance data.
+    ///  * `bin` - The binning used to obtain this image luminance data.
+    ///
+    /// # Returns
+    ///  * `Ok((Duration, u16))` - The optimum exposure time and binning.
+    ///
+    /// # Errors
+    ///  - Errors are returned as static string slices.
+    pub fn calculate_copy<T: PixelStor + Ord>(
+        &self,
+        img: &[T],
+        exposure: Duration,
+        bin: u8,
+    ) -> Result<(Duration, u16), &'static str> {
+        self.calculate(&mut img.to_vec(), img.len(), exposure, bin)
+    }
+
+    /// Find the optimum exposure time and binning to reach a target pixel value,
+    /// flagging measurements that carry no exposure information.
+    ///
+    /// # Arguments
+    ///  * `mut img` - The image luminance data as a vector of u16 that is consumed.
+    ///  * `len` - The number of valid pixels in `img`.
+    ///  * `exposure` - The exposure duration used to obtain this image luminance data.
+    ///  * `bin` - The binning used to obtain this image luminance data.
+    ///
+    /// # Returns
+    ///  * `Ok(ExposureOutcome::Optimum(Duration, u16))` - The optimum exposure time and binning.
+    ///  * `Ok(ExposureOutcome::Degenerate(Duration, u16))` - The image is constant, and the
+    ///    input exposure time and binning are returned unchanged.
+    ///
+    /// # Errors
+    ///  - Errors are returned as static string slices.
+    pub fn evaluate<T: PixelStor + Ord>(
+        &self,
+        img: &mut [T],
+        len: usize,
+        exposure: Duration,
+        bin: u8,
+    ) -> Result<ExposureOutcome, &'static str> {
         let mut target_exposure;
 
         let mut change_bin = true;
@@ -229,6 +287,10 @@ impl OptimumExposure {
             return Err("Minimum allowed exposure must be less than maximum allowed exposure");
         }
 
+        if len == 0 || len > img.len() {
+            return Err("Length must be non-zero and at most the number of pixels");
+        }
+
         if pixel_exclusion > img.len() as u32 {
             return Err("Pixel exclusion must be less than the number of pixels");
         }
@@ -246,24 +308,26 @@ impl OptimumExposure {
             change_bin = false;
         }
         let mut bin = bin as u16;
-        img[..len].sort();
-        let mut coord: usize;
-        if percentile_pix > 0.99999 {
-            coord = len - 1_usize;
-        } else {
-            coord = (percentile_pix * (len - 1) as f32).floor() as usize;
+        let img = &mut img[..len];
+        let (min, max) = img
+            .iter()
+            .fold((img[0], img[0]), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+        if min == max {
+            return Ok(ExposureOutcome::Degenerate(exposure, bin));
         }
+        let mut coord = percentile_index(percentile_pix, len);
         if coord < pixel_exclusion as usize {
             coord = len - 1 - pixel_exclusion as usize;
         }
-        let val = img[..len].get(coord);
-        let val = match val {
-            Some(v) => (*v).to_f32(),
-            None => 1e-5_f32,
+        // Only the value at the percentile is needed, so a full sort is not necessary
+        let val = if coord < len {
+            (*img.select_nth_unstable(coord).1).to_f32()
+        } else {
+            1e-5_f32
         };
 
         if (pixel_tgt - val).abs() < pixel_uncertainty {
-            return Ok((exposure, bin));
+            return Ok(ExposureOutcome::Optimum(exposure, bin));
         }
 
         let val = {
@@ -311,7 +375,7 @@ impl OptimumExposure {
             bin = max_allowed_bin;
         }
 
-        Ok((target_exposure, bin))
+        Ok(ExposureOutcome::Optimum(target_exposure, bin))
     }
 
     /// Retrieve the builder for the [`OptimumExposure`] calculator.
@@ -329,6 +393,33 @@ impl OptimumExposure {
     }
 }
 
+/// Result of an optimum exposure evaluation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExposureOutcome {
+    /// The optimum exposure time and binning.
+    Optimum(Duration, u16),
+    /// The image is constant (all pixels have the same value), so no exposure
+    /// information could be extracted. Contains the input exposure time and
+    /// binning, unchanged.
+    Degenerate(Duration, u16),
+}
+
+impl ExposureOutcome {
+    /// Check if the measurement was degenerate.
+    pub fn is_degenerate(&self) -> bool {
+        matches!(self, ExposureOutcome::Degenerate(..))
+    }
+
+    /// Get the exposure time and binning, regardless of the outcome.
+    pub fn into_inner(self) -> (Duration, u16) {
+        match self {
+            ExposureOutcome::Optimum(exp, bin) | ExposureOutcome::Degenerate(exp, bin) => {
+                (exp, bin)
+            }
+        }
+    }
+}
+
 /// Trait to calculate the optimum exposure time and binning.
 ///
 /// This trait abstracts the retrieval of underlying image data.
@@ -345,13 +436,53 @@ pub trait CalcOptExp {
     /// * `Err(&'static str)` - Error message.
     ///
     /// # Note
-    /// The image data is consumed by the function.
+    /// The image data is consumed by the function. If the image is constant,
+    /// the input exposure and binning are returned unchanged; use
+    /// [`eval_opt_exp`](CalcOptExp::eval_opt_exp) to detect this case.
     fn calc_opt_exp(
         self,
         eval: &OptimumExposure,
         exposure: Duration,
         bin: u8,
     ) -> Result<(Duration, u16), &'static str>;
+
+    /// Evaluate the optimum exposure time and binning, flagging constant images.
+    ///
+    /// # Arguments
+    /// * `eval` - The [`OptimumExposure`] calculator.
+    /// * `exposure` - The exposure duration used to obtain the image data.
+    /// * `bin` - The binning used to obtain the image data.
+    ///
+    /// # Returns
+    /// * `Ok(ExposureOutcome)` - The optimum exposure time and binning, or
+    ///   [`ExposureOutcome::Degenerate`] if the image is constant.
+    /// * `Err(&'static str)` - Error message.
+    ///
+    /// # Note
+    /// The image data is consumed by the function. The default implementation can not
+    /// detect constant images, and always reports the result of
+    /// [`calc_opt_exp`](CalcOptExp::calc_opt_exp) as [`ExposureOutcome::Optimum`].
+    fn eval_opt_exp(
+        self,
+        eval: &OptimumExposure,
+        exposure: Duration,
+        bin: u8,
+    ) -> Result<ExposureOutcome, &'static str>
+    where
+        Self: Sized,
+    {
+        self.calc_opt_exp(eval, exposure, bin)
+            .map(|(exp, bin)| ExposureOutcome::Optimum(exp, bin))
+    }
+}
+
+/// Index of the `percentile` (in `[0, 1]`) element of `len` sorted values.
+pub(crate) fn percentile_index(percentile: f32, len: usize) -> usize {
+    if percentile > 0.99999 {
+        len - 1
+    } else {
+        (percentile * (len - 1) as f32).floor() as usize
+    }
 }
 
 #[cfg(test)]
@@ -382,4 +513,69 @@ mod test {
         let res = img.calc_opt_exp(&opt_exp, exp, bin).unwrap();
         assert_eq!(res, (exp, bin as u16));
     }
+
+    #[test]
+    fn test_optimum_exposure_copy() {
+        let opt_exp = OptimumExposureBuilder::default()
+            .percentile_pix(0.5)
+            .build()
+            .unwrap();
+        // A permutation of 0, 10, ..., 9990
+        let img: Vec<u16> = (0..1000u32)
+            .map(|v| (v * 7919 % 1000 * 10) as u16)
+            .collect();
+        let orig = img.clone();
+        let exp = Duration::from_millis(100);
+        let res = opt_exp.calculate_copy(&img, exp, 1).unwrap();
+        assert_eq!(img, orig);
+        assert!(res.0 > exp);
+        let mut sorted = img.clone();
+        sorted.sort();
+        assert_eq!(opt_exp.calculate(&mut sorted, 1000, exp, 1).unwrap(), res);
+        let mut shuffled = img.clone();
+        assert_eq!(opt_exp.calculate(&mut shuffled, 1000, exp, 1).unwrap(), res);
+    }
+
+    #[test]
+    fn test_optimum_exposure_degenerate() {
+        let opt_exp = OptimumExposureBuilder::default()
+            .pixel_exclusion(1)
+            .build()
+            .unwrap();
+        let exp = Duration::from_millis(100);
+        let mut img = vec![0u16; 10];
+        let res = opt_exp.evaluate(&mut img, 10, exp, 1).unwrap();
+        assert_eq!(res, ExposureOutcome::Degenerate(exp, 1));
+        assert!(res.is_degenerate());
+        let mut img = vec![u16::MAX; 10];
+        assert_eq!(opt_exp.calculate(&mut img, 10, exp, 1).unwrap(), (exp, 1));
+        let mut img = vec![0u16, 0, 0, 0, 0, 0, 0, 0, 0, 10];
+        let res = opt_exp.evaluate(&mut img, 10, exp, 1).unwrap();
+        assert!(!res.is_degenerate());
+    }
+
+    #[test]
+    fn test_eval_opt_exp_default() {
+        // Implementors of only `calc_opt_exp` get `eval_opt_exp` for free
+        struct Frame(Vec<u16>);
+        impl CalcOptExp for Frame {
+            fn calc_opt_exp(
+                mut self,
+                eval: &OptimumExposure,
+                exposure: Duration,
+                bin: u8,
+            ) -> Result<(Duration, u16), &'static str> {
+                let len = self.0.len();
+                eval.calculate(&mut self.0, len, exposure, bin)
+            }
+        }
+        let opt_exp = OptimumExposureBuilder::default()
+            .pixel_exclusion(1)
+            .build()
+            .unwrap();
+        let exp = Duration::from_secs(10);
+        let frame = Frame((0..10).collect());
+        let res = frame.eval_opt_exp(&opt_exp, exp, 1).unwrap();
+        assert_eq!(res, ExposureOutcome::Optimum(exp, 1));
+    }
 }
@@ -0,0 +1,158 @@
+//! PNG output
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{BufWriter, Error, ErrorKind},
+    path::Path,
+};
+
+use png::{BitDepth, ColorType, Encoder, Info};
+
+use crate::{coretraits::cast_u8, ColorSpace, DynamicImageOwned, GenericImageOwned, ImageProps};
+
+/// Get the PNG color type of an image with the given color space.
+fn png_color_type(cspace: &ColorSpace) -> Result<ColorType, Error> {
+    match cspace {
+        ColorSpace::Gray => Ok(ColorType::Grayscale),
+        ColorSpace::GrayAlpha => Ok(ColorType::GrayscaleAlpha),
+        ColorSpace::Rgb => Ok(ColorType::Rgb),
+        ColorSpace::Rgba => Ok(ColorType::Rgba),
+        ColorSpace::Bayer(_) => Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Bayer mosaic images can not be written to PNG, debayer the image first",
+        )),
+        ColorSpace::Custom(_, _) | ColorSpace::Cmyk => Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Only gray, gray-alpha, RGB and RGBA images can be written to PNG",
+        )),
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "png")))]
+impl DynamicImageOwned {
+    /// Write the image to a PNG file.
+    ///
+    /// Gray, gray-alpha, RGB and RGBA images are written as such.
+    ///
+    /// `u8` images are written with a bit depth of 8, and `u16` images with a bit depth
+    /// of 16. Images of other pixel types are converted to `u8` as with
+    /// [`DynamicImageOwned::into_u8`] first, and written with a bit depth of 8.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the PNG file, which is overwritten if it exists.
+    ///
+    /// # Errors
+    /// - If the image is a Bayer mosaic image, or has an unsupported color space.
+    /// - If the file can not be created or written to.
+    pub fn write_png(&self, path: &Path) -> std::io::Result<()> {
+        self.write_png_icc(path, None)
+    }
+
+    /// Write the image to a PNG file, embedding `icc` as the ICC color profile if present.
+    fn write_png_icc(&self, path: &Path, icc: Option<&[u8]>) -> std::io::Result<()> {
+        let color = png_color_type(&self.color_space())?;
+        let (depth, data) = match self {
+            DynamicImageOwned::U8(img) => (BitDepth::Eight, Cow::Borrowed(img.as_slice())),
+            DynamicImageOwned::U16(img) => (
+                BitDepth::Sixteen,
+                Cow::Owned(
+                    img.as_slice()
+                        .iter()
+                        .flat_map(|v| v.to_be_bytes())
+                        .collect(),
+                ),
+            ),
+            DynamicImageOwned::I16(img) => (BitDepth::Eight, Cow::Owned(cast_u8(img.as_slice()))),
+            DynamicImageOwned::U32(img) => (BitDepth::Eight, Cow::Owned(cast_u8(img.as_slice()))),
+            DynamicImageOwned::F32(img) => (BitDepth::Eight, Cow::Owned(cast_u8(img.as_slice()))),
+        };
+        let file = BufWriter::new(File::create(path)?);
+        let mut info = Info::with_size(self.width() as u32, self.height() as u32);
+        info.color_type = color;
+        info.bit_depth = depth;
+        info.icc_profile = icc.map(Cow::Borrowed);
+        let encoder = Encoder::with_info(file, info).map_err(Error::other)?;
+        let mut writer = encoder.write_header().map_err(Error::other)?;
+        writer.write_image_data(&data).map_err(Error::other)?;
+        writer.finish().map_err(Error::other)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "png")))]
+impl GenericImageOwned {
+    /// Write the image to a PNG file. The ICC color profile of the image, if any, is
+    /// embedded in the file. The rest of the metadata is not written.
+    ///
+    /// See [`DynamicImageOwned::write_png`] for details.
+    ///
+    /// # Errors
+    /// - If the image is a Bayer mosaic image, or has an unsupported color space.
+    /// - If the file can not be created or written to.
+    pub fn write_png(&self, path: &Path) -> std::io::Result<()> {
+        self.image.write_png_icc(path, self.icc_profile())
+    }
+}
+
+mod test {
+    #[test]
+    fn test_write_png() {
+        use crate::{BayerPattern, ColorSpace, DynamicImageOwned, GenericImageOwned, ImageOwned};
+        use std::path::Path;
+        let read = |path: &Path| {
+            let decoder =
+                png::Decoder::new(std::io::BufReader::new(std::fs::File::open(path).unwrap()));
+            let mut reader = decoder.read_info().unwrap();
+            let mut buf = vec![0; reader.output_buffer_size().unwrap()];
+            let info = reader.next_frame(&mut buf).unwrap();
+            buf.truncate(info.buffer_size());
+            let icc = reader.info().icc_profile.as_ref().map(|icc| icc.to_vec());
+            (info.width, info.color_type, info.bit_depth, buf, icc)
+        };
+        let path =
+            std::env::temp_dir().join(format!("refimage_test_write_{}.png", std::process::id()));
+        let path = path.as_path();
+
+        let img = ImageOwned::from_owned(vec![1u8, 2, 3, 4, 5, 6], 1, 2, ColorSpace::Rgb).unwrap();
+        let mut img = GenericImageOwned::new(std::time::SystemTime::now(), img.into());
+        img.write_png(path).unwrap();
+        let (width, color, depth, buf, icc) = read(path);
+        assert_eq!(
+            (width, color, depth),
+            (1, png::ColorType::Rgb, png::BitDepth::Eight)
+        );
+        assert_eq!(buf, [1, 2, 3, 4, 5, 6]);
+        assert!(icc.is_none());
+        img.set_icc_profile(vec![0xa5; 16]);
+        img.write_png(path).unwrap();
+        let (.., icc) = read(path);
+        assert_eq!(icc, Some(vec![0xa5; 16]));
+
+        let img =
+            ImageOwned::from_owned(vec![0x1234u16, 0xabcd], 1, 1, ColorSpace::GrayAlpha).unwrap();
+        DynamicImageOwned::from(img).write_png(path).unwrap();
+        let (_, color, depth, buf, _) = read(path);
+        assert_eq!(
+            (color, depth),
+            (png::ColorType::GrayscaleAlpha, png::BitDepth::Sixteen)
+        );
+        assert_eq!(buf, [0x12, 0x34, 0xab, 0xcd]);
+
+        let img = ImageOwned::from_owned(vec![0.0f32, 1.0], 2, 1, ColorSpace::Gray).unwrap();
+        DynamicImageOwned::from(img).write_png(path).unwrap();
+        let (width, color, depth, buf, _) = read(path);
+        assert_eq!(
+            (width, color, depth),
+            (2, png::ColorType::Grayscale, png::BitDepth::Eight)
+        );
+        assert_eq!(buf, [0, 255]);
+        std::fs::remove_file(path).unwrap();
+
+        let img = ImageOwned::from_owned(vec![0u8; 4], 2, 2, BayerPattern::Rggb.into()).unwrap();
+        let err = DynamicImageOwned::from(img).write_png(path).unwrap_err();
+        assert!(err.to_string().contains("debayer"));
+        let img = ImageOwned::from_owned(vec![0u8; 4], 1, 1, ColorSpace::Custom(4, "RGBN".into()))
+            .unwrap();
+        assert!(DynamicImageOwned::from(img).write_png(path).is_err());
+        assert!(!path.exists());
+    }
+}
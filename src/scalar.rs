@@ -0,0 +1,152 @@
+//! In-place arithmetic with a scalar, e.g. to remove a constant bias.
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{
+    coretraits::{clamp_from_f64, Enlargeable},
+    DynamicImageOwned, DynamicImageRef, MutImageData, PixelStor,
+};
+use num_traits::{Bounded, Zero};
+
+/// Apply `f` to every element in place.
+///
+/// Note: This operation is parallelized if the `rayon` feature is enabled.
+fn for_each_mut<T: PixelStor, F: Fn(&mut T) + Send + Sync>(data: &mut [T], f: F) {
+    #[cfg(not(feature = "rayon"))]
+    data.iter_mut().for_each(f);
+    #[cfg(feature = "rayon")]
+    data.par_iter_mut().for_each(f);
+}
+
+/// Add `v` to every element, saturating at the valid range of the pixel type.
+pub(crate) fn run_add_scalar<T: PixelStor + Enlargeable>(data: &mut [T], v: T) {
+    for_each_mut(data, |px| {
+        let sum = T::clamp_larger(px.make_larger() + v.make_larger());
+        *px = if sum > T::DEFAULT_MAX_VALUE {
+            T::DEFAULT_MAX_VALUE
+        } else if sum < T::DEFAULT_MIN_VALUE {
+            T::DEFAULT_MIN_VALUE
+        } else {
+            sum
+        };
+    });
+}
+
+/// Subtract `v` from every element, saturating at the valid range of the pixel type.
+pub(crate) fn run_sub_scalar<T: PixelStor + Enlargeable>(data: &mut [T], v: T) {
+    for_each_mut(data, |px| {
+        let (a, b) = (px.make_larger(), v.make_larger());
+        // Unsigned larger types can not hold negative differences
+        if a < b && T::Larger::min_value() >= T::Larger::zero() {
+            *px = T::DEFAULT_MIN_VALUE;
+            return;
+        }
+        let diff = T::clamp_larger(a - b);
+        *px = if diff > T::DEFAULT_MAX_VALUE {
+            T::DEFAULT_MAX_VALUE
+        } else if diff < T::DEFAULT_MIN_VALUE {
+            T::DEFAULT_MIN_VALUE
+        } else {
+            diff
+        };
+    });
+}
+
+/// Multiply every element by `factor`, saturating at the valid range of the pixel type.
+pub(crate) fn run_mul_scalar<T: PixelStor>(data: &mut [T], factor: f64) {
+    let round = !T::PIXEL_TYPE.is_float();
+    for_each_mut(data, |px| {
+        let v = px.to_f64() * factor;
+        *px = clamp_from_f64(if round { v.round() } else { v });
+    });
+}
+
+macro_rules! impl_scalar_dynamic {
+    ($t:ty) => {
+        impl $t {
+            /// Add `v` to every sample of the image in-place.
+            ///
+            /// `v` is converted to the pixel type of the image, clamping it to the valid
+            /// range. See [`MutImageData::add_scalar`] for details.
+            pub fn add_scalar(&mut self, v: f64) {
+                match self {
+                    Self::U8(img) => img.add_scalar(clamp_from_f64(v)),
+                    Self::U16(img) => img.add_scalar(clamp_from_f64(v)),
+                    Self::I16(img) => img.add_scalar(clamp_from_f64(v)),
+                    Self::U32(img) => img.add_scalar(clamp_from_f64(v)),
+                    Self::F32(img) => img.add_scalar(clamp_from_f64(v)),
+                }
+            }
+
+            /// Subtract `v` from every sample of the image in-place.
+            ///
+            /// `v` is converted to the pixel type of the image, clamping it to the valid
+            /// range. See [`MutImageData::sub_scalar`] for details.
+            pub fn sub_scalar(&mut self, v: f64) {
+                match self {
+                    Self::U8(img) => img.sub_scalar(clamp_from_f64(v)),
+                    Self::U16(img) => img.sub_scalar(clamp_from_f64(v)),
+                    Self::I16(img) => img.sub_scalar(clamp_from_f64(v)),
+                    Self::U32(img) => img.sub_scalar(clamp_from_f64(v)),
+                    Self::F32(img) => img.sub_scalar(clamp_from_f64(v)),
+                }
+            }
+
+            /// Multiply every sample of the image by `factor` in-place.
+            ///
+            /// See [`MutImageData::mul_scalar`] for details.
+            pub fn mul_scalar(&mut self, factor: f64) {
+                match self {
+                    Self::U8(img) => img.mul_scalar(factor),
+                    Self::U16(img) => img.mul_scalar(factor),
+                    Self::I16(img) => img.mul_scalar(factor),
+                    Self::U32(img) => img.mul_scalar(factor),
+                    Self::F32(img) => img.mul_scalar(factor),
+                }
+            }
+        }
+    };
+}
+
+impl_scalar_dynamic!(DynamicImageRef<'_>);
+impl_scalar_dynamic!(DynamicImageOwned);
+
+mod test {
+    #[test]
+    fn test_scalar() {
+        use crate::{ColorSpace, DynamicImageOwned, ImageOwned, ImageRef, MutImageData};
+        let mut img =
+            ImageOwned::from_owned(vec![10u8, 100, 200, 250], 2, 2, ColorSpace::Gray).unwrap();
+        img.add_scalar(10);
+        assert_eq!(img.as_slice(), &[20, 110, 210, 255]);
+        img.sub_scalar(30);
+        assert_eq!(img.as_slice(), &[0, 80, 180, 225]);
+        img.mul_scalar(1.5);
+        assert_eq!(img.as_slice(), &[0, 120, 255, 255]);
+        img.mul_scalar(-1.0);
+        assert_eq!(img.as_slice(), &[0; 4]);
+
+        let mut data = vec![-32000i16, 0, 32000, 5, 5, 5];
+        let mut img = ImageRef::new(&mut data, 3, 2, ColorSpace::Gray).unwrap();
+        img.add_scalar(1000);
+        assert_eq!(img.as_slice(), &[-31000, 1000, i16::MAX, 1005, 1005, 1005]);
+        img.sub_scalar(2000);
+        assert_eq!(img.as_slice(), &[i16::MIN, -1000, 30767, -995, -995, -995]);
+
+        let mut img = DynamicImageOwned::from(
+            ImageOwned::from_owned(vec![0.25f32, 0.75], 2, 1, ColorSpace::Gray).unwrap(),
+        );
+        img.add_scalar(0.5);
+        assert_eq!(img.as_slice_f32().unwrap(), &[0.75, 1.0]);
+        img.sub_scalar(0.25);
+        img.mul_scalar(0.5);
+        assert_eq!(img.as_slice_f32().unwrap(), &[0.25, 0.375]);
+        let mut img = DynamicImageOwned::from(
+            ImageOwned::from_owned(vec![65000u16, 3], 2, 1, ColorSpace::Gray).unwrap(),
+        );
+        img.add_scalar(1e9);
+        img.sub_scalar(10.0);
+        assert_eq!(img.as_slice_u16().unwrap(), &[65525, 65525]);
+    }
+}
@@ -0,0 +1,422 @@
+use num_traits::{Bounded, NumCast, Zero};
+#[cfg(feature = "rayon")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::{
+    coretraits::large_to_f64, defects::median, ColorSpace, Enlargeable, ImageOwned, ImageProps,
+    PixelStor,
+};
+
+/// Check that all frames in a stack have the same geometry and color space.
+pub(crate) fn check_stack<T: PixelStor>(frames: &[ImageOwned<T>]) -> Result<(), &'static str> {
+    let first = frames.first().ok_or("No frames to stack.")?;
+    for frame in frames.iter().skip(1) {
+        if frame.width() != first.width() || frame.height() != first.height() {
+            return Err("Frames do not have the same dimensions.");
+        }
+        if frame.cspace != first.cspace {
+            return Err("Frames do not have the same color space.");
+        }
+    }
+    Ok(())
+}
+
+/// Reduce a stack of frames into a single frame, pixel by pixel.
+fn reduce_stack<T, F>(frames: &[ImageOwned<T>], f: F) -> Result<ImageOwned<T>, &'static str>
+where
+    T: PixelStor,
+    F: Fn(T, T) -> T,
+{
+    check_stack(frames)?;
+    let mut out = frames[0].clone();
+    for frame in frames.iter().skip(1) {
+        out.data
+            .iter_mut()
+            .zip(frame.data.iter())
+            .for_each(|(acc, &px)| *acc = f(*acc, px));
+    }
+    Ok(out)
+}
+
+/// Create an image from the per-pixel maximum across a stack of frames.
+///
+/// This is commonly used for "lighten" compositing, e.g. star-trail images.
+///
+/// # Arguments
+/// - `frames`: The frames to stack.
+///
+/// # Errors
+/// - If there are no frames.
+/// - If the frames do not have the same dimensions or color space.
+pub fn stack_max<T: PixelStor>(frames: &[ImageOwned<T>]) -> Result<ImageOwned<T>, &'static str> {
+    reduce_stack(frames, |a, b| if b > a { b } else { a })
+}
+
+/// Create an image from the per-pixel minimum across a stack of frames.
+///
+/// This is commonly used for "darken" compositing.
+///
+/// # Arguments
+/// - `frames`: The frames to stack.
+///
+/// # Errors
+/// - If there are no frames.
+/// - If the frames do not have the same dimensions or color space.
+pub fn stack_min<T: PixelStor>(frames: &[ImageOwned<T>]) -> Result<ImageOwned<T>, &'static str> {
+    reduce_stack(frames, |a, b| if b < a { b } else { a })
+}
+
+/// Combine a stack of frames into a single frame, computing each output pixel from
+/// the values of that pixel across the frames.
+///
+/// Note: This operation is parallelized if the `rayon` feature is enabled.
+fn combine_stack<T, F>(frames: &[ImageOwned<T>], f: F) -> Result<ImageOwned<T>, &'static str>
+where
+    T: PixelStor,
+    F: Fn(usize) -> T + Send + Sync,
+{
+    check_stack(frames)?;
+    let first = &frames[0];
+    #[cfg(not(feature = "rayon"))]
+    let data = (0..first.len()).map(f).collect();
+    #[cfg(feature = "rayon")]
+    let data = (0..first.len()).into_par_iter().map(f).collect();
+    ImageOwned::new(data, first.width(), first.height(), first.cspace.clone())
+}
+
+/// Create an image from the per-pixel mean across a stack of frames, e.g. to build
+/// a master dark or flat frame.
+///
+/// The values are summed in [`Enlargeable::Larger`], saturating at the bounds of that
+/// type, and the sum is divided by the number of frames. For integer types the
+/// division truncates, as with [`Accumulator::finalize_mean`].
+///
+/// Note: This operation is parallelized over the output pixels if the `rayon` feature
+/// is enabled.
+///
+/// # Arguments
+/// - `frames`: The frames to stack.
+///
+/// # Errors
+/// - If there are no frames.
+/// - If the frames do not have the same dimensions or color space.
+pub fn stack_mean<T: PixelStor + Enlargeable>(
+    frames: &[ImageOwned<T>],
+) -> Result<ImageOwned<T>, &'static str> {
+    let _: T::Larger = NumCast::from(frames.len()).ok_or("Too many frames.")?;
+    combine_stack(frames, |i| {
+        let count: T::Larger = NumCast::from(frames.len()).expect("Checked above");
+        let sum = frames.iter().fold(T::Larger::zero(), |sum, frame| {
+            saturating_add(sum, frame.data[i].make_larger())
+        });
+        T::clamp_larger(sum / count)
+    })
+}
+
+/// Create an image from the per-pixel median across a stack of frames.
+///
+/// The median rejects outliers such as cosmic rays and satellite trails, at the cost
+/// of sorting the values of every pixel. For an even number of frames, the median is
+/// the mean of the two middle values, rounded to the nearest integer for integer types.
+///
+/// Note: This operation is parallelized over the output pixels if the `rayon` feature
+/// is enabled.
+///
+/// # Arguments
+/// - `frames`: The frames to stack.
+///
+/// # Errors
+/// - If there are no frames.
+/// - If the frames do not have the same dimensions or color space.
+pub fn stack_median<T: PixelStor>(frames: &[ImageOwned<T>]) -> Result<ImageOwned<T>, &'static str> {
+    let round = !T::PIXEL_TYPE.is_float();
+    combine_stack(frames, |i| {
+        let mut values: Vec<f64> = frames.iter().map(|frame| frame.data[i].to_f64()).collect();
+        let m = median(&mut values).expect("The stack is not empty");
+        T::from_f64(if round { m.round() } else { m })
+    })
+}
+
+/// Create images of the per-pixel mean and standard deviation across a stack of frames,
+/// e.g. to build a master dark or flat frame along with its noise map.
+///
+/// Both images are computed in a single pass over the frames. The standard deviation
+/// is the unbiased sample standard deviation, normalized by the valid range of the
+/// pixel type (so that the full range of `T` maps to `1.0`), and is zero if the stack
+/// contains a single frame.
+///
+/// # Arguments
+/// - `frames`: The frames to stack.
+///
+/// # Returns
+/// The per-pixel mean, and the per-pixel normalized standard deviation.
+///
+/// # Errors
+/// - If there are no frames.
+/// - If the frames do not have the same dimensions or color space.
+pub fn stack_mean_and_sigma<T: PixelStor>(
+    frames: &[ImageOwned<T>],
+) -> Result<(ImageOwned<T>, ImageOwned<f32>), &'static str> {
+    check_stack(frames)?;
+    let first = &frames[0];
+    let len = first.len();
+    // Welford's algorithm, for numerical stability
+    let mut mean = vec![0f64; len];
+    let mut m2 = vec![0f64; len];
+    for (k, frame) in frames.iter().enumerate() {
+        let n = (k + 1) as f64;
+        mean.iter_mut()
+            .zip(m2.iter_mut())
+            .zip(frame.as_slice())
+            .for_each(|((mean, m2), &px)| {
+                let px = px.to_f64();
+                let delta = px - *mean;
+                *mean += delta / n;
+                *m2 += delta * (px - *mean);
+            });
+    }
+    let round = !T::PIXEL_TYPE.is_float();
+    let scale = T::DEFAULT_MAX_VALUE.to_f64() - T::DEFAULT_MIN_VALUE.to_f64();
+    let dof = frames.len().saturating_sub(1).max(1) as f64;
+    let sigma = m2
+        .into_iter()
+        .map(|m2| ((m2 / dof).sqrt() / scale) as f32)
+        .collect();
+    let mean = mean
+        .into_iter()
+        .map(|m| T::from_f64(if round { m.round() } else { m }))
+        .collect();
+    Ok((
+        ImageOwned::new(mean, first.width(), first.height(), first.cspace.clone())?,
+        ImageOwned::new(sigma, first.width(), first.height(), first.cspace.clone())?,
+    ))
+}
+
+/// A running per-pixel sum of frames, for computing the mean and variance of a
+/// stream of frames without holding all of them in memory.
+///
+/// The sums are accumulated in [`Enlargeable::Larger`], and saturate at the bounds of
+/// that type instead of overflowing.
+///
+/// # Usage
+/// ```
+/// use refimage::{Accumulator, ColorSpace, ImageOwned};
+///
+/// let mut acc = Accumulator::new();
+/// for i in 0..4u16 {
+///     let frame = ImageOwned::from_owned(vec![i; 6], 3, 2, ColorSpace::Gray).unwrap();
+///     acc.add_frame(&frame).unwrap();
+/// }
+/// let mean = acc.finalize_mean().unwrap();
+/// assert_eq!(mean.as_slice(), &[1u16; 6]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Accumulator<T: PixelStor + Enlargeable> {
+    sums: Vec<T::Larger>,
+    sumsq: Vec<f64>,
+    count: usize,
+    width: usize,
+    height: usize,
+    cspace: ColorSpace,
+}
+
+impl<T: PixelStor + Enlargeable> Default for Accumulator<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PixelStor + Enlargeable> Accumulator<T> {
+    /// Create an empty [`Accumulator`].
+    ///
+    /// The geometry and color space are set by the first frame added.
+    pub fn new() -> Self {
+        Self {
+            sums: Vec::new(),
+            sumsq: Vec::new(),
+            count: 0,
+            width: 0,
+            height: 0,
+            cspace: ColorSpace::Gray,
+        }
+    }
+
+    /// Get the number of frames accumulated.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Add a frame to the running sums.
+    ///
+    /// # Errors
+    /// - If the frame does not have the same dimensions or color space as the
+    ///   previously added frames.
+    pub fn add_frame(&mut self, img: &ImageOwned<T>) -> Result<(), &'static str> {
+        if self.count == 0 {
+            self.sums = vec![T::Larger::zero(); img.len()];
+            self.sumsq = vec![0.0; img.len()];
+            self.width = img.width();
+            self.height = img.height();
+            self.cspace = img.cspace.clone();
+        } else if img.width() != self.width || img.height() != self.height {
+            return Err("Frames do not have the same dimensions.");
+        } else if img.cspace != self.cspace {
+            return Err("Frames do not have the same color space.");
+        }
+        self.sums
+            .iter_mut()
+            .zip(self.sumsq.iter_mut())
+            .zip(img.as_slice())
+            .for_each(|((sum, sumsq), &px)| {
+                *sum = saturating_add(*sum, px.make_larger());
+                *sumsq += px.to_f64() * px.to_f64();
+            });
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Compute the per-pixel mean of the accumulated frames.
+    ///
+    /// # Errors
+    /// - If no frames were added.
+    pub fn finalize_mean(self) -> Result<ImageOwned<T>, &'static str> {
+        if self.count == 0 {
+            return Err("No frames to stack.");
+        }
+        let count: T::Larger = NumCast::from(self.count).ok_or("Too many frames.")?;
+        let data = self
+            .sums
+            .into_iter()
+            .map(|sum| T::clamp_larger(sum / count))
+            .collect();
+        ImageOwned::new(data, self.width, self.height, self.cspace)
+    }
+
+    /// Compute the per-pixel unbiased sample variance of the accumulated frames.
+    ///
+    /// The variance is in units of the squared pixel value, and is zero if only one
+    /// frame was added.
+    ///
+    /// # Errors
+    /// - If no frames were added.
+    pub fn finalize_variance(self) -> Result<ImageOwned<f32>, &'static str> {
+        if self.count == 0 {
+            return Err("No frames to stack.");
+        }
+        let n = self.count as f64;
+        let data = self
+            .sums
+            .into_iter()
+            .zip(self.sumsq)
+            .map(|(sum, sumsq)| {
+                if self.count < 2 {
+                    return 0.0;
+                }
+                let sum = large_to_f64(sum);
+                ((sumsq - sum * sum / n) / (n - 1.0)).max(0.0) as f32
+            })
+            .collect();
+        ImageOwned::new(data, self.width, self.height, self.cspace)
+    }
+}
+
+/// Add two values, saturating at the bounds of the type.
+fn saturating_add<L: Copy + NumCast + Bounded + std::ops::Add<Output = L>>(a: L, b: L) -> L {
+    let sum = large_to_f64(a) + large_to_f64(b);
+    if sum >= large_to_f64(L::max_value()) {
+        L::max_value()
+    } else if sum <= large_to_f64(L::min_value()) {
+        L::min_value()
+    } else {
+        a + b
+    }
+}
+
+mod test {
+    #[test]
+    fn test_stack_extrema() {
+        use crate::{ColorSpace, ImageOwned};
+        let frames = vec![
+            ImageOwned::from_owned(vec![1u16, 5, 3, 8], 2, 2, ColorSpace::Gray).unwrap(),
+            ImageOwned::from_owned(vec![4u16, 2, 3, 9], 2, 2, ColorSpace::Gray).unwrap(),
+            ImageOwned::from_owned(vec![0u16, 7, 6, 1], 2, 2, ColorSpace::Gray).unwrap(),
+        ];
+        let max = super::stack_max(&frames).unwrap();
+        assert_eq!(max.as_slice(), &[4, 7, 6, 9]);
+        let min = super::stack_min(&frames).unwrap();
+        assert_eq!(min.as_slice(), &[0, 2, 3, 1]);
+        assert!(super::stack_max::<u16>(&[]).is_err());
+        let odd = ImageOwned::from_owned(vec![0u16; 4], 4, 1, ColorSpace::Gray).unwrap();
+        assert!(super::stack_min(&[frames[0].clone(), odd]).is_err());
+    }
+
+    #[test]
+    fn test_stack_mean_median() {
+        use crate::{BayerPattern, ColorSpace, ImageOwned};
+        let frames: Vec<_> = [[2u8, 250, 7], [4, 250, 1], [6, 250, 200], [9, 250, 3]]
+            .iter()
+            .map(|v| ImageOwned::from_owned(v.to_vec(), 3, 1, ColorSpace::Gray).unwrap())
+            .collect();
+        let mean = super::stack_mean(&frames).unwrap();
+        assert_eq!(mean.as_slice(), &[5, 250, 52]);
+        let median = super::stack_median(&frames).unwrap();
+        assert_eq!(median.as_slice(), &[5, 250, 5]);
+        let median = super::stack_median(&frames[..3]).unwrap();
+        assert_eq!(median.as_slice(), &[4, 250, 7]);
+
+        let frames: Vec<_> = [0.25f32, 0.5, 1.0]
+            .iter()
+            .map(|&v| ImageOwned::from_owned(vec![v; 2], 2, 1, ColorSpace::Gray).unwrap())
+            .collect();
+        let mean = super::stack_mean(&frames).unwrap();
+        assert!((mean.as_slice()[0] - 1.75 / 3.0).abs() < 1e-6);
+        assert_eq!(super::stack_median(&frames).unwrap().as_slice(), &[0.5; 2]);
+
+        assert_eq!(super::stack_mean::<u16>(&[]), Err("No frames to stack."));
+        assert!(super::stack_median::<u16>(&[]).is_err());
+        let a = ImageOwned::from_owned(vec![0u8; 4], 2, 2, ColorSpace::Gray).unwrap();
+        let b = ImageOwned::from_owned(vec![0u8; 4], 2, 2, BayerPattern::Rggb.into()).unwrap();
+        assert_eq!(
+            super::stack_median(&[a, b]),
+            Err("Frames do not have the same color space.")
+        );
+    }
+
+    #[test]
+    fn test_stack_mean_and_sigma() {
+        use crate::{ColorSpace, ImageOwned};
+        let frames: Vec<_> = [[2u8, 250], [4, 250], [6, 250], [9, 250]]
+            .iter()
+            .map(|v| ImageOwned::from_owned(v.to_vec(), 2, 1, ColorSpace::Gray).unwrap())
+            .collect();
+        let (mean, sigma) = super::stack_mean_and_sigma(&frames).unwrap();
+        assert_eq!(mean.as_slice(), &[5, 250]);
+        let expected = (26.75f32 / 3.0).sqrt() / 255.0;
+        assert!((sigma.as_slice()[0] - expected).abs() < 1e-6);
+        assert_eq!(sigma.as_slice()[1], 0.0);
+        let (mean, sigma) = super::stack_mean_and_sigma(&frames[..1]).unwrap();
+        assert_eq!(mean.as_slice(), &[2, 250]);
+        assert_eq!(sigma.as_slice(), &[0.0, 0.0]);
+        assert!(super::stack_mean_and_sigma::<u8>(&[]).is_err());
+    }
+
+    #[test]
+    fn test_accumulator() {
+        use crate::{Accumulator, ColorSpace, ImageOwned};
+        let mut acc = Accumulator::new();
+        assert!(acc.clone().finalize_mean().is_err());
+        for v in [[2u8, 250], [4, 250], [6, 250], [8, 250]] {
+            let frame = ImageOwned::from_owned(v.to_vec(), 2, 1, ColorSpace::Gray).unwrap();
+            acc.add_frame(&frame).unwrap();
+        }
+        assert_eq!(acc.count(), 4);
+        let odd = ImageOwned::from_owned(vec![0u8; 2], 1, 2, ColorSpace::Gray).unwrap();
+        assert!(acc.add_frame(&odd).is_err());
+        let mean = acc.clone().finalize_mean().unwrap();
+        assert_eq!(mean.as_slice(), &[5, 250]);
+        let var = acc.finalize_variance().unwrap();
+        assert!((var.as_slice()[0] - 20.0 / 3.0).abs() < 1e-5);
+        assert_eq!(var.as_slice()[1], 0.0);
+        assert_eq!(super::saturating_add(u32::MAX - 1, 5), u32::MAX);
+    }
+}
@@ -0,0 +1,150 @@
+//! Per-channel image statistics.
+
+use crate::{
+    defects::median, DynamicImageOwned, DynamicImageRef, ImageOwned, ImageProps, ImageRef,
+    PixelStor,
+};
+
+/// Summary statistics of one channel of an image, computed by [`ImageOwned::statistics`].
+///
+/// NaN values are skipped. If a channel has no valid values, all statistics are NaN.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelStats {
+    /// The minimum value.
+    pub min: f64,
+    /// The maximum value.
+    pub max: f64,
+    /// The mean value.
+    pub mean: f64,
+    /// The population standard deviation.
+    pub stddev: f64,
+    /// The median value.
+    pub median: f64,
+}
+
+impl ChannelStats {
+    /// Compute the statistics of the values, reordering them in the process.
+    fn from_values(values: &mut [f64]) -> Self {
+        let Some(median) = median(values) else {
+            return ChannelStats {
+                min: f64::NAN,
+                max: f64::NAN,
+                mean: f64::NAN,
+                stddev: f64::NAN,
+                median: f64::NAN,
+            };
+        };
+        // The values are sorted by the median computation
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+        ChannelStats {
+            min: values[0],
+            max: values[values.len() - 1],
+            mean,
+            stddev: var.sqrt(),
+            median,
+        }
+    }
+}
+
+/// Compute the statistics of each channel of interleaved image data.
+fn run_statistics<T: PixelStor>(data: &[T], channels: usize) -> Vec<ChannelStats> {
+    (0..channels)
+        .map(|ch| {
+            let mut values: Vec<f64> = data
+                .iter()
+                .skip(ch)
+                .step_by(channels)
+                .map(|&v| PixelStor::to_f64(v))
+                .filter(|v| !v.is_nan())
+                .collect();
+            ChannelStats::from_values(&mut values)
+        })
+        .collect()
+}
+
+macro_rules! impl_statistics {
+    ($($t:tt)*) => {
+        $($t)* {
+            /// Compute the minimum, maximum, mean, standard deviation and median of each
+            /// channel of the image.
+            ///
+            /// The values are accumulated as [`f64`], and NaN values of floating point
+            /// images are skipped. The median is computed by sorting a copy of each channel.
+            ///
+            /// # Returns
+            /// One [`ChannelStats`] per channel, in channel order.
+            pub fn statistics(&self) -> Vec<ChannelStats> {
+                run_statistics(self.as_slice(), self.channels() as usize)
+            }
+        }
+    };
+}
+
+impl_statistics!(impl<T: PixelStor> ImageOwned<T>);
+impl_statistics!(impl<T: PixelStor> ImageRef<'_, T>);
+
+macro_rules! impl_statistics_dynamic {
+    ($t:ty) => {
+        impl $t {
+            /// Compute the minimum, maximum, mean, standard deviation and median of each
+            /// channel of the image.
+            ///
+            /// See [`ImageOwned::statistics`] for details.
+            pub fn statistics(&self) -> Vec<ChannelStats> {
+                match self {
+                    Self::U8(img) => img.statistics(),
+                    Self::U16(img) => img.statistics(),
+                    Self::I16(img) => img.statistics(),
+                    Self::U32(img) => img.statistics(),
+                    Self::F32(img) => img.statistics(),
+                }
+            }
+        }
+    };
+}
+
+impl_statistics_dynamic!(DynamicImageRef<'_>);
+impl_statistics_dynamic!(DynamicImageOwned);
+
+mod test {
+    #[test]
+    fn test_statistics() {
+        use crate::{ColorSpace, DynamicImageOwned, GenericImage, ImageOwned, ImageRef};
+        let img = ImageOwned::from_owned(
+            vec![1u8, 10, 3, 20, 2, 30, 4, 40],
+            2,
+            2,
+            ColorSpace::Custom(2, "GA".into()),
+        )
+        .unwrap();
+        let stats = img.statistics();
+        assert_eq!(stats.len(), 2);
+        assert_eq!((stats[0].min, stats[0].max), (1.0, 4.0));
+        assert_eq!((stats[0].mean, stats[0].median), (2.5, 2.5));
+        assert!((stats[0].stddev - 1.25f64.sqrt()).abs() < 1e-12);
+        assert_eq!(
+            (stats[1].min, stats[1].max, stats[1].mean),
+            (10.0, 40.0, 25.0)
+        );
+
+        let mut data = vec![0.5f32, f32::NAN, 0.25, 1.0];
+        let img = ImageRef::new(&mut data, 2, 2, ColorSpace::Gray).unwrap();
+        let stats = img.statistics();
+        assert_eq!((stats[0].min, stats[0].max), (0.25, 1.0));
+        assert_eq!((stats[0].mean, stats[0].median), (0.5 + 0.25 / 3.0, 0.5));
+
+        let mut data = vec![f32::NAN; 4];
+        let img = ImageRef::new(&mut data, 2, 2, ColorSpace::Gray).unwrap();
+        assert!(img.statistics()[0].mean.is_nan());
+
+        let img = ImageOwned::from_owned(vec![u16::MAX; 6], 3, 2, ColorSpace::Gray).unwrap();
+        let img = GenericImage::from(crate::GenericImageOwned::new(
+            std::time::SystemTime::now(),
+            DynamicImageOwned::from(img),
+        ));
+        let stats = img.statistics();
+        assert_eq!((stats[0].mean, stats[0].stddev), (65535.0, 0.0));
+    }
+}
@@ -0,0 +1,133 @@
+//! Thresholding of single-channel images into binary masks.
+
+use crate::{coreimpls::run_histogram, ColorSpace, ImageOwned, ImageProps, ImageRef, PixelStor};
+
+/// Number of histogram bins used to compute the Otsu threshold.
+const OTSU_BINS: usize = 256;
+
+/// Emit 255 where the value is at least `level`, and 0 elsewhere.
+fn run_threshold<T: PixelStor>(data: &[T], level: T) -> Vec<u8> {
+    data.iter()
+        .map(|&v| if v >= level { 255 } else { 0 })
+        .collect()
+}
+
+/// Compute the Otsu threshold of the data from a histogram spanning the valid range
+/// of the pixel type.
+///
+/// The threshold is the lower edge of the first bin of the upper class.
+/// Returns [`None`] if the data can not be split into two classes.
+fn otsu_level<T: PixelStor>(data: &[T]) -> Option<T> {
+    let hist = run_histogram(data, 1, 0, OTSU_BINS);
+    let total = data.len() as f64;
+    let sum: f64 = hist
+        .iter()
+        .enumerate()
+        .map(|(i, &n)| i as f64 * n as f64)
+        .sum();
+    let (mut w0, mut sum0) = (0f64, 0f64);
+    let mut best: Option<(usize, f64)> = None;
+    for (k, &n) in hist.iter().enumerate().take(OTSU_BINS - 1) {
+        w0 += n as f64;
+        sum0 += k as f64 * n as f64;
+        let w1 = total - w0;
+        if w0 == 0.0 || w1 == 0.0 {
+            continue;
+        }
+        let diff = sum0 / w0 - (sum - sum0) / w1;
+        let var = w0 * w1 * diff * diff;
+        if best.is_none_or(|(_, v)| var > v) {
+            best = Some((k, var));
+        }
+    }
+    let (k, _) = best?;
+    let min = T::DEFAULT_MIN_VALUE.to_f64();
+    let max = T::DEFAULT_MAX_VALUE.to_f64();
+    let level = min + (k + 1) as f64 * (max - min) / OTSU_BINS as f64;
+    let level = if T::PIXEL_TYPE.is_float() {
+        level
+    } else {
+        level.ceil()
+    };
+    Some(T::from_f64(level.clamp(min, max)))
+}
+
+macro_rules! impl_threshold {
+    ($($t:tt)*) => {
+        $($t)* {
+            /// Threshold a single-channel image into a binary mask.
+            ///
+            /// The output is a [`ColorSpace::Gray`] image of the same dimensions, which is
+            /// 255 where the pixel is greater than or equal to `level`, and 0 elsewhere.
+            ///
+            /// # Arguments
+            /// - `level`: The threshold level.
+            ///
+            /// # Errors
+            /// - If the image has more than one channel.
+            pub fn threshold(&self, level: T) -> Result<ImageOwned<u8>, &'static str> {
+                if self.channels() != 1 {
+                    return Err("Threshold requires a single-channel image.");
+                }
+                ImageOwned::new(
+                    run_threshold(self.as_slice(), level),
+                    self.width(),
+                    self.height(),
+                    ColorSpace::Gray,
+                )
+            }
+
+            /// Threshold a single-channel image into a binary mask, with the level chosen
+            /// by Otsu's method.
+            ///
+            /// The level maximizes the between-class variance of a 256-bin histogram that
+            /// spans the valid range of the pixel type (`[0, 1]` for floating point images).
+            /// See [`Self::threshold`] for the output. If the pixels all fall in a single
+            /// bin, the mask is empty.
+            ///
+            /// # Errors
+            /// - If the image has more than one channel.
+            pub fn threshold_otsu(&self) -> Result<ImageOwned<u8>, &'static str> {
+                if self.channels() != 1 {
+                    return Err("Threshold requires a single-channel image.");
+                }
+                let data = match otsu_level(self.as_slice()) {
+                    Some(level) => run_threshold(self.as_slice(), level),
+                    None => vec![0; self.as_slice().len()],
+                };
+                ImageOwned::new(data, self.width(), self.height(), ColorSpace::Gray)
+            }
+        }
+    };
+}
+
+impl_threshold!(impl<T: PixelStor> ImageOwned<T>);
+impl_threshold!(impl<T: PixelStor> ImageRef<'_, T>);
+
+mod test {
+    #[test]
+    fn test_threshold() {
+        use crate::{BayerPattern, ColorSpace, ImageOwned, ImageProps, ImageRef};
+        let mut data = vec![10u16, 500, 1000, 20, 0, 0];
+        let img = ImageRef::new(&mut data, 2, 2, BayerPattern::Rggb.into()).unwrap();
+        let mask = img.threshold(500).unwrap();
+        assert_eq!(mask.color_space(), ColorSpace::Gray);
+        assert_eq!((mask.width(), mask.height()), (2, 2));
+        assert_eq!(mask.as_slice(), &[0, 255, 255, 0]);
+
+        // Two clusters of values, split by Otsu's method
+        let data: Vec<u8> = [20u8, 22, 25, 30, 200, 210, 220, 230].repeat(2);
+        let img = ImageOwned::from_owned(data, 4, 4, ColorSpace::Gray).unwrap();
+        let mask = img.threshold_otsu().unwrap();
+        assert_eq!(mask.as_slice(), &[0, 0, 0, 0, 255, 255, 255, 255].repeat(2));
+        let img = ImageOwned::from_owned(vec![0.5f32; 4], 2, 2, ColorSpace::Gray).unwrap();
+        assert_eq!(img.threshold_otsu().unwrap().as_slice(), &[0; 4]);
+
+        let img = ImageOwned::from_owned(vec![0u8; 6], 2, 1, ColorSpace::Rgb).unwrap();
+        assert_eq!(
+            img.threshold(1),
+            Err("Threshold requires a single-channel image.")
+        );
+        assert!(img.threshold_otsu().is_err());
+    }
+}